@@ -0,0 +1,31 @@
+use cfg_aliases::cfg_aliases;
+
+fn main() {
+    cfg_aliases! {
+        // A built-in sensor backend that takes `OpenConfig::reconnect`.
+        reconnecting_backends: { any(
+            feature = "mac_hid_feature",
+            feature = "mac_als",
+            all(
+                target_os = "macos",
+                any(feature = "mac_iokit_raw", feature = "mac_smc", feature = "mac_clamshell")
+            ),
+            all(
+                target_os = "windows",
+                any(feature = "win_sensors", feature = "win_lid_switch")
+            ),
+            all(
+                target_os = "linux",
+                any(
+                    feature = "linux_iio_proxy",
+                    feature = "linux_iio_sys",
+                    feature = "linux_libinput",
+                    feature = "linux_upower",
+                    feature = "linux_acpi_lid"
+                )
+            )
+        ) },
+        // Some source has a built-in backend in this build (`open_builtin`).
+        builtin_backends: { any(reconnecting_backends, feature = "mock") },
+    }
+}
@@ -0,0 +1,9 @@
+use booklid_rust::{OpenConfig, SampleRate, daemon};
+
+// Run once per machine; other apps calling `open()` attach to it.
+#[tokio::main]
+async fn main() -> booklid_rust::Result<()> {
+    let cfg = OpenConfig::new(SampleRate::hz(60.0)).diagnostics(true);
+    println!("serving on {}", daemon::socket_path().display());
+    daemon::run(cfg).await
+}
@@ -1,8 +1,10 @@
-use booklid_rust::{OpenConfig, open_with_config};
+use booklid_rust::{OpenConfig, SampleRate, open_with_config};
 
 #[tokio::main]
 async fn main() -> booklid_rust::Result<()> {
-    let cfg = OpenConfig::new(60.0).allow_mock(true).diagnostics(true);
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .diagnostics(true);
 
     let dev = open_with_config(cfg).await?;
 
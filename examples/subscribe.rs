@@ -1,9 +1,9 @@
-use booklid_rust::open;
+use booklid_rust::{SampleRate, open};
 use futures_util::StreamExt;
 
 #[tokio::main]
 async fn main() -> booklid_rust::Result<()> {
-    let dev = open(60.0).await?;
+    let dev = open(SampleRate::hz(60.0)).await?;
     let mut stream = dev.subscribe();
     println!("Streaming via subscribe(), source={:?}", dev.info().source);
     let mut n = 0u32;
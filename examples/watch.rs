@@ -1,10 +1,10 @@
-use booklid_rust::open;
+use booklid_rust::{SampleRate, open};
 use tokio::time::{Duration, sleep};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let hz = 60.0;
-    let client = open(hz).await?;
+    let rate = SampleRate::hz(60.0);
+    let client = open(rate).await?;
 
     // CI mode: exit after a few seconds
     let ci = std::env::var("BOOKLID_CI").ok().as_deref() == Some("1");
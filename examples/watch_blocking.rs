@@ -1,10 +1,10 @@
-use booklid_rust::open_blocking;
+use booklid_rust::{SampleRate, open_blocking};
 use std::thread::sleep;
 use std::time::Duration;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let hz = 60.0;
-    let client = open_blocking(hz)?;
+    let rate = SampleRate::hz(60.0);
+    let client = open_blocking(rate)?;
 
     let ci = std::env::var("BOOKLID_CI").ok().as_deref() == Some("1");
     let mut ticks = 0usize;
@@ -0,0 +1,20 @@
+#![no_main]
+
+//! Report descriptor parsing, then reading each field it finds from the
+//! same bytes as a report.
+
+use booklid_rust::hid_descriptor::{find_angle_field, parse};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let fields = parse(data);
+    for f in &fields {
+        if let Some(format) = f.format() {
+            assert!(format.is_valid());
+            let _ = format.parse(data);
+        }
+    }
+    if let Some(f) = find_angle_field(&fields) {
+        assert!(fields.contains(&f));
+    }
+});
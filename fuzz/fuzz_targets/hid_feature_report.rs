@@ -0,0 +1,17 @@
+#![no_main]
+
+//! Feature-report decoding: first byte is the expected report id, the rest
+//! is what the device returned.
+
+use booklid_rust::hid::parse_feature_report;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&id, report)) = data.split_first() else {
+        return;
+    };
+    if let Some(angle) = parse_feature_report(report, id) {
+        assert!(report.len() >= 3 && report[0] == id);
+        assert!((0.0..=u16::MAX as f32).contains(&angle));
+    }
+});
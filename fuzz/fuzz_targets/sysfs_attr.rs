@@ -0,0 +1,28 @@
+#![no_main]
+
+//! sysfs attribute parsing and the accelerometer pitch derived from it.
+
+use booklid_rust::linux::{parse_attr, parse_hwmon_lux, pitch_from_accel};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+    if let Some(v) = parse_attr(s) {
+        assert!(v.is_finite());
+    }
+    if let Some(v) = parse_hwmon_lux(s) {
+        assert!(v.is_finite());
+    }
+    // Up to three whitespace-separated axes, as read from in_accel_{x,y,z}.
+    let mut axes = s.split_whitespace().map(|a| parse_attr(a).unwrap_or(0.0));
+    let (x, y, z) = (
+        axes.next().unwrap_or(0.0),
+        axes.next().unwrap_or(0.0),
+        axes.next().unwrap_or(0.0),
+    );
+    if let Some(p) = pitch_from_accel(x, y, z) {
+        assert!((-90.0..=90.0).contains(&p), "pitch {p}");
+    }
+});
@@ -3,112 +3,170 @@
     any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
 ))]
 
-use crate::{AngleDevice, AngleSample, AngleStream, DeviceInfo, Error, Result, Source};
-use futures_util::StreamExt;
+use crate::convention::{Seam, hinge_from_gravity_near};
+use crate::filters::{self, Baseline, Filter, OutlierRejection, RollingVariance, Smoother, Stages};
+#[cfg(feature = "linux_iio_proxy")]
+use crate::linux::{PROXY_TILT_CONFIDENCE, parse_tilt_class};
+use crate::linux::{
+    Roots, ScanType, accel_threshold_raw, glob_under, parse_attr, parse_hwmon_lux,
+    parse_mount_matrix, parse_scan_type, pick_sampling_frequency, pitch_from_accel, scan_layout,
+};
+use crate::types::Velocity;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, Backoff, DeviceInfo, Error, LightSample, Result, Source,
+    TaskGuard, convention,
+};
+use futures_util::{StreamExt, stream::BoxStream};
 use std::{
     fs,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
     time::Instant,
 };
 use tokio::{
-    sync::broadcast,
+    sync::{broadcast, mpsc, oneshot},
     time::{self, Duration},
 };
 use tokio_stream::wrappers::BroadcastStream;
 
-#[cfg(feature = "linux_iio_proxy")]
-use zbus::blocking::{Connection as ZConn, Proxy as ZProxy};
-
 pub struct LinuxAngle {
     latest: Arc<Mutex<Option<AngleSample>>>,
     tx: broadcast::Sender<AngleSample>,
-    alpha: Arc<Mutex<f32>>,
+    /// Set for ALS sources only.
+    lux: Option<broadcast::Sender<LightSample>>,
+    task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
     conf: Arc<Mutex<f32>>,
     src: Source,
     note: &'static str,
+    hardware_id: Option<String>,
+    /// The accelerometer's own rate, read back after setting it.
+    sample_hz: Option<f32>,
+    /// The rate as it was before, put back on drop.
+    _rate: AttrChanges,
+    /// Per accelerometer, whether it is read from its IIO buffer; empty
+    /// unless buffered reads are allowed.
+    buffered: Vec<Arc<AtomicBool>>,
 }
 
+// On read failures every sampling loop waits per `backoff` (re-discovering the
+// sensor where it can) and stops once the attempts are exhausted.
 impl LinuxAngle {
-    pub async fn open_tilt(hz: f32) -> Result<Self> {
+    /// `buffer` allows reading the sysfs accelerometer through its IIO
+    /// buffer, see `OpenConfig::linux_iio_buffer`; `roots` are where sysfs
+    /// and the IIO devices are found.
+    pub async fn open_tilt(hz: f32, backoff: Backoff, buffer: bool, roots: Roots) -> Result<Self> {
         // Try DBus first, else /sys accelerometers
         #[cfg(feature = "linux_iio_proxy")]
-        if let Ok(dev) = Self::spawn_from_proxy_tilt(hz).await {
+        if let Ok(dev) = Self::spawn_from_proxy_tilt(hz, backoff).await {
             return Ok(dev);
         }
-        Self::spawn_from_sys_tilt(hz).await
+        Self::spawn_from_sys_tilt(roots, hz, backoff, buffer).await
     }
 
-    pub async fn open_als(hz: f32) -> Result<Self> {
+    /// The hinge angle from a lid and a base accelerometer (IIO `label`s
+    /// `accel-display` and `accel-base`); sysfs only, as iio-sensor-proxy
+    /// exposes just one accelerometer.
+    pub async fn open_hinge(hz: f32, backoff: Backoff, buffer: bool, roots: Roots) -> Result<Self> {
+        Self::spawn_from_sys_hinge(roots, hz, backoff, buffer).await
+    }
+
+    pub async fn open_als(hz: f32, backoff: Backoff, roots: Roots) -> Result<Self> {
+        let root = roots.sys;
+
         // 1) DBus proxy (optional)
         #[cfg(feature = "linux_iio_proxy")]
-        if let Ok(dev) = Self::spawn_from_proxy_als(hz).await {
+        if let Ok(dev) = Self::spawn_from_proxy_als(hz, backoff).await {
             return Ok(dev);
         }
 
         // 2) /sys iio
-        if let Ok(dev) = Self::spawn_from_sys_als(hz).await {
+        if let Ok(dev) = Self::spawn_from_sys_als(root.clone(), hz, backoff).await {
             return Ok(dev);
         }
 
         // 3) hwmon fallback (common on desktops)
-        if let Some(input) = find_hwmon_light_input() {
+        if let Some(input) = find_hwmon_light_input(&root) {
+            let hardware_id = input
+                .parent()
+                .and_then(|dir| sysfs_hardware_id("hwmon", dir));
             let latest = Arc::new(Mutex::new(None));
             let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-            let alpha = Arc::new(Mutex::new(0.25f32));
+            let (lux, _rx) = broadcast::channel::<LightSample>(256);
+            let stages = Arc::new(Mutex::new(Stages::default()));
             let conf = Arc::new(Mutex::new(0.2f32));
 
             let latest_c = latest.clone();
             let tx_c = tx.clone();
-            let alpha_c = alpha.clone();
+            let lux_c = lux.clone();
+            let stages_c = stages.clone();
             let conf_c = conf.clone();
 
-            tokio::spawn(async move {
-                let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(10.0)));
-                let mut baseline = 10.0f32;
-                let mut smoothed: Option<f32> = None;
-                let mut buf: std::collections::VecDeque<f32> =
-                    std::collections::VecDeque::with_capacity(64);
-
-                loop {
-                    interval.tick().await;
-
-                    if let Some(lux) = read_hwmon_lux(&input) {
-                        // high-pass-ish, normalize to 0..1 “bellows”
-                        baseline = 0.995 * baseline + 0.005 * lux;
-                        let val = lux - baseline;
-                        let n = (val * 0.02 + 0.5).clamp(0.0, 1.0);
-
-                        let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                        let s = match smoothed {
-                            None => n,
-                            Some(prev) => prev + a * (n - prev),
-                        };
-                        smoothed = Some(s);
-
-                        if buf.len() == 64 {
-                            buf.pop_front();
+            let task = TaskGuard::supervised(backoff, move || {
+                let latest_c = latest_c.clone();
+                let tx_c = tx_c.clone();
+                let lux_c = lux_c.clone();
+                let stages_c = stages_c.clone();
+                let conf_c = conf_c.clone();
+                let mut input = input.clone();
+                let root = root.clone();
+                async move {
+                    let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(10.0)));
+                    let mut baseline = Baseline::new(10.0);
+                    let mut smoother = Smoother::default();
+                    let mut velocity = Velocity::default();
+                    let mut attempt = 0u32;
+                    let mut variance = RollingVariance::default();
+
+                    loop {
+                        interval.tick().await;
+
+                        if let Some(lux) = read_hwmon_lux(&input) {
+                            attempt = 0;
+                            // high-pass-ish, normalize to 0..1 “bellows”
+                            let n = filters::bellows(baseline.update(lux), 0.02);
+
+                            let now = Instant::now();
+                            let Some(s) = smoother.apply(
+                                *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                                n,
+                                now,
+                            ) else {
+                                continue;
+                            };
+
+                            let v = variance.push(s);
+                            let stability = filters::stability(v, 20.0);
+                            *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = stability;
+
+                            let sample = AngleSample {
+                                angle_deg: s,
+                                raw: n,
+                                timestamp: now,
+                                source: Source::LinuxALS,
+                                confidence: stability,
+                                velocity_deg_s: velocity.update(n, now),
+                            };
+                            let _ = lux_c.send(LightSample {
+                                lux,
+                                timestamp: sample.timestamp,
+                                source: Source::LinuxALS,
+                            });
+                            *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                            let _ = tx_c.send(sample);
+                        } else {
+                            let Some(d) = backoff.delay(attempt) else {
+                                return;
+                            };
+                            attempt += 1;
+                            time::sleep(d).await;
+                            if let Some(p) = find_hwmon_light_input(&root) {
+                                input = p;
+                            }
                         }
-                        buf.push_back(s);
-                        let m = buf.iter().copied().sum::<f32>() / (buf.len() as f32);
-                        let v = buf
-                            .iter()
-                            .map(|v| {
-                                let d = *v - m;
-                                d * d
-                            })
-                            .sum::<f32>()
-                            / (buf.len() as f32);
-                        let stability = (1.0 / (1.0 + 20.0 * v)).clamp(0.0, 1.0);
-                        *conf_c.lock().unwrap() = stability;
-
-                        let sample = AngleSample {
-                            angle_deg: s,
-                            timestamp: Instant::now(),
-                            source: Source::LinuxALS,
-                        };
-                        *latest_c.lock().unwrap() = Some(sample);
-                        let _ = tx_c.send(sample);
                     }
                 }
             });
@@ -116,10 +174,16 @@ impl LinuxAngle {
             return Ok(Self {
                 latest,
                 tx,
-                alpha,
+                lux: Some(lux),
+                task,
+                stages,
                 conf,
                 src: Source::LinuxALS,
                 note: "linux_hwmon_als",
+                hardware_id,
+                sample_hz: None,
+                _rate: AttrChanges::default(),
+                buffered: Vec::new(),
             });
         }
 
@@ -130,204 +194,280 @@ impl LinuxAngle {
     }
 
     #[cfg(feature = "linux_iio_proxy")]
-    async fn spawn_from_proxy_tilt(hz: f32) -> Result<Self> {
+    async fn spawn_from_proxy_tilt(hz: f32, backoff: Backoff) -> Result<Self> {
         // iio-sensor-proxy exposes tilt classification (strings), not raw hinge degrees.
+        let first = ProxyFollower::open(ProxySensor::Accelerometer).await?;
+        let first = Arc::new(Mutex::new(Some(first)));
         let latest = Arc::new(Mutex::new(None));
         let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha = Arc::new(Mutex::new(0.25f32));
+        let stages = Arc::new(Mutex::new(Stages::default()));
         let conf = Arc::new(Mutex::new(0.2f32));
 
         let latest_c = latest.clone();
         let tx_c = tx.clone();
-        let alpha_c = alpha.clone();
+        let stages_c = stages.clone();
         let conf_c = conf.clone();
 
-        tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(20.0)));
-            let mut buf: std::collections::VecDeque<f32> =
-                std::collections::VecDeque::with_capacity(64);
-            let mut smoothed: Option<f32> = None;
-
-            loop {
-                interval.tick().await;
-                // Blocking DBus per tick isn't ideal; keep it simple for 1.0.
-                // (We can switch to an async zbus connection later.)
-                let angle = query_proxy_pitch_degrees().unwrap_or(0.0);
-
-                let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                let s = match smoothed {
-                    None => angle,
-                    Some(prev) => prev + a * (angle - prev),
-                };
-                smoothed = Some(s);
+        let task = TaskGuard::supervised(backoff, move || {
+            let first = first.clone();
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let stages_c = stages_c.clone();
+            let conf_c = conf_c.clone();
+            async move {
+                let mut follower = first.lock().unwrap_or_else(PoisonError::into_inner).take();
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(20.0)));
+                let mut variance = RollingVariance::default();
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut attempt = 0u32;
 
-                if buf.len() == 64 {
-                    buf.pop_front();
+                loop {
+                    let Some(f) = follower.as_mut() else {
+                        let Some(d) = backoff.delay(attempt) else {
+                            return;
+                        };
+                        attempt += 1;
+                        time::sleep(d).await;
+                        follower = ProxyFollower::open(ProxySensor::Accelerometer).await.ok();
+                        continue;
+                    };
+                    if !f.tick(&mut interval).await {
+                        follower = None;
+                        continue;
+                    }
+                    // "undefined" until the proxy has a reading.
+                    let Some(angle) = f.value.as_deref().and_then(proxy_tilt_angle) else {
+                        continue;
+                    };
+                    attempt = 0;
+
+                    let now = Instant::now();
+                    let Some(s) = smoother.apply(
+                        *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                        angle,
+                        now,
+                    ) else {
+                        continue;
+                    };
+
+                    let v = variance.push(s);
+                    let stability = filters::stability(v, 0.05).min(PROXY_TILT_CONFIDENCE);
+                    *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = stability;
+
+                    let sample = AngleSample {
+                        angle_deg: s,
+                        raw: angle,
+                        timestamp: now,
+                        source: Source::LinuxTilt,
+                        confidence: stability,
+                        velocity_deg_s: velocity.update(angle, now),
+                    };
+                    *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                    let _ = tx_c.send(sample);
                 }
-                buf.push_back(s);
-                let n = buf.len() as f32;
-                let m = buf.iter().copied().sum::<f32>() / n;
-                let v = buf
-                    .iter()
-                    .map(|v| {
-                        let d = *v - m;
-                        d * d
-                    })
-                    .sum::<f32>()
-                    / n;
-                let stability = (1.0 / (1.0 + 0.05 * v)).clamp(0.0, 1.0);
-                *conf_c.lock().unwrap() = stability;
-
-                let sample = AngleSample {
-                    angle_deg: s,
-                    timestamp: Instant::now(),
-                    source: Source::LinuxTilt,
-                };
-                *latest_c.lock().unwrap() = Some(sample);
-                let _ = tx_c.send(sample);
             }
         });
 
         Ok(Self {
             latest,
             tx,
-            alpha,
+            lux: None,
+            task,
+            stages,
             conf,
             src: Source::LinuxTilt,
             note: "linux_proxy_tilt",
+            hardware_id: None,
+            sample_hz: None,
+            _rate: AttrChanges::default(),
+            buffered: Vec::new(),
         })
     }
 
     #[cfg(feature = "linux_iio_proxy")]
-    async fn spawn_from_proxy_als(hz: f32) -> Result<Self> {
+    async fn spawn_from_proxy_als(hz: f32, backoff: Backoff) -> Result<Self> {
+        let first = ProxyFollower::open(ProxySensor::Light).await?;
+        let first = Arc::new(Mutex::new(Some(first)));
         let latest = Arc::new(Mutex::new(None));
         let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha = Arc::new(Mutex::new(0.25f32));
+        let (lux, _rx) = broadcast::channel::<LightSample>(256);
+        let stages = Arc::new(Mutex::new(Stages::default()));
         let conf = Arc::new(Mutex::new(0.2f32));
 
         let latest_c = latest.clone();
         let tx_c = tx.clone();
-        let alpha_c = alpha.clone();
+        let lux_c = lux.clone();
+        let stages_c = stages.clone();
         let conf_c = conf.clone();
 
-        tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(10.0)));
-            let mut baseline = 10.0f32;
-            let mut smoothed: Option<f32> = None;
-            let mut buf: std::collections::VecDeque<f32> =
-                std::collections::VecDeque::with_capacity(64);
-
-            loop {
-                interval.tick().await;
-                let lux = query_proxy_lux().unwrap_or(1.0);
-
-                baseline = 0.995 * baseline + 0.005 * lux;
-                let val = lux - baseline;
-                let n = (val * 0.02 + 0.5).clamp(0.0, 1.0);
-
-                let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                let s = match smoothed {
-                    None => n,
-                    Some(prev) => prev + a * (n - prev),
-                };
-                smoothed = Some(s);
+        let task = TaskGuard::supervised(backoff, move || {
+            let first = first.clone();
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let lux_c = lux_c.clone();
+            let stages_c = stages_c.clone();
+            let conf_c = conf_c.clone();
+            async move {
+                let mut follower = first.lock().unwrap_or_else(PoisonError::into_inner).take();
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(10.0)));
+                let mut baseline = Baseline::new(10.0);
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut attempt = 0u32;
+                let mut variance = RollingVariance::default();
+
+                loop {
+                    let Some(f) = follower.as_mut() else {
+                        let Some(d) = backoff.delay(attempt) else {
+                            return;
+                        };
+                        attempt += 1;
+                        time::sleep(d).await;
+                        follower = ProxyFollower::open(ProxySensor::Light).await.ok();
+                        continue;
+                    };
+                    if !f.tick(&mut interval).await {
+                        follower = None;
+                        continue;
+                    }
+                    let Some(lux) = f.value.as_deref().and_then(proxy_lux) else {
+                        continue;
+                    };
+                    attempt = 0;
+
+                    let n = filters::bellows(baseline.update(lux), 0.02);
+
+                    let now = Instant::now();
+                    let Some(s) = smoother.apply(
+                        *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                        n,
+                        now,
+                    ) else {
+                        continue;
+                    };
 
-                if buf.len() == 64 {
-                    buf.pop_front();
+                    let v = variance.push(s);
+                    let stability = filters::stability(v, 20.0);
+                    *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = stability;
+
+                    let sample = AngleSample {
+                        angle_deg: s,
+                        raw: n,
+                        timestamp: now,
+                        source: Source::LinuxALS,
+                        confidence: stability,
+                        velocity_deg_s: velocity.update(n, now),
+                    };
+                    let _ = lux_c.send(LightSample {
+                        lux,
+                        timestamp: sample.timestamp,
+                        source: Source::LinuxALS,
+                    });
+                    *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                    let _ = tx_c.send(sample);
                 }
-                buf.push_back(s);
-                let m = buf.iter().copied().sum::<f32>() / (buf.len() as f32);
-                let v = buf
-                    .iter()
-                    .map(|v| {
-                        let d = *v - m;
-                        d * d
-                    })
-                    .sum::<f32>()
-                    / (buf.len() as f32);
-                let stability = (1.0 / (1.0 + 20.0 * v)).clamp(0.0, 1.0);
-                *conf_c.lock().unwrap() = stability;
-
-                let sample = AngleSample {
-                    angle_deg: s,
-                    timestamp: Instant::now(),
-                    source: Source::LinuxALS,
-                };
-                *latest_c.lock().unwrap() = Some(sample);
-                let _ = tx_c.send(sample);
             }
         });
 
         Ok(Self {
             latest,
             tx,
-            alpha,
+            lux: Some(lux),
+            task,
+            stages,
             conf,
             src: Source::LinuxALS,
             note: "linux_proxy_als",
+            hardware_id: None,
+            sample_hz: None,
+            _rate: AttrChanges::default(),
+            buffered: Vec::new(),
         })
     }
 
-    async fn spawn_from_sys_tilt(hz: f32) -> Result<Self> {
+    async fn spawn_from_sys_tilt(
+        roots: Roots,
+        hz: f32,
+        backoff: Backoff,
+        buffer: bool,
+    ) -> Result<Self> {
         // Find an iio device with accel channels
-        let dev = find_iio_accel_device()
+        let dev = find_iio_accel_device(&roots.sys)
             .ok_or_else(|| Error::Backend("linux: no accel in /sys".into()))?;
+        let hardware_id = sysfs_hardware_id("iio", &dev);
+        let mut rate = AttrChanges::default();
+        let sample_hz = set_sampling_frequency(&dev, hz, &mut rate);
+        let hz = sample_hz.unwrap_or(hz);
+        let buffered = buffered_flags(buffer, 1);
 
         let latest = Arc::new(Mutex::new(None));
         let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha = Arc::new(Mutex::new(0.25f32));
+        let stages = Arc::new(Mutex::new(Stages::default()));
         let conf = Arc::new(Mutex::new(0.2f32));
 
         let latest_c = latest.clone();
         let tx_c = tx.clone();
-        let alpha_c = alpha.clone();
+        let stages_c = stages.clone();
         let conf_c = conf.clone();
+        let buffered_c = buffered.clone();
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let stages_c = stages_c.clone();
+            let conf_c = conf_c.clone();
+            let dev = dev.clone();
+            let roots = roots.clone();
+            let flag = buffered_c.first().cloned();
+            async move {
+                let mut reader = AccelReader::open(&roots, &dev, hz, flag.clone());
+                let mut variance = RollingVariance::default();
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut seam = Seam::default();
+                let mut attempt = 0u32;
 
-        tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(60.0)));
-            let mut buf: std::collections::VecDeque<f32> =
-                std::collections::VecDeque::with_capacity(64);
-            let mut smoothed: Option<f32> = None;
-
-            loop {
-                interval.tick().await;
-
-                if let Some((ax, ay, az)) = read_accel_triplet(&dev) {
-                    // Simple pitch estimate from accel
-                    let g = (ax * ax + ay * ay + az * az).sqrt().max(1e-6);
-                    let pitch = (-ax / g).asin().to_degrees().clamp(-180.0, 180.0);
+                loop {
+                    if let Some(([ax, ay, az], now)) = reader.next().await {
+                        attempt = 0;
+                        let Some(pitch) = pitch_from_accel(ax, ay, az) else {
+                            continue;
+                        };
+                        let angle = convention::normalize(Source::LinuxTilt, pitch);
+                        let unwrapped = seam.unwrap(angle);
+
+                        let Some(s) = smoother.apply(
+                            *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                            unwrapped,
+                            now,
+                        ) else {
+                            continue;
+                        };
 
-                    let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                    let s = match smoothed {
-                        None => pitch,
-                        Some(prev) => prev + a * (pitch - prev),
-                    };
-                    smoothed = Some(s);
+                        let v = variance.push(s);
+                        let stability = filters::stability(v, 0.05);
+                        *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = stability;
 
-                    if buf.len() == 64 {
-                        buf.pop_front();
+                        let sample = AngleSample {
+                            angle_deg: convention::wrap(s),
+                            raw: angle,
+                            timestamp: now,
+                            source: Source::LinuxTilt,
+                            confidence: stability,
+                            velocity_deg_s: velocity.update(unwrapped, now),
+                        };
+                        *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                        let _ = tx_c.send(sample);
+                    } else {
+                        let Some(d) = backoff.delay(attempt) else {
+                            return;
+                        };
+                        attempt += 1;
+                        time::sleep(d).await;
+                        let dev = find_iio_accel_device(&roots.sys).unwrap_or_else(|| dev.clone());
+                        reader = AccelReader::open(&roots, &dev, hz, flag.clone());
                     }
-                    buf.push_back(s);
-                    let n = buf.len() as f32;
-                    let m = buf.iter().copied().sum::<f32>() / n;
-                    let v = buf
-                        .iter()
-                        .map(|v| {
-                            let d = *v - m;
-                            d * d
-                        })
-                        .sum::<f32>()
-                        / n;
-                    let stability = (1.0 / (1.0 + 0.05 * v)).clamp(0.0, 1.0);
-                    *conf_c.lock().unwrap() = stability;
-
-                    let sample = AngleSample {
-                        angle_deg: s,
-                        timestamp: Instant::now(),
-                        source: Source::LinuxTilt,
-                    };
-                    *latest_c.lock().unwrap() = Some(sample);
-                    let _ = tx_c.send(sample);
                 }
             }
         });
@@ -335,72 +475,221 @@ impl LinuxAngle {
         Ok(Self {
             latest,
             tx,
-            alpha,
+            lux: None,
+            task,
+            stages,
             conf,
             src: Source::LinuxTilt,
             note: "linux_sys_tilt",
+            hardware_id,
+            sample_hz,
+            _rate: rate,
+            buffered,
         })
     }
 
-    async fn spawn_from_sys_als(hz: f32) -> Result<Self> {
-        let dev = find_iio_light_device()
-            .ok_or_else(|| Error::Backend("linux: no light sensor in /sys".into()))?;
+    async fn spawn_from_sys_hinge(
+        roots: Roots,
+        hz: f32,
+        backoff: Backoff,
+        buffer: bool,
+    ) -> Result<Self> {
+        let pair = find_iio_accel_pair(&roots.sys).ok_or_else(|| {
+            Error::Backend("linux: no accel-display/accel-base pair in /sys".into())
+        })?;
+        let hardware_id = sysfs_hardware_id("iio", &pair.0.dev);
+        // Scans pair up at the slower panel's rate.
+        let mut rate = AttrChanges::default();
+        let sample_hz = [&pair.0.dev, &pair.1.dev]
+            .map(|dev| set_sampling_frequency(dev, hz, &mut rate))
+            .into_iter()
+            .flatten()
+            .reduce(f32::min);
+        let hz = sample_hz.unwrap_or(hz);
+        let buffered = buffered_flags(buffer, 2);
 
         let latest = Arc::new(Mutex::new(None));
         let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha = Arc::new(Mutex::new(0.25f32));
+        let stages = Arc::new(Mutex::new(Stages::default()));
         let conf = Arc::new(Mutex::new(0.2f32));
 
         let latest_c = latest.clone();
         let tx_c = tx.clone();
-        let alpha_c = alpha.clone();
+        let stages_c = stages.clone();
         let conf_c = conf.clone();
+        let buffered_c = buffered.clone();
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let stages_c = stages_c.clone();
+            let conf_c = conf_c.clone();
+            let (mut lid, mut base) = pair.clone();
+            let roots = roots.clone();
+            let flags = buffered_c.clone();
+            async move {
+                let open = |panel: &AccelPanel, i: usize| {
+                    AccelReader::open(&roots, &panel.dev, hz, flags.get(i).cloned())
+                };
+                let (mut lid_reader, mut base_reader) = (open(&lid, 0), open(&base, 1));
+                // Each panel's latest reading; a sample needs both.
+                let (mut l, mut b) = (None, None);
+                let mut variance = RollingVariance::default();
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut last = None;
+                let mut attempt = 0u32;
 
-        tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(10.0)));
-            let mut baseline = 10.0f32;
-            let mut smoothed: Option<f32> = None;
-            let mut buf: std::collections::VecDeque<f32> =
-                std::collections::VecDeque::with_capacity(64);
-
-            loop {
-                interval.tick().await;
-
-                if let Some(lux) = read_lux(&dev) {
-                    baseline = 0.995 * baseline + 0.005 * lux;
-                    let val = lux - baseline;
-                    let n = (val * 0.02 + 0.5).clamp(0.0, 1.0);
-
-                    let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                    let s = match smoothed {
-                        None => n,
-                        Some(prev) => prev + a * (n - prev),
+                loop {
+                    let read = tokio::select! {
+                        r = lid_reader.next() => r.map(|(v, at)| l = Some((lid.orient(v), at))),
+                        r = base_reader.next() => r.map(|(v, at)| b = Some((base.orient(v), at))),
                     };
-                    smoothed = Some(s);
 
-                    if buf.len() == 64 {
-                        buf.pop_front();
+                    if read.is_some() {
+                        attempt = 0;
+                        let (Some((lv, lt)), Some((bv, bt))) = (l, b) else {
+                            continue;
+                        };
+                        // The hinge points (nearly) straight up or down.
+                        let Some(angle) = hinge_from_gravity_near(lv, bv, last) else {
+                            continue;
+                        };
+                        last = Some(angle);
+
+                        let now = lt.max(bt);
+                        let Some(s) = smoother.apply(
+                            *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                            angle,
+                            now,
+                        ) else {
+                            continue;
+                        };
+
+                        let v = variance.push(s);
+                        let stability = filters::stability(v, 0.05);
+                        *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = stability;
+
+                        let sample = AngleSample {
+                            angle_deg: s,
+                            raw: angle,
+                            timestamp: now,
+                            source: Source::LinuxHinge,
+                            confidence: stability,
+                            velocity_deg_s: velocity.update(angle, now),
+                        };
+                        *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                        let _ = tx_c.send(sample);
+                    } else {
+                        let Some(d) = backoff.delay(attempt) else {
+                            return;
+                        };
+                        attempt += 1;
+                        time::sleep(d).await;
+                        if let Some(p) = find_iio_accel_pair(&roots.sys) {
+                            (lid, base) = p;
+                        }
+                        (lid_reader, base_reader) = (open(&lid, 0), open(&base, 1));
+                        (l, b) = (None, None);
                     }
-                    buf.push_back(s);
-                    let m = buf.iter().copied().sum::<f32>() / (buf.len() as f32);
-                    let v = buf
-                        .iter()
-                        .map(|v| {
-                            let d = *v - m;
-                            d * d
-                        })
-                        .sum::<f32>()
-                        / (buf.len() as f32);
-                    let stability = (1.0 / (1.0 + 20.0 * v)).clamp(0.0, 1.0);
-                    *conf_c.lock().unwrap() = stability;
+                }
+            }
+        });
 
-                    let sample = AngleSample {
-                        angle_deg: s,
-                        timestamp: Instant::now(),
-                        source: Source::LinuxALS,
-                    };
-                    *latest_c.lock().unwrap() = Some(sample);
-                    let _ = tx_c.send(sample);
+        Ok(Self {
+            latest,
+            tx,
+            lux: None,
+            task,
+            stages,
+            conf,
+            src: Source::LinuxHinge,
+            note: "linux_sys_hinge",
+            hardware_id,
+            sample_hz,
+            _rate: rate,
+            buffered,
+        })
+    }
+
+    async fn spawn_from_sys_als(root: PathBuf, hz: f32, backoff: Backoff) -> Result<Self> {
+        let dev = find_iio_light_device(&root)
+            .ok_or_else(|| Error::Backend("linux: no light sensor in /sys".into()))?;
+        let hardware_id = sysfs_hardware_id("iio", &dev);
+
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let (lux, _rx) = broadcast::channel::<LightSample>(256);
+        let stages = Arc::new(Mutex::new(Stages::default()));
+        let conf = Arc::new(Mutex::new(0.2f32));
+
+        let latest_c = latest.clone();
+        let tx_c = tx.clone();
+        let lux_c = lux.clone();
+        let stages_c = stages.clone();
+        let conf_c = conf.clone();
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let lux_c = lux_c.clone();
+            let stages_c = stages_c.clone();
+            let conf_c = conf_c.clone();
+            let mut dev = dev.clone();
+            let root = root.clone();
+            async move {
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(10.0)));
+                let mut baseline = Baseline::new(10.0);
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut attempt = 0u32;
+                let mut variance = RollingVariance::default();
+
+                loop {
+                    interval.tick().await;
+
+                    if let Some(lux) = read_lux(&dev) {
+                        attempt = 0;
+                        let n = filters::bellows(baseline.update(lux), 0.02);
+
+                        let now = Instant::now();
+                        let Some(s) = smoother.apply(
+                            *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                            n,
+                            now,
+                        ) else {
+                            continue;
+                        };
+
+                        let v = variance.push(s);
+                        let stability = filters::stability(v, 20.0);
+                        *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = stability;
+
+                        let sample = AngleSample {
+                            angle_deg: s,
+                            raw: n,
+                            timestamp: now,
+                            source: Source::LinuxALS,
+                            confidence: stability,
+                            velocity_deg_s: velocity.update(n, now),
+                        };
+                        let _ = lux_c.send(LightSample {
+                            lux,
+                            timestamp: sample.timestamp,
+                            source: Source::LinuxALS,
+                        });
+                        *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                        let _ = tx_c.send(sample);
+                    } else {
+                        let Some(d) = backoff.delay(attempt) else {
+                            return;
+                        };
+                        attempt += 1;
+                        time::sleep(d).await;
+                        if let Some(p) = find_iio_light_device(&root) {
+                            dev = p;
+                        }
+                    }
                 }
             }
         });
@@ -408,82 +697,231 @@ impl LinuxAngle {
         Ok(Self {
             latest,
             tx,
-            alpha,
+            lux: Some(lux),
+            task,
+            stages,
             conf,
             src: Source::LinuxALS,
             note: "linux_sys_als",
+            hardware_id,
+            sample_hz: None,
+            _rate: AttrChanges::default(),
+            buffered: Vec::new(),
         })
     }
 }
 
 impl AngleDevice for LinuxAngle {
     fn latest(&self) -> Option<AngleSample> {
-        *self.latest.lock().unwrap()
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
     }
     fn subscribe(&self) -> AngleStream {
         BroadcastStream::new(self.tx.subscribe())
             .filter_map(|it| async move { it.ok() })
             .boxed()
     }
+    fn subscribe_lux(&self) -> BoxStream<'static, LightSample> {
+        match &self.lux {
+            Some(lux) => BroadcastStream::new(lux.subscribe())
+                .filter_map(|it| async move { it.ok() })
+                .boxed(),
+            None => futures_util::stream::empty().boxed(),
+        }
+    }
     fn set_smoothing(&self, alpha: f32) {
-        *self.alpha.lock().unwrap() = alpha;
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = Filter::Ema { alpha };
+    }
+    fn set_filter(&self, filter: Filter) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = filter;
+    }
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .outliers = outliers;
     }
     fn confidence(&self) -> f32 {
-        *self.conf.lock().unwrap()
+        *self.conf.lock().unwrap_or_else(PoisonError::into_inner)
     }
     fn info(&self) -> DeviceInfo {
-        DeviceInfo {
-            source: self.src,
-            note: self.note,
-        }
+        let buffered =
+            !self.buffered.is_empty() && self.buffered.iter().all(|b| b.load(Ordering::Relaxed));
+        DeviceInfo::new(self.src, self.note)
+            .with_sample_hz(self.sample_hz)
+            .with_hardware_timestamps(buffered)
+    }
+    fn hardware_id(&self) -> Option<String> {
+        self.hardware_id.clone()
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
     }
 }
 
 // ==== helpers ====
 
+/// The iio-sensor-proxy sensors this backend claims.
 #[cfg(feature = "linux_iio_proxy")]
-fn query_proxy_pitch_degrees() -> Option<f32> {
-    let conn = ZConn::system().ok()?;
-    let p = ZProxy::new(
-        &conn,
-        "net.hadess.SensorProxy",
-        "/net/hadess/SensorProxy",
-        "net.hadess.SensorProxy",
-    )
-    .ok()?;
-
-    // Start updates (best-effort)
-    let _ = p.call_method("ClaimAccelerometer", &());
-
-    // Map tilt classes -> monotonic “angle-ish” signal
-    let tilt: String = p.get_property("AccelerometerTilt").ok()?;
-    let deg = match tilt.as_str() {
-        "face-up" => 0.0,
-        "tilted-up" => 45.0,
-        "vertical" => 90.0,
-        "tilted-down" => 135.0,
-        "face-down" => 180.0,
-        _ => return None,
-    };
-    Some(deg)
+#[derive(Clone, Copy)]
+enum ProxySensor {
+    Accelerometer,
+    Light,
 }
 
 #[cfg(feature = "linux_iio_proxy")]
-fn query_proxy_lux() -> Option<f32> {
-    let conn = ZConn::system().ok()?;
-    let p = ZProxy::new(
-        &conn,
-        "net.hadess.SensorProxy",
-        "/net/hadess/SensorProxy",
-        "net.hadess.SensorProxy",
-    )
-    .ok()?;
+impl ProxySensor {
+    fn has(self) -> &'static str {
+        match self {
+            ProxySensor::Accelerometer => "HasAccelerometer",
+            ProxySensor::Light => "HasAmbientLight",
+        }
+    }
+
+    fn claim(self) -> &'static str {
+        match self {
+            ProxySensor::Accelerometer => "ClaimAccelerometer",
+            ProxySensor::Light => "ClaimLight",
+        }
+    }
+
+    fn release(self) -> &'static str {
+        match self {
+            ProxySensor::Accelerometer => "ReleaseAccelerometer",
+            ProxySensor::Light => "ReleaseLight",
+        }
+    }
+
+    /// The property carrying the reading.
+    fn property(self) -> &'static str {
+        match self {
+            ProxySensor::Accelerometer => "AccelerometerTilt",
+            ProxySensor::Light => "LightLevel",
+        }
+    }
+}
+
+/// The process's one system bus connection for iio-sensor-proxy, made on
+/// first use (and retried on the next if that failed).
+#[cfg(feature = "linux_iio_proxy")]
+static PROXY_BUS: tokio::sync::OnceCell<zbus::Connection> = tokio::sync::OnceCell::const_new();
+
+/// A claim on one of iio-sensor-proxy's sensors, released when dropped.
+#[cfg(feature = "linux_iio_proxy")]
+struct Claim {
+    proxy: zbus::Proxy<'static>,
+    sensor: ProxySensor,
+}
+
+#[cfg(feature = "linux_iio_proxy")]
+impl Claim {
+    /// Fails (keeping the zbus cause) when iio-sensor-proxy is not reachable
+    /// or lacks the sensor, so `open_*` can fall through to `/sys`.
+    async fn new(sensor: ProxySensor) -> Result<Self> {
+        let bus = PROXY_BUS.get_or_try_init(zbus::Connection::system).await?;
+        let proxy = zbus::Proxy::new(
+            bus,
+            "net.hadess.SensorProxy",
+            "/net/hadess/SensorProxy",
+            "net.hadess.SensorProxy",
+        )
+        .await?;
+        if !proxy.get_property::<bool>(sensor.has()).await? {
+            return Err(Error::Backend(format!(
+                "iio-sensor-proxy: {} is false",
+                sensor.has()
+            )));
+        }
+        proxy.call_method(sensor.claim(), &()).await?;
+        Ok(Self { proxy, sensor })
+    }
+}
+
+#[cfg(feature = "linux_iio_proxy")]
+impl Drop for Claim {
+    fn drop(&mut self) {
+        // The bus connection outlives this claim, so the proxy would not
+        // notice it end by itself.
+        let (proxy, release) = (self.proxy.clone(), self.sensor.release());
+        if let Ok(rt) = tokio::runtime::Handle::try_current() {
+            rt.spawn(async move {
+                let _ = proxy.call_method(release, &()).await;
+            });
+        }
+    }
+}
+
+/// A claimed sensor's property, kept current from `PropertiesChanged`
+/// signals rather than read per sample.
+#[cfg(feature = "linux_iio_proxy")]
+struct ProxyFollower {
+    _claim: Claim,
+    changes: zbus::PropertyStream<'static, zbus::zvariant::OwnedValue>,
+    owner: zbus::fdo::NameOwnerChangedStream<'static>,
+    value: Option<zbus::zvariant::OwnedValue>,
+}
 
-    // Start updates (best-effort)
-    let _ = p.call_method("ClaimLight", &());
+#[cfg(feature = "linux_iio_proxy")]
+impl ProxyFollower {
+    async fn open(sensor: ProxySensor) -> Result<Self> {
+        let claim = Claim::new(sensor).await?;
+        // Subscribe before the first read, so no change falls in between.
+        let changes = claim
+            .proxy
+            .receive_property_changed(sensor.property())
+            .await;
+        let owner = zbus::fdo::DBusProxy::new(claim.proxy.connection())
+            .await?
+            .receive_name_owner_changed_with_args(&[(0, "net.hadess.SensorProxy")])
+            .await?;
+        let value = claim.proxy.get_property(sensor.property()).await.ok();
+        Ok(Self {
+            _claim: claim,
+            changes,
+            owner,
+            value,
+        })
+    }
+
+    /// Wait for the next tick of `interval`, taking in changes meanwhile;
+    /// false once iio-sensor-proxy has left the bus (its claims with it).
+    async fn tick(&mut self, interval: &mut time::Interval) -> bool {
+        loop {
+            tokio::select! {
+                _ = interval.tick() => return true,
+                Some(change) = self.changes.next() => {
+                    if let Ok(v) = change.get().await {
+                        self.value = Some(v);
+                    }
+                }
+                Some(signal) = self.owner.next() => {
+                    if signal.args().map_or(true, |a| a.new_owner().is_none()) {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// `AccelerometerTilt` in the lid-angle frame, via its tilt class's pitch.
+#[cfg(feature = "linux_iio_proxy")]
+fn proxy_tilt_angle(v: &zbus::zvariant::Value) -> Option<f32> {
+    let pitch = parse_tilt_class(<&str>::try_from(v).ok()?)?;
+    Some(convention::normalize(Source::LinuxTilt, pitch))
+}
 
-    let lux: f64 = p.get_property("LightLevel").ok()?;
-    Some(lux as f32)
+/// `LightLevel`, in lux (or vendor units where `LightLevelUnit` says so).
+#[cfg(feature = "linux_iio_proxy")]
+fn proxy_lux(v: &zbus::zvariant::Value) -> Option<f32> {
+    let lux = f64::try_from(v).ok()? as f32;
+    lux.is_finite().then_some(lux)
 }
 
 fn first_existing(base: &Path, names: &[&str]) -> Option<PathBuf> {
@@ -496,18 +934,73 @@ fn first_existing(base: &Path, names: &[&str]) -> Option<PathBuf> {
     None
 }
 
-fn find_iio_accel_device() -> Option<PathBuf> {
-    for dev in glob::glob("/sys/bus/iio/devices/iio:device*").ok()? {
-        let p = dev.ok()?;
-        // Accept *_raw OR *_input
-        let have_x = first_existing(&p, &["in_accel_x_raw", "in_accel_x_input"]).is_some();
-        let have_y = first_existing(&p, &["in_accel_y_raw", "in_accel_y_input"]).is_some();
-        let have_z = first_existing(&p, &["in_accel_z_raw", "in_accel_z_input"]).is_some();
-        if have_x && have_y && have_z {
-            return Some(p);
-        }
+fn iio_accel_devices(root: &Path) -> Vec<PathBuf> {
+    glob_under(root, "bus/iio/devices/iio:device*")
+        .into_iter()
+        .filter(|p| {
+            // Accept *_raw OR *_input
+            let have_x = first_existing(p, &["in_accel_x_raw", "in_accel_x_input"]).is_some();
+            let have_y = first_existing(p, &["in_accel_y_raw", "in_accel_y_input"]).is_some();
+            let have_z = first_existing(p, &["in_accel_z_raw", "in_accel_z_input"]).is_some();
+            have_x && have_y && have_z
+        })
+        .collect()
+}
+
+/// The lid's accelerometer: the one labelled `accel-display`, else any but
+/// the base's.
+fn find_iio_accel_device(root: &Path) -> Option<PathBuf> {
+    let devs = iio_accel_devices(root);
+    let labelled = |want: &str| {
+        devs.iter()
+            .find(|p| iio_label(p).as_deref() == Some(want))
+            .cloned()
+    };
+    labelled("accel-display").or_else(|| {
+        devs.iter()
+            .find(|p| iio_label(p).as_deref() != Some("accel-base"))
+            .cloned()
+    })
+}
+
+/// The (lid, base) accelerometers of a 2-in-1 that labels both.
+fn find_iio_accel_pair(root: &Path) -> Option<(AccelPanel, AccelPanel)> {
+    let devs = iio_accel_devices(root);
+    let labelled = |want: &str| {
+        devs.iter()
+            .find(|p| iio_label(p).as_deref() == Some(want))
+            .map(|p| AccelPanel::at(p.clone()))
+    };
+    Some((labelled("accel-display")?, labelled("accel-base")?))
+}
+
+/// An IIO device's `label` attribute, e.g. `accel-display`.
+fn iio_label(dev: &Path) -> Option<String> {
+    let label = fs::read_to_string(dev.join("label")).ok()?;
+    Some(label.trim().to_owned())
+}
+
+/// One panel's accelerometer and its mount matrix (identity if the driver
+/// has none), which turns its readings into the panel's own axes.
+#[derive(Clone)]
+struct AccelPanel {
+    dev: PathBuf,
+    mount: [[f32; 3]; 3],
+}
+
+impl AccelPanel {
+    fn at(dev: PathBuf) -> Self {
+        let mount = first_existing(&dev, &["in_accel_mount_matrix", "mount_matrix"])
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|s| parse_mount_matrix(&s))
+            .unwrap_or([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]);
+        Self { dev, mount }
+    }
+
+    /// A reading of this panel's sensor in the panel's axes.
+    fn orient(&self, [x, y, z]: [f32; 3]) -> [f32; 3] {
+        self.mount.map(|r| r[0] * x + r[1] * y + r[2] * z)
     }
-    None
 }
 
 fn read_accel_triplet(dev: &Path) -> Option<(f32, f32, f32)> {
@@ -518,47 +1011,609 @@ fn read_accel_triplet(dev: &Path) -> Option<(f32, f32, f32)> {
     let syp = first_existing(dev, &["in_accel_scale", "in_accel_y_scale"]);
     let szp = first_existing(dev, &["in_accel_scale", "in_accel_z_scale"]);
 
-    let rx = fs::read_to_string(rxp).ok()?.trim().parse::<f32>().ok()?;
-    let ry = fs::read_to_string(ryp).ok()?.trim().parse::<f32>().ok()?;
-    let rz = fs::read_to_string(rzp).ok()?.trim().parse::<f32>().ok()?;
+    let rx = read_attr(rxp)?;
+    let ry = read_attr(ryp)?;
+    let rz = read_attr(rzp)?;
 
     // Some drivers expose per-axis scales; default to 1.0 if absent.
-    let sx = sxp
-        .and_then(|p| fs::read_to_string(p).ok()?.trim().parse::<f32>().ok())
-        .unwrap_or(1.0);
-    let sy = syp
-        .and_then(|p| fs::read_to_string(p).ok()?.trim().parse::<f32>().ok())
-        .unwrap_or(1.0);
-    let sz = szp
-        .and_then(|p| fs::read_to_string(p).ok()?.trim().parse::<f32>().ok())
-        .unwrap_or(1.0);
+    let sx = sxp.and_then(read_attr).unwrap_or(1.0);
+    let sy = syp.and_then(read_attr).unwrap_or(1.0);
+    let sz = szp.and_then(read_attr).unwrap_or(1.0);
 
     Some((rx * sx, ry * sy, rz * sz))
 }
 
-fn find_iio_light_device() -> Option<PathBuf> {
-    for dev in glob::glob("/sys/bus/iio/devices/iio:device*").ok()? {
-        let p = dev.ok()?;
-        // A bunch of ALS variants exist; accept any of these:
-        if first_existing(
-            &p,
-            &[
-                "in_illuminance_raw",
-                "in_illuminance_input",
-                "in_illuminance0_raw",
-                "in_illuminance0_input",
-                "in_intensity_both_raw",
-                "in_intensity_input",
-            ],
-        )
-        .is_some()
-        {
-            return Some(p);
+/// `n` flags for `AccelReader`s allowed to buffer, or none.
+fn buffered_flags(buffer: bool, n: usize) -> Vec<Arc<AtomicBool>> {
+    let n = if buffer { n } else { 0 };
+    (0..n).map(|_| Arc::new(AtomicBool::new(false))).collect()
+}
+
+/// Asks an accelerometer to sample at `hz` (the nearest rate it lists as
+/// available) where its `sampling_frequency` is writable, recording the old
+/// rate in `attrs`, and returns the rate it reports afterwards; `None` if it
+/// has no such attribute.
+fn set_sampling_frequency(dev: &Path, hz: f32, attrs: &mut AttrChanges) -> Option<f32> {
+    let attr = first_existing(dev, &["in_accel_sampling_frequency", "sampling_frequency"])?;
+    let want = first_existing(
+        dev,
+        &[
+            "in_accel_sampling_frequency_available",
+            "sampling_frequency_available",
+        ],
+    )
+    .and_then(|p| fs::read_to_string(p).ok())
+    .and_then(|s| pick_sampling_frequency(&s, hz))
+    .unwrap_or(hz);
+    // Read-only, or a rate the driver refuses: keep what it has.
+    let _ = attrs.set(&attr, &format!("{want}"));
+    read_attr(&attr).filter(|r| *r > 0.0)
+}
+
+/// How long a buffered reader waits for a scan before repeating the last
+/// one: triggered devices can go quiet while the lid is still.
+const BUFFER_IDLE: Duration = Duration::from_millis(500);
+
+/// How long readings must stay within `WAKE_DELTA` of each other before a
+/// reader with threshold events stops reading and waits for one.
+const PARK_AFTER: Duration = Duration::from_secs(3);
+/// Longest a reader stays parked before looking again, in case a wake-up
+/// was missed.
+const PARK_MAX: Duration = Duration::from_secs(60);
+/// How far (m/s², about 3° of tilt) any axis may move before it counts as
+/// motion, both for settling and for the thresholds armed while parked.
+const WAKE_DELTA: f32 = 0.5;
+
+/// Accelerometer readings, each with the time it was taken: `in_accel_*`
+/// attributes polled at `hz`, or with `OpenConfig::linux_iio_buffer` scans
+/// from the device's IIO buffer (`/dev/iio:deviceN`) where it can be
+/// enabled. Once the lid has settled, a device with threshold events is left
+/// alone until they fire.
+struct AccelReader {
+    feed: Feed,
+    roots: Roots,
+    dev: PathBuf,
+    hz: f32,
+    /// Set when buffered reads are allowed; tells whether the feed is one.
+    buffered: Option<Arc<AtomicBool>>,
+    wake: Option<WakeEvents>,
+    /// The reading the lid settled at, and since when.
+    settled: Option<([f32; 3], Instant)>,
+}
+
+enum Feed {
+    Buffered {
+        rx: mpsc::Receiver<([f32; 3], Instant)>,
+        last: Option<[f32; 3]>,
+    },
+    Polled(time::Interval),
+    /// Nothing is read until the events thread reports motion (`true`),
+    /// gives up arming (dropped), or `until` passes; meanwhile `last` is
+    /// repeated like a quiet buffer's.
+    Parked {
+        woken: oneshot::Receiver<bool>,
+        last: [f32; 3],
+        until: Instant,
+    },
+}
+
+impl AccelReader {
+    fn open(roots: &Roots, dev: &Path, hz: f32, buffered: Option<Arc<AtomicBool>>) -> Self {
+        let mut reader = Self {
+            feed: Feed::polled(hz),
+            roots: roots.clone(),
+            dev: dev.to_owned(),
+            hz,
+            buffered,
+            wake: WakeEvents::find(dev),
+            settled: None,
+        };
+        reader.start();
+        reader
+    }
+
+    /// Starts reading again: from the buffer if allowed and it can be
+    /// enabled, else by polling.
+    fn start(&mut self) {
+        let buffer = self.buffered.as_ref().and_then(|buffered| {
+            let rx = AccelBuffer::start(&self.roots, &self.dev, self.hz);
+            buffered.store(rx.is_some(), Ordering::Relaxed);
+            rx
+        });
+        self.feed = match buffer {
+            Some(rx) => Feed::Buffered { rx, last: None },
+            None => Feed::polled(self.hz),
+        };
+    }
+
+    /// The next reading; `None` once the device can't be read.
+    async fn next(&mut self) -> Option<([f32; 3], Instant)> {
+        loop {
+            let (v, at) = match &mut self.feed {
+                Feed::Polled(interval) => {
+                    interval.tick().await;
+                    let (x, y, z) = read_accel_triplet(&self.dev)?;
+                    ([x, y, z], Instant::now())
+                }
+                Feed::Buffered { rx, last } => match time::timeout(BUFFER_IDLE, rx.recv()).await {
+                    Ok(Some((v, at))) => {
+                        *last = Some(v);
+                        (v, at)
+                    }
+                    Ok(None) => return None,
+                    Err(_) => match last {
+                        Some(v) => return Some((*v, Instant::now())),
+                        // The buffer enabled but never filled (no working
+                        // trigger): poll instead.
+                        None => {
+                            if let Some(buffered) = &self.buffered {
+                                buffered.store(false, Ordering::Relaxed);
+                            }
+                            self.feed = Feed::polled(self.hz);
+                            continue;
+                        }
+                    },
+                },
+                Feed::Parked { woken, last, until } => {
+                    let armed = match time::timeout(BUFFER_IDLE, woken).await {
+                        Err(_) if Instant::now() < *until => {
+                            return Some((*last, Instant::now()));
+                        }
+                        // Parked for `PARK_MAX`: read once more anyway.
+                        Err(_) => true,
+                        Ok(woken) => woken.unwrap_or(false),
+                    };
+                    if !armed {
+                        // Events can't be armed here; keep reading.
+                        self.wake = None;
+                    }
+                    self.start();
+                    self.settled = None;
+                    continue;
+                }
+            };
+            self.settle(v);
+            return Some((v, at));
+        }
+    }
+
+    /// Tracks whether the lid has settled at `v`, and parks once it has
+    /// for `PARK_AFTER`.
+    fn settle(&mut self, v: [f32; 3]) {
+        let now = Instant::now();
+        match self.settled {
+            Some((at, since)) if v.iter().zip(at).all(|(v, a)| (v - a).abs() < WAKE_DELTA) => {
+                if now - since < PARK_AFTER {
+                    return;
+                }
+            }
+            _ => {
+                self.settled = Some((v, now));
+                return;
+            }
+        }
+        let Some(wake) = &self.wake else {
+            return;
+        };
+        let (tx, woken) = oneshot::channel();
+        // Stopping the buffer first lets its thread release the device.
+        self.feed = Feed::Parked {
+            woken,
+            last: v,
+            until: now + PARK_MAX,
+        };
+        wake.arm(&self.roots.dev, &self.dev, v, tx);
+    }
+}
+
+impl Feed {
+    fn polled(hz: f32) -> Self {
+        Self::Polled(time::interval(Duration::from_secs_f32(1.0 / hz.max(60.0))))
+    }
+}
+
+/// An accelerometer's per-axis threshold events
+/// (`events/in_accel_x_thresh_rising_en` and so on), armed `WAKE_DELTA`
+/// either side of where the lid settled so the hardware itself reports when
+/// it moves.
+struct WakeEvents {
+    /// `(axis index, ..._en, ..._value, rising, scale)` for each threshold
+    /// the driver has.
+    thresholds: Vec<(usize, PathBuf, PathBuf, bool, f32)>,
+}
+
+impl WakeEvents {
+    fn find(dev: &Path) -> Option<Self> {
+        let events = dev.join("events");
+        let mut thresholds = Vec::new();
+        for (i, axis) in ["x", "y", "z"].into_iter().enumerate() {
+            let scale = first_existing(dev, &["in_accel_scale", &format!("in_accel_{axis}_scale")])
+                .and_then(read_attr)
+                .unwrap_or(1.0);
+            for (dir, rising) in [("rising", true), ("falling", false)] {
+                let en = events.join(format!("in_accel_{axis}_thresh_{dir}_en"));
+                let value = events.join(format!("in_accel_{axis}_thresh_{dir}_value"));
+                if en.exists() && value.exists() {
+                    thresholds.push((i, en, value, rising, scale));
+                }
+            }
+        }
+        (!thresholds.is_empty()).then_some(Self { thresholds })
+    }
+
+    /// Arms the thresholds around `at` and waits on the device's event fd on
+    /// a thread of its own, sending `true` on `woken` at the first event.
+    /// Drops `woken` if there is no event fd or a threshold can't be set;
+    /// disarms them when done. `dev_root` holds the device's node.
+    fn arm(&self, dev_root: &Path, dev: &Path, at: [f32; 3], woken: oneshot::Sender<bool>) {
+        let Some(node) = dev.file_name().map(|n| dev_root.join(n)) else {
+            return;
+        };
+        let thresholds = self.thresholds.clone();
+        let _ = std::thread::Builder::new()
+            .name("booklid-iio-events".into())
+            .spawn(move || {
+                // Events only queue while someone holds the fd, so open it
+                // before arming.
+                let Some(events) = open_event_fd(&node) else {
+                    return;
+                };
+                let mut attrs = AttrChanges::default();
+                for (i, en, value, rising, scale) in &thresholds {
+                    let Some(raw) = accel_threshold_raw(at[*i], WAKE_DELTA, *scale, *rising) else {
+                        return;
+                    };
+                    if attrs.set(value, &format!("{raw}")).is_none() || attrs.set(en, "1").is_none()
+                    {
+                        return;
+                    }
+                }
+                while !woken.is_closed() {
+                    if poll_readable(&events, 200) {
+                        let _ = woken.send(true);
+                        return;
+                    }
+                }
+            });
+    }
+}
+
+/// The IIO event fd for the character device at `node`. The buffer's
+/// reader thread may hold the device for a moment after it is stopped, so
+/// a busy device is retried briefly.
+fn open_event_fd(node: &Path) -> Option<fs::File> {
+    use std::os::fd::{AsRawFd, FromRawFd};
+    /// `IIO_GET_EVENT_FD_IOCTL`: `_IOR('i', 0x90, int)`.
+    const GET_EVENT_FD: std::ffi::c_ulong = 0x8004_6990;
+    const EBUSY: i32 = 16;
+    for _ in 0..10 {
+        match fs::File::open(node) {
+            Ok(dev) => {
+                let mut fd: std::ffi::c_int = -1;
+                // SAFETY: the ioctl writes one int through a valid pointer.
+                let ok = unsafe { ioctl(dev.as_raw_fd(), GET_EVENT_FD, &mut fd) } == 0;
+                // SAFETY: on success `fd` is a new descriptor we now own.
+                return (ok && fd >= 0).then(|| unsafe { fs::File::from_raw_fd(fd) });
+            }
+            Err(e) if e.raw_os_error() == Some(EBUSY) => {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            Err(_) => return None,
         }
     }
     None
 }
 
+/// Attributes written through `set`, put back when dropped.
+#[derive(Default)]
+struct AttrChanges(Vec<(PathBuf, String)>);
+
+impl AttrChanges {
+    /// Writes `value` to `attr`, remembering what was there.
+    fn set(&mut self, attr: &Path, value: &str) -> Option<()> {
+        let prev = fs::read_to_string(attr).ok()?;
+        fs::write(attr, value).ok()?;
+        self.0.push((attr.to_owned(), prev.trim().to_owned()));
+        Some(())
+    }
+
+    /// Puts every attribute back, latest change first.
+    fn undo(&mut self) {
+        for (attr, value) in self.0.drain(..).rev() {
+            let _ = fs::write(attr, value);
+        }
+    }
+}
+
+impl Drop for AttrChanges {
+    fn drop(&mut self) {
+        self.undo();
+    }
+}
+
+/// An accelerometer's IIO buffer, set up through sysfs and read on a thread
+/// of its own. Dropping it puts back every attribute it changed and removes
+/// the hrtimer trigger it created, if any.
+struct AccelBuffer {
+    attrs: AttrChanges,
+    /// The configfs directory of our hrtimer trigger.
+    hrtimer: Option<PathBuf>,
+}
+
+impl AccelBuffer {
+    /// Enables the x/y/z (and timestamp) scan elements, a trigger if the
+    /// device has none (its own, else an hrtimer one at `hz`), and the
+    /// buffer, then streams scaled readings until the receiver is dropped.
+    /// `None` if the device has no buffer or it is already in use
+    /// (iio-sensor-proxy enables it for some sensors).
+    fn start(roots: &Roots, dev: &Path, hz: f32) -> Option<mpsc::Receiver<([f32; 3], Instant)>> {
+        let root = &roots.sys;
+        let scan = dev.join("scan_elements");
+        let enable = dev.join("buffer/enable");
+        if !scan.is_dir() || fs::read_to_string(&enable).ok()?.trim() != "0" {
+            return None;
+        }
+        let node = roots.dev.join(dev.file_name()?);
+        let mut buffer = Self {
+            attrs: AttrChanges::default(),
+            hrtimer: None,
+        };
+
+        for axis in ["x", "y", "z"] {
+            buffer
+                .attrs
+                .set(&scan.join(format!("in_accel_{axis}_en")), "1")?;
+        }
+        // Timestamps are welcome but not required.
+        let _ = buffer.attrs.set(&scan.join("in_timestamp_en"), "1");
+        let trigger = dev.join("trigger/current_trigger");
+        if fs::read_to_string(&trigger).is_ok_and(|t| t.trim().is_empty()) {
+            let name = match find_iio_trigger(root, dev) {
+                Some(name) => name,
+                None => buffer.create_hrtimer(root, dev, hz)?,
+            };
+            buffer.attrs.set(&trigger, &name)?;
+        }
+
+        let layout = ScanLayout::read(dev)?;
+        let clock = fs::read_to_string(dev.join("current_timestamp_clock"))
+            .ok()
+            .and_then(|c| clock_id(c.trim()));
+        let file = fs::File::open(&node).ok()?;
+        buffer.attrs.set(&enable, "1")?;
+
+        let (tx, rx) = mpsc::channel(64);
+        std::thread::Builder::new()
+            .name("booklid-iio-buffer".into())
+            .spawn(move || buffer.pump(file, layout, clock, tx))
+            .ok()?;
+        Some(rx)
+    }
+
+    /// Creates an hrtimer trigger for `dev` through configfs (needs the
+    /// `iio-trig-hrtimer` module and write access to it) ticking at `hz`, so
+    /// scans are timed by the kernel rather than by our polling; its name.
+    fn create_hrtimer(&mut self, root: &Path, dev: &Path, hz: f32) -> Option<String> {
+        let name = format!("booklid-{}", dev.file_name()?.to_str()?);
+        let dir = root.join("kernel/config/iio/triggers/hrtimer").join(&name);
+        match fs::create_dir(&dir) {
+            Ok(()) => {}
+            // Left behind by a process that didn't get to clean up.
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+            Err(_) => return None,
+        }
+        self.hrtimer = Some(dir);
+        let trigger = glob_under(root, "bus/iio/devices/trigger*")
+            .into_iter()
+            .find(|t| fs::read_to_string(t.join("name")).is_ok_and(|n| n.trim() == name))?;
+        fs::write(trigger.join("sampling_frequency"), format!("{hz}")).ok()?;
+        Some(name)
+    }
+
+    fn pump(
+        self,
+        mut file: fs::File,
+        layout: ScanLayout,
+        clock: Option<i32>,
+        tx: mpsc::Sender<([f32; 3], Instant)>,
+    ) {
+        use std::io::Read;
+        let mut buf = vec![0u8; layout.bytes * 16];
+        let mut filled = 0;
+        while !tx.is_closed() {
+            if !poll_readable(&file, 200) {
+                continue;
+            }
+            match file.read(&mut buf[filled..]) {
+                // Nothing new (a fixture file at its end); wait as poll would.
+                Ok(0) => std::thread::sleep(std::time::Duration::from_millis(200)),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(_) => return,
+            }
+            let whole = filled - filled % layout.bytes;
+            for scan in buf[..whole].chunks_exact(layout.bytes) {
+                let Some(reading) = layout.decode(scan, clock) else {
+                    continue;
+                };
+                if tx.blocking_send(reading).is_err() {
+                    return;
+                }
+            }
+            buf.copy_within(whole..filled, 0);
+            filled -= whole;
+        }
+    }
+}
+
+impl Drop for AccelBuffer {
+    fn drop(&mut self) {
+        self.attrs.undo();
+        // Only once no device uses it.
+        if let Some(dir) = &self.hrtimer {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// The trigger an IIO driver registers for its own device (`<name>-dev<N>`),
+/// by name.
+fn find_iio_trigger(root: &Path, dev: &Path) -> Option<String> {
+    let name = fs::read_to_string(dev.join("name")).ok()?;
+    let prefix = format!("{}-dev", name.trim());
+    glob_under(root, "bus/iio/devices/trigger*")
+        .into_iter()
+        .filter_map(|t| fs::read_to_string(t.join("name")).ok())
+        .map(|t| t.trim().to_owned())
+        .find(|t| t.starts_with(&prefix))
+}
+
+/// Where x, y, z and the timestamp sit in each buffer scan.
+struct ScanLayout {
+    axes: [(usize, ScanType, f32); 3],
+    timestamp: Option<(usize, ScanType)>,
+    bytes: usize,
+}
+
+impl ScanLayout {
+    /// From the enabled scan elements' `_index` and `_type` attributes.
+    fn read(dev: &Path) -> Option<Self> {
+        let scan = dev.join("scan_elements");
+        let mut channels = Vec::new();
+        for en in glob_under(&scan, "*_en") {
+            if fs::read_to_string(&en).ok()?.trim() != "1" {
+                continue;
+            }
+            let name = en.file_name()?.to_str()?.strip_suffix("_en")?.to_owned();
+            let index: u32 = fs::read_to_string(scan.join(format!("{name}_index")))
+                .ok()?
+                .trim()
+                .parse()
+                .ok()?;
+            let ty = parse_scan_type(&fs::read_to_string(scan.join(format!("{name}_type"))).ok()?)?;
+            channels.push((index, name, ty));
+        }
+        channels.sort_by_key(|c| c.0);
+        let types: Vec<ScanType> = channels.iter().map(|c| c.2).collect();
+        let (offsets, bytes) = scan_layout(&types);
+        let at = |want: &str| {
+            let i = channels.iter().position(|c| c.1 == want)?;
+            Some((offsets[i], channels[i].2))
+        };
+        let axis = |axis: &str| {
+            let (offset, ty) = at(&format!("in_accel_{axis}"))?;
+            let scale = first_existing(dev, &["in_accel_scale", &format!("in_accel_{axis}_scale")])
+                .and_then(read_attr)
+                .unwrap_or(1.0);
+            Some((offset, ty, scale))
+        };
+        Some(Self {
+            axes: [axis("x")?, axis("y")?, axis("z")?],
+            timestamp: at("in_timestamp"),
+            bytes,
+        })
+    }
+
+    /// One scan's scaled reading and when it was taken: its timestamp on
+    /// `clock` if that is plausible, else now.
+    fn decode(&self, scan: &[u8], clock: Option<i32>) -> Option<([f32; 3], Instant)> {
+        let mut v = [0.0; 3];
+        for (v, (offset, ty, scale)) in v.iter_mut().zip(&self.axes) {
+            *v = ty.decode(scan.get(*offset..)?)? as f32 * scale;
+        }
+        let now = Instant::now();
+        let taken = self
+            .timestamp
+            .zip(clock)
+            .and_then(|((offset, ty), clock)| {
+                let age = clock_now_ns(clock)? - ty.decode(scan.get(offset..)?)?;
+                // Scans older than a second are from a clock we misread.
+                (0..1_000_000_000).contains(&age).then_some(age)
+            })
+            .and_then(|age| now.checked_sub(Duration::from_nanos(age as u64)));
+        Some((v, taken.unwrap_or(now)))
+    }
+}
+
+/// The clock id for an IIO `current_timestamp_clock` name.
+fn clock_id(name: &str) -> Option<i32> {
+    Some(match name {
+        "realtime" => 0,
+        "monotonic" => 1,
+        "monotonic_raw" => 4,
+        "realtime_coarse" => 5,
+        "monotonic_coarse" => 6,
+        "boottime" => 7,
+        "tai" => 11,
+        _ => return None,
+    })
+}
+
+#[repr(C)]
+struct Timespec {
+    tv_sec: std::ffi::c_long,
+    tv_nsec: std::ffi::c_long,
+}
+
+#[repr(C)]
+struct PollFd {
+    fd: std::ffi::c_int,
+    events: i16,
+    revents: i16,
+}
+
+unsafe extern "C" {
+    fn clock_gettime(clock: std::ffi::c_int, tp: *mut Timespec) -> std::ffi::c_int;
+    fn ioctl(fd: std::ffi::c_int, request: std::ffi::c_ulong, ...) -> std::ffi::c_int;
+    fn poll(fds: *mut PollFd, nfds: std::ffi::c_ulong, timeout: std::ffi::c_int)
+    -> std::ffi::c_int;
+}
+
+// `c_long` is already `i64` on 64-bit targets.
+#[allow(clippy::useless_conversion)]
+fn clock_now_ns(clock: i32) -> Option<i64> {
+    let mut ts = Timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` is a valid out pointer for the call.
+    let ok = unsafe { clock_gettime(clock, &mut ts) } == 0;
+    ok.then(|| i64::from(ts.tv_sec) * 1_000_000_000 + i64::from(ts.tv_nsec))
+}
+
+/// Waits up to `timeout_ms` for `file` to have data.
+fn poll_readable(file: &fs::File, timeout_ms: i32) -> bool {
+    use std::os::fd::AsRawFd;
+    const POLLIN: i16 = 0x1;
+    let mut fd = PollFd {
+        fd: file.as_raw_fd(),
+        events: POLLIN,
+        revents: 0,
+    };
+    // SAFETY: one valid pollfd, for the duration of the call.
+    unsafe { poll(&mut fd, 1, timeout_ms) > 0 && fd.revents & POLLIN != 0 }
+}
+
+fn find_iio_light_device(root: &Path) -> Option<PathBuf> {
+    glob_under(root, "bus/iio/devices/iio:device*")
+        .into_iter()
+        .find(|p| {
+            // A bunch of ALS variants exist; accept any of these:
+            first_existing(
+                p,
+                &[
+                    "in_illuminance_raw",
+                    "in_illuminance_input",
+                    "in_illuminance0_raw",
+                    "in_illuminance0_input",
+                    "in_intensity_both_raw",
+                    "in_intensity_input",
+                ],
+            )
+            .is_some()
+        })
+}
+
 fn read_lux(dev: &Path) -> Option<f32> {
     let valp = first_existing(
         dev,
@@ -571,7 +1626,7 @@ fn read_lux(dev: &Path) -> Option<f32> {
             "in_intensity_input",
         ],
     )?;
-    let raw = fs::read_to_string(valp).ok()?.trim().parse::<f32>().ok()?;
+    let raw = read_attr(valp)?;
 
     // Try scale names; fall back to 1.0 if none found.
     let scalep = first_existing(
@@ -583,17 +1638,14 @@ fn read_lux(dev: &Path) -> Option<f32> {
             "in_intensity0_scale",
         ],
     );
-    let scale = scalep
-        .and_then(|p| fs::read_to_string(p).ok()?.trim().parse::<f32>().ok())
-        .unwrap_or(1.0);
+    let scale = scalep.and_then(read_attr).unwrap_or(1.0);
 
     Some(raw * scale)
 }
 
-fn find_hwmon_light_input() -> Option<PathBuf> {
+fn find_hwmon_light_input(root: &Path) -> Option<PathBuf> {
     // Scan for common ALS attributes under hwmon
-    for dev in glob::glob("/sys/class/hwmon/hwmon*").ok()? {
-        let p = dev.ok()?;
+    for p in glob_under(root, "class/hwmon/hwmon*") {
         // Names vary widely across drivers; try multiple patterns
         let candidates = &[
             "illuminance0_input",
@@ -616,14 +1668,18 @@ fn find_hwmon_light_input() -> Option<PathBuf> {
     None
 }
 
+/// `<kind>:<name>` from a sysfs device's `name` attribute, which (unlike
+/// the `iio:deviceN` / `hwmonN` directory) stays the same across boots.
+fn sysfs_hardware_id(kind: &str, dev: &Path) -> Option<String> {
+    let name = fs::read_to_string(dev.join("name")).ok()?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| format!("{kind}:{name}"))
+}
+
 fn read_hwmon_lux(input_file: &Path) -> Option<f32> {
-    // Many hwmon drivers already expose scaled units; just parse as f32
-    let s = fs::read_to_string(input_file).ok()?;
-    // Some drivers expose millilux; try to detect
-    let mut v: f32 = s.trim().parse().ok()?;
-    if v > 10_000.0 {
-        // treat very large values as millilux (best-effort)
-        v /= 1000.0;
-    }
-    Some(v)
+    parse_hwmon_lux(&fs::read_to_string(input_file).ok()?)
+}
+
+fn read_attr(path: impl AsRef<Path>) -> Option<f32> {
+    parse_attr(&fs::read_to_string(path).ok()?)
 }
@@ -1,8 +1,17 @@
-use crate::{AngleDevice, AngleSample, AngleStream, DeviceInfo, Result, Source};
-use futures_util::StreamExt;
+use crate::filters::{self, Baseline, Filter, OutlierRejection, RollingVariance, Smoother, Stages};
+#[cfg(target_os = "macos")]
+use crate::mac::{EventService, Lmu};
+use crate::types::Velocity;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, Backoff, DeviceInfo, Error, LightSample, Result, Source,
+    TaskGuard,
+};
+use futures_util::{StreamExt, stream::BoxStream};
 use std::{
-    collections::VecDeque,
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
     time::Instant,
 };
 use tokio::{
@@ -11,110 +20,187 @@ use tokio::{
 };
 use tokio_stream::wrappers::BroadcastStream;
 
-/// Ambient Light fallback (placeholder signal).
-/// - Streams a normalized “bellows” value in [0.0, 1.0] tagged as ALS.
+/// Apple Silicon ALS service (vendor page, `kIOHIDEventTypeAmbientLightSensor`,
+/// whose first field is the level in lux).
+#[cfg(target_os = "macos")]
+const ALS_USAGE_PAGE: i32 = 0xFF00;
+#[cfg(target_os = "macos")]
+const ALS_USAGE: i32 = 4;
+#[cfg(target_os = "macos")]
+const ALS_EVENT: i64 = 12;
+#[cfg(target_os = "macos")]
+const ALS_LEVEL_FIELD: u32 = (ALS_EVENT as u32) << 16;
+
+/// Where the light level comes from (none off macOS).
+enum LightSensor {
+    /// The ALS service of an `IOHIDEventSystemClient` (Apple Silicon).
+    #[cfg(target_os = "macos")]
+    Event(EventService),
+    /// `AppleLMUController` (Intel MacBooks).
+    #[cfg(target_os = "macos")]
+    Lmu(Lmu),
+}
+
+impl LightSensor {
+    /// The first sensor that gives a reading.
+    fn find() -> Option<Self> {
+        #[cfg(target_os = "macos")]
+        {
+            let event = EventService::find(ALS_USAGE_PAGE, ALS_USAGE).map(Self::Event);
+            let lmu = || Lmu::open().map(Self::Lmu);
+            event
+                .filter(|s| s.lux().is_some())
+                .or_else(|| lmu().filter(|s| s.lux().is_some()))
+        }
+        #[cfg(not(target_os = "macos"))]
+        None
+    }
+
+    /// Current light level: lux from the event service, the mean of the
+    /// two sensors' (uncalibrated) counts from the LMU.
+    fn lux(&self) -> Option<f32> {
+        match *self {
+            #[cfg(target_os = "macos")]
+            Self::Event(ref s) => s.read(ALS_EVENT, ALS_LEVEL_FIELD).map(|v| v as f32),
+            #[cfg(target_os = "macos")]
+            Self::Lmu(ref l) => l.read().map(|[a, b]| (a as f32 + b as f32) / 2.0),
+        }
+    }
+}
+
+/// Ambient Light fallback.
+/// - Streams a normalized “bellows” value in [0.0, 1.0] tagged as ALS,
+///   from changes in the Mac's ambient light level.
 /// - `AngleSample.angle_deg` carries the normalized value (NOT degrees).
+/// - The underlying light level is published on `subscribe_lux`.
 /// - Confidence grows as the signal stabilizes (simple rolling-variance heuristic).
 pub struct AlsAngle {
     latest: Arc<Mutex<Option<AngleSample>>>,
     tx: broadcast::Sender<AngleSample>,
-    alpha: Arc<Mutex<f32>>,
+    lux: broadcast::Sender<LightSample>,
+    task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
     conf: Arc<Mutex<f32>>,
+    paused: Arc<AtomicBool>,
 }
 
 impl AlsAngle {
-    pub async fn open(hz: f32) -> Result<Self> {
+    /// Fails at once if this Mac has no light sensor that answers; after
+    /// that the sensor is re-found per `backoff` whenever reads stop.
+    pub async fn open(hz: f32, backoff: Backoff) -> Result<Self> {
+        let found = tokio::task::spawn_blocking(LightSensor::find)
+            .await
+            .ok()
+            .flatten()
+            .ok_or_else(|| Error::Backend("mac_als: no ambient light sensor found".into()))?;
+        let found = Arc::new(Mutex::new(Some(found)));
+
         let latest = Arc::new(Mutex::new(None));
         let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha: Arc<Mutex<f32>> = Arc::new(Mutex::new(0.25));
+        let (lux, _rx) = broadcast::channel::<LightSample>(256);
+        let stages = Arc::new(Mutex::new(Stages::default()));
         let conf: Arc<Mutex<f32>> = Arc::new(Mutex::new(0.2));
+        let paused = Arc::new(AtomicBool::new(false));
 
         // clones for task
         let latest_c = Arc::clone(&latest);
         let tx_c = tx.clone();
-        let alpha_c = Arc::clone(&alpha);
+        let lux_c = lux.clone();
+        let stages_c = Arc::clone(&stages);
         let conf_c = Arc::clone(&conf);
+        let paused_c = Arc::clone(&paused);
 
-        // Target rate and simple high-pass + normalization model.
         let target_hz: f32 = hz.max(10.0); // ALS is fine around 10–60 Hz
 
-        tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / target_hz));
-            let mut t = 0.0f32;
-            let mut baseline = 0.5f32; // slow baseline
-            let mut smoothed: Option<f32> = None;
-
-            // Confidence via rolling variance on last N samples
-            const CAP: usize = 64;
-            let mut buf: VecDeque<f32> = VecDeque::with_capacity(CAP);
-
-            loop {
-                interval.tick().await;
-                t += 0.03;
-
-                // Placeholder signal: smoothly varying value in [0,1].
-                // Later: replace with real ALS Δlux and normalization.
-                let raw = 0.5 + 0.45 * t.sin() * (1.0 + 0.2 * (0.6 * t).sin());
-
-                // Slow LPF baseline to simulate drift removal (high-pass-ish)
-                baseline = 0.995 * baseline + 0.005 * raw;
-                let mut val = raw - baseline;
-
-                // Normalize to [0,1]
-                val = (val * 3.0 + 0.5).clamp(0.0, 1.0);
-
-                // Apply user EMA smoothing
-                let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                let s = match smoothed {
-                    None => val,
-                    Some(prev) => prev + a * (val - prev),
-                };
-                smoothed = Some(s);
-
-                let sample = AngleSample {
-                    angle_deg: s, // NOT degrees; normalized 0..1
-                    timestamp: Instant::now(),
-                    source: Source::ALS,
-                };
-
-                // Update latest & broadcast
-                *latest_c.lock().unwrap() = Some(sample);
-                let _ = tx_c.send(sample);
-
-                // Update confidence from rolling variance (stable => high)
-                if buf.len() == CAP {
-                    buf.pop_front();
+        let task = TaskGuard::supervised(backoff, move || {
+            let found = found.clone();
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let lux_c = lux_c.clone();
+            let stages_c = stages_c.clone();
+            let conf_c = conf_c.clone();
+            let paused_c = paused_c.clone();
+            async move {
+                let mut sensor = found.lock().unwrap_or_else(PoisonError::into_inner).take();
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / target_hz));
+                let mut baseline = Baseline::new(10.0);
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut variance = RollingVariance::default();
+                let mut attempt = 0u32;
+
+                loop {
+                    interval.tick().await;
+                    if paused_c.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let Some(lux) = sensor.as_ref().and_then(LightSensor::lux) else {
+                        #[cfg(feature = "diagnostics")]
+                        eprintln!("[booklid] ALS read failed; re-finding the sensor…");
+                        sensor = LightSensor::find();
+                        let Some(d) = backoff.delay(attempt) else {
+                            return;
+                        };
+                        attempt += 1;
+                        time::sleep(d).await;
+                        continue;
+                    };
+                    attempt = 0;
+
+                    // Slow baseline removes drift (high-pass-ish), then normalize to [0,1]
+                    let val = filters::bellows(baseline.update(lux), 0.02);
+
+                    let now = Instant::now();
+                    let Some(s) = smoother.apply(
+                        *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                        val,
+                        now,
+                    ) else {
+                        continue;
+                    };
+
+                    // Confidence from rolling variance (stable => high)
+                    let var = variance.push(s);
+                    let stability = filters::stability(var, 20.0);
+                    *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = stability;
+
+                    let sample = AngleSample {
+                        angle_deg: s, // NOT degrees; normalized 0..1
+                        raw: val,
+                        timestamp: now,
+                        source: Source::ALS,
+                        confidence: stability,
+                        velocity_deg_s: velocity.update(val, now),
+                    };
+
+                    let _ = lux_c.send(LightSample {
+                        lux,
+                        timestamp: sample.timestamp,
+                        source: Source::ALS,
+                    });
+
+                    // Update latest & broadcast
+                    *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                    let _ = tx_c.send(sample);
                 }
-                buf.push_back(s);
-                let n = buf.len() as f32;
-                let mean = buf.iter().copied().sum::<f32>() / n;
-                let var = buf
-                    .iter()
-                    .map(|v| {
-                        let d = *v - mean;
-                        d * d
-                    })
-                    .sum::<f32>()
-                    / n;
-
-                // Tunable mapping: 1 / (1 + k * var)
-                let stability = 1.0 / (1.0 + 20.0 * var);
-                *conf_c.lock().unwrap() = stability.clamp(0.0, 1.0);
             }
         });
 
         Ok(Self {
             latest,
             tx,
-            alpha,
+            lux,
+            task,
+            stages,
             conf,
+            paused,
         })
     }
 }
 
 impl AngleDevice for AlsAngle {
     fn latest(&self) -> Option<AngleSample> {
-        *self.latest.lock().unwrap()
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
     }
 
     fn subscribe(&self) -> AngleStream {
@@ -123,18 +209,46 @@ impl AngleDevice for AlsAngle {
             .boxed()
     }
 
+    fn subscribe_lux(&self) -> BoxStream<'static, LightSample> {
+        BroadcastStream::new(self.lux.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
     fn set_smoothing(&self, alpha: f32) {
-        *self.alpha.lock().unwrap() = alpha;
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = Filter::Ema { alpha };
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = filter;
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .outliers = outliers;
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
     }
 
     fn confidence(&self) -> f32 {
-        *self.conf.lock().unwrap()
+        *self.conf.lock().unwrap_or_else(PoisonError::into_inner)
     }
 
     fn info(&self) -> DeviceInfo {
-        DeviceInfo {
-            source: Source::ALS,
-            note: "mac_als",
-        }
+        DeviceInfo::new(Source::ALS, "mac_als")
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
     }
 }
@@ -0,0 +1,219 @@
+use crate::filters::{Filter, OutlierRejection, Smoother, Stages};
+use crate::mac::EventService;
+use crate::types::Velocity;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, Backoff, DeviceInfo, Error, Result, Source, TaskGuard,
+};
+use futures_util::{StreamExt, stream::BoxStream};
+use std::sync::{
+    Arc, Mutex, PoisonError,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::Instant;
+use tokio::{
+    sync::broadcast,
+    time::{self, Duration},
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+/// HID sensor usage page and the usage Apple Silicon MacBooks give the lid
+/// angle sensor's event service.
+const SENSOR_USAGE_PAGE: i32 = 0x20;
+const HINGE_USAGE: i32 = 0x8A;
+/// `kIOHIDEventTypeOrientation`; the hinge service reports its angle (in
+/// degrees) as the event's first field.
+const ORIENTATION_EVENT: i64 = 10;
+const ANGLE_FIELD: u32 = (ORIENTATION_EVENT as u32) << 16;
+
+/// The lid angle service, if this Mac has one.
+fn find_hinge() -> Option<EventService> {
+    EventService::find(SENSOR_USAGE_PAGE, HINGE_USAGE)
+}
+
+/// Current lid angle in degrees; `None` when the service has no event.
+fn read_angle(service: &EventService) -> Option<f32> {
+    service
+        .read(ORIENTATION_EVENT, ANGLE_FIELD)
+        .map(|v| v as f32)
+}
+
+/// `read_angle` on the blocking pool, since IOKit calls take as long as the
+/// driver does; hands `service` back with the reading.
+async fn read_blocking(service: Option<EventService>) -> (Option<EventService>, Option<f32>) {
+    tokio::task::spawn_blocking(move || {
+        let read = service.as_ref().and_then(read_angle);
+        (service, read)
+    })
+    .await
+    .unwrap_or((None, None))
+}
+
+/// Apple Silicon lid angle through `IOHIDEventSystemClient`, for MacBooks
+/// whose hinge sensor is an event service rather than a feature report.
+pub struct IoKitAngle {
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
+    reconnecting: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+}
+
+impl IoKitAngle {
+    /// Fails at once if no hinge service answers; after that the sensor is
+    /// re-found per `backoff` whenever reads stop.
+    pub async fn open(hz: f32, backoff: Backoff) -> Result<Self> {
+        let found =
+            tokio::task::spawn_blocking(|| find_hinge().filter(|s| read_angle(s).is_some()))
+                .await
+                .ok()
+                .flatten()
+                .ok_or_else(|| Error::Backend("iokit: no lid angle service found".into()))?;
+        let found = Arc::new(Mutex::new(Some(found)));
+
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let stages = Arc::new(Mutex::new(Stages::default()));
+        let reconnecting = Arc::new(AtomicBool::new(false));
+        let paused = Arc::new(AtomicBool::new(false));
+
+        let latest_c = Arc::clone(&latest);
+        let tx_c = tx.clone();
+        let stages_c = Arc::clone(&stages);
+        let reconnecting_c = Arc::clone(&reconnecting);
+        let paused_c = Arc::clone(&paused);
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let found = found.clone();
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let stages_c = stages_c.clone();
+            let reconnecting_c = reconnecting_c.clone();
+            let paused_c = paused_c.clone();
+            async move {
+                let mut service = found.lock().unwrap_or_else(PoisonError::into_inner).take();
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let target_hz = if hz.is_finite() && hz > 0.0 { hz } else { 60.0 };
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / target_hz));
+                let mut attempt = 0u32;
+
+                loop {
+                    interval.tick().await;
+                    if paused_c.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let read;
+                    (service, read) = read_blocking(service.take()).await;
+                    match read {
+                        // A glitch, not a lost sensor.
+                        Some(angle_deg) if !(0.0..=360.0).contains(&angle_deg) => {}
+                        Some(angle_deg) => {
+                            reconnecting_c.store(false, Ordering::Relaxed);
+                            attempt = 0;
+
+                            let now = Instant::now();
+                            let Some(s) = smoother.apply(
+                                *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                                angle_deg,
+                                now,
+                            ) else {
+                                continue;
+                            };
+
+                            let sample = AngleSample {
+                                angle_deg: s,
+                                raw: angle_deg,
+                                timestamp: now,
+                                source: Source::HingeIOKit,
+                                confidence: 1.0,
+                                velocity_deg_s: velocity.update(angle_deg, now),
+                            };
+
+                            *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                            let _ = tx_c.send(sample);
+                        }
+                        None => {
+                            reconnecting_c.store(true, Ordering::Relaxed);
+                            #[cfg(feature = "diagnostics")]
+                            eprintln!("[booklid] iokit read failed; re-finding the service…");
+                            service = tokio::task::spawn_blocking(find_hinge).await.ok().flatten();
+                            let Some(d) = backoff.delay(attempt) else {
+                                #[cfg(feature = "diagnostics")]
+                                eprintln!(
+                                    "[booklid] giving up on iokit after {} attempts",
+                                    attempt
+                                );
+                                return;
+                            };
+                            attempt += 1;
+                            time::sleep(d).await;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            latest,
+            tx,
+            task,
+            stages,
+            reconnecting,
+            paused,
+        })
+    }
+}
+
+impl AngleDevice for IoKitAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn set_smoothing(&self, alpha: f32) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = Filter::Ema { alpha };
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = filter;
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .outliers = outliers;
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    fn confidence(&self) -> f32 {
+        1.0
+    }
+
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(Source::HingeIOKit, "mac_iokit")
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        self.reconnecting.load(Ordering::Relaxed)
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
+    }
+}
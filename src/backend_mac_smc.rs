@@ -0,0 +1,202 @@
+use crate::filters::{Filter, OutlierRejection, Smoother, Stages};
+use crate::mac::Smc;
+use crate::types::Velocity;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, Backoff, DeviceInfo, Error, Result, Source, TaskGuard,
+};
+use futures_util::{StreamExt, stream::BoxStream};
+use std::sync::{
+    Arc, Mutex, PoisonError,
+    atomic::{AtomicBool, Ordering},
+};
+use std::time::Instant;
+use tokio::{
+    sync::broadcast,
+    time::{self, Duration},
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+/// The SMC key MacBooks that publish the lid angle there use, in degrees.
+const LID_ANGLE_KEY: [u8; 4] = *b"LidA";
+
+/// Current lid angle in degrees; `None` when the key can't be read.
+fn read_angle(smc: &Smc) -> Option<f32> {
+    smc.read_f32(LID_ANGLE_KEY)
+}
+
+/// `read_angle` on the blocking pool, since SMC calls go through the
+/// kernel; hands `smc` back with the reading.
+async fn read_blocking(smc: Option<Smc>) -> (Option<Smc>, Option<f32>) {
+    tokio::task::spawn_blocking(move || {
+        let read = smc.as_ref().and_then(read_angle);
+        (smc, read)
+    })
+    .await
+    .unwrap_or((None, None))
+}
+
+/// Lid angle from an SMC key, for MacBooks that expose it there rather
+/// than as a HID report.
+pub struct SmcAngle {
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
+    reconnecting: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+}
+
+impl SmcAngle {
+    /// Fails at once if the SMC has no lid angle key; after that the SMC is
+    /// re-opened per `backoff` whenever reads stop.
+    pub async fn open(hz: f32, backoff: Backoff) -> Result<Self> {
+        let found = tokio::task::spawn_blocking(|| Smc::open().filter(|s| read_angle(s).is_some()))
+            .await
+            .ok()
+            .flatten()
+            .ok_or_else(|| Error::Backend("smc: no lid angle key found".into()))?;
+        let found = Arc::new(Mutex::new(Some(found)));
+
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let stages = Arc::new(Mutex::new(Stages::default()));
+        let reconnecting = Arc::new(AtomicBool::new(false));
+        let paused = Arc::new(AtomicBool::new(false));
+
+        let latest_c = Arc::clone(&latest);
+        let tx_c = tx.clone();
+        let stages_c = Arc::clone(&stages);
+        let reconnecting_c = Arc::clone(&reconnecting);
+        let paused_c = Arc::clone(&paused);
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let found = found.clone();
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let stages_c = stages_c.clone();
+            let reconnecting_c = reconnecting_c.clone();
+            let paused_c = paused_c.clone();
+            async move {
+                let mut smc = found.lock().unwrap_or_else(PoisonError::into_inner).take();
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let target_hz = if hz.is_finite() && hz > 0.0 { hz } else { 60.0 };
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / target_hz));
+                let mut attempt = 0u32;
+
+                loop {
+                    interval.tick().await;
+                    if paused_c.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let read;
+                    (smc, read) = read_blocking(smc.take()).await;
+                    match read {
+                        // A glitch, not a lost sensor.
+                        Some(angle_deg) if !(0.0..=360.0).contains(&angle_deg) => {}
+                        Some(angle_deg) => {
+                            reconnecting_c.store(false, Ordering::Relaxed);
+                            attempt = 0;
+
+                            let now = Instant::now();
+                            let Some(s) = smoother.apply(
+                                *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                                angle_deg,
+                                now,
+                            ) else {
+                                continue;
+                            };
+
+                            let sample = AngleSample {
+                                angle_deg: s,
+                                raw: angle_deg,
+                                timestamp: now,
+                                source: Source::HingeSMC,
+                                confidence: 1.0,
+                                velocity_deg_s: velocity.update(angle_deg, now),
+                            };
+
+                            *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                            let _ = tx_c.send(sample);
+                        }
+                        None => {
+                            reconnecting_c.store(true, Ordering::Relaxed);
+                            #[cfg(feature = "diagnostics")]
+                            eprintln!("[booklid] smc read failed; re-opening…");
+                            smc = tokio::task::spawn_blocking(Smc::open).await.ok().flatten();
+                            let Some(d) = backoff.delay(attempt) else {
+                                #[cfg(feature = "diagnostics")]
+                                eprintln!("[booklid] giving up on smc after {} attempts", attempt);
+                                return;
+                            };
+                            attempt += 1;
+                            time::sleep(d).await;
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            latest,
+            tx,
+            task,
+            stages,
+            reconnecting,
+            paused,
+        })
+    }
+}
+
+impl AngleDevice for SmcAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn set_smoothing(&self, alpha: f32) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = Filter::Ema { alpha };
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = filter;
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .outliers = outliers;
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    fn confidence(&self) -> f32 {
+        1.0
+    }
+
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(Source::HingeSMC, "mac_smc")
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        self.reconnecting.load(Ordering::Relaxed)
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
+    }
+}
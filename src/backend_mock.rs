@@ -1,8 +1,14 @@
 // src/backend_mock.rs
-use crate::{AngleDevice, AngleSample, AngleStream, Source};
+use crate::filters::{Filter, OutlierRejection, Smoother, Stages};
+use crate::types::Velocity;
+use crate::{AngleDevice, AngleSample, AngleStream, Backoff, Source, TaskGuard};
+use futures_util::stream::BoxStream;
 // use futures_util::StreamExt;
 use std::{
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
     time::Instant,
 };
 use tokio::{
@@ -14,58 +20,85 @@ use tokio::{
 pub struct MockAngle {
     latest: Arc<Mutex<Option<AngleSample>>>,
     tx: broadcast::Sender<AngleSample>,
-    alpha: Arc<Mutex<f32>>,
+    task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
 }
 
 impl MockAngle {
     pub async fn open(hz: f32) -> crate::Result<Self> {
         let latest = Arc::new(Mutex::new(None));
         let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha = Arc::new(Mutex::new(0.25));
+        let stages = Arc::new(Mutex::new(Stages::default()));
 
         let latest_c = Arc::clone(&latest);
         let tx_c = tx.clone();
-        let alpha_c = Arc::clone(&alpha);
+        let stages_c = Arc::clone(&stages);
+
+        // Test hook: panic once after N samples to exercise the supervisor.
+        let panic_after = std::env::var("BOOKLID_MOCK_PANIC_AFTER")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok());
+        let panicked = Arc::new(AtomicBool::new(false));
 
         // Generate a smooth, slightly modulated waveform around ~95–115°
         let target_hz = hz.max(1.0);
-        tokio::spawn(async move {
-            let mut t = 0.0f32;
-            let mut smoothed: Option<f32> = None;
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / target_hz));
-            loop {
-                interval.tick().await;
-                t += 0.04;
-                let angle = 95.0 + 20.0 * (t).sin() + 0.5 * (3.7 * t).sin();
-
-                // Apply EMA smoothing like the HID backend
-                let a = {
-                    let a: f32 = *alpha_c.lock().unwrap();
-                    a.clamp(0.0f32, 1.0f32)
-                };
-                let s = match smoothed {
-                    None => angle,
-                    Some(prev) => prev + a * (angle - prev),
-                };
-                smoothed = Some(s);
-
-                let sample = AngleSample {
-                    angle_deg: s,
-                    timestamp: Instant::now(),
-                    source: Source::Mock,
-                };
-                *latest_c.lock().unwrap() = Some(sample);
-                let _ = tx_c.send(sample);
+        let task = TaskGuard::supervised(Backoff::default(), move || {
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let stages_c = stages_c.clone();
+            let panicked = panicked.clone();
+            async move {
+                let mut t = 0.0f32;
+                let mut n = 0u32;
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / target_hz));
+                loop {
+                    interval.tick().await;
+                    n += 1;
+                    if panic_after == Some(n) && !panicked.swap(true, Ordering::Relaxed) {
+                        // Holding the lock, so the restarted sampler finds it poisoned.
+                        let _latest = latest_c.lock().unwrap_or_else(PoisonError::into_inner);
+                        panic!("mock sampler panic (BOOKLID_MOCK_PANIC_AFTER)");
+                    }
+                    t += 0.04;
+                    let angle = 95.0 + 20.0 * (t).sin() + 0.5 * (3.7 * t).sin();
+
+                    let now = Instant::now();
+                    let Some(s) = smoother.apply(
+                        *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                        angle,
+                        now,
+                    ) else {
+                        continue;
+                    };
+
+                    let sample = AngleSample {
+                        angle_deg: s,
+                        raw: angle,
+                        timestamp: now,
+                        source: Source::Mock,
+                        confidence: 1.0,
+                        velocity_deg_s: velocity.update(angle, now),
+                    };
+                    *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                    let _ = tx_c.send(sample);
+                }
             }
         });
 
-        Ok(Self { latest, tx, alpha })
+        Ok(Self {
+            latest,
+            tx,
+            task,
+            stages,
+        })
     }
 }
 
 impl AngleDevice for MockAngle {
     fn latest(&self) -> Option<AngleSample> {
-        *self.latest.lock().unwrap()
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
     }
 
     fn subscribe(&self) -> AngleStream {
@@ -77,7 +110,24 @@ impl AngleDevice for MockAngle {
     }
 
     fn set_smoothing(&self, alpha: f32) {
-        *self.alpha.lock().unwrap() = alpha;
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = Filter::Ema { alpha };
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = filter;
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .outliers = outliers;
     }
 
     fn confidence(&self) -> f32 {
@@ -85,9 +135,10 @@ impl AngleDevice for MockAngle {
     }
 
     fn info(&self) -> crate::DeviceInfo {
-        crate::DeviceInfo {
-            source: Source::Mock,
-            note: "mock",
-        }
+        crate::DeviceInfo::new(Source::Mock, "mock")
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
     }
 }
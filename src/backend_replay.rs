@@ -0,0 +1,253 @@
+use crate::filters::{Filter, OutlierRejection, Smoother, Stages};
+use crate::record::Recording;
+use crate::{AngleDevice, AngleSample, AngleStream, DeviceInfo, Error, Result, Source, TaskGuard};
+use futures_util::StreamExt;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::{
+    sync::{Notify, broadcast},
+    time,
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+/// Playback rates accepted by `ReplayControl::set_speed`.
+pub const SPEED_RANGE: std::ops::RangeInclusive<f32> = 0.5..=100.0;
+
+#[derive(Debug)]
+struct Playback {
+    speed: f32,
+    paused: bool,
+    seek: Option<Duration>,
+    /// Offset of the last sample played.
+    position: Duration,
+}
+
+/// Controls a replay opened with `open_replay_with_control`: playback rate,
+/// pause, and seeking. Clones control the same replay.
+#[derive(Clone, Debug)]
+pub struct ReplayControl {
+    state: Arc<Mutex<Playback>>,
+    wake: Arc<Notify>,
+    started_at: SystemTime,
+    duration: Duration,
+}
+
+impl ReplayControl {
+    /// Play at `speed` times the recorded rate (0.5 to 100). Sample
+    /// timestamps are spaced at the new rate from here on.
+    pub fn set_speed(&self, speed: f32) -> Result<()> {
+        if !SPEED_RANGE.contains(&speed) {
+            return Err(Error::Other(format!(
+                "replay speed {speed} outside {}..={}",
+                SPEED_RANGE.start(),
+                SPEED_RANGE.end()
+            )));
+        }
+        self.update(|p| p.speed = speed);
+        Ok(())
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.state.lock().unwrap().speed
+    }
+
+    /// Stop delivering samples until `resume`.
+    pub fn pause(&self) {
+        self.update(|p| p.paused = true);
+    }
+
+    pub fn resume(&self) {
+        self.update(|p| p.paused = false);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.lock().unwrap().paused
+    }
+
+    /// Continue from the first sample at or after `offset`. Seeking past the
+    /// end leaves playback idle until the next seek; it does not unpause.
+    pub fn seek(&self, offset: Duration) {
+        self.update(|p| p.seek = Some(offset));
+    }
+
+    /// Seek to a wall-clock time on the recording's timeline (see
+    /// `Recording::wall_time`); times before the start seek to the start.
+    pub fn seek_to(&self, t: SystemTime) {
+        self.seek(t.duration_since(self.started_at).unwrap_or_default());
+    }
+
+    /// Offset of the last sample played.
+    pub fn position(&self) -> Duration {
+        self.state.lock().unwrap().position
+    }
+
+    /// Offset of the recording's last sample.
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Playback)) {
+        f(&mut self.state.lock().unwrap());
+        self.wake.notify_one();
+    }
+}
+
+/// Plays a recording back, in real time unless its `ReplayControl` says
+/// otherwise. Timestamps are rebuilt from the moment playback starts plus
+/// each sample's recorded offset (scaled by the playback rate), so intervals
+/// match the original trace at 1×.
+pub struct ReplayAngle {
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    _task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
+    source: Source,
+    control: ReplayControl,
+}
+
+impl ReplayAngle {
+    pub fn open(rec: Recording) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        // Recorded values are already smoothed; replay them verbatim by default.
+        let stages = Arc::new(Mutex::new(Stages {
+            filter: Filter::Ema { alpha: 1.0 },
+            outliers: None,
+        }));
+        let source = rec.header.source;
+        let control = ReplayControl {
+            state: Arc::new(Mutex::new(Playback {
+                speed: 1.0,
+                paused: false,
+                seek: None,
+                position: Duration::ZERO,
+            })),
+            wake: Arc::new(Notify::new()),
+            started_at: rec.header.started_at(),
+            duration: rec.samples.last().map_or(Duration::ZERO, |s| s.offset),
+        };
+
+        let latest_c = Arc::clone(&latest);
+        let tx_c = tx.clone();
+        let stages_c = Arc::clone(&stages);
+        let ctl = control.clone();
+
+        let task = TaskGuard::spawn(async move {
+            let base = Instant::now();
+            let samples: Vec<AngleSample> = rec.samples_at(base).collect();
+            let offsets: Vec<Duration> = rec.samples.iter().map(|s| s.offset).collect();
+            // Playback maps offset `anchor.1` to instant `anchor.0`; samples
+            // after it are due `(offset - anchor.1) / speed` later.
+            let mut anchor = (base, Duration::ZERO);
+            let mut speed = 1.0f32;
+            let mut was_paused = false;
+            let mut next = 0usize;
+            let mut smoother = Smoother::default();
+            loop {
+                let (want_speed, paused, seek) = {
+                    let mut p = ctl.state.lock().unwrap();
+                    (p.speed, p.paused, p.seek.take())
+                };
+                let now = Instant::now();
+                if let Some(to) = seek {
+                    next = offsets.partition_point(|o| *o < to);
+                    anchor = (now, to);
+                    smoother = Smoother::default();
+                } else if was_paused {
+                    // Nothing played while paused.
+                    anchor.0 = now;
+                } else if paused || want_speed != speed {
+                    // Re-anchor at the current playhead so neither pausing nor
+                    // a rate change skips or repeats part of the trace.
+                    let played = now
+                        .saturating_duration_since(anchor.0)
+                        .mul_f64(f64::from(speed));
+                    let head = offsets
+                        .get(next)
+                        .map_or(anchor.1, |o| (anchor.1 + played).min(*o));
+                    anchor = (now, head);
+                }
+                was_paused = paused;
+                speed = want_speed;
+                let Some(&due_sample) = samples.get(next).filter(|_| !paused) else {
+                    ctl.wake.notified().await;
+                    continue;
+                };
+
+                let due = anchor.0 + scale(offsets[next].saturating_sub(anchor.1), speed);
+                tokio::select! {
+                    _ = time::sleep_until(due.into()) => {}
+                    _ = ctl.wake.notified() => continue,
+                }
+
+                let mut sample = due_sample;
+                sample.timestamp = due;
+                let smoothed = smoother.apply(*stages_c.lock().unwrap(), sample.angle_deg, due);
+
+                ctl.state.lock().unwrap().position = offsets[next];
+                next += 1;
+                let Some(angle) = smoothed else { continue };
+                sample.angle_deg = angle;
+                *latest_c.lock().unwrap() = Some(sample);
+                let _ = tx_c.send(sample);
+            }
+        });
+
+        Self {
+            latest,
+            tx,
+            _task: task,
+            stages,
+            source,
+            control,
+        }
+    }
+
+    pub fn control(&self) -> ReplayControl {
+        self.control.clone()
+    }
+}
+
+/// `d` at `speed`× playback; exact at 1× so replayed spacing matches the
+/// recording to the nanosecond.
+fn scale(d: Duration, speed: f32) -> Duration {
+    if speed == 1.0 {
+        d
+    } else {
+        d.div_f64(f64::from(speed))
+    }
+}
+
+impl AngleDevice for ReplayAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap()
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn set_smoothing(&self, alpha: f32) {
+        self.stages.lock().unwrap().filter = Filter::Ema { alpha };
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages.lock().unwrap().filter = filter;
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages.lock().unwrap().outliers = outliers;
+    }
+
+    fn confidence(&self) -> f32 {
+        self.latest().map_or(0.0, |s| s.confidence)
+    }
+
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(self.source, "replay")
+    }
+}
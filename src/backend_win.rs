@@ -1,311 +1,468 @@
 #![cfg(all(target_os = "windows", feature = "win_sensors"))]
 
-use crate::{AngleDevice, AngleSample, AngleStream, DeviceInfo, Error, Result, Source};
-use futures_util::StreamExt;
-use std::{
-    sync::{Arc, Mutex},
-    time::Instant,
-};
-use tokio::{
-    sync::broadcast,
-    time::{self, Duration},
-};
-use tokio_stream::wrappers::BroadcastStream;
+//! WinRT adapters for `win::WinSensor`; the sampling logic lives in `win`.
+
+use crate::convention::hinge_from_gravity_near;
+use crate::win::{WinAngle, WinReporting, WinSensor, WinSensorKind};
+use crate::{Backoff, Error, Result};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::sync::Notify;
+use windows::Devices::Enumeration::{DeviceInformation, Panel};
+use windows::Devices::Sensors::Custom::{CustomSensor, CustomSensorReadingChangedEventArgs};
 use windows::Devices::Sensors::{
-    HingeAngleSensor, HingeAngleSensorReadingChangedEventArgs, Inclinometer, LightSensor,
+    Accelerometer, AccelerometerReadingType, HingeAngleSensor,
+    HingeAngleSensorReadingChangedEventArgs, Inclinometer, InclinometerReadingChangedEventArgs,
+    LightSensor, LightSensorReadingChangedEventArgs,
 };
-use windows::Foundation::TypedEventHandler;
-
-pub struct WinAngle {
-    latest: Arc<Mutex<Option<AngleSample>>>,
-    tx: broadcast::Sender<AngleSample>,
-    alpha: Arc<Mutex<f32>>,
-    conf: Arc<Mutex<f32>>,
-    src: Source,
-    note: &'static str,
+use windows::Foundation::{IPropertyValue, TypedEventHandler};
+use windows::core::{GUID, HSTRING, Interface};
+
+/// `interval` in whole milliseconds, no shorter than the sensor allows.
+fn report_interval(interval: Duration, minimum: windows::core::Result<u32>) -> u32 {
+    let ms = u32::try_from(interval.as_millis()).unwrap_or(u32::MAX);
+    ms.max(minimum.unwrap_or(0))
 }
 
-impl WinAngle {
-    pub async fn open_hinge(hz: f32) -> Result<Self> {
-        // WinRT async ops (IAsyncOperation<T>) are not Rust Futures in windows-rs 0.58,
-        // so use `.get()` to block until completion.
-        let sensor = HingeAngleSensor::GetDefaultAsync()
-            .map_err(|e| Error::Backend(format!("win hinge: {e:?}")))?
-            .get()
-            .map_err(|e| Error::Backend(format!("win hinge: {e:?}")))?;
+/// Event-driven: `ReadingChanged` updates a cell that `read()` returns, and
+/// wakes the sampler once it watches.
+struct Hinge {
+    sensor: HingeAngleSensor,
+    angle: Arc<Mutex<Option<f32>>>,
+    notify: Arc<Mutex<Option<Arc<Notify>>>>,
+    reporting: WinReporting,
+}
 
-        Self::spawn_from_hinge(sensor, hz).await
+impl Hinge {
+    fn new(sensor: HingeAngleSensor, reporting: WinReporting) -> Result<Self> {
+        let angle = Arc::new(Mutex::new(None::<f32>));
+        let notify = Arc::new(Mutex::new(None::<Arc<Notify>>));
+        let angle_c = angle.clone();
+        let notify_c = notify.clone();
+        sensor
+            .ReadingChanged(&TypedEventHandler::<
+                HingeAngleSensor,
+                HingeAngleSensorReadingChangedEventArgs,
+            >::new(move |_, args| {
+                if let Some(args) = args.as_ref() {
+                    if let Ok(reading) = args.Reading() {
+                        if let Ok(deg) = reading.AngleInDegrees() {
+                            *angle_c.lock().unwrap_or_else(PoisonError::into_inner) =
+                                Some(deg as f32);
+                            if let Some(n) = notify_c
+                                .lock()
+                                .unwrap_or_else(PoisonError::into_inner)
+                                .as_ref()
+                            {
+                                n.notify_one();
+                            }
+                        }
+                    }
+                }
+                Ok(())
+            }))
+            .map_err(|source| Error::Windows {
+                context: "hinge events",
+                source,
+            })?;
+        Ok(Self {
+            sensor,
+            angle,
+            notify,
+            reporting,
+        })
     }
+}
 
-    pub async fn open_tilt(hz: f32) -> Result<Self> {
-        let incl = Inclinometer::GetDefault()
-            .map_err(|e| Error::Backend(format!("win inclinometer: {e:?}")))?;
-        Self::spawn_from_tilt(incl, hz).await
+impl WinSensor for Hinge {
+    fn read(&self) -> Result<Option<f32>> {
+        Ok(*self.angle.lock().unwrap_or_else(PoisonError::into_inner))
     }
 
-    pub async fn open_als(hz: f32) -> Result<Self> {
-        let ls =
-            LightSensor::GetDefault().map_err(|e| Error::Backend(format!("win light: {e:?}")))?;
-        Self::spawn_from_als(ls, hz).await
+    fn device_id(&self) -> Option<String> {
+        self.sensor.DeviceId().ok().map(|id| id.to_string())
     }
 
-    async fn spawn_from_hinge(sensor: HingeAngleSensor, hz: f32) -> Result<Self> {
-        let latest = Arc::new(Mutex::new(None));
-        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha = Arc::new(Mutex::new(0.25f32));
-        let conf = Arc::new(Mutex::new(0.2f32));
-
-        let latest_c = latest.clone();
-        let tx_c = tx.clone();
-        let alpha_c = alpha.clone();
-        let conf_c = conf.clone();
-
-        // Event → shared cell; timer ensures steady sampling cadence.
-        tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(20.0)));
-            let mut buf: std::collections::VecDeque<f32> =
-                std::collections::VecDeque::with_capacity(64);
-
-            let angle_cell = Arc::new(Mutex::new(None::<f32>));
-            let angle_cell_c = angle_cell.clone();
-
-            // Keep token alive in this task
-            let _token = sensor
-                .ReadingChanged(&TypedEventHandler::<
-                    HingeAngleSensor,
-                    HingeAngleSensorReadingChangedEventArgs,
-                >::new(move |_, args| {
-                    if let Some(args) = args.as_ref() {
-                        if let Ok(reading) = args.Reading() {
-                            if let Ok(deg) = reading.AngleInDegrees() {
-                                *angle_cell_c.lock().unwrap() = Some(deg as f32);
-                            }
-                        }
-                    }
-                    Ok(())
-                }))
-                .ok();
+    /// Every change of at least `WinReporting::threshold_deg`; the sensor has
+    /// no report interval to set.
+    fn watch(&self, _interval: Duration, notify: Arc<Notify>) -> Result<bool> {
+        let min = self.sensor.MinReportThresholdInDegrees().unwrap_or(0.0);
+        let _ = self
+            .sensor
+            .SetReportThresholdInDegrees(f64::from(self.reporting.threshold_deg).max(min));
+        *self.notify.lock().unwrap_or_else(PoisonError::into_inner) = Some(notify);
+        Ok(true)
+    }
 
-            let mut smoothed: Option<f32> = None;
+    fn quiet_read(&self) -> Duration {
+        self.reporting.quiet_read
+    }
+}
 
-            loop {
-                interval.tick().await;
+struct Tilt(Inclinometer, WinReporting);
+
+impl WinSensor for Tilt {
+    fn read(&self) -> Result<Option<f32>> {
+        let r = self
+            .0
+            .GetCurrentReading()
+            .map_err(|source| Error::Windows {
+                context: "inclinometer",
+                source,
+            })?;
+        Ok(r.PitchDegrees().ok())
+    }
 
-                let raw = *angle_cell.lock().unwrap();
-                if let Some(deg) = raw {
-                    // sanity clamp (0..180 typical, but don’t crash if exotic)
-                    if !(-5.0..=365.0).contains(&deg) {
-                        continue;
-                    }
+    fn device_id(&self) -> Option<String> {
+        self.0.DeviceId().ok().map(|id| id.to_string())
+    }
 
-                    let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                    let s = match smoothed {
-                        None => deg,
-                        Some(prev) => prev + a * (deg - prev),
-                    };
-                    smoothed = Some(s);
+    /// Every pitch change past the threshold, at most once per `interval`.
+    fn watch(&self, interval: Duration, notify: Arc<Notify>) -> Result<bool> {
+        let err = |source| Error::Windows {
+            context: "inclinometer events",
+            source,
+        };
+        let interval = self.1.interval(interval);
+        self.0
+            .SetReportInterval(report_interval(interval, self.0.MinimumReportInterval()))
+            .map_err(err)?;
+        // Not every driver takes a threshold; its default then applies.
+        if let Ok(t) = self.0.ReportThreshold() {
+            let _ = t.SetPitchInDegrees(self.1.threshold_deg);
+        }
+        self.0
+            .ReadingChanged(&TypedEventHandler::<
+                Inclinometer,
+                InclinometerReadingChangedEventArgs,
+            >::new(move |_, _| {
+                notify.notify_one();
+                Ok(())
+            }))
+            .map_err(err)?;
+        Ok(true)
+    }
 
-                    // confidence from variance
-                    if buf.len() == 64 {
-                        buf.pop_front();
-                    }
-                    buf.push_back(s);
-                    let n = buf.len() as f32;
-                    let mean = buf.iter().copied().sum::<f32>() / n;
-                    let var = buf
-                        .iter()
-                        .map(|v| {
-                            let d = *v - mean;
-                            d * d
-                        })
-                        .sum::<f32>()
-                        / n;
-                    let stability = (1.0 / (1.0 + 0.02 * var)).clamp(0.0, 1.0);
-                    *conf_c.lock().unwrap() = stability;
-
-                    let sample = AngleSample {
-                        angle_deg: s,
-                        timestamp: Instant::now(),
-                        source: Source::WinHinge,
-                    };
-                    *latest_c.lock().unwrap() = Some(sample);
-                    let _ = tx_c.send(sample);
-                }
-            }
-        });
+    fn quiet_read(&self) -> Duration {
+        self.1.quiet_read
+    }
+}
 
-        Ok(Self {
-            latest,
-            tx,
-            alpha,
-            conf,
-            src: Source::WinHinge,
-            note: "win_hinge",
-        })
+struct Light(LightSensor, WinReporting);
+
+impl WinSensor for Light {
+    fn read(&self) -> Result<Option<f32>> {
+        let r = self
+            .0
+            .GetCurrentReading()
+            .map_err(|source| Error::Windows {
+                context: "light",
+                source,
+            })?;
+        Ok(r.IlluminanceInLux().ok())
     }
 
-    async fn spawn_from_tilt(incl: Inclinometer, hz: f32) -> Result<Self> {
-        let latest = Arc::new(Mutex::new(None));
-        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha = Arc::new(Mutex::new(0.25f32));
-        let conf = Arc::new(Mutex::new(0.2f32));
-
-        let latest_c = latest.clone();
-        let tx_c = tx.clone();
-        let alpha_c = alpha.clone();
-        let conf_c = conf.clone();
-
-        tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(20.0)));
-            let mut buf: std::collections::VecDeque<f32> =
-                std::collections::VecDeque::with_capacity(64);
-            let mut smoothed: Option<f32> = None;
-
-            loop {
-                interval.tick().await;
-
-                if let Ok(r) = incl.GetCurrentReading() {
-                    if let Ok(pitch) = r.PitchDegrees() {
-                        let deg = pitch.clamp(-180.0, 180.0);
-
-                        let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                        let s = match smoothed {
-                            None => deg,
-                            Some(prev) => prev + a * (deg - prev),
-                        };
-                        smoothed = Some(s);
-
-                        if buf.len() == 64 {
-                            buf.pop_front();
-                        }
-                        buf.push_back(s);
-                        let n = buf.len() as f32;
-                        let mean = buf.iter().copied().sum::<f32>() / n;
-                        let var = buf
-                            .iter()
-                            .map(|v| {
-                                let d = *v - mean;
-                                d * d
-                            })
-                            .sum::<f32>()
-                            / n;
-                        let stability = (1.0 / (1.0 + 0.05 * var)).clamp(0.0, 1.0);
-                        *conf_c.lock().unwrap() = stability;
-
-                        let sample = AngleSample {
-                            angle_deg: s,
-                            timestamp: Instant::now(),
-                            source: Source::WinTilt,
-                        };
-                        *latest_c.lock().unwrap() = Some(sample);
-                        let _ = tx_c.send(sample);
-                    }
-                }
-            }
-        });
+    fn device_id(&self) -> Option<String> {
+        self.0.DeviceId().ok().map(|id| id.to_string())
+    }
 
-        Ok(Self {
-            latest,
-            tx,
-            alpha,
-            conf,
-            src: Source::WinTilt,
-            note: "win_tilt",
-        })
+    /// Every illuminance change past the threshold, at most once per
+    /// `interval`.
+    fn watch(&self, interval: Duration, notify: Arc<Notify>) -> Result<bool> {
+        let err = |source| Error::Windows {
+            context: "light events",
+            source,
+        };
+        let interval = self.1.interval(interval);
+        self.0
+            .SetReportInterval(report_interval(interval, self.0.MinimumReportInterval()))
+            .map_err(err)?;
+        if let Ok(t) = self.0.ReportThreshold() {
+            let _ = t.SetAbsoluteLux(0.0);
+            let _ = t.SetLuxPercentage(self.1.threshold_lux_percent);
+        }
+        self.0
+            .ReadingChanged(&TypedEventHandler::<
+                LightSensor,
+                LightSensorReadingChangedEventArgs,
+            >::new(move |_, _| {
+                notify.notify_one();
+                Ok(())
+            }))
+            .map_err(err)?;
+        Ok(true)
     }
 
-    async fn spawn_from_als(ls: LightSensor, hz: f32) -> Result<Self> {
-        let latest = Arc::new(Mutex::new(None));
-        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
-        let alpha = Arc::new(Mutex::new(0.25f32));
-        let conf = Arc::new(Mutex::new(0.2f32));
-
-        let latest_c = latest.clone();
-        let tx_c = tx.clone();
-        let alpha_c = alpha.clone();
-        let conf_c = conf.clone();
-
-        tokio::spawn(async move {
-            let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz.max(10.0)));
-            let mut baseline = 10.0f32;
-            let mut smoothed: Option<f32> = None;
-            let mut buf: std::collections::VecDeque<f32> =
-                std::collections::VecDeque::with_capacity(64);
-
-            loop {
-                interval.tick().await;
-
-                if let Ok(r) = ls.GetCurrentReading() {
-                    if let Ok(lux) = r.IlluminanceInLux() {
-                        baseline = 0.995 * baseline + 0.005 * lux;
-                        let val = lux - baseline;
-                        let n = (val * 0.02 + 0.5).clamp(0.0, 1.0);
-
-                        let a = (*alpha_c.lock().unwrap()).clamp(0.0, 1.0);
-                        let s = match smoothed {
-                            None => n,
-                            Some(prev) => prev + a * (n - prev),
-                        };
-                        smoothed = Some(s);
-
-                        if buf.len() == 64 {
-                            buf.pop_front();
-                        }
-                        buf.push_back(s);
-                        let m = buf.iter().copied().sum::<f32>() / (buf.len() as f32);
-                        let v = buf
-                            .iter()
-                            .map(|v| {
-                                let d = *v - m;
-                                d * d
-                            })
-                            .sum::<f32>()
-                            / (buf.len() as f32);
-                        let stability = (1.0 / (1.0 + 20.0 * v)).clamp(0.0, 1.0);
-                        *conf_c.lock().unwrap() = stability;
-
-                        let sample = AngleSample {
-                            angle_deg: s,
-                            timestamp: Instant::now(),
-                            source: Source::WinALS,
-                        };
-                        *latest_c.lock().unwrap() = Some(sample);
-                        let _ = tx_c.send(sample);
-                    }
-                }
-            }
-        });
+    fn quiet_read(&self) -> Duration {
+        self.1.quiet_read
+    }
+}
 
-        Ok(Self {
-            latest,
-            tx,
-            alpha,
-            conf,
-            src: Source::WinALS,
-            note: "win_als",
-        })
+/// A hinge exposed only as a custom Sensor API sensor, its angle under
+/// `key` in each reading (`quirks::WinCustomHinge`).
+struct CustomHinge {
+    sensor: CustomSensor,
+    key: HSTRING,
+    reporting: WinReporting,
+}
+
+impl WinSensor for CustomHinge {
+    fn read(&self) -> Result<Option<f32>> {
+        let err = |source| Error::Windows {
+            context: "custom hinge",
+            source,
+        };
+        let value = self
+            .sensor
+            .GetCurrentReading()
+            .and_then(|r| r.Properties())
+            .and_then(|p| p.Lookup(&self.key))
+            .map_err(err)?;
+        // Drivers store it as whatever numeric type they like.
+        let deg = value
+            .cast::<IPropertyValue>()
+            .and_then(|v| v.GetDouble())
+            .map_err(err)?;
+        Ok(Some(deg as f32))
+    }
+
+    fn device_id(&self) -> Option<String> {
+        self.sensor.DeviceId().ok().map(|id| id.to_string())
     }
+
+    /// Every reading, at most once per `interval`; custom sensors take no
+    /// threshold.
+    fn watch(&self, interval: Duration, notify: Arc<Notify>) -> Result<bool> {
+        let err = |source| Error::Windows {
+            context: "custom hinge events",
+            source,
+        };
+        let interval = self.reporting.interval(interval);
+        self.sensor
+            .SetReportInterval(report_interval(
+                interval,
+                self.sensor.MinimumReportInterval(),
+            ))
+            .map_err(err)?;
+        self.sensor
+            .ReadingChanged(&TypedEventHandler::<
+                CustomSensor,
+                CustomSensorReadingChangedEventArgs,
+            >::new(move |_, _| {
+                notify.notify_one();
+                Ok(())
+            }))
+            .map_err(err)?;
+        Ok(true)
+    }
+
+    fn quiet_read(&self) -> Duration {
+        self.reporting.quiet_read
+    }
+}
+
+/// Lid and base accelerometers, read together; the angle between their
+/// gravity vectors is the hinge angle (`convention::hinge_from_gravity_near`,
+/// following on from the last angle read).
+struct DualAccel {
+    lid: Accelerometer,
+    base: Accelerometer,
+    last: Mutex<Option<f32>>,
+}
+
+fn gravity(accel: &Accelerometer) -> windows::core::Result<[f32; 3]> {
+    let r = accel.GetCurrentReading()?;
+    Ok([
+        r.XAxisInGForce()? as f32,
+        r.YAxisInGForce()? as f32,
+        r.ZAxisInGForce()? as f32,
+    ])
 }
 
-impl AngleDevice for WinAngle {
-    fn latest(&self) -> Option<AngleSample> {
-        *self.latest.lock().unwrap()
+impl WinSensor for DualAccel {
+    fn read(&self) -> Result<Option<f32>> {
+        let lid = gravity(&self.lid).map_err(|source| Error::Windows {
+            context: "lid accelerometer",
+            source,
+        })?;
+        let base = gravity(&self.base).map_err(|source| Error::Windows {
+            context: "base accelerometer",
+            source,
+        })?;
+        let mut last = self.last.lock().unwrap_or_else(PoisonError::into_inner);
+        let angle = hinge_from_gravity_near(lid, base, *last);
+        *last = angle.or(*last);
+        Ok(angle)
+    }
+
+    fn device_id(&self) -> Option<String> {
+        self.lid.DeviceId().ok().map(|id| id.to_string())
     }
-    fn subscribe(&self) -> AngleStream {
-        BroadcastStream::new(self.tx.subscribe())
-            .filter_map(|it| async move { it.ok() })
-            .boxed()
+}
+
+impl WinAngle {
+    pub async fn open_hinge(hz: f32, backoff: Backoff, reporting: WinReporting) -> Result<Self> {
+        // WinRT async ops (IAsyncOperation<T>) are not Rust Futures in windows-rs 0.58,
+        // so `.get()` blocks until completion. Run it off the runtime so other
+        // backends keep probing meanwhile.
+        let sensor = tokio::task::spawn_blocking(|| HingeAngleSensor::GetDefaultAsync()?.get())
+            .await
+            .map_err(|e| Error::Backend(format!("hinge probe: {e}")))?
+            .map_err(|source| Error::Windows {
+                context: "hinge",
+                source,
+            })?;
+        Ok(Self::with_sensor(
+            WinSensorKind::Hinge,
+            Hinge::new(sensor, reporting)?,
+            hz,
+            backoff,
+        ))
     }
-    fn set_smoothing(&self, alpha: f32) {
-        *self.alpha.lock().unwrap() = alpha;
+
+    /// For machines whose hinge is only a custom sensor: the first
+    /// `quirks::win_custom_hinges()` entry for this machine that is present.
+    pub async fn open_custom_hinge(
+        hz: f32,
+        backoff: Backoff,
+        reporting: WinReporting,
+    ) -> Result<Self> {
+        let hinges = crate::quirks::win_custom_hinges();
+        Self::open_custom_hinge_in(hz, backoff, reporting, hinges).await
     }
-    fn confidence(&self) -> f32 {
-        *self.conf.lock().unwrap()
+
+    /// `open_custom_hinge` over `hinges`, in order.
+    pub(crate) async fn open_custom_hinge_in(
+        hz: f32,
+        backoff: Backoff,
+        reporting: WinReporting,
+        hinges: Vec<crate::quirks::WinCustomHinge>,
+    ) -> Result<Self> {
+        if hinges.is_empty() {
+            return Err(Error::Backend(
+                "custom hinge: no quirk for this machine".into(),
+            ));
+        }
+        // Blocking WinRT `.get()`s, as in `open_hinge`.
+        let found = tokio::task::spawn_blocking(move || {
+            for h in hinges {
+                let selector = CustomSensor::GetDeviceSelector(GUID::from_u128(h.interface_id))?;
+                let devices = DeviceInformation::FindAllAsyncAqsFilter(&selector)?.get()?;
+                if let Some(info) = devices.into_iter().next() {
+                    let sensor = CustomSensor::FromIdAsync(&info.Id()?)?.get()?;
+                    return Ok(Some((sensor, h.angle_key)));
+                }
+            }
+            Ok::<_, windows::core::Error>(None)
+        })
+        .await
+        .map_err(|e| Error::Backend(format!("custom hinge probe: {e}")))?
+        .map_err(|source| Error::Windows {
+            context: "custom hinge",
+            source,
+        })?;
+        let (sensor, key) =
+            found.ok_or_else(|| Error::Backend("custom hinge: sensor not present".into()))?;
+        Ok(Self::with_sensor(
+            WinSensorKind::CustomHinge,
+            CustomHinge {
+                sensor,
+                key: HSTRING::from(&*key),
+                reporting,
+            },
+            hz,
+            backoff,
+        ))
     }
-    fn info(&self) -> DeviceInfo {
-        DeviceInfo {
-            source: self.src,
-            note: self.note,
+
+    /// For 2-in-1s without a `HingeAngleSensor`: the default accelerometer,
+    /// which is the display's, against any other one, taken to be in the
+    /// base. Fails unless there are two.
+    pub async fn open_accel_hinge(hz: f32, backoff: Backoff) -> Result<Self> {
+        // Blocking WinRT `.get()`s, as in `open_hinge`.
+        let (lid, base) = tokio::task::spawn_blocking(|| {
+            let lid = Accelerometer::GetDefault()?;
+            let lid_id = lid.DeviceId()?;
+            let selector = Accelerometer::GetDeviceSelector(AccelerometerReadingType::Standard)?;
+            // The firmware places each accelerometer on a panel (ACPI
+            // `_PLD`): the base's is the bottom one. Without that, any
+            // accelerometer other than the lid's, the display-side ones last.
+            let mut base = None;
+            for info in DeviceInformation::FindAllAsyncAqsFilter(&selector)?.get()? {
+                let id = info.Id()?;
+                if id == lid_id {
+                    continue;
+                }
+                let panel = info
+                    .EnclosureLocation()
+                    .and_then(|l| l.Panel())
+                    .unwrap_or(Panel::Unknown);
+                let rank = match panel {
+                    Panel::Bottom => 0,
+                    Panel::Front => 2,
+                    _ => 1,
+                };
+                if base.as_ref().is_none_or(|(best, _)| rank < *best) {
+                    base = Some((rank, id));
+                }
+            }
+            let base = match base {
+                Some((_, id)) => Some(Accelerometer::FromIdAsync(&id)?.get()?),
+                None => None,
+            };
+            Ok::<_, windows::core::Error>((lid, base))
+        })
+        .await
+        .map_err(|e| Error::Backend(format!("accelerometer probe: {e}")))?
+        .map_err(|source| Error::Windows {
+            context: "accelerometers",
+            source,
+        })?;
+        let base =
+            base.ok_or_else(|| Error::Backend("accel hinge: no base accelerometer".into()))?;
+        let interval = Duration::from_secs_f32(1.0 / hz.max(20.0));
+        for accel in [&lid, &base] {
+            let _ =
+                accel.SetReportInterval(report_interval(interval, accel.MinimumReportInterval()));
         }
+        Ok(Self::with_sensor(
+            WinSensorKind::AccelHinge,
+            DualAccel {
+                lid,
+                base,
+                last: Mutex::new(None),
+            },
+            hz,
+            backoff,
+        ))
+    }
+
+    pub async fn open_tilt(hz: f32, backoff: Backoff, reporting: WinReporting) -> Result<Self> {
+        let incl = Inclinometer::GetDefault().map_err(|source| Error::Windows {
+            context: "inclinometer",
+            source,
+        })?;
+        Ok(Self::with_sensor(
+            WinSensorKind::Tilt,
+            Tilt(incl, reporting),
+            hz,
+            backoff,
+        ))
+    }
+
+    pub async fn open_als(hz: f32, backoff: Backoff, reporting: WinReporting) -> Result<Self> {
+        let ls = LightSensor::GetDefault().map_err(|source| Error::Windows {
+            context: "light",
+            source,
+        })?;
+        Ok(Self::with_sensor(
+            WinSensorKind::Light,
+            Light(ls, reporting),
+            hz,
+            backoff,
+        ))
     }
 }
+
+#[cfg(feature = "win_posture")]
+pub(crate) mod posture;
@@ -0,0 +1,234 @@
+//! Posture support. `Windows.System.Preview` left windows-rs after 0.43, so
+//! the few members used here are declared by hand against `windows::core`,
+//! laid out as in the Windows SDK metadata.
+
+use super::*;
+use crate::win::HingeState;
+use std::ffi::c_void;
+use windows::Foundation::{EventRegistrationToken, IAsyncOperation};
+use windows::core::imp::{ConstBuffer, FactoryCache};
+use windows::core::{HRESULT, IInspectable_Vtbl, IUnknown, RuntimeName, RuntimeType, Type};
+
+#[repr(C)]
+struct DeviceVtbl {
+    base__: IInspectable_Vtbl,
+    get_current_posture_async: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+    posture_changed:
+        unsafe extern "system" fn(*mut c_void, *mut c_void, *mut EventRegistrationToken) -> HRESULT,
+    _remove_posture_changed: usize,
+}
+
+#[repr(C)]
+struct ReadingVtbl {
+    base__: IInspectable_Vtbl,
+    _timestamp: usize,
+    hinge_state: unsafe extern "system" fn(*mut c_void, *mut i32) -> HRESULT,
+    _panel1_orientation: usize,
+    _panel1_id: usize,
+    _panel2_orientation: usize,
+    _panel2_id: usize,
+}
+
+#[repr(C)]
+struct ChangedArgsVtbl {
+    base__: IInspectable_Vtbl,
+    reading: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+}
+
+#[repr(C)]
+struct StaticsVtbl {
+    base__: IInspectable_Vtbl,
+    get_default_async: unsafe extern "system" fn(*mut c_void, *mut *mut c_void) -> HRESULT,
+}
+
+/// A WinRT runtime class wrapping its default interface, as windows-rs
+/// generates them.
+macro_rules! runtime_class {
+    ($name:ident, $vtbl:ty, $iid:literal, $runtime_name:literal, $signature:literal) => {
+        #[repr(transparent)]
+        #[derive(Clone, PartialEq, Eq, Debug)]
+        struct $name(IUnknown);
+
+        unsafe impl Interface for $name {
+            type Vtable = $vtbl;
+            const IID: GUID = GUID::from_u128($iid);
+        }
+
+        impl RuntimeName for $name {
+            const NAME: &'static str = $runtime_name;
+        }
+
+        impl RuntimeType for $name {
+            const SIGNATURE: ConstBuffer = ConstBuffer::from_slice($signature);
+        }
+
+        // WinRT objects in this namespace are agile.
+        unsafe impl Send for $name {}
+        unsafe impl Sync for $name {}
+    };
+}
+
+runtime_class!(
+    TwoPanelHingedDevicePosturePreview,
+    DeviceVtbl,
+    0x72245c31_4b39_42a6_8e73_7235ade16853,
+    "Windows.System.Preview.TwoPanelHingedDevicePosturePreview",
+    b"rc(Windows.System.Preview.TwoPanelHingedDevicePosturePreview;{72245c31-4b39-42a6-8e73-7235ade16853})"
+);
+runtime_class!(
+    TwoPanelHingedDevicePosturePreviewReading,
+    ReadingVtbl,
+    0xa0251452_4ad6_4b38_8426_c59a15493a7d,
+    "Windows.System.Preview.TwoPanelHingedDevicePosturePreviewReading",
+    b"rc(Windows.System.Preview.TwoPanelHingedDevicePosturePreviewReading;{a0251452-4ad6-4b38-8426-c59a15493a7d})"
+);
+runtime_class!(
+    TwoPanelHingedDevicePosturePreviewReadingChangedEventArgs,
+    ChangedArgsVtbl,
+    0x2d2d1bc6_02ce_474a_a556_a75b1cf93a03,
+    "Windows.System.Preview.TwoPanelHingedDevicePosturePreviewReadingChangedEventArgs",
+    b"rc(Windows.System.Preview.TwoPanelHingedDevicePosturePreviewReadingChangedEventArgs;{2d2d1bc6-02ce-474a-a556-a75b1cf93a03})"
+);
+
+/// The activation factory behind `GetDefaultAsync`.
+#[repr(transparent)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct ITwoPanelHingedDevicePosturePreviewStatics(IUnknown);
+
+unsafe impl Interface for ITwoPanelHingedDevicePosturePreviewStatics {
+    type Vtable = StaticsVtbl;
+    const IID: GUID = GUID::from_u128(0x0c4733d2_57e0_4180_bd5e_f31a2138423e);
+}
+
+impl TwoPanelHingedDevicePosturePreview {
+    fn get_default_async() -> windows::core::Result<IAsyncOperation<Self>> {
+        static SHARED: FactoryCache<
+            TwoPanelHingedDevicePosturePreview,
+            ITwoPanelHingedDevicePosturePreviewStatics,
+        > = FactoryCache::new();
+        SHARED.call(|this| unsafe {
+            let mut result = std::ptr::null_mut();
+            (this.vtable().get_default_async)(this.as_raw(), &mut result)
+                .and_then(|| Type::from_abi(result))
+        })
+    }
+
+    fn get_current_posture_async(
+        &self,
+    ) -> windows::core::Result<IAsyncOperation<TwoPanelHingedDevicePosturePreviewReading>> {
+        unsafe {
+            let mut result = std::ptr::null_mut();
+            (self.vtable().get_current_posture_async)(self.as_raw(), &mut result)
+                .and_then(|| Type::from_abi(result))
+        }
+    }
+
+    fn posture_changed(
+        &self,
+        handler: &TypedEventHandler<
+            Self,
+            TwoPanelHingedDevicePosturePreviewReadingChangedEventArgs,
+        >,
+    ) -> windows::core::Result<EventRegistrationToken> {
+        unsafe {
+            let mut token = EventRegistrationToken::default();
+            (self.vtable().posture_changed)(self.as_raw(), handler.as_raw(), &mut token)
+                .map(|| token)
+        }
+    }
+}
+
+impl TwoPanelHingedDevicePosturePreviewReading {
+    fn hinge_state(&self) -> windows::core::Result<i32> {
+        unsafe {
+            let mut state = 0;
+            (self.vtable().hinge_state)(self.as_raw(), &mut state).map(|| state)
+        }
+    }
+}
+
+impl TwoPanelHingedDevicePosturePreviewReadingChangedEventArgs {
+    fn reading(&self) -> windows::core::Result<TwoPanelHingedDevicePosturePreviewReading> {
+        unsafe {
+            let mut result = std::ptr::null_mut();
+            (self.vtable().reading)(self.as_raw(), &mut result).and_then(|| Type::from_abi(result))
+        }
+    }
+}
+
+/// Whether this is a two-panel device, without opening it.
+pub(crate) fn find() -> windows::core::Result<()> {
+    TwoPanelHingedDevicePosturePreview::get_default_async()?
+        .get()
+        .map(|_| ())
+}
+
+/// A foldable's hinge state: the current one at open, then each
+/// `PostureChanged`.
+struct Posture {
+    device: TwoPanelHingedDevicePosturePreview,
+    angle: Arc<Mutex<Option<f32>>>,
+}
+
+fn posture_angle(reading: &TwoPanelHingedDevicePosturePreviewReading) -> Option<f32> {
+    let state = reading.hinge_state().ok()?;
+    HingeState::from_raw(state).map(HingeState::angle_deg)
+}
+
+impl WinSensor for Posture {
+    fn read(&self) -> Result<Option<f32>> {
+        Ok(*self.angle.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Postures change rarely; every change is reported, whatever `interval`.
+    fn watch(&self, _interval: Duration, notify: Arc<Notify>) -> Result<bool> {
+        let angle = self.angle.clone();
+        self.device
+            .posture_changed(&TypedEventHandler::<
+                TwoPanelHingedDevicePosturePreview,
+                TwoPanelHingedDevicePosturePreviewReadingChangedEventArgs,
+            >::new(move |_, args| {
+                if let Some(args) = args.as_ref()
+                    && let Ok(reading) = args.reading()
+                {
+                    *angle.lock().unwrap_or_else(PoisonError::into_inner) = posture_angle(&reading);
+                    notify.notify_one();
+                }
+                Ok(())
+            }))
+            .map_err(|source| Error::Windows {
+                context: "posture events",
+                source,
+            })?;
+        Ok(true)
+    }
+}
+
+impl WinAngle {
+    /// Fails unless this is a two-panel device that reports a hinge state.
+    pub async fn open_posture(hz: f32, backoff: Backoff) -> Result<Self> {
+        // Blocking WinRT `.get()`s, as in `open_hinge`.
+        let (device, angle) = tokio::task::spawn_blocking(|| {
+            let device = TwoPanelHingedDevicePosturePreview::get_default_async()?.get()?;
+            let reading = device.get_current_posture_async()?.get()?;
+            Ok::<_, windows::core::Error>((device, posture_angle(&reading)))
+        })
+        .await
+        .map_err(|e| Error::Backend(format!("posture probe: {e}")))?
+        .map_err(|source| Error::Windows {
+            context: "posture",
+            source,
+        })?;
+        let angle = angle.ok_or_else(|| Error::Backend("posture: hinge state unknown".into()))?;
+        let sensor = Posture {
+            device,
+            angle: Arc::new(Mutex::new(Some(angle))),
+        };
+        Ok(Self::with_sensor(
+            WinSensorKind::Posture,
+            sensor,
+            hz,
+            backoff,
+        ))
+    }
+}
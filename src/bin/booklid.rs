@@ -0,0 +1,217 @@
+//! `booklid` command-line tool.
+//!
+//!     booklid doctor [--fix]   check sensor permissions (and install udev rules on Linux)
+//!     booklid daemon           serve samples to other apps (feature `daemon`)
+//!     booklid install-agent    run the daemon at login via launchd (macOS, feature `daemon`)
+//!     booklid soak [SECS] [EVERY]  watch stability for SECS (default 3600), reporting every EVERY (60)
+//!
+//! With feature `plugins`, `doctor` and `daemon` first load backend plugins
+//! from `booklid_rust::plugin::plugin_dir()`.
+
+use booklid_rust::{PermissionStatus, Source, check_permissions};
+use std::process::ExitCode;
+use std::time::Duration;
+
+const SOURCES: &[Source] = &[
+    Source::HingeFeature,
+    Source::HingeIOKit,
+    Source::HingeSMC,
+    Source::ALS,
+    Source::MacClamshell,
+    Source::WinHinge,
+    Source::WinPosture,
+    Source::WinTilt,
+    Source::WinALS,
+    Source::WinLidSwitch,
+    Source::LinuxHinge,
+    Source::LinuxTilt,
+    Source::LinuxALS,
+    Source::LinuxLidSwitch,
+    Source::LinuxUPowerLid,
+    Source::LinuxAcpiLid,
+];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("doctor") => {
+            load_plugins();
+            doctor(args.iter().any(|a| a == "--fix"))
+        }
+        #[cfg(all(unix, feature = "daemon"))]
+        Some("daemon") => {
+            load_plugins();
+            daemon()
+        }
+        #[cfg(all(target_os = "macos", feature = "daemon"))]
+        Some("install-agent") => install_agent(),
+        Some("soak") => {
+            load_plugins();
+            let secs = |i: usize, default: u64| {
+                args.get(i)
+                    .and_then(|a| a.parse().ok())
+                    .map_or(Duration::from_secs(default), Duration::from_secs)
+            };
+            soak(secs(1, 3600), secs(2, 60))
+        }
+        _ => {
+            eprintln!(
+                "usage: booklid doctor [--fix] | daemon | install-agent | soak [secs] [every]"
+            );
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(feature = "plugins")]
+fn load_plugins() {
+    let Some(dir) = booklid_rust::plugin::plugin_dir() else {
+        return;
+    };
+    // SAFETY: the plugin directory is the user's own; whatever is installed
+    // there is trusted to implement the plugin ABI.
+    for (path, r) in unsafe { booklid_rust::plugin::load_plugins(&dir) } {
+        match r {
+            Ok(src) => println!("plugin {}: {src:?}", path.display()),
+            Err(e) => eprintln!("plugin {}: {e}", path.display()),
+        }
+    }
+}
+
+#[cfg(not(feature = "plugins"))]
+fn load_plugins() {}
+
+fn doctor(fix: bool) -> ExitCode {
+    let mut denied = false;
+    for &src in SOURCES {
+        let status = check_permissions(src);
+        let label = match &status {
+            PermissionStatus::Granted => "ok",
+            PermissionStatus::Denied { .. } => {
+                denied = true;
+                "DENIED"
+            }
+            PermissionStatus::Unavailable { .. } => "n/a",
+            _ => "unknown",
+        };
+        match status.hint() {
+            Some(h) => println!("{src:?}: {label} ({h})"),
+            None => println!("{src:?}: {label}"),
+        }
+    }
+    if !denied {
+        return ExitCode::SUCCESS;
+    }
+    if fix {
+        return apply_fix();
+    }
+    println!("some sources are denied; run `booklid doctor --fix` where supported");
+    ExitCode::FAILURE
+}
+
+#[cfg(target_os = "linux")]
+fn apply_fix() -> ExitCode {
+    match booklid_rust::linux::install_udev_rules() {
+        Ok(true) => {
+            println!("installed {}", booklid_rust::linux::UDEV_RULES_PATH);
+            ExitCode::SUCCESS
+        }
+        Ok(false) => {
+            println!("udev rules already installed; replug the device or reboot");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("fix failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn apply_fix() -> ExitCode {
+    eprintln!("no automatic fix on this platform; follow the hints above");
+    ExitCode::FAILURE
+}
+
+fn soak(duration: Duration, every: Duration) -> ExitCode {
+    let rt = tokio::runtime::Runtime::new().expect("failed to init Tokio runtime");
+    rt.block_on(async {
+        let client = match booklid_rust::open_with_config(booklid_rust::OpenConfig::default()).await
+        {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("soak: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+        println!(
+            "soak: {:?} for {}s",
+            client.info().source,
+            duration.as_secs()
+        );
+        let report = booklid_rust::soak(&client, duration, every, |s| {
+            println!(
+                "{:>7}s rss={} tasks={} samples={} rate={} restarts={} reconnects={} {:?}",
+                s.elapsed.as_secs(),
+                s.rss_bytes
+                    .map_or("?".into(), |b| format!("{}KiB", b / 1024)),
+                s.tasks,
+                s.samples,
+                s.rate_hz.map_or("?".into(), |r| format!("{r:.1}Hz")),
+                s.sampler_restarts,
+                s.reconnects,
+                s.health,
+            )
+        })
+        .await;
+        println!(
+            "rss growth={} task growth={} max drift={} reconnects={} restarts={} switches={}",
+            report
+                .rss_growth_bytes
+                .map_or("?".into(), |b| format!("{}KiB", b / 1024)),
+            report.task_growth,
+            report
+                .max_drift
+                .map_or("?".into(), |d| format!("{:.1}%", d * 100.0)),
+            report.reconnects,
+            report.sampler_restarts,
+            report.source_switches,
+        );
+        ExitCode::SUCCESS
+    })
+}
+
+#[cfg(all(unix, feature = "daemon"))]
+fn daemon() -> ExitCode {
+    let rt = tokio::runtime::Runtime::new().expect("failed to init Tokio runtime");
+    match rt.block_on(booklid_rust::daemon::run(
+        booklid_rust::OpenConfig::default(),
+    )) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("daemon: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "daemon"))]
+fn install_agent() -> ExitCode {
+    let exe = match std::env::current_exe() {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("install-agent: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match booklid_rust::daemon::install_launch_agent(&exe) {
+        Ok(path) => {
+            println!("installed {}", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("install-agent: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
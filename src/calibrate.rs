@@ -0,0 +1,511 @@
+//! Calibration against known angles.
+//!
+//! `AngleCalibration` maps a source's readings onto true degrees, fitted
+//! from readings taken with the lid held at known angles (say 0°, 90°, and
+//! 180°); use it for sensors whose raw value only roughly tracks the hinge:
+//!
+//! ```ignore
+//! let mut points = Vec::new();
+//! for deg in [0.0, 90.0, 180.0] {
+//!     // ...ask the user to hold the lid at `deg`...
+//!     points.push(dev.calibration_point(deg, 30).await?);
+//! }
+//! let cal = dev.fit_calibration(&points)?;
+//! dev.set_calibration(cal.clone());
+//! calibrate::save(&cal)?; // applied again on later opens
+//! ```
+//!
+//! Calibrations fitted by the client carry the sensor's
+//! `AngleClient::hardware_id` (the backend's id plus the machine model), so
+//! a saved one is only applied to that sensor, not to another of the same
+//! `Source` (a second laptop sharing a home directory, say).
+//!
+//! ALS sources report a normalized 0..1 value rather than an angle.
+//! `AlsModel` learns how that value relates to the hinge angle on one
+//! machine (it depends on the room's lighting and the screen) and turns it
+//! into an approximate angle with an uncertainty. Train it against a
+//! reference source while moving the lid through its range, or from guided
+//! points as above:
+//!
+//! ```ignore
+//! let model = als.train_als_model(&hinge, 600).await?;
+//! let mut angles = als.subscribe_als().als_angles(model);
+//! while let Some((_, est)) = angles.next().await {
+//!     println!("{:.0}° ± {:.0}°", est.angle_deg, est.uncertainty_deg);
+//! }
+//! ```
+//!
+//! `fit_confidence` (feature `record`) fits per-source confidence scales
+//! against ground truth, so that a given confidence means roughly the same
+//! error bound on every source. Record the source under test alongside a
+//! trusted reference (a hinge or dual-accelerometer source, say, on the same
+//! or another machine; traces are aligned by wall-clock time), then fit:
+//!
+//! ```ignore
+//! let fit = calibrate::fit_confidence(&Recording::load("tilt.jsonl")?, &Recording::load("hinge.jsonl")?)?;
+//! let cfg = OpenConfig::default().confidence_scale(fit.source, fit.scale);
+//! ```
+
+use crate::client::rescale_confidence;
+#[cfg(feature = "record")]
+use crate::record::Recording;
+use crate::{AngleSample, Error, Result, Source, persist};
+use std::time::Duration;
+
+/// A reading taken with the lid at a known angle, see
+/// `AngleClient::calibration_point`.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CalibrationPoint {
+    /// The source's reading (`AngleSample::raw`).
+    pub raw: f32,
+    /// The true angle at the time.
+    pub angle_deg: f32,
+}
+
+/// Piecewise-linear map from one source's readings to degrees through the
+/// fitted points, extended past the outer points along the end segments.
+/// Applied by the client before the axis correction, see
+/// `OpenConfig::calibration` and `AngleClient::set_calibration`.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AngleCalibration {
+    pub source: Source,
+    /// The sensor the points were taken on, see `AngleClient::hardware_id`;
+    /// `None` for a backend that cannot tell its sensors apart.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware_id: Option<String>,
+    /// Sorted by `raw`.
+    pub points: Vec<CalibrationPoint>,
+}
+
+impl AngleCalibration {
+    /// Fit from at least two points. Fails when `source` does not report
+    /// degrees, a value is not finite, two points share a reading, or the
+    /// angles do not rise (or fall) steadily with the reading.
+    pub fn fit(source: Source, points: &[CalibrationPoint]) -> Result<Self> {
+        if !source.is_degrees() {
+            return Err(Error::Other(format!(
+                "calibration: {source:?} does not report degrees"
+            )));
+        }
+        let mut points = points.to_vec();
+        points.sort_by(|a, b| a.raw.total_cmp(&b.raw));
+        let cal = Self {
+            source,
+            hardware_id: None,
+            points,
+        };
+        if !cal.is_valid() {
+            return Err(Error::Other(
+                "calibration: need two or more finite points with distinct readings and monotonic angles"
+                    .into(),
+            ));
+        }
+        Ok(cal)
+    }
+
+    /// Tie the calibration to one sensor (see `hardware_id`).
+    pub fn for_hardware(mut self, hardware_id: Option<String>) -> Self {
+        self.hardware_id = hardware_id;
+        self
+    }
+
+    /// Whether this calibration applies to `source`'s sensor `hardware_id`:
+    /// the ids must be equal, `None` only matching `None`.
+    pub fn matches(&self, source: Source, hardware_id: Option<&str>) -> bool {
+        self.source == source && self.hardware_id.as_deref() == hardware_id
+    }
+
+    /// Degrees for reading `raw`.
+    pub fn apply(&self, raw: f32) -> f32 {
+        match self.segment(raw) {
+            Some((a, b)) => a.angle_deg + (raw - a.raw) * slope(a, b),
+            None => raw,
+        }
+    }
+
+    /// Degrees per unit of reading around `raw`.
+    pub fn slope_at(&self, raw: f32) -> f32 {
+        self.segment(raw).map_or(1.0, |(a, b)| slope(a, b))
+    }
+
+    /// Map `s`'s angle and velocity (degree sources only).
+    pub fn map_sample(&self, mut s: AngleSample) -> AngleSample {
+        if s.is_degrees() {
+            s.velocity_deg_s *= self.slope_at(s.angle_deg);
+            s.angle_deg = self.apply(s.angle_deg);
+        }
+        s
+    }
+
+    /// The two points whose segment covers `raw`, or the end segment
+    /// nearest to it.
+    fn segment(&self, raw: f32) -> Option<(&CalibrationPoint, &CalibrationPoint)> {
+        let n = self.points.len();
+        if n < 2 {
+            return None;
+        }
+        let i = self.points.partition_point(|p| p.raw < raw).clamp(1, n - 1);
+        Some((&self.points[i - 1], &self.points[i]))
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        let p = &self.points;
+        let rising = p.windows(2).all(|w| w[1].angle_deg > w[0].angle_deg);
+        let falling = p.windows(2).all(|w| w[1].angle_deg < w[0].angle_deg);
+        p.len() >= 2
+            && p.iter()
+                .all(|p| p.raw.is_finite() && p.angle_deg.is_finite())
+            && p.windows(2).all(|w| w[1].raw > w[0].raw)
+            && (rising || falling)
+    }
+}
+
+fn slope(a: &CalibrationPoint, b: &CalibrationPoint) -> f32 {
+    (b.angle_deg - a.angle_deg) / (b.raw - a.raw)
+}
+
+/// Persist `cal`, replacing any saved calibration for the same source and
+/// hardware id. Opens with `OpenConfig::persistence` on (the default) apply
+/// it to matching sensors.
+pub fn save(cal: &AngleCalibration) -> Result<()> {
+    if !cal.is_valid() {
+        return Err(Error::Other("calibration: invalid points".into()));
+    }
+    let mut state = persist::load();
+    state
+        .calibrations
+        .retain(|c| c.source != cal.source || c.hardware_id != cal.hardware_id);
+    state.calibrations.push(cal.clone());
+    persist::store(&state)
+}
+
+/// Calibrations saved with `save`.
+pub fn saved() -> Vec<AngleCalibration> {
+    persist::load().calibrations
+}
+
+/// The saved calibration for `source`'s sensor `hardware_id`.
+pub(crate) fn saved_for(source: Source, hardware_id: Option<&str>) -> Option<AngleCalibration> {
+    saved()
+        .into_iter()
+        .find(|c| c.is_valid() && c.matches(source, hardware_id))
+}
+
+/// A backend's `hardware_id` qualified with `quirks::machine_model()` as
+/// `<id>@<model>`: ids such as `iio:accel_3d` or `hid:05ac:8104` are the
+/// same on every laptop of a kind, and calibrations should not follow a
+/// home directory from one machine to another.
+pub(crate) fn on_this_machine(hardware_id: Option<String>) -> Option<String> {
+    let id = hardware_id?;
+    Some(match crate::quirks::machine_model() {
+        Some(model) => format!("{id}@{model}"),
+        None => id,
+    })
+}
+
+/// Remove every saved calibration for `source`.
+pub fn forget(source: Source) -> Result<()> {
+    let mut state = persist::load();
+    state.calibrations.retain(|c| c.source != source);
+    persist::store(&state)
+}
+
+/// Most bins an `AlsModel` is fitted with; more points are averaged.
+const ALS_MAX_BINS: usize = 12;
+
+/// Training points with similar readings, averaged.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AlsBin {
+    /// Mean reading.
+    pub value: f32,
+    /// Mean true angle at those readings.
+    pub angle_deg: f32,
+    /// Standard deviation of those angles.
+    pub spread_deg: f32,
+    pub samples: usize,
+}
+
+/// An ALS reading's approximate angle, see `AlsModel::estimate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlsEstimate {
+    pub angle_deg: f32,
+    /// Expected error (about one standard deviation) in degrees.
+    pub uncertainty_deg: f32,
+}
+
+/// Learned map from an ALS source's normalized value to the hinge angle:
+/// training points binned by reading, interpolated between bin means. Only
+/// as good as the lighting it was trained in; `uncertainty_deg` says how
+/// far to trust it.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AlsModel {
+    pub source: Source,
+    /// See `AngleCalibration::hardware_id`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hardware_id: Option<String>,
+    /// Sorted by `value`.
+    pub bins: Vec<AlsBin>,
+    /// RMS error of each inner bin against the line between its
+    /// neighbours: how much the map bends between bins.
+    pub interpolation_error_deg: f32,
+}
+
+impl AlsModel {
+    /// Fit from points pairing a reading (`raw`) with the true angle; see
+    /// `AngleClient::train_als_model` and `AngleClient::calibration_point`.
+    /// Non-finite points are skipped. Fails when `source` already reports
+    /// degrees or the readings do not vary.
+    pub fn fit(source: Source, points: &[CalibrationPoint]) -> Result<Self> {
+        if source.is_degrees() {
+            return Err(Error::Other(format!(
+                "calibration: {source:?} already reports degrees"
+            )));
+        }
+        let mut points: Vec<_> = points
+            .iter()
+            .filter(|p| p.raw.is_finite() && p.angle_deg.is_finite())
+            .copied()
+            .collect();
+        points.sort_by(|a, b| a.raw.total_cmp(&b.raw));
+
+        let mut bins: Vec<AlsBin> = Vec::new();
+        for chunk in points.chunks(points.len().div_ceil(ALS_MAX_BINS).max(1)) {
+            let bin = AlsBin::of(chunk);
+            match bins.last_mut() {
+                Some(last) if last.value >= bin.value => *last = last.merge(&bin),
+                _ => bins.push(bin),
+            }
+        }
+        if bins.len() < 2 {
+            return Err(Error::Other(
+                "calibration: need readings at two or more distinct values".into(),
+            ));
+        }
+
+        let residuals: Vec<f32> = bins
+            .windows(3)
+            .map(|w| w[1].angle_deg - lerp(&w[0], &w[2], w[1].value))
+            .collect();
+        let interpolation_error_deg = if residuals.is_empty() {
+            0.0
+        } else {
+            (residuals.iter().map(|r| r * r).sum::<f32>() / residuals.len() as f32).sqrt()
+        };
+        Ok(Self {
+            source,
+            hardware_id: None,
+            bins,
+            interpolation_error_deg,
+        })
+    }
+
+    /// Tie the model to one sensor, see `AngleCalibration::for_hardware`.
+    pub fn for_hardware(mut self, hardware_id: Option<String>) -> Self {
+        self.hardware_id = hardware_id;
+        self
+    }
+
+    /// See `AngleCalibration::matches`.
+    pub fn matches(&self, source: Source, hardware_id: Option<&str>) -> bool {
+        self.source == source && self.hardware_id.as_deref() == hardware_id
+    }
+
+    /// Angle for reading `value`. Outside the trained range the angle stays
+    /// at the nearest end and the uncertainty grows with the distance, at
+    /// the end segment's slope.
+    pub fn estimate(&self, value: f32) -> AlsEstimate {
+        let n = self.bins.len();
+        let i = self
+            .bins
+            .partition_point(|b| b.value < value)
+            .clamp(1, n.max(2) - 1);
+        let (Some(a), Some(b)) = (self.bins.get(i - 1), self.bins.get(i)) else {
+            return AlsEstimate {
+                angle_deg: value,
+                uncertainty_deg: f32::INFINITY,
+            };
+        };
+        let v = value.clamp(a.value, b.value);
+        let f = (v - a.value) / (b.value - a.value);
+        let spread = a.spread_deg + f * (b.spread_deg - a.spread_deg);
+        let outside = (value - v).abs() * ((b.angle_deg - a.angle_deg) / (b.value - a.value)).abs();
+        AlsEstimate {
+            angle_deg: lerp(a, b, v),
+            uncertainty_deg: spread.hypot(self.interpolation_error_deg) + outside,
+        }
+    }
+}
+
+impl AlsBin {
+    fn of(points: &[CalibrationPoint]) -> Self {
+        let n = points.len() as f32;
+        let value = points.iter().map(|p| p.raw).sum::<f32>() / n;
+        let angle_deg = points.iter().map(|p| p.angle_deg).sum::<f32>() / n;
+        let var = points
+            .iter()
+            .map(|p| (p.angle_deg - angle_deg).powi(2))
+            .sum::<f32>()
+            / n;
+        Self {
+            value,
+            angle_deg,
+            spread_deg: var.sqrt(),
+            samples: points.len(),
+        }
+    }
+
+    /// Both bins' points as one.
+    fn merge(&self, o: &Self) -> Self {
+        let (na, nb) = (self.samples as f32, o.samples as f32);
+        let n = na + nb;
+        let angle_deg = (na * self.angle_deg + nb * o.angle_deg) / n;
+        let second = |b: &Self| b.spread_deg.powi(2) + b.angle_deg.powi(2);
+        let var = (na * second(self) + nb * second(o)) / n - angle_deg.powi(2);
+        Self {
+            value: (na * self.value + nb * o.value) / n,
+            angle_deg,
+            spread_deg: var.max(0.0).sqrt(),
+            samples: self.samples + o.samples,
+        }
+    }
+}
+
+fn lerp(a: &AlsBin, b: &AlsBin, value: f32) -> f32 {
+    a.angle_deg + (value - a.value) * (b.angle_deg - a.angle_deg) / (b.value - a.value)
+}
+
+/// Fit an `AlsModel` for `trace`'s ALS source, pairing each of its readings
+/// with `reference`'s angle at the same wall-clock time (see
+/// `fit_confidence`).
+#[cfg(feature = "record")]
+pub fn fit_als(trace: &Recording, reference: &Recording) -> Result<AlsModel> {
+    if !reference.header.source.is_degrees() {
+        return Err(Error::Other(
+            "calibration: the reference must be in degrees".into(),
+        ));
+    }
+    let points: Vec<_> = trace
+        .samples
+        .iter()
+        .filter_map(|s| {
+            let t = reference.offset_of(trace.wall_time(s))?;
+            Some(CalibrationPoint {
+                raw: s.raw.unwrap_or(s.angle_deg),
+                angle_deg: angle_at(reference, t)?,
+            })
+        })
+        .collect();
+    if points.len() < MIN_PAIRS {
+        return Err(Error::Other(format!(
+            "calibration: only {} samples overlap the reference",
+            points.len()
+        )));
+    }
+    AlsModel::fit(trace.header.source, &points)
+}
+
+/// The confidence that `fit_confidence` pins to `REFERENCE_ERROR_DEG`: after
+/// calibration, samples at this confidence are off by about that much.
+pub const REFERENCE_CONFIDENCE: f32 = 0.7;
+/// Expected absolute error at `REFERENCE_CONFIDENCE`, in degrees.
+pub const REFERENCE_ERROR_DEG: f32 = 5.0;
+/// Reference samples further apart than this are not interpolated between
+/// (nor, live, used once older than this).
+pub(crate) const MAX_GAP: Duration = Duration::from_millis(250);
+/// Fewer matched samples than this are not enough to fit.
+#[cfg(feature = "record")]
+const MIN_PAIRS: usize = 10;
+
+/// A fitted confidence scale for one source, see `fit_confidence`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConfidenceCalibration {
+    pub source: Source,
+    /// Pass to `OpenConfig::confidence_scale`.
+    pub scale: f32,
+    /// Trace samples matched to a reference angle.
+    pub samples: usize,
+    /// Mean absolute error of those samples against the reference.
+    pub mean_error_deg: f32,
+}
+
+impl ConfidenceCalibration {
+    /// `c` as the calibrated client would report it.
+    pub fn apply(&self, c: f32) -> f32 {
+        rescale_confidence(c, self.scale)
+    }
+}
+
+/// Fit a confidence scale for `trace`'s source from its recorded error
+/// against `reference`. Confidence is modeled as `1 / (1 + u)`, with the
+/// error proportional to `u`; the scale makes `REFERENCE_CONFIDENCE` map to
+/// `REFERENCE_ERROR_DEG`. Fails when either trace is not in degrees, too few
+/// samples overlap, or the trace's confidence never drops below 1.
+#[cfg(feature = "record")]
+pub fn fit_confidence(trace: &Recording, reference: &Recording) -> Result<ConfidenceCalibration> {
+    let source = trace.header.source;
+    if !source.is_degrees() || !reference.header.source.is_degrees() {
+        return Err(Error::Other(
+            "calibration: both traces must be in degrees".into(),
+        ));
+    }
+
+    let (mut eu, mut uu, mut err_sum, mut n) = (0.0f64, 0.0f64, 0.0f64, 0usize);
+    for s in &trace.samples {
+        if s.confidence <= 0.0 {
+            continue;
+        }
+        let Some(truth) = reference
+            .offset_of(trace.wall_time(s))
+            .and_then(|t| angle_at(reference, t))
+        else {
+            continue;
+        };
+        let e = f64::from((s.angle_deg - truth).abs());
+        let u = f64::from(1.0 / s.confidence - 1.0);
+        eu += e * u;
+        uu += u * u;
+        err_sum += e;
+        n += 1;
+    }
+
+    if n < MIN_PAIRS {
+        return Err(Error::Other(format!(
+            "calibration: only {n} samples overlap the reference"
+        )));
+    }
+    if uu == 0.0 {
+        return Err(Error::Other(
+            "calibration: confidence is constant, nothing to fit".into(),
+        ));
+    }
+    // Least-squares slope of error over `u`, then the scale that puts
+    // `REFERENCE_ERROR_DEG` at `REFERENCE_CONFIDENCE`.
+    let slope = eu / uu;
+    let u_ref = f64::from(1.0 / REFERENCE_CONFIDENCE - 1.0);
+    Ok(ConfidenceCalibration {
+        source,
+        scale: (slope * u_ref / f64::from(REFERENCE_ERROR_DEG)) as f32,
+        samples: n,
+        mean_error_deg: (err_sum / n as f64) as f32,
+    })
+}
+
+/// Reference angle at offset `t`, interpolated between the neighbouring
+/// samples.
+#[cfg(feature = "record")]
+fn angle_at(rec: &Recording, t: Duration) -> Option<f32> {
+    let i = rec.samples.partition_point(|s| s.offset < t);
+    let after = rec.samples.get(i)?;
+    if after.offset == t {
+        return Some(after.angle_deg);
+    }
+    let before = rec.samples.get(i.checked_sub(1)?)?;
+    let gap = after.offset - before.offset;
+    if gap > MAX_GAP {
+        return None;
+    }
+    let f = (t - before.offset).as_secs_f32() / gap.as_secs_f32();
+    Some(before.angle_deg + f * (after.angle_deg - before.angle_deg))
+}
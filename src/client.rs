@@ -0,0 +1,1345 @@
+//! `AngleClient`: the handle returned by `open*`. Wraps the selected backend,
+//! applies the confidence gate, and adds consumer-side conveniences.
+
+use crate::calibrate::{self, AlsModel, AngleCalibration, CalibrationPoint};
+use crate::events::LidEvents;
+use crate::lid_switch::AutoCalibrationConfig;
+use crate::{
+    Aggregate, AngleDevice, AngleSample, AngleStream, AngleStreamExt, Annotation, AxisTransform,
+    Crossing, DeviceInfo, Error, Event, Filter, FilterParams, Gesture, GestureConfig, Histogram,
+    LidEvent, LidEventConfig, LidSwitchState, LightSample, OutlierRejection, OutputMap, Posture,
+    PostureConfig, PowerEvent, PowerNotifications, Probe, Result, SampleRate, SelfTestReport,
+    Source, TaskGuard, ThresholdWatcher, Zone, persist, runtime,
+};
+use futures_util::StreamExt;
+use once_cell::sync::Lazy;
+use std::collections::VecDeque;
+use std::sync::{
+    Arc, Mutex, RwLock, Weak,
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    mpsc,
+};
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::{BroadcastStream, errors::BroadcastStreamRecvError};
+
+pub(crate) type DynDevice = Box<dyn AngleDevice>;
+
+const BLOCKING_QUEUE: usize = 256;
+const FANOUT_QUEUE: usize = 256;
+/// How often `resolve_calibrations` checks for a new source or hardware id.
+const RESOLVE_EVERY: Duration = Duration::from_secs(1);
+
+/// Snapshot of client-side counters.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+    /// Samples observed since open.
+    pub samples: u64,
+    /// Number of times the confidence gate flipped (live <-> waiting).
+    pub gate_transitions: u64,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub last_sample_at: Option<Instant>,
+    pub live: bool,
+    /// Observed sample rate (smoothed), once two samples have arrived.
+    pub rate_hz: Option<f32>,
+    /// Samples missed by slow subscribers, summed over all subscriptions.
+    pub lagged: u64,
+    /// Times the backend sampler was restarted after a panic.
+    pub sampler_restarts: u32,
+}
+
+/// See `AngleClient::health()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Health {
+    Healthy,
+    /// Opened with `open_lazy` and no backend attached yet.
+    Acquiring,
+    Degraded(Degraded),
+    /// No recent samples.
+    Stale,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Degraded {
+    Reconnecting,
+    LowConfidence,
+    LowRate,
+}
+
+// ===== Confidence gate =====
+
+struct Gate {
+    live: AtomicBool,
+    /// `f32` bits of the go-live threshold; the drop threshold is 0.05 below.
+    min: AtomicU32,
+    transitions: AtomicU64,
+    events: broadcast::Sender<Event>,
+    /// The last `EVENT_LOG_CAP` events, oldest first, for `events_since`.
+    log: Mutex<VecDeque<(Instant, Event)>>,
+}
+
+/// Events kept for `AngleClient::events_since`.
+const EVENT_LOG_CAP: usize = 256;
+
+impl Gate {
+    fn new(min: f32, events: broadcast::Sender<Event>) -> Self {
+        Self {
+            live: AtomicBool::new(false),
+            min: AtomicU32::new(min.to_bits()),
+            transitions: AtomicU64::new(0),
+            events,
+            log: Mutex::new(VecDeque::with_capacity(EVENT_LOG_CAP)),
+        }
+    }
+
+    fn bump(&self, c: f32) {
+        let min = f32::from_bits(self.min.load(Ordering::Relaxed));
+        let drop = (min - 0.05).clamp(0.0, 1.0);
+        let live = self.live.load(Ordering::Relaxed);
+        let next = if !live && c >= min {
+            true
+        } else if live && c < drop {
+            false
+        } else {
+            return;
+        };
+        if self
+            .live
+            .compare_exchange(live, next, Ordering::Relaxed, Ordering::Relaxed)
+            .is_ok()
+        {
+            self.transitions.fetch_add(1, Ordering::Relaxed);
+            let ev = if next {
+                Event::Live { confidence: c }
+            } else {
+                Event::Waiting { confidence: c }
+            };
+            self.publish(ev);
+        }
+    }
+
+    /// Log `ev` and send it to `events()` subscribers.
+    fn publish(&self, ev: Event) {
+        let mut log = self.log.lock().unwrap();
+        if log.len() == EVENT_LOG_CAP {
+            log.pop_front();
+        }
+        log.push_back((Instant::now(), ev));
+        let _ = self.events.send(ev);
+    }
+
+    fn is_live(&self) -> bool {
+        self.live.load(Ordering::Relaxed)
+    }
+}
+
+// ===== Fan-out =====
+
+pub type SubscriberId = u64;
+
+type LagHook = Arc<dyn Fn(SubscriberId, u64) + Send + Sync>;
+
+/// Lag accounting for one live subscription.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SubscriberLag {
+    pub id: SubscriberId,
+    /// Samples this subscriber missed because it fell behind.
+    pub missed: u64,
+}
+
+struct SubEntry {
+    id: SubscriberId,
+    missed: AtomicU64,
+}
+
+/// Client-level re-broadcast of backend samples, so each subscriber's lag
+/// can be observed (backend streams drop `Lagged` silently).
+struct Fanout {
+    /// `None` once the client is closed; subscriber streams then end.
+    tx: Mutex<Option<broadcast::Sender<AngleSample>>>,
+    next_id: AtomicU64,
+    subs: Mutex<Vec<Weak<SubEntry>>>,
+    on_lag: Mutex<Option<LagHook>>,
+    lagged: AtomicU64,
+}
+
+impl Fanout {
+    fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(FANOUT_QUEUE);
+        Self {
+            tx: Mutex::new(Some(tx)),
+            next_id: AtomicU64::new(1),
+            subs: Mutex::new(Vec::new()),
+            on_lag: Mutex::new(None),
+            lagged: AtomicU64::new(0),
+        }
+    }
+
+    fn report_lag(&self, entry: &SubEntry, n: u64) {
+        entry.missed.fetch_add(n, Ordering::Relaxed);
+        self.lagged.fetch_add(n, Ordering::Relaxed);
+        let hook = self.on_lag.lock().unwrap().clone();
+        if let Some(hook) = hook {
+            hook(entry.id, n);
+        }
+    }
+
+    fn subscribe(self: &Arc<Self>) -> (SubscriberId, AngleStream) {
+        let Some(rx) = self.tx.lock().unwrap().as_ref().map(|tx| tx.subscribe()) else {
+            return (0, futures_util::stream::empty().boxed());
+        };
+        let entry = Arc::new(SubEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            missed: AtomicU64::new(0),
+        });
+        {
+            let mut subs = self.subs.lock().unwrap();
+            subs.retain(|w| w.strong_count() > 0);
+            subs.push(Arc::downgrade(&entry));
+        }
+        let id = entry.id;
+        let fanout = Arc::clone(self);
+        let stream = BroadcastStream::new(rx)
+            .filter_map(move |it| {
+                let out = match it {
+                    Ok(s) => Some(s),
+                    Err(BroadcastStreamRecvError::Lagged(n)) => {
+                        fanout.report_lag(&entry, n);
+                        None
+                    }
+                };
+                futures_util::future::ready(out)
+            })
+            .boxed();
+        (id, stream)
+    }
+
+    fn lag(&self) -> Vec<SubscriberLag> {
+        let mut subs = self.subs.lock().unwrap();
+        subs.retain(|w| w.strong_count() > 0);
+        subs.iter()
+            .filter_map(Weak::upgrade)
+            .map(|e| SubscriberLag {
+                id: e.id,
+                missed: e.missed.load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+}
+
+// ===== Client =====
+
+/// Cheap to clone (`Arc`-backed); all clones share the same backend sampler,
+/// confidence gate, and counters.
+#[derive(Clone)]
+pub struct AngleClient {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    /// `None` once closed (see `shutdown()`); dropping it stops the sampler.
+    dev: RwLock<Option<DynDevice>>,
+    info: DeviceInfo,
+    rate: SampleRate,
+    gate: Arc<Gate>,
+    annotations: broadcast::Sender<Annotation>,
+    counters: Arc<Counters>,
+    fanout: Arc<Fanout>,
+    /// Lid switch states for `subscribe_events`, kept out of the sample
+    /// stream; `None` once closed.
+    switch: Mutex<Option<broadcast::Sender<Option<LidSwitchState>>>>,
+    output: Arc<RwLock<Output>>,
+    probes: Vec<Probe>,
+    monitor: JoinHandle<()>,
+    /// The `follow_power` task, if any.
+    power: Mutex<Option<TaskGuard>>,
+    /// The `resolve_calibrations` task, if any.
+    calibration: Mutex<Option<TaskGuard>>,
+    /// The `OpenConfig::auto_calibration` coordinator, if any.
+    auto_calibration: Mutex<Option<TaskGuard>>,
+    /// Zones for `subscribe_zones`, read at every sample.
+    zones: watch::Sender<Vec<Zone>>,
+}
+
+/// A client that its own background tasks hold without keeping it open.
+pub(crate) struct WeakClient(Weak<Shared>);
+
+impl WeakClient {
+    pub(crate) fn upgrade(&self) -> Option<AngleClient> {
+        self.0.upgrade().map(|shared| AngleClient { shared })
+    }
+}
+
+/// Value transforms applied to every sample before it reaches consumers.
+pub(crate) struct Output {
+    /// Mounting correction for samples from `source` (the selected backend,
+    /// not a failover partner).
+    pub(crate) axis: Option<(Source, AxisTransform)>,
+    pub(crate) map: Option<OutputMap>,
+    /// Per-source confidence scales, see `OpenConfig::confidence_scale`.
+    pub(crate) confidence: Vec<(Source, f32)>,
+    /// Per-source reading-to-degrees maps, applied before `axis`.
+    pub(crate) calibrations: Vec<AngleCalibration>,
+}
+
+impl Output {
+    fn correct(&self, mut s: AngleSample) -> AngleSample {
+        if let Some(c) = self.calibrations.iter().find(|c| c.source == s.source) {
+            s = c.map_sample(s);
+        }
+        let mut s = match self.axis {
+            Some((src, a)) if s.source == src => a.map_sample(s),
+            _ => s,
+        };
+        s.confidence = self.confidence(s.source, s.confidence);
+        s
+    }
+
+    fn confidence(&self, source: Source, c: f32) -> f32 {
+        self.confidence
+            .iter()
+            .find(|(s, _)| *s == source)
+            .map_or(c, |(_, scale)| rescale_confidence(c, *scale))
+    }
+
+    fn map(&self, s: AngleSample) -> AngleSample {
+        self.map.as_ref().map_or(s, |m| m.map_sample(s))
+    }
+}
+
+/// Backends report confidence as `1 / (1 + k·instability)`; scaling the
+/// instability term by `scale` is the same as scaling the backend's `k`.
+pub(crate) fn rescale_confidence(c: f32, scale: f32) -> f32 {
+    if c <= 0.0 {
+        return 0.0;
+    }
+    (1.0 / (1.0 + scale * (1.0 / c - 1.0))).clamp(0.0, 1.0)
+}
+
+/// Updated by the monitor task on every sample.
+#[derive(Default)]
+struct Counters {
+    samples: AtomicU64,
+    last_at: Mutex<Option<Instant>>,
+    /// EMA of the interval between samples, in seconds.
+    interval: Mutex<Option<f32>>,
+    restarts: AtomicU32,
+    histogram: Mutex<Option<Histogram>>,
+    /// Save the histogram to persisted state on close.
+    persist_histogram: AtomicBool,
+    lid: Mutex<LidState>,
+    /// The system is asleep (see `follow_power`): samples are dropped.
+    suspended: AtomicBool,
+}
+
+/// Open/closed state behind `AngleClient::is_open` / `is_closed`, kept by
+/// an undebounced `LidEvents`: it only changes once the angle reaches the
+/// far threshold, so jitter between the two does not flip it. A lid switch
+/// reporting closed holds it closed until the switch reports open, or the
+/// system wakes from sleep.
+struct LidState {
+    events: LidEvents,
+    /// Until a threshold is reached: the one nearer the first sample.
+    guess: Option<bool>,
+}
+
+impl Default for LidState {
+    fn default() -> Self {
+        Self {
+            events: LidEvents::new(LidEventConfig::default().debounce(Duration::ZERO)),
+            guess: None,
+        }
+    }
+}
+
+impl LidState {
+    /// The open/closed change `s` completes, if any.
+    fn update(&mut self, s: &AngleSample) -> Option<LidEvent> {
+        let changed = Self::lid_change(self.events.push(s));
+        if self.guess.is_none() && s.is_degrees() && s.angle_deg.is_finite() {
+            let cfg = self.events.config();
+            self.guess = Some(s.angle_deg > (cfg.closed_below + cfg.opened_above) / 2.0);
+        }
+        changed
+    }
+
+    fn lid_change(events: Vec<LidEvent>) -> Option<LidEvent> {
+        events
+            .into_iter()
+            .find(|e| matches!(e, LidEvent::LidOpened | LidEvent::LidClosed))
+    }
+
+    fn open(&self) -> Option<bool> {
+        self.events.is_open().or(self.guess)
+    }
+
+    /// A lid switch's state; `None` once it is no longer known (after
+    /// system sleep), so angles decide again.
+    fn switch(&mut self, state: Option<LidSwitchState>) -> Option<LidEvent> {
+        Self::lid_change(self.events.switch(state, Instant::now()))
+    }
+}
+
+impl Counters {
+    /// Count `s`; returns the open/closed change it completes.
+    fn record(&self, s: &AngleSample) -> Option<LidEvent> {
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        let mut last = self.last_at.lock().unwrap();
+        if let Some(prev) = *last {
+            let dt = s.timestamp.saturating_duration_since(prev).as_secs_f32();
+            let mut iv = self.interval.lock().unwrap();
+            *iv = Some(match *iv {
+                None => dt,
+                Some(p) => p + 0.1 * (dt - p),
+            });
+        }
+        *last = Some(s.timestamp);
+        if let Some(h) = self.histogram.lock().unwrap().as_mut() {
+            h.add(s.angle_deg);
+        }
+        self.lid.lock().unwrap().update(s)
+    }
+
+    fn rate_hz(&self) -> Option<f32> {
+        self.interval
+            .lock()
+            .unwrap()
+            .filter(|i| *i > 0.0)
+            .map(|i| 1.0 / i)
+    }
+}
+
+impl AngleClient {
+    /// Must be called from within a Tokio runtime.
+    pub(crate) fn new(
+        dev: DynDevice,
+        rate: SampleRate,
+        min_confidence: f32,
+        axis: Option<AxisTransform>,
+        output_map: Option<OutputMap>,
+        probes: Vec<Probe>,
+    ) -> Self {
+        let output = Arc::new(RwLock::new(Output {
+            axis: axis.map(|a| (dev.info().source, a)),
+            map: output_map,
+            confidence: Vec::new(),
+            calibrations: Vec::new(),
+        }));
+        Self::with_output(dev, rate, min_confidence, output, probes)
+    }
+
+    /// Like `new`, sharing `output` with the caller (`open_lazy` sets the
+    /// axis once its backend attaches).
+    pub(crate) fn with_output(
+        dev: DynDevice,
+        rate: SampleRate,
+        min_confidence: f32,
+        output: Arc<RwLock<Output>>,
+        probes: Vec<Probe>,
+    ) -> Self {
+        let (events, _rx) = broadcast::channel(64);
+        let gate = Arc::new(Gate::new(min_confidence, events));
+        let counters = Arc::new(Counters::default());
+        let fanout = Arc::new(Fanout::new());
+        let (switch, _rx) = broadcast::channel(16);
+        let info = dev.info();
+
+        let mut stream = dev.subscribe();
+        let mut restarts = dev.restarts();
+        let mut dev_events = dev.events();
+        let gate_c = Arc::clone(&gate);
+        let counters_c = Arc::clone(&counters);
+        let tx = fanout.tx.lock().unwrap().clone();
+        let switch_c = switch.clone();
+        let output_c = Arc::clone(&output);
+        let monitor = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    s = stream.next() => {
+                        let Some(s) = s else { break };
+                        if counters_c.suspended.load(Ordering::Relaxed) {
+                            continue;
+                        }
+                        let out = output_c.read().unwrap();
+                        let s = out.correct(s);
+                        if let Some(ev) = counters_c.record(&s) {
+                            gate_c.publish(Event::Lid(ev));
+                        }
+                        gate_c.bump(s.confidence);
+                        if s.source.is_lid_switch() {
+                            if let Some(state) = s.lid_switch_state() {
+                                let _ = switch_c.send(Some(state));
+                            }
+                        } else if let Some(tx) = &tx {
+                            let _ = tx.send(out.map(s));
+                        }
+                    }
+                    Some(n) = restarts.next() => {
+                        counters_c.restarts.store(n, Ordering::Relaxed);
+                        gate_c.publish(Event::SamplerRestarted { restarts: n });
+                    }
+                    Some(ev) = dev_events.next() => {
+                        gate_c.publish(ev);
+                    }
+                }
+            }
+        });
+
+        let shared = Arc::new(Shared {
+            dev: RwLock::new(Some(dev)),
+            info,
+            rate,
+            gate,
+            annotations: broadcast::channel(64).0,
+            counters,
+            fanout,
+            switch: Mutex::new(Some(switch)),
+            output,
+            probes,
+            monitor,
+            power: Mutex::new(None),
+            calibration: Mutex::new(None),
+            auto_calibration: Mutex::new(None),
+            zones: watch::channel(Vec::new()).0,
+        });
+        register(&shared);
+        Self { shared }
+    }
+
+    fn with_dev<R>(&self, f: impl FnOnce(&DynDevice) -> R) -> Option<R> {
+        self.shared.dev.read().unwrap().as_ref().map(f)
+    }
+
+    /// Stop sampling for this client and every clone: drops the backend and
+    /// ends all subscriber streams. Afterwards `latest()` is `None`.
+    pub fn close(&self) {
+        self.shared.close();
+    }
+
+    /// Latest sample, or `None` until confidence passes the gate (and while
+    /// the system is asleep, see `follow_power`). An ALS source's readings
+    /// are not angles and never show up here; see `subscribe_als`.
+    pub fn latest(&self) -> Option<AngleSample> {
+        if self.is_suspended() {
+            return None;
+        }
+        self.shared.gate.bump(self.confidence());
+        if self.shared.gate.is_live() {
+            let s = self.with_dev(|d| d.latest()).flatten()?;
+            if s.source.is_als() {
+                return None;
+            }
+            let out = self.shared.output.read().unwrap();
+            Some(out.map(out.correct(s)))
+        } else {
+            None
+        }
+    }
+
+    /// Angle samples as the backend produces them. An ALS source's
+    /// normalized readings are kept off it (they still count toward
+    /// confidence and failover); see `subscribe_als` and `subscribe_lux`.
+    pub fn subscribe(&self) -> AngleStream {
+        self.subscribe_with_id().1
+    }
+
+    /// The normalized 0..1 readings (`raw`) of an ALS source backing this
+    /// client, not angles: for `AngleStreamExt::als_angles` and training
+    /// `AlsModel`s. Empty for other sources.
+    pub fn subscribe_als(&self) -> AngleStream {
+        self.shared
+            .fanout
+            .subscribe()
+            .1
+            .filter(|s| futures_util::future::ready(s.source.is_als()))
+            .boxed()
+    }
+
+    /// Like `subscribe()`, but `angle_deg` is each sample's unsmoothed `raw`
+    /// reading (axis and output map still applied), for consumers running
+    /// their own filter. Samples arrive regardless of the confidence gate.
+    pub fn subscribe_raw(&self) -> AngleStream {
+        let out = Arc::clone(&self.shared.output);
+        // Samples here are already corrected: only the angle is redone.
+        self.subscribe()
+            .map(move |s| {
+                let out = out.read().unwrap();
+                let raw = AngleSample {
+                    angle_deg: s.raw,
+                    ..s
+                };
+                AngleSample {
+                    angle_deg: out.map(out.correct(raw)).angle_deg,
+                    ..s
+                }
+            })
+            .boxed()
+    }
+
+    /// Every sample the backend produces, corrected and mapped as for
+    /// `subscribe()` but with ALS readings and lid switch states (unmapped)
+    /// kept: what a daemon serves to the clients attached to it.
+    #[cfg(all(unix, feature = "daemon"))]
+    pub(crate) fn subscribe_wire(&self) -> AngleStream {
+        let Some(stream) = self.with_dev(|d| d.subscribe()) else {
+            return futures_util::stream::empty().boxed();
+        };
+        let out = Arc::clone(&self.shared.output);
+        let counters = Arc::clone(&self.shared.counters);
+        stream
+            .filter_map(move |s| {
+                let s = (!counters.suspended.load(Ordering::Relaxed)).then(|| {
+                    let out = out.read().unwrap();
+                    let s = out.correct(s);
+                    if s.source.is_lid_switch() {
+                        s
+                    } else {
+                        out.map(s)
+                    }
+                });
+                futures_util::future::ready(s)
+            })
+            .boxed()
+    }
+
+    /// One min/max/mean/stddev summary per `window`, for dashboards and
+    /// long-term logging that don't need the full sample rate.
+    pub fn subscribe_aggregated(
+        &self,
+        window: Duration,
+    ) -> futures_util::stream::BoxStream<'static, Aggregate> {
+        self.subscribe().aggregated(window)
+    }
+
+    /// Run the backend's `self_test` over `samples` readings, bypassing the
+    /// confidence gate. Hold the lid still while it runs. After `close()`
+    /// the report fails with `TooFewSamples`.
+    pub async fn self_test(&self, samples: usize) -> SelfTestReport {
+        let test = self.with_dev(|d| d.self_test(samples));
+        match test {
+            Some(test) => test.await,
+            None => {
+                let empty = futures_util::stream::empty().boxed();
+                crate::self_test::run(empty, self.info().source, samples).await
+            }
+        }
+    }
+
+    /// Illuminance from the ambient-light source backing this client, in
+    /// lux. Empty when the source has no light sensor (hinge, tilt, mock) or
+    /// after `close()`. Not subject to the confidence gate.
+    pub fn subscribe_lux(&self) -> futures_util::stream::BoxStream<'static, LightSample> {
+        self.with_dev(|d| d.subscribe_lux())
+            .unwrap_or_else(|| futures_util::stream::empty().boxed())
+    }
+
+    /// The raw reports the HID hinge backend reads (feature `hid_debug`),
+    /// to work out the layout of an unfamiliar sensor: polled feature
+    /// reports, or with `hid::ReadMode::Input` every input report. Empty
+    /// for other sources or after `close()`.
+    #[cfg(feature = "hid_debug")]
+    pub fn subscribe_raw_reports(
+        &self,
+    ) -> futures_util::stream::BoxStream<'static, crate::hid::RawReport> {
+        self.with_dev(|d| d.subscribe_raw_reports())
+            .unwrap_or_else(|| futures_util::stream::empty().boxed())
+    }
+
+    /// Crossings of `watcher`'s thresholds from now on; await `next()` on
+    /// the stream for a one-off notification.
+    pub fn watch_thresholds(
+        &self,
+        watcher: ThresholdWatcher,
+    ) -> futures_util::stream::BoxStream<'static, Crossing> {
+        watcher.watch(self.subscribe())
+    }
+
+    /// Posture of the latest sample under the default `PostureConfig`;
+    /// `Unknown` until the gate opens. Use `PostureConfig::classify` on
+    /// `latest()` for other bounds.
+    pub fn current_posture(&self) -> Posture {
+        self.latest()
+            .map_or(Posture::Unknown, |s| PostureConfig::default().classify(&s))
+    }
+
+    /// Whether the lid is shut: true once the angle falls to
+    /// `OpenConfig::lid_closed_below` and until it rises to
+    /// `lid_opened_above`. False while `latest()` is `None`.
+    pub fn is_closed(&self) -> bool {
+        self.lid_open() == Some(false)
+    }
+
+    /// The opposite of `is_closed`, also false while `latest()` is `None`.
+    pub fn is_open(&self) -> bool {
+        self.lid_open() == Some(true)
+    }
+
+    fn lid_open(&self) -> Option<bool> {
+        self.latest()?;
+        self.shared.counters.lid.lock().unwrap().open()
+    }
+
+    /// Move the `is_closed` / `is_open` thresholds (see
+    /// `OpenConfig::lid_thresholds`). The current state holds until a sample
+    /// reaches one of them. Fails unless both are finite and
+    /// `closed_below < opened_above`.
+    pub fn set_lid_thresholds(&self, closed_below: f32, opened_above: f32) -> Result<()> {
+        if !(closed_below.is_finite() && opened_above.is_finite() && closed_below < opened_above) {
+            return Err(Error::Other(
+                "lid thresholds must be finite with closed_below < opened_above".into(),
+            ));
+        }
+        self.shared
+            .counters
+            .lid
+            .lock()
+            .unwrap()
+            .events
+            .set_lid_thresholds(closed_below, opened_above);
+        Ok(())
+    }
+
+    /// Follow `notifications` until `close()` (replacing any earlier ones):
+    /// across system sleep the backend is paused and samples are dropped,
+    /// and the clamshell closing or opening is taken as authoritative by
+    /// `is_closed` and `subscribe_events` until the system next wakes
+    /// (when the lid may have moved unseen). It is not sent to
+    /// `subscribe()`, which only carries angle samples. Opening with
+    /// `OpenConfig::power_notifications` follows `sleep::platform()`. Must be
+    /// called from within a Tokio runtime.
+    pub fn follow_power(&self, notifications: impl PowerNotifications) {
+        let shared = Arc::downgrade(&self.shared);
+        let mut events = notifications.subscribe();
+        let task = TaskGuard::spawn(async move {
+            while let Some(ev) = events.next().await {
+                let Some(shared) = shared.upgrade() else {
+                    return;
+                };
+                AngleClient { shared }.on_power(ev);
+            }
+        });
+        *self.shared.power.lock().unwrap() = Some(task);
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakClient {
+        WeakClient(Arc::downgrade(&self.shared))
+    }
+
+    /// Anchor on the platform lid switch until `close()`, see
+    /// `OpenConfig::auto_calibration`.
+    pub(crate) fn keep_auto_calibration(&self, cfg: AutoCalibrationConfig) {
+        let task = self.auto_calibrate_platform(cfg);
+        *self.shared.auto_calibration.lock().unwrap() = Some(task);
+    }
+
+    /// True between `PowerEvent::WillSleep` and `DidWake` (see
+    /// `follow_power`).
+    pub fn is_suspended(&self) -> bool {
+        self.shared.counters.suspended.load(Ordering::Relaxed)
+    }
+
+    fn on_power(&self, ev: PowerEvent) {
+        let counters = &self.shared.counters;
+        match ev {
+            PowerEvent::WillSleep | PowerEvent::DidWake => {
+                let asleep = ev == PowerEvent::WillSleep;
+                counters.suspended.store(asleep, Ordering::Relaxed);
+                self.with_dev(|d| d.set_paused(asleep));
+                // The gap is not a sample interval.
+                *counters.last_at.lock().unwrap() = None;
+                if !asleep {
+                    self.on_switch(None);
+                }
+            }
+            PowerEvent::ClamshellClosed => self.on_switch(Some(LidSwitchState::Closed)),
+            PowerEvent::ClamshellOpened => self.on_switch(Some(LidSwitchState::Open)),
+        }
+    }
+
+    fn on_switch(&self, state: Option<LidSwitchState>) {
+        let changed = self.shared.counters.lid.lock().unwrap().switch(state);
+        if let Some(ev) = changed {
+            self.emit(Event::Lid(ev));
+        }
+        if let Some(tx) = self.shared.switch.lock().unwrap().as_ref() {
+            let _ = tx.send(state);
+        }
+    }
+
+    /// The current posture, then each change, under the default
+    /// `PostureConfig`; see `AngleStreamExt::postures` for other bounds.
+    pub fn subscribe_posture(&self) -> futures_util::stream::BoxStream<'static, Posture> {
+        self.subscribe().postures(PostureConfig::default())
+    }
+
+    /// Lid opened / closed, threshold crossings, and source changes, derived
+    /// per `cfg` from `subscribe()` and from lid switch states (a switch
+    /// source's, or the clamshell's under `follow_power`).
+    pub fn subscribe_events(
+        &self,
+        cfg: LidEventConfig,
+    ) -> futures_util::stream::BoxStream<'static, LidEvent> {
+        enum Input {
+            Sample(AngleSample),
+            Switch(Option<LidSwitchState>),
+        }
+        let switch = match self.shared.switch.lock().unwrap().as_ref() {
+            Some(tx) => BroadcastStream::new(tx.subscribe())
+                .filter_map(|it| futures_util::future::ready(it.ok().map(Input::Switch)))
+                .boxed(),
+            None => futures_util::stream::empty().boxed(),
+        };
+        let mut detector = LidEvents::new(cfg);
+        futures_util::stream::select(self.subscribe().map(Input::Sample), switch)
+            .flat_map(move |input| {
+                futures_util::stream::iter(match input {
+                    Input::Sample(s) => detector.push(&s),
+                    Input::Switch(state) => detector.switch(state, Instant::now()),
+                })
+            })
+            .boxed()
+    }
+
+    /// The name of each zone (see `OpenConfig::zone`) the angle enters;
+    /// samples outside every zone are ignored. Follows `set_zones`, so a
+    /// reloaded config applies to streams already running.
+    pub fn subscribe_zones(&self) -> futures_util::stream::BoxStream<'static, String> {
+        let zones = self.shared.zones.subscribe();
+        let mut current: Option<String> = None;
+        self.subscribe()
+            .filter_map(move |s| {
+                let zones = zones.borrow();
+                let hit = zones.iter().find(|z| z.range.contains(&s.angle_deg));
+                let out = match hit {
+                    Some(z) if current.as_ref() != Some(&z.name) => {
+                        current = Some(z.name.clone());
+                        current.clone()
+                    }
+                    _ => None,
+                };
+                futures_util::future::ready(out)
+            })
+            .boxed()
+    }
+
+    /// Replace the zones for `subscribe_zones`, including running streams.
+    pub fn set_zones(&self, zones: Vec<Zone>) {
+        self.shared.zones.send_replace(zones);
+    }
+
+    /// Rapid-close gestures derived from `subscribe()` per `cfg`, e.g. to
+    /// save state on `Gesture::ClosingFast` before the OS suspends.
+    pub fn subscribe_gestures(
+        &self,
+        cfg: GestureConfig,
+    ) -> futures_util::stream::BoxStream<'static, Gesture> {
+        self.subscribe().gestures(cfg)
+    }
+
+    /// Like `subscribe()`, also returning the id used by `subscriber_lag()`
+    /// and the `on_lag` hook.
+    pub fn subscribe_with_id(&self) -> (SubscriberId, AngleStream) {
+        let (id, stream) = self.shared.fanout.subscribe();
+        let angles = stream.filter(|s| futures_util::future::ready(!s.source.is_als()));
+        (id, angles.boxed())
+    }
+
+    /// Missed-sample counts for every live subscription.
+    pub fn subscriber_lag(&self) -> Vec<SubscriberLag> {
+        self.shared.fanout.lag()
+    }
+
+    /// Called with `(subscriber, missed)` whenever a subscriber falls behind
+    /// and samples are dropped for it. Replaces any previous hook. Runs on the
+    /// lagging subscriber's task, so keep it cheap.
+    pub fn on_lag<F>(&self, hook: F)
+    where
+        F: Fn(SubscriberId, u64) + Send + Sync + 'static,
+    {
+        *self.shared.fanout.on_lag.lock().unwrap() = Some(Arc::new(hook));
+    }
+
+    pub fn set_smoothing(&self, alpha: f32) {
+        self.with_dev(|d| d.set_smoothing(alpha));
+    }
+
+    /// Smooth with time constant `tau`, see `OpenConfig::smoothing_tau`.
+    pub fn set_smoothing_tau(&self, tau: Duration) {
+        self.set_filter(Filter::TimeConstant {
+            tau_s: tau.as_secs_f32(),
+        });
+    }
+
+    /// Switch the backend's smoothing filter (see `OpenConfig::filter`).
+    pub fn set_filter(&self, filter: Filter) {
+        self.with_dev(|d| d.set_filter(filter));
+    }
+
+    /// Turn outlier rejection on or off (see `OpenConfig::reject_outliers`).
+    pub fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.with_dev(|d| d.set_outlier_rejection(outliers));
+    }
+
+    /// Switch filter and outlier rejection together, e.g. between
+    /// `FilterParams::responsive()` and `FilterParams::stable()`.
+    pub fn set_filter_params(&self, params: FilterParams) {
+        self.with_dev(|d| d.set_filter_params(params));
+    }
+
+    pub(crate) fn emit(&self, ev: Event) {
+        self.shared.gate.publish(ev);
+    }
+
+    /// Change the go-live confidence threshold (clamped to 0..1); the drop
+    /// threshold follows 0.05 below it.
+    pub fn set_min_confidence(&self, c: f32) {
+        let c = if c.is_finite() {
+            c.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        self.shared.gate.min.store(c.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Replace the output map; `None` passes values through.
+    pub fn set_output_map(&self, map: Option<OutputMap>) {
+        self.shared.output.write().unwrap().map = map;
+    }
+
+    /// Replace the axis correction for the current source; `None` turns it
+    /// off.
+    pub fn set_axis(&self, axis: Option<AxisTransform>) {
+        let source = self.info().source;
+        self.shared.output.write().unwrap().axis = axis.map(|a| (source, a));
+    }
+
+    /// "The lid is closed now": shift the axis offset so the current reading
+    /// becomes 0°. Meant for tilt-derived sources (`LinuxTilt`, `WinTilt`)
+    /// that report pitch rather than hinge angle. Returns the new axis
+    /// correction, which can be kept via `OpenConfig::axis`.
+    pub fn calibrate_zero(&self) -> Result<AxisTransform> {
+        self.calibrate_reference(0.0)
+    }
+
+    /// Shift the axis offset so the current reading becomes `angle_deg`, see
+    /// `calibrate_zero`. Fails until the backend has a degree reading.
+    pub fn calibrate_reference(&self, angle_deg: f32) -> Result<AxisTransform> {
+        self.shift_axis(angle_deg, 1.0)
+    }
+
+    /// Move the axis offset `weight` (0..1) of the way toward putting the
+    /// current reading at `angle_deg`.
+    pub(crate) fn shift_axis(&self, angle_deg: f32, weight: f32) -> Result<AxisTransform> {
+        if !angle_deg.is_finite() {
+            return Err(Error::Other("calibration: angle must be finite".into()));
+        }
+        let s = self
+            .with_dev(|d| d.latest())
+            .flatten()
+            .filter(|s| s.is_degrees() && s.angle_deg.is_finite())
+            .ok_or_else(|| Error::Other("calibration: no degree reading yet".into()))?;
+        let mut out = self.shared.output.write().unwrap();
+        let current = out.correct(s).angle_deg;
+        let mut axis = match out.axis {
+            Some((src, a)) if src == s.source => a,
+            _ => AxisTransform::default(),
+        };
+        axis.offset_deg += weight.clamp(0.0, 1.0) * (angle_deg - current);
+        out.axis = Some((s.source, axis));
+        Ok(axis)
+    }
+
+    /// Replace the per-source confidence scales (see
+    /// `OpenConfig::confidence_scale`); sources not listed are left as is.
+    pub fn set_confidence_scales(&self, scales: Vec<(Source, f32)>) {
+        self.shared.output.write().unwrap().confidence = scales;
+    }
+
+    /// Map `cal.source`'s readings to degrees from now on, replacing any
+    /// calibration for it (see `calibrate::AngleCalibration`). Use
+    /// `calibrate::save` to keep it for later opens.
+    pub fn set_calibration(&self, cal: AngleCalibration) {
+        let mut out = self.shared.output.write().unwrap();
+        out.calibrations.retain(|c| c.source != cal.source);
+        out.calibrations.push(cal);
+    }
+
+    /// Stop calibrating `source`'s readings.
+    pub fn clear_calibration(&self, source: Source) {
+        let mut out = self.shared.output.write().unwrap();
+        out.calibrations.retain(|c| c.source != source);
+    }
+
+    /// Calibrations in use, one per source.
+    pub fn calibrations(&self) -> Vec<AngleCalibration> {
+        self.shared.output.read().unwrap().calibrations.clone()
+    }
+
+    pub(crate) fn set_calibrations(&self, cals: Vec<AngleCalibration>) {
+        self.shared.output.write().unwrap().calibrations = cals;
+    }
+
+    /// Apply `crate::calibrations_for(explicit, persistence, ..)` for the
+    /// current sensor, and again whenever the source or `hardware_id`
+    /// changes: HID backends learn the id from the first reports, and
+    /// `open_lazy` or a reconnect can bring another sensor.
+    pub(crate) fn resolve_calibrations(&self, explicit: Vec<AngleCalibration>, persistence: bool) {
+        let resolve = move |client: &AngleClient, explicit: &[AngleCalibration]| {
+            let key = (client.info().source, client.hardware_id());
+            client.set_calibrations(crate::calibrations_for(
+                explicit,
+                persistence,
+                key.0,
+                key.1.as_deref(),
+            ));
+            key
+        };
+        let mut seen = resolve(self, &explicit);
+        let shared = Arc::downgrade(&self.shared);
+        let mut events = self.events();
+        let task = TaskGuard::spawn(async move {
+            let mut every = tokio::time::interval(RESOLVE_EVERY);
+            loop {
+                tokio::select! {
+                    _ = every.tick() => {}
+                    Some(_) = events.next() => {}
+                }
+                let Some(shared) = shared.upgrade() else {
+                    return;
+                };
+                let client = AngleClient { shared };
+                if (client.info().source, client.hardware_id()) != seen {
+                    seen = resolve(&client, &explicit);
+                }
+            }
+        });
+        *self.shared.calibration.lock().unwrap() = Some(task);
+    }
+
+    /// Fit `points` (see `calibration_point`) for the current source and
+    /// sensor, see `calibrate::AngleCalibration::fit`.
+    pub fn fit_calibration(&self, points: &[CalibrationPoint]) -> Result<AngleCalibration> {
+        Ok(AngleCalibration::fit(self.info().source, points)?.for_hardware(self.hardware_id()))
+    }
+
+    /// Fit `points` for the current ALS source and sensor, see
+    /// `calibrate::AlsModel::fit`.
+    pub fn fit_als_model(&self, points: &[CalibrationPoint]) -> Result<AlsModel> {
+        Ok(AlsModel::fit(self.info().source, points)?.for_hardware(self.hardware_id()))
+    }
+
+    /// Train an `AlsModel` for this (ALS) client against `reference`, a
+    /// client reporting degrees, while the lid is moved slowly through its
+    /// range: each of the next readings is paired with `reference`'s latest
+    /// angle until `samples` pairs (at least two) are collected. Readings
+    /// with no recent reference angle are skipped. Fails if either source
+    /// is the wrong kind or this client closes first.
+    pub async fn train_als_model(
+        &self,
+        reference: &AngleClient,
+        samples: usize,
+    ) -> Result<AlsModel> {
+        let (source, ref_source) = (self.info().source, reference.info().source);
+        if source.is_degrees() || !ref_source.is_degrees() {
+            return Err(Error::Other(format!(
+                "calibration: cannot train {source:?} against {ref_source:?}"
+            )));
+        }
+        let points: Vec<CalibrationPoint> = self
+            .subscribe_als()
+            .filter_map(|s| {
+                let truth = reference
+                    .latest()
+                    .filter(|r| r.is_degrees() && r.timestamp.elapsed() <= calibrate::MAX_GAP);
+                futures_util::future::ready(truth.map(|r| CalibrationPoint {
+                    raw: s.raw,
+                    angle_deg: r.angle_deg,
+                }))
+            })
+            .take(samples.max(2))
+            .collect()
+            .await;
+        if points.len() < samples.max(2) {
+            return Err(Error::Other("calibration: client closed".into()));
+        }
+        self.fit_als_model(&points)
+    }
+
+    /// Hold the lid at `angle_deg` while this runs: the median reading of
+    /// the next `samples` samples (at least one), for
+    /// `calibrate::AngleCalibration::fit` (or `AlsModel::fit` on an ALS
+    /// client). Fails if the client closes first.
+    pub async fn calibration_point(
+        &self,
+        angle_deg: f32,
+        samples: usize,
+    ) -> Result<CalibrationPoint> {
+        // ALS readings included, for `fit_als_model`.
+        let mut raw: Vec<f32> = self
+            .shared
+            .fanout
+            .subscribe()
+            .1
+            .take(samples.max(1))
+            .map(|s| s.raw)
+            .collect()
+            .await;
+        if raw.is_empty() {
+            return Err(Error::Other("calibration: no samples".into()));
+        }
+        raw.sort_by(f32::total_cmp);
+        Ok(CalibrationPoint {
+            raw: raw[raw.len() / 2],
+            angle_deg,
+        })
+    }
+
+    /// 0.0 once the client is closed.
+    pub fn confidence(&self) -> f32 {
+        let source = self.info().source;
+        let c = self.with_dev(|d| d.confidence()).unwrap_or(0.0);
+        self.shared.output.read().unwrap().confidence(source, c)
+    }
+
+    /// The backend's `AngleDevice::hardware_id` on this machine (see
+    /// `calibrate::on_this_machine`), which keys calibrations fitted by the
+    /// client.
+    pub fn hardware_id(&self) -> Option<String> {
+        calibrate::on_this_machine(self.with_dev(|d| d.hardware_id()).flatten())
+    }
+
+    pub fn info(&self) -> DeviceInfo {
+        self.with_dev(|d| d.info())
+            .unwrap_or_else(|| self.shared.info.clone())
+    }
+
+    /// Blocking counterpart of `subscribe()` for non-async threads.
+    /// Forwarding runs on the global runtime and stops once the receiver is
+    /// dropped; samples are dropped (like a lagging subscriber) if it falls
+    /// `BLOCKING_QUEUE` samples behind.
+    pub fn subscribe_blocking(&self) -> mpsc::Receiver<AngleSample> {
+        let (tx, rx) = mpsc::sync_channel(BLOCKING_QUEUE);
+        let mut stream = self.subscribe();
+        runtime().spawn(async move {
+            while let Some(s) = stream.next().await {
+                match tx.try_send(s) {
+                    Ok(()) | Err(mpsc::TrySendError::Full(_)) => {}
+                    Err(mpsc::TrySendError::Disconnected(_)) => break,
+                }
+            }
+        });
+        rx
+    }
+
+    /// Feed samples into a `watch` channel (initially `None`). The forwarding
+    /// task owns this handle and stops once every receiver is dropped.
+    /// Must be called from within a Tokio runtime.
+    pub fn into_watch(self) -> watch::Receiver<Option<AngleSample>> {
+        let (tx, rx) = watch::channel(self.latest());
+        tokio::spawn(async move {
+            let mut stream = self.subscribe();
+            while let Some(s) = stream.next().await {
+                if tx.send(Some(s)).is_err() {
+                    break;
+                }
+            }
+        });
+        rx
+    }
+
+    /// Forward every sample into an existing `broadcast` sender. Sends with no
+    /// receivers are ignored; abort the returned handle to stop forwarding.
+    /// Must be called from within a Tokio runtime.
+    pub fn broadcast_to(&self, sender: broadcast::Sender<AngleSample>) -> JoinHandle<()> {
+        let mut stream = self.subscribe();
+        tokio::spawn(async move {
+            while let Some(s) = stream.next().await {
+                let _ = sender.send(s);
+            }
+        })
+    }
+
+    /// Wait up to `timeout` for a sample matching `pred`.
+    pub async fn wait_for<F>(&self, mut pred: F, timeout: Duration) -> Option<AngleSample>
+    where
+        F: FnMut(&AngleSample) -> bool,
+    {
+        if let Some(s) = self.latest().filter(|s| pred(s)) {
+            return Some(s);
+        }
+        let mut stream = self.subscribe();
+        tokio::time::timeout(timeout, async {
+            while let Some(s) = stream.next().await {
+                if pred(&s) {
+                    return Some(s);
+                }
+            }
+            None
+        })
+        .await
+        .ok()
+        .flatten()
+    }
+
+    /// Attach a named marker at the current time, delivered to `annotations()`
+    /// subscribers (such as a running `Recorder`). Markers from clones of this
+    /// client share one stream.
+    pub fn annotate(&self, label: impl Into<String>) {
+        let _ = self.shared.annotations.send(Annotation {
+            label: label.into(),
+            timestamp: Instant::now(),
+        });
+    }
+
+    /// Markers passed to `annotate` from now on.
+    pub fn annotations(&self) -> futures_util::stream::BoxStream<'static, Annotation> {
+        BroadcastStream::new(self.shared.annotations.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    /// Gate transitions (live / waiting), sampler restarts, and device events
+    /// (such as source switches) from now on.
+    pub fn events(&self) -> futures_util::stream::BoxStream<'static, Event> {
+        BroadcastStream::new(self.shared.gate.events.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    /// Events logged after `since`, oldest first, with the time each was
+    /// emitted; covers the same events as `events()`, for callers that were
+    /// not subscribed. Only the most recent 256 are kept.
+    pub fn events_since(&self, since: Instant) -> Vec<(Instant, Event)> {
+        let log = self.shared.gate.log.lock().unwrap();
+        let start = log.partition_point(|(at, _)| *at <= since);
+        log.range(start..).copied().collect()
+    }
+
+    /// Backend open attempts made while selecting this client's source,
+    /// with how long each took.
+    pub fn probes(&self) -> &[Probe] {
+        &self.shared.probes
+    }
+
+    /// Snapshot of the angle histogram, if `OpenConfig::histogram` enabled it.
+    /// Counts every sample the backend delivers, gated or not, after the
+    /// axis correction and before `OpenConfig::output_map`.
+    pub fn histogram(&self) -> Option<Histogram> {
+        self.shared.counters.histogram.lock().unwrap().clone()
+    }
+
+    /// Zero the histogram's counts (keeps its layout).
+    pub fn reset_histogram(&self) {
+        if let Some(h) = self.shared.counters.histogram.lock().unwrap().as_mut() {
+            h.counts.iter_mut().for_each(|c| *c = 0);
+        }
+    }
+
+    pub(crate) fn enable_histogram(&self, h: Histogram, persist: bool) {
+        let c = &self.shared.counters;
+        *c.histogram.lock().unwrap() = Some(h);
+        c.persist_histogram.store(persist, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> Stats {
+        let c = &self.shared.counters;
+        Stats {
+            samples: c.samples.load(Ordering::Relaxed),
+            gate_transitions: self.shared.gate.transitions.load(Ordering::Relaxed),
+            last_sample_at: *c.last_at.lock().unwrap(),
+            live: self.shared.gate.is_live(),
+            rate_hz: c.rate_hz(),
+            lagged: self.shared.fanout.lagged.load(Ordering::Relaxed),
+            sampler_restarts: c.restarts.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The sample rate requested at open.
+    pub fn rate(&self) -> SampleRate {
+        self.shared.rate
+    }
+
+    /// One-value summary for supervisors and watchdogs. Checks, in order:
+    /// backend reconnecting, stale (no sample for 10 periods, at least 1 s),
+    /// confidence below the gate, observed rate under half the requested rate.
+    pub fn health(&self) -> Health {
+        if self.with_dev(|d| d.is_acquiring()).unwrap_or(false) {
+            return Health::Acquiring;
+        }
+        if self.with_dev(|d| d.is_reconnecting()).unwrap_or(false) {
+            return Health::Degraded(Degraded::Reconnecting);
+        }
+        let stale_after = (self.shared.rate.period() * 10).max(Duration::from_secs(1));
+        let fresh = self
+            .shared
+            .counters
+            .last_at
+            .lock()
+            .unwrap()
+            .is_some_and(|t| t.elapsed() <= stale_after);
+        if !fresh {
+            return Health::Stale;
+        }
+        if !self.shared.gate.is_live() {
+            return Health::Degraded(Degraded::LowConfidence);
+        }
+        match self.shared.counters.rate_hz() {
+            Some(r) if r < 0.5 * self.shared.rate.as_hz() => Health::Degraded(Degraded::LowRate),
+            _ => Health::Healthy,
+        }
+    }
+}
+
+impl Shared {
+    fn save_histogram(&self) {
+        if !self.counters.persist_histogram.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(h) = self.counters.histogram.lock().unwrap().clone() {
+            let mut st = persist::load();
+            st.histogram = Some(h);
+            persist::store(&st).ok();
+        }
+    }
+
+    fn close(&self) {
+        self.save_histogram();
+        self.monitor.abort();
+        self.power.lock().unwrap().take();
+        self.calibration.lock().unwrap().take();
+        self.auto_calibration.lock().unwrap().take();
+        self.fanout.tx.lock().unwrap().take();
+        self.switch.lock().unwrap().take();
+        self.dev.write().unwrap().take();
+    }
+}
+
+// ===== Registry (for `shutdown()`) =====
+
+static CLIENTS: Lazy<Mutex<Vec<Weak<Shared>>>> = Lazy::new(Default::default);
+
+fn register(shared: &Arc<Shared>) {
+    let mut clients = CLIENTS.lock().unwrap();
+    clients.retain(|w| w.strong_count() > 0);
+    clients.push(Arc::downgrade(shared));
+}
+
+/// Close every client that is still alive.
+pub(crate) fn close_all() {
+    let clients: Vec<_> = CLIENTS.lock().unwrap().drain(..).collect();
+    for shared in clients.iter().filter_map(Weak::upgrade) {
+        shared.close();
+    }
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        self.save_histogram();
+        self.monitor.abort();
+    }
+}
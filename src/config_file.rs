@@ -0,0 +1,83 @@
+//! JSON config files (feature `serde`): load an `OpenConfig` from disk and
+//! re-apply its live-updatable settings to an open client when it changes.
+
+use crate::{AngleClient, Event, OpenConfig, Result, TaskGuard};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+use tokio::time;
+
+/// How often `watch_config` checks the file for changes.
+const POLL: Duration = Duration::from_secs(1);
+
+impl OpenConfig {
+    /// Read a JSON `OpenConfig` (the `serde` representation; missing fields
+    /// take their defaults) and validate it.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let s = fs::read_to_string(path)?;
+        let cfg: OpenConfig = serde_json::from_str(&s)?;
+        cfg.validate()
+    }
+}
+
+/// Stops watching when dropped, see `AngleClient::watch_config`.
+pub struct ConfigWatch {
+    _task: TaskGuard,
+}
+
+impl AngleClient {
+    /// Poll `path` for changes and apply the live-updatable settings of each
+    /// new version: `smoothing_alpha` / `filter`, `outlier_rejection`,
+    /// `min_confidence`, `output_map`, `confidence_scales`, the lid
+    /// thresholds, `zones` (running `subscribe_zones` streams follow them),
+    /// and `axis` (unset falls back to this machine's quirk). Everything
+    /// else (rate, sources, ...) only takes effect on the next open. A file
+    /// that fails to load or validate is ignored until it changes again;
+    /// each applied reload emits `Event::ConfigReloaded`. Must be called
+    /// from within a Tokio runtime.
+    pub fn watch_config(&self, path: impl Into<PathBuf>) -> ConfigWatch {
+        let path = path.into();
+        let client = self.clone();
+        let mut seen = stamp(&path);
+        let task = TaskGuard::spawn(async move {
+            let mut tick = time::interval(POLL);
+            loop {
+                tick.tick().await;
+                let now = stamp(&path);
+                if now == seen {
+                    continue;
+                }
+                seen = now;
+                match OpenConfig::from_file(&path) {
+                    Ok(cfg) => client.apply_live(&cfg),
+                    Err(_e) => {
+                        #[cfg(feature = "diagnostics")]
+                        eprintln!("[booklid] ignoring config {}: {}", path.display(), _e);
+                    }
+                }
+            }
+        });
+        ConfigWatch { _task: task }
+    }
+
+    fn apply_live(&self, cfg: &OpenConfig) {
+        self.set_filter(cfg.smoothing_filter());
+        self.set_outlier_rejection(cfg.outlier_rejection);
+        self.set_min_confidence(cfg.min_confidence);
+        self.set_output_map(cfg.output_map.clone());
+        self.set_confidence_scales(cfg.confidence_scales.clone());
+        self.set_lid_thresholds(cfg.lid_closed_below, cfg.lid_opened_above)
+            .ok();
+        self.set_zones(cfg.zones.clone());
+        self.set_axis(cfg.axis.or_else(|| cfg.quirk_axis(self.info().source)));
+        self.emit(Event::ConfigReloaded);
+    }
+}
+
+/// Modification time and length, `None` while the file is missing.
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let m = fs::metadata(path).ok()?;
+    Some((m.modified().ok()?, m.len()))
+}
@@ -0,0 +1,270 @@
+//! Reusable contract tests for `AngleDevice` implementations (feature
+//! `conformance`). Built-in backends (via `builtin`) and third-party
+//! `BackendFactory`s go through the same checks:
+//!
+//! ```ignore
+//! #[tokio::test]
+//! async fn my_backend_conforms() {
+//!     booklid_rust::conformance::assert_conforms(&MyFactory, 30.0).await;
+//! }
+//! ```
+//!
+//! Run each check on a runtime of its own (as `#[tokio::test]` does): task
+//! leaks are measured on the current runtime.
+
+use crate::{
+    AngleDevice, BackendContext, BackendFactory, Backoff, BuiltinOpts, Error, Result, Source, hid,
+    open_builtin,
+};
+use futures_util::{StreamExt, future::BoxFuture};
+use std::time::{Duration, Instant};
+use tokio::time;
+
+/// How long `open` may take.
+const OPEN_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the first sample may take to show up in `latest()`.
+const FIRST_SAMPLE: Duration = Duration::from_secs(2);
+/// How long a dropped device may take to end streams and tasks.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+/// Observed rate must be within this factor of the requested one.
+const RATE_TOLERANCE: f32 = 0.5;
+/// Samples after `set_smoothing` that may still use the old factor.
+const SETTLE: usize = 2;
+
+/// Outcome of `check`; the device conforms when `violations` is empty.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ConformanceReport {
+    pub source: Source,
+    /// Samples received from `subscribe`.
+    pub samples: usize,
+    /// Observed rate, once two samples have arrived.
+    pub rate_hz: Option<f32>,
+    pub violations: Vec<Violation>,
+}
+
+impl ConformanceReport {
+    pub fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// One broken rule of the `AngleDevice` contract.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum Violation {
+    /// `open` failed or timed out; nothing else was checked.
+    OpenFailed { reason: String },
+    /// `latest()` stayed `None` after the device started.
+    NoLatest,
+    /// `subscribe()` delivered fewer samples than asked for in time.
+    TooFewSamples { got: usize, wanted: usize },
+    /// Samples arrived more than 50% off the requested rate.
+    Rate { requested_hz: f32, observed_hz: f32 },
+    /// `confidence()` or a sample's confidence was outside 0..=1.
+    ConfidenceOutOfRange { value: f32 },
+    /// With this smoothing factor, `angle_deg` did not follow `raw` as
+    /// expected (1.0 = equal to `raw`, 0.0 = held).
+    SmoothingIgnored { alpha: f32 },
+    /// A `subscribe()` stream kept going after the device was dropped.
+    StreamNotClosed,
+    /// Tasks spawned by the device outlived it.
+    TasksLeaked { before: usize, after: usize },
+}
+
+/// A factory for the built-in backend of `source`, so in-tree sensors can be
+/// put through `check` like registered ones. `Mock` is allowed; opening
+/// fails where this build or machine lacks the backend.
+pub fn builtin(source: Source) -> impl BackendFactory {
+    Builtin(source)
+}
+
+struct Builtin(Source);
+
+impl BackendFactory for Builtin {
+    fn source(&self) -> Source {
+        self.0
+    }
+
+    fn open(&self, ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        let src = self.0;
+        let opts = BuiltinOpts {
+            #[cfg(reconnecting_backends)]
+            reconnect: ctx.reconnect,
+            discovery: true,
+            persistence: false,
+            hid_override: None,
+            hid_read_mode: hid::ReadMode::Poll,
+            allow_mock: true,
+            desktop: false,
+            failover_tilt: true,
+            win_reporting: crate::win::WinReporting::default(),
+            linux_iio_buffer: false,
+            sysfs_root: None,
+            dev_root: None,
+            quirks: crate::quirks::user_for(None)
+                .expect("the default quirks file never fails the open"),
+        };
+        Box::pin(async move {
+            open_builtin(src, ctx.hz, opts)
+                .await
+                .unwrap_or_else(|| Err(Error::Backend(format!("{src:?} has no built-in backend"))))
+        })
+    }
+}
+
+/// Open a device from `factory` at `hz` and check it against the contract:
+/// `latest()` becomes `Some`, `subscribe()` yields at about `hz`,
+/// `set_smoothing` takes effect, confidence stays in 0..=1, and dropping the
+/// device ends its streams and tasks. Takes about a second plus the
+/// device's startup time.
+pub async fn check(factory: &dyn BackendFactory, hz: f32) -> ConformanceReport {
+    let source = factory.source();
+    let mut report = ConformanceReport {
+        source,
+        samples: 0,
+        rate_hz: None,
+        violations: Vec::new(),
+    };
+    let v = &mut report.violations;
+    let tasks_before = alive_tasks();
+
+    let ctx = BackendContext {
+        hz,
+        reconnect: Backoff::default(),
+    };
+    let dev = match time::timeout(OPEN_TIMEOUT, factory.open(ctx)).await {
+        Ok(Ok(dev)) => dev,
+        Ok(Err(e)) => {
+            v.push(Violation::OpenFailed {
+                reason: e.to_string(),
+            });
+            return report;
+        }
+        Err(_) => {
+            v.push(Violation::OpenFailed {
+                reason: format!("timed out after {OPEN_TIMEOUT:?}"),
+            });
+            return report;
+        }
+    };
+    let mut stream = dev.subscribe();
+
+    let first = Instant::now();
+    while dev.latest().is_none() && first.elapsed() < FIRST_SAMPLE {
+        time::sleep(Duration::from_secs_f32(1.0 / hz).min(FIRST_SAMPLE / 20)).await;
+    }
+    if dev.latest().is_none() {
+        v.push(Violation::NoLatest);
+    }
+
+    // About a second of samples without smoothing, then a few held.
+    let wanted = (hz.round() as usize).clamp(10, 120);
+    let budget = Duration::from_secs_f32(1.0 + 2.0 * (wanted + SETTLE + 10) as f32 / hz);
+    let deadline = time::Instant::now() + budget;
+    let mut collect = async |n: usize| {
+        let mut got = Vec::with_capacity(n);
+        while got.len() < n {
+            match time::timeout_at(deadline, stream.next()).await {
+                Ok(Some(s)) => got.push(s),
+                _ => break,
+            }
+        }
+        got
+    };
+
+    dev.set_smoothing(1.0);
+    let free = collect(wanted).await;
+    dev.set_smoothing(0.0);
+    let held = collect(SETTLE + 10).await;
+    report.samples = free.len() + held.len();
+    if free.len() < wanted {
+        v.push(Violation::TooFewSamples {
+            got: free.len(),
+            wanted,
+        });
+    }
+
+    if let (Some(a), Some(b)) = (free.first(), free.last())
+        && b.timestamp > a.timestamp
+    {
+        let observed_hz = (free.len() - 1) as f32 / (b.timestamp - a.timestamp).as_secs_f32();
+        report.rate_hz = Some(observed_hz);
+        if (observed_hz / hz - 1.0).abs() > RATE_TOLERANCE {
+            v.push(Violation::Rate {
+                requested_hz: hz,
+                observed_hz,
+            });
+        }
+    }
+
+    let in_range = |c: f32| (0.0..=1.0).contains(&c);
+    if let Some(value) = free
+        .iter()
+        .chain(&held)
+        .map(|s| s.confidence)
+        .chain([dev.confidence()])
+        .find(|c| !in_range(*c))
+    {
+        v.push(Violation::ConfidenceOutOfRange { value });
+    }
+
+    let close = |a: f32, b: f32| (a - b).abs() <= 1e-3 * a.abs().max(1.0);
+    if free.iter().skip(SETTLE).any(|s| !close(s.angle_deg, s.raw)) {
+        v.push(Violation::SmoothingIgnored { alpha: 1.0 });
+    }
+    let held = held.get(SETTLE..).unwrap_or_default();
+    let spread = |f: fn(&crate::AngleSample) -> f32| {
+        let (lo, hi) = held
+            .iter()
+            .map(f)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), x| {
+                (lo.min(x), hi.max(x))
+            });
+        hi - lo
+    };
+    // Only conclusive when the raw reading moved.
+    if held.len() >= 2 && spread(|s| s.raw) > 1e-3 && spread(|s| s.angle_deg) > 1e-3 {
+        v.push(Violation::SmoothingIgnored { alpha: 0.0 });
+    }
+
+    drop(dev);
+    let closed = time::timeout(CLOSE_TIMEOUT, async {
+        while stream.next().await.is_some() {}
+    })
+    .await;
+    if closed.is_err() {
+        v.push(Violation::StreamNotClosed);
+    }
+    drop(stream);
+    let until = Instant::now() + CLOSE_TIMEOUT;
+    while alive_tasks() > tasks_before && Instant::now() < until {
+        time::sleep(Duration::from_millis(10)).await;
+    }
+    let after = alive_tasks();
+    if after > tasks_before {
+        v.push(Violation::TasksLeaked {
+            before: tasks_before,
+            after,
+        });
+    }
+    report
+}
+
+/// `check`, panicking with the report unless the device conforms.
+pub async fn assert_conforms(factory: &dyn BackendFactory, hz: f32) {
+    let report = check(factory, hz).await;
+    assert!(
+        report.passed(),
+        "{:?} violates the AngleDevice contract: {:#?}",
+        report.source,
+        report.violations
+    );
+}
+
+fn alive_tasks() -> usize {
+    tokio::runtime::Handle::current()
+        .metrics()
+        .num_alive_tasks()
+}
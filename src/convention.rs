@@ -0,0 +1,105 @@
+//! The frame every degree-valued source reports in: 0° = closed, 180° = flat,
+//! above 180° = folded back (360° = tablet).
+//!
+//! Hinge sensors (`HingeFeature`, `HingeHid`, `WinHinge`) measure this angle
+//! directly; where there are accelerometers in both the lid and the base,
+//! `hinge_from_gravity` computes it (`WinHinge`, `LinuxHinge`). Tilt sources
+//! (`WinTilt`, `LinuxTilt`) measure the lid's pitch against gravity and are
+//! converted by `from_pitch`, which assumes the base lies level. Gravity can't
+//! tell closed from fully folded back, so tilt sources report a face-down lid
+//! as 0°.
+
+use crate::Source;
+
+pub const CLOSED_DEG: f32 = 0.0;
+pub const FLAT_DEG: f32 = 180.0;
+
+/// Lid angle from the lid's pitch: 0° lying face-up, 90° upright, 180°
+/// face-down, negative when tipped back past flat.
+pub fn from_pitch(pitch_deg: f32) -> f32 {
+    (FLAT_DEG - pitch_deg).rem_euclid(360.0)
+}
+
+/// Follows a reading in the 0–360° frame across the 0°/360° seam, so the
+/// smoothing and velocity stages see 359° → 1° as a 2° step rather than a
+/// 358° jump. `wrap` brings their output back into the frame.
+#[derive(Default)]
+pub(crate) struct Seam {
+    last: Option<f32>,
+}
+
+impl Seam {
+    /// `deg` moved by whole turns to within 180° of the previous reading.
+    pub(crate) fn unwrap(&mut self, deg: f32) -> f32 {
+        if !deg.is_finite() {
+            return deg;
+        }
+        let out = match self.last {
+            Some(prev) => prev + (deg - prev + 180.0).rem_euclid(360.0) - 180.0,
+            None => deg,
+        };
+        self.last = Some(out);
+        out
+    }
+}
+
+/// An unwrapped angle back in 0..360.
+pub(crate) fn wrap(deg: f32) -> f32 {
+    deg.rem_euclid(360.0)
+}
+
+/// Below this share of gravity across the hinge, the hinge axis is too close
+/// to vertical for the two vectors to say anything.
+const MIN_ACROSS_HINGE: f32 = 0.25;
+/// Closed and folded all the way back look the same to gravity; within this
+/// far of the 0°/360° seam the side is taken from the previous angle (or,
+/// without one, read as closed).
+const CLOSED_MARGIN_DEG: f32 = 10.0;
+
+/// The hinge angle from the lid and base accelerometers' readings (any
+/// consistent unit and sign; axes: x along the hinge, y up the panel, z out
+/// of it), both sensors' axes agreeing when the device lies flat open at
+/// 180°. `None` while the hinge points too close to straight up or down,
+/// e.g. a laptop standing on its side. Readings next to the seam are taken as
+/// closed; use `hinge_from_gravity_near` to follow a lid folding back.
+pub fn hinge_from_gravity(lid: [f32; 3], base: [f32; 3]) -> Option<f32> {
+    hinge_from_gravity_near(lid, base, None)
+}
+
+/// `hinge_from_gravity`, resolving readings next to the 0°/360° seam by
+/// continuity with `prev`, the last angle reported: a lid folded back past
+/// 350° stays there instead of snapping to closed, and jitter past 360°
+/// stays at 360°.
+pub fn hinge_from_gravity_near(lid: [f32; 3], base: [f32; 3], prev: Option<f32>) -> Option<f32> {
+    let across = |v: [f32; 3]| {
+        let norm = (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt();
+        v[1].hypot(v[2]) / norm
+    };
+    // NaN (a zero vector) fails this too.
+    if !(across(lid) >= MIN_ACROSS_HINGE && across(base) >= MIN_ACROSS_HINGE) {
+        return None;
+    }
+    // Rotation of the lid's gravity relative to the base's, about the hinge.
+    let turn = (lid[1].atan2(lid[2]) - base[1].atan2(base[2])).to_degrees();
+    let deg = (FLAT_DEG - turn).rem_euclid(360.0);
+    if deg > CLOSED_MARGIN_DEG && deg < 360.0 - CLOSED_MARGIN_DEG {
+        return Some(deg);
+    }
+    let folded_back = prev.is_some_and(|p| p > FLAT_DEG);
+    Some(match (folded_back, deg < FLAT_DEG) {
+        // Folded back past 360°: as far as it goes.
+        (true, true) => 360.0,
+        // Just short of 360° on a closing lid: closed.
+        (false, false) => CLOSED_DEG,
+        _ => deg,
+    })
+}
+
+/// Convert a backend's native reading from `source` into the common frame.
+/// Hinge and non-degree (ALS) readings pass through unchanged.
+pub fn normalize(source: Source, reading: f32) -> f32 {
+    match source {
+        Source::WinTilt | Source::LinuxTilt => from_pitch(reading),
+        _ => reading,
+    }
+}
@@ -0,0 +1,375 @@
+//! Daemon mode: one process owns the sensors and serves samples over a local
+//! (Unix domain) socket, so several apps share one backend instead of each
+//! polling the HID device.
+//!
+//! The wire format is newline-delimited JSON: a `Hello` line describing the
+//! daemon's backend, then one line per sample.
+
+use crate::filters::{Filter, OutlierRejection, Smoother, Stages};
+use crate::types::Velocity;
+use crate::{
+    AngleClient, AngleDevice, AngleSample, AngleStream, DeviceInfo, Error, OpenConfig, Result,
+    Source, TaskGuard,
+};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{UnixListener, UnixStream},
+    sync::broadcast,
+    time::{self, Duration},
+};
+
+/// How long `open()` waits for a daemon before falling back to local backends.
+const ATTACH_TIMEOUT: Duration = Duration::from_millis(250);
+
+#[derive(Serialize, Deserialize)]
+struct Hello {
+    source: Source,
+    rate_hz: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireSample {
+    angle_deg: f32,
+    /// Absent from older daemons.
+    #[serde(default)]
+    raw: Option<f32>,
+    source: Source,
+    confidence: f32,
+    /// Absent from older daemons; estimated from `raw` then.
+    #[serde(default)]
+    velocity_deg_s: Option<f32>,
+}
+
+/// Socket path used by `run()` and by `open()` when attaching: `$BOOKLID_SOCKET`,
+/// else `$XDG_RUNTIME_DIR/booklid.sock`, else `booklid.sock` in a per-user
+/// `booklid-<uid>` directory under the temp dir.
+pub fn socket_path() -> PathBuf {
+    if let Some(p) = std::env::var_os("BOOKLID_SOCKET") {
+        return PathBuf::from(p);
+    }
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join(format!("booklid-{}", euid())))
+        .join("booklid.sock")
+}
+
+fn euid() -> u32 {
+    // SAFETY: `geteuid` has no preconditions and cannot fail.
+    unsafe { libc::geteuid() }
+}
+
+/// Refuse a socket another user could have bound: it must be owned by this
+/// user and not writable by others.
+fn check_owner(path: &Path) -> Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = std::fs::symlink_metadata(path)?;
+    if meta.uid() != euid() || meta.mode() & 0o002 != 0 {
+        return Err(Error::Other(format!(
+            "daemon socket {} is not owned by this user",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Open local backends per `cfg` and serve them on `socket_path()` until the
+/// client is closed. Attaching is disabled for the daemon itself, and daemon
+/// smoothing is turned off (an EMA with alpha 1 passes samples through) so
+/// each attached app applies its own filter.
+pub async fn run(cfg: OpenConfig) -> Result<()> {
+    let cfg = OpenConfig {
+        filter: None,
+        ..cfg.attach_daemon(false).smoothing(1.0)
+    };
+    let client = crate::open_with_config(cfg).await?;
+    serve(client, socket_path()).await
+}
+
+/// Serve `client`'s samples on a Unix socket at `path`, readable by this
+/// user only. A missing parent directory is created private to this user. A
+/// stale socket file left by a dead daemon is replaced; a live one yields an
+/// error.
+pub async fn serve(client: AngleClient, path: impl AsRef<Path>) -> Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    let path = path.as_ref();
+    if let Some(dir) = path
+        .parent()
+        .filter(|d| !d.as_os_str().is_empty() && !d.exists())
+    {
+        std::fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)?;
+    }
+    if path.exists() {
+        if UnixStream::connect(path).await.is_ok() {
+            return Err(Error::Other(format!(
+                "daemon already running at {}",
+                path.display()
+            )));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    // Closed client: the fanout stream ends and so does the daemon.
+    let closed = client.subscribe().for_each(|_| async {});
+    tokio::pin!(closed);
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (conn, _) = accepted?;
+                tokio::spawn(handle(client.clone(), conn));
+            }
+            _ = &mut closed => break,
+        }
+    }
+    std::fs::remove_file(path).ok();
+    Ok(())
+}
+
+async fn handle(client: AngleClient, mut conn: UnixStream) {
+    let hello = Hello {
+        source: client.info().source,
+        rate_hz: client.stats().rate_hz.unwrap_or(0.0),
+    };
+    // ALS readings and lid switch states too: `subscribe()` drops them.
+    let mut stream = client.subscribe_wire();
+    let Ok(mut line) = serde_json::to_vec(&hello) else {
+        return;
+    };
+    line.push(b'\n');
+    if conn.write_all(&line).await.is_err() {
+        return;
+    }
+    while let Some(s) = stream.next().await {
+        let wire = WireSample {
+            angle_deg: s.angle_deg,
+            raw: Some(s.raw),
+            source: s.source,
+            confidence: s.confidence,
+            velocity_deg_s: Some(s.velocity_deg_s),
+        };
+        let Ok(mut line) = serde_json::to_vec(&wire) else {
+            continue;
+        };
+        line.push(b'\n');
+        // Client went away.
+        if conn.write_all(&line).await.is_err() {
+            return;
+        }
+    }
+}
+
+// ===== launchd (macOS) =====
+
+/// launchd label used by `install_launch_agent()`.
+pub const LAUNCHD_LABEL: &str = "com.booklid.daemon";
+
+/// A launchd agent plist that runs `program daemon` at login and keeps it
+/// alive. The current `socket_path()` is pinned via `BOOKLID_SOCKET`, so apps
+/// started by the same user attach to it.
+pub fn launchd_plist(program: &Path) -> String {
+    let esc = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let program = esc(&program.display().to_string());
+    let socket = esc(&socket_path().display().to_string());
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{program}</string>
+        <string>daemon</string>
+    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>BOOKLID_SOCKET</key>
+        <string>{socket}</string>
+    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <true/>
+    <key>ProcessType</key>
+    <string>Background</string>
+</dict>
+</plist>
+"#
+    )
+}
+
+/// Write the agent plist to `~/Library/LaunchAgents` and load it with
+/// `launchctl`, replacing any previous version. Returns the plist path.
+#[cfg(target_os = "macos")]
+pub fn install_launch_agent(program: &Path) -> Result<PathBuf> {
+    let path = launch_agent_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    // Unload first so a changed plist takes effect; fails harmlessly if absent.
+    let _ = launchctl(&["unload", &path.display().to_string()]);
+    std::fs::write(&path, launchd_plist(program))?;
+    launchctl(&["load", "-w", &path.display().to_string()])?;
+    Ok(path)
+}
+
+/// Unload the agent and remove its plist. Missing agents are not an error.
+#[cfg(target_os = "macos")]
+pub fn uninstall_launch_agent() -> Result<()> {
+    let path = launch_agent_path()?;
+    if path.exists() {
+        let _ = launchctl(&["unload", "-w", &path.display().to_string()]);
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn launch_agent_path() -> Result<PathBuf> {
+    let home = std::env::var_os("HOME").ok_or_else(|| Error::Other("HOME is not set".into()))?;
+    Ok(PathBuf::from(home)
+        .join("Library/LaunchAgents")
+        .join(format!("{LAUNCHD_LABEL}.plist")))
+}
+
+#[cfg(target_os = "macos")]
+fn launchctl(args: &[&str]) -> Result<()> {
+    let status = std::process::Command::new("launchctl")
+        .args(args)
+        .status()?;
+    if !status.success() {
+        return Err(Error::Other(format!(
+            "launchctl {} failed: {status}",
+            args[0]
+        )));
+    }
+    Ok(())
+}
+
+/// A device backed by a running daemon.
+pub(crate) struct RemoteAngle {
+    source: Source,
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    _task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
+}
+
+impl RemoteAngle {
+    pub(crate) async fn connect(path: &Path) -> Result<Self> {
+        check_owner(path)?;
+        let attach = async {
+            let conn = UnixStream::connect(path).await?;
+            let mut lines = BufReader::new(conn).lines();
+            let hello = lines
+                .next_line()
+                .await?
+                .ok_or_else(|| Error::Other("daemon closed before hello".into()))?;
+            let hello: Hello = serde_json::from_str(&hello)?;
+            Ok::<_, Error>((hello, lines))
+        };
+        let (hello, mut lines) = time::timeout(ATTACH_TIMEOUT, attach)
+            .await
+            .map_err(|_| Error::Other("daemon did not answer".into()))??;
+
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let stages = Arc::new(Mutex::new(Stages::default()));
+
+        let latest_c = Arc::clone(&latest);
+        let tx_c = tx.clone();
+        let stages_c = Arc::clone(&stages);
+
+        let task = TaskGuard::spawn(async move {
+            let mut smoother = Smoother::default();
+            let mut velocity = Velocity::default();
+            // Ends when the daemon goes away; `latest()` then reads `None`.
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Ok(w) = serde_json::from_str::<WireSample>(&line) else {
+                    continue;
+                };
+                let now = Instant::now();
+                // A switch's open/closed is not an angle to smooth.
+                let s = if w.source.is_lid_switch() {
+                    w.angle_deg
+                } else {
+                    let Some(s) = smoother.apply(*stages_c.lock().unwrap(), w.angle_deg, now)
+                    else {
+                        continue;
+                    };
+                    s
+                };
+
+                let raw = w.raw.unwrap_or(w.angle_deg);
+                let estimated = velocity.update(raw, now);
+                let sample = AngleSample {
+                    angle_deg: s,
+                    raw,
+                    timestamp: now,
+                    source: w.source,
+                    confidence: w.confidence,
+                    velocity_deg_s: w.velocity_deg_s.unwrap_or(estimated),
+                };
+                *latest_c.lock().unwrap() = Some(sample);
+                let _ = tx_c.send(sample);
+            }
+            latest_c.lock().unwrap().take();
+        });
+
+        Ok(Self {
+            source: hello.source,
+            latest,
+            tx,
+            _task: task,
+            stages,
+        })
+    }
+}
+
+impl AngleDevice for RemoteAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap()
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        use tokio_stream::wrappers::BroadcastStream;
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn set_smoothing(&self, alpha: f32) {
+        self.stages.lock().unwrap().filter = Filter::Ema { alpha };
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages.lock().unwrap().filter = filter;
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages.lock().unwrap().outliers = outliers;
+    }
+
+    fn confidence(&self) -> f32 {
+        self.latest().map_or(0.0, |s| s.confidence)
+    }
+
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(self.source, "daemon")
+    }
+}
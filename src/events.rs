@@ -0,0 +1,383 @@
+//! Semantic lid events derived from samples: opened / closed, threshold
+//! crossings, and source changes, with hysteresis and debouncing so
+//! consumers don't each re-implement them on top of `subscribe()`.
+//!
+//! Use `AngleClient::subscribe_events`, `AngleStreamExt::lid_events` on any
+//! sample stream, or feed a `LidEvents` detector by hand. For thresholds
+//! with their own hysteresis and debounce, use a `ThresholdWatcher`.
+
+use crate::{AngleSample, AngleStream, LidSwitchState, Source};
+use futures_util::{StreamExt, stream::BoxStream};
+use std::time::{Duration, Instant};
+
+/// A change in lid state, see `LidEvents`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LidEvent {
+    /// The angle rose to `LidEventConfig::opened_above` or more.
+    LidOpened,
+    /// The angle fell to `LidEventConfig::closed_below` or less.
+    LidClosed,
+    /// The angle crossed one of `LidEventConfig::thresholds`.
+    AngleCrossed {
+        threshold: f32,
+        direction: Direction,
+    },
+    /// Samples started coming from another source (e.g. a failover).
+    SourceChanged { from: Source, to: Source },
+}
+
+/// Which way an `AngleCrossed` threshold was crossed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    Rising,
+    Falling,
+}
+
+/// Thresholds and filtering for `LidEvents`. Angles are in degrees in the
+/// `convention` frame; normalized (ALS) samples only produce
+/// `SourceChanged`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct LidEventConfig {
+    /// At or below this the lid counts as closed.
+    pub closed_below: f32,
+    /// At or above this the lid counts as open; the gap to `closed_below`
+    /// is the open/closed hysteresis.
+    pub opened_above: f32,
+    /// Angles reported through `AngleCrossed`.
+    pub thresholds: Vec<f32>,
+    /// Width of the dead band around each threshold.
+    pub hysteresis: f32,
+    /// How long a new state must hold (by sample timestamps) before it is
+    /// reported.
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub debounce: Duration,
+}
+
+impl Default for LidEventConfig {
+    /// Closed at 10° or less, open from 20°, no thresholds, 2° hysteresis,
+    /// 100 ms debounce.
+    fn default() -> Self {
+        Self {
+            closed_below: 10.0,
+            opened_above: 20.0,
+            thresholds: Vec::new(),
+            hysteresis: 2.0,
+            debounce: Duration::from_millis(100),
+        }
+    }
+}
+
+impl LidEventConfig {
+    pub fn closed_below(mut self, deg: f32) -> Self {
+        self.closed_below = deg;
+        self
+    }
+    pub fn opened_above(mut self, deg: f32) -> Self {
+        self.opened_above = deg;
+        self
+    }
+    /// Also report crossings of `deg`.
+    pub fn threshold(mut self, deg: f32) -> Self {
+        self.thresholds.push(deg);
+        self
+    }
+    pub fn hysteresis(mut self, deg: f32) -> Self {
+        self.hysteresis = deg;
+        self
+    }
+    pub fn debounce(mut self, d: Duration) -> Self {
+        self.debounce = d;
+        self
+    }
+}
+
+/// Turns samples into `LidEvent`s. Only transitions are reported: the state
+/// the first samples establish is not, so check `latest()` for it. Lid
+/// switch states (`switch`, or samples from a `Source::is_lid_switch`
+/// source) are not debounced, and while one reports closed, angles do not
+/// reopen the lid.
+pub struct LidEvents {
+    cfg: LidEventConfig,
+    source: Option<Source>,
+    lid: Debounced<bool>,
+    switch_closed: bool,
+    crossings: ThresholdWatcher,
+}
+
+impl LidEvents {
+    pub fn new(cfg: LidEventConfig) -> Self {
+        let crossings = cfg
+            .thresholds
+            .iter()
+            .fold(ThresholdWatcher::new(), |w, &t| {
+                w.threshold(
+                    Threshold::new(t)
+                        .hysteresis(cfg.hysteresis)
+                        .debounce(cfg.debounce),
+                )
+            });
+        Self {
+            cfg,
+            source: None,
+            lid: Debounced::default(),
+            switch_closed: false,
+            crossings,
+        }
+    }
+
+    /// Feed one sample; returns the events it completes, source change
+    /// first, then open/closed, then crossings in threshold order.
+    pub fn push(&mut self, s: &AngleSample) -> Vec<LidEvent> {
+        let mut out = Vec::new();
+        match self.source.replace(s.source) {
+            Some(from) if from != s.source => {
+                out.push(LidEvent::SourceChanged { from, to: s.source })
+            }
+            _ => {}
+        }
+        if s.source.is_lid_switch() {
+            out.extend(self.switch(s.lid_switch_state(), s.timestamp));
+            return out;
+        }
+        if !s.is_degrees() || !s.angle_deg.is_finite() {
+            return out;
+        }
+
+        let (a, at) = (s.angle_deg, s.timestamp);
+        let open = if self.switch_closed {
+            None
+        } else if a <= self.cfg.closed_below {
+            Some(false)
+        } else if a >= self.cfg.opened_above {
+            Some(true)
+        } else {
+            None
+        };
+        out.extend(self.lid_changed(open, at, self.cfg.debounce));
+
+        out.extend(
+            self.crossings
+                .push(s)
+                .into_iter()
+                .map(|c| LidEvent::AngleCrossed {
+                    threshold: c.threshold,
+                    direction: c.direction,
+                }),
+        );
+        out
+    }
+
+    /// Whether the lid is open as of the samples so far; `None` until one
+    /// reaches `closed_below` or `opened_above`, or a switch reports.
+    pub fn is_open(&self) -> Option<bool> {
+        self.lid.current
+    }
+
+    pub fn config(&self) -> &LidEventConfig {
+        &self.cfg
+    }
+
+    /// Move the open/closed thresholds, keeping the current state.
+    pub(crate) fn set_lid_thresholds(&mut self, closed_below: f32, opened_above: f32) {
+        self.cfg.closed_below = closed_below;
+        self.cfg.opened_above = opened_above;
+    }
+
+    /// Feed a lid switch's state, observed `at`: reported at once, and
+    /// while closed, angles do not reopen the lid. `None` when the switch
+    /// state is no longer known (after system sleep), so angles decide
+    /// again.
+    pub fn switch(&mut self, state: Option<LidSwitchState>, at: Instant) -> Vec<LidEvent> {
+        self.switch_closed = state == Some(LidSwitchState::Closed);
+        let open = state.map(|s| s == LidSwitchState::Open);
+        self.lid_changed(open, at, Duration::ZERO)
+            .into_iter()
+            .collect()
+    }
+
+    fn lid_changed(
+        &mut self,
+        open: Option<bool>,
+        at: Instant,
+        debounce: Duration,
+    ) -> Option<LidEvent> {
+        self.lid.update(open, at, debounce).map(|open| {
+            if open {
+                LidEvent::LidOpened
+            } else {
+                LidEvent::LidClosed
+            }
+        })
+    }
+}
+
+/// One angle to watch, see `ThresholdWatcher`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Threshold {
+    pub angle: f32,
+    /// Width of the dead band centered on `angle`: the angle must clear it
+    /// on the other side to count as crossed.
+    pub hysteresis: f32,
+    /// How long (by sample timestamps) the other side must hold.
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub debounce: Duration,
+}
+
+impl Threshold {
+    /// `angle` with 2° hysteresis and a 100 ms debounce.
+    pub fn new(angle: f32) -> Self {
+        Self {
+            angle,
+            hysteresis: 2.0,
+            debounce: Duration::from_millis(100),
+        }
+    }
+    pub fn hysteresis(mut self, deg: f32) -> Self {
+        self.hysteresis = deg;
+        self
+    }
+    pub fn debounce(mut self, d: Duration) -> Self {
+        self.debounce = d;
+        self
+    }
+
+    fn side(&self, a: f32) -> Option<bool> {
+        let half = self.hysteresis.abs() / 2.0;
+        if a >= self.angle + half {
+            Some(true)
+        } else if a <= self.angle - half {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+/// A crossing reported by `ThresholdWatcher`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Crossing {
+    pub threshold: f32,
+    pub direction: Direction,
+    /// Angle of the sample that completed the crossing.
+    pub angle_deg: f32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub timestamp: Instant,
+}
+
+/// Reports when the angle crosses any of a set of thresholds, each with its
+/// own hysteresis and debounce so sensor jitter around a threshold is not
+/// reported as a series of crossings. Only degree samples count, and the
+/// side the first samples land on is not reported.
+///
+/// ```no_run
+/// use booklid_rust::{OpenConfig, SampleRate, Threshold, ThresholdWatcher, open_with_config};
+/// use futures_util::StreamExt;
+/// use std::time::Duration;
+///
+/// # async fn run() -> booklid_rust::Result<()> {
+/// let client = open_with_config(OpenConfig::new(SampleRate::hz(60.0))).await?;
+/// let w = ThresholdWatcher::new()
+///     .threshold(Threshold::new(20.0).hysteresis(4.0))
+///     .threshold(Threshold::new(160.0).debounce(Duration::from_millis(250)));
+/// let mut crossings = client.watch_thresholds(w);
+/// while let Some(c) = crossings.next().await {
+///     println!("{:?} {}", c.direction, c.threshold);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Default)]
+pub struct ThresholdWatcher {
+    thresholds: Vec<(Threshold, Debounced<bool>)>,
+}
+
+impl ThresholdWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn threshold(mut self, t: Threshold) -> Self {
+        self.thresholds.push((t, Debounced::default()));
+        self
+    }
+
+    /// Feed one sample; returns the crossings it completes, in the order the
+    /// thresholds were added.
+    pub fn push(&mut self, s: &AngleSample) -> Vec<Crossing> {
+        if !s.is_degrees() || !s.angle_deg.is_finite() {
+            return Vec::new();
+        }
+        let mut out = Vec::new();
+        for (t, state) in &mut self.thresholds {
+            if let Some(rising) = state.update(t.side(s.angle_deg), s.timestamp, t.debounce) {
+                out.push(Crossing {
+                    threshold: t.angle,
+                    direction: if rising {
+                        Direction::Rising
+                    } else {
+                        Direction::Falling
+                    },
+                    angle_deg: s.angle_deg,
+                    timestamp: s.timestamp,
+                });
+            }
+        }
+        out
+    }
+
+    /// Crossings in `stream`, as they happen.
+    pub fn watch(mut self, stream: AngleStream) -> BoxStream<'static, Crossing> {
+        stream
+            .flat_map(move |s| futures_util::stream::iter(self.push(&s)))
+            .boxed()
+    }
+}
+
+/// A two-sided state that only changes once the other side has held for the
+/// debounce time.
+#[derive(Default)]
+struct Debounced<T> {
+    current: Option<T>,
+    pending: Option<(T, Instant)>,
+}
+
+impl<T: Copy + PartialEq> Debounced<T> {
+    /// `seen` is `None` inside a hysteresis band. Returns the new state when
+    /// it changes.
+    fn update(&mut self, seen: Option<T>, at: Instant, debounce: Duration) -> Option<T> {
+        let Some(seen) = seen else {
+            self.pending = None;
+            return None;
+        };
+        let Some(current) = self.current else {
+            self.current = Some(seen);
+            return None;
+        };
+        if seen == current {
+            self.pending = None;
+            return None;
+        }
+        let since = match self.pending {
+            Some((p, since)) if p == seen => since,
+            _ => {
+                self.pending = Some((seen, at));
+                at
+            }
+        };
+        if at.saturating_duration_since(since) < debounce {
+            return None;
+        }
+        self.current = Some(seen);
+        self.pending = None;
+        Some(seen)
+    }
+}
@@ -0,0 +1,87 @@
+//! Arrow / Parquet export for recordings (feature `arrow`).
+
+use crate::Result;
+use crate::record::Recording;
+use arrow_array::{
+    ArrayRef, Float32Array, RecordBatch, StringArray, TimestampMicrosecondArray, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use std::{fs::File, path::Path, sync::Arc};
+
+impl Recording {
+    /// Arrow schema of `to_record_batch()`: `utc` (wall-clock timestamp),
+    /// `offset_us` (monotonic offset), `angle_deg`, `raw` (null in older
+    /// recordings), `confidence`, `source`, `velocity_deg_s` (null in older
+    /// recordings).
+    pub fn arrow_schema() -> Schema {
+        Schema::new(vec![
+            Field::new(
+                "utc",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                false,
+            ),
+            Field::new("offset_us", DataType::UInt64, false),
+            Field::new("angle_deg", DataType::Float32, false),
+            Field::new("raw", DataType::Float32, true),
+            Field::new("confidence", DataType::Float32, false),
+            Field::new("source", DataType::Utf8, false),
+            Field::new("velocity_deg_s", DataType::Float32, true),
+        ])
+    }
+
+    /// All samples as one Arrow record batch.
+    pub fn to_record_batch(&self) -> Result<RecordBatch> {
+        let start_us = self.header.started_utc_ms as i64 * 1000;
+        let offsets: Vec<u64> = self
+            .samples
+            .iter()
+            .map(|s| s.offset.as_micros() as u64)
+            .collect();
+        let utc: Vec<i64> = offsets.iter().map(|o| start_us + *o as i64).collect();
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(TimestampMicrosecondArray::from(utc).with_timezone("UTC")),
+            Arc::new(UInt64Array::from(offsets)),
+            Arc::new(Float32Array::from_iter_values(
+                self.samples.iter().map(|s| s.angle_deg),
+            )),
+            Arc::new(Float32Array::from_iter(self.samples.iter().map(|s| s.raw))),
+            Arc::new(Float32Array::from_iter_values(
+                self.samples.iter().map(|s| s.confidence),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                self.samples.iter().map(|s| source_name(s.source)),
+            )),
+            Arc::new(Float32Array::from_iter(
+                self.samples.iter().map(|s| s.velocity_deg_s),
+            )),
+        ];
+        Ok(RecordBatch::try_new(
+            Arc::new(Self::arrow_schema()),
+            columns,
+        )?)
+    }
+
+    /// Write the recording as a Parquet file (one row group).
+    pub fn write_parquet(&self, path: impl AsRef<Path>) -> Result<()> {
+        let batch = self.to_record_batch()?;
+        let mut w =
+            parquet::arrow::ArrowWriter::try_new(File::create(path)?, batch.schema(), None)?;
+        w.write(&batch)?;
+        w.close()?;
+        Ok(())
+    }
+}
+
+/// Same spelling as the JSON recording (`"HingeFeature"`, or the id for `Other`).
+fn source_name(s: crate::Source) -> String {
+    match serde_json::to_value(s) {
+        Ok(serde_json::Value::String(name)) => name,
+        Ok(serde_json::Value::Object(m)) => m
+            .values()
+            .next()
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        _ => format!("{s:?}"),
+    }
+}
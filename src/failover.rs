@@ -0,0 +1,264 @@
+//! Quality-aware switching between a primary sensor and a cross-check sensor
+//! (WinHinge with WinTilt as backup, or any backend with its warm standby).
+//! Some hinge drivers stall or report a constant angle; when that happens
+//! while the cross-check sensor sees the lid move, samples come from the
+//! backup until the primary recovers. A primary whose stream ends switches
+//! over immediately.
+
+use crate::client::DynDevice;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, DeviceInfo, Event, Filter, LightSample,
+    OutlierRejection, SwitchReason, TaskGuard,
+};
+use futures_util::{StreamExt, stream::BoxStream};
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+use tokio::{sync::broadcast, time};
+use tokio_stream::wrappers::BroadcastStream;
+
+/// Primary values closer than this count as "unchanged".
+const EPS: f32 = 0.01;
+
+/// Missed sample periods before the primary counts as stalled.
+const STALL_PERIODS: f32 = 10.0;
+
+/// Stall floor: event-driven sensors are read at least twice a second even
+/// when the lid is still (the default `WinReporting::quiet_read`), so
+/// anything shorter would switch on a quiet lid.
+const MIN_STALL: Duration = Duration::from_millis(750);
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FailoverPolicy {
+    /// Switch when the primary delivers nothing for this long; `None` for a
+    /// primary that may stay silent while the lid is still. Either way it
+    /// switches when the primary is silent for `constant_for` while the
+    /// cross-check sensor moves.
+    pub stall_after: Option<Duration>,
+    /// Switch when the primary holds one value this long...
+    pub constant_for: Duration,
+    /// ...while the cross-check sensor moved at least this much.
+    pub min_motion: f32,
+}
+
+impl FailoverPolicy {
+    /// Policy for a primary sampled at `hz`: it stalls after
+    /// [`STALL_PERIODS`] missed samples (at least [`MIN_STALL`]).
+    pub(crate) fn for_rate(hz: f32) -> Self {
+        Self {
+            stall_after: Some(Duration::from_secs_f32(STALL_PERIODS / hz.max(0.1)).max(MIN_STALL)),
+            constant_for: Duration::from_secs(3),
+            min_motion: 5.0,
+        }
+    }
+
+    /// For a primary that a still lid leaves quiet for up to `quiet_read`
+    /// (`WinReporting::quiet_read`); zero means indefinitely.
+    pub(crate) fn quiet_read(mut self, quiet_read: Duration) -> Self {
+        self.stall_after = self
+            .stall_after
+            .filter(|_| !quiet_read.is_zero())
+            .map(|s| s.max(quiet_read * 3 / 2));
+        self
+    }
+}
+
+pub(crate) struct Failover {
+    primary: DynDevice,
+    secondary: DynDevice,
+    on_secondary: Arc<AtomicBool>,
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    events: broadcast::Sender<Event>,
+    _task: TaskGuard,
+}
+
+impl Failover {
+    pub(crate) fn new(primary: DynDevice, secondary: DynDevice, policy: FailoverPolicy) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let (events, _rx) = broadcast::channel::<Event>(16);
+        let on_secondary = Arc::new(AtomicBool::new(false));
+
+        let mut p_stream = primary.subscribe();
+        let mut s_stream = secondary.subscribe();
+        let (p_src, s_src) = (primary.info().source, secondary.info().source);
+        let latest_c = Arc::clone(&latest);
+        let tx_c = tx.clone();
+        let events_c = events.clone();
+        let on_secondary_c = Arc::clone(&on_secondary);
+
+        let task = TaskGuard::spawn(async move {
+            let switch = |to_secondary: bool, reason: SwitchReason| {
+                on_secondary_c.store(to_secondary, Ordering::Relaxed);
+                let (from, to) = if to_secondary {
+                    (p_src, s_src)
+                } else {
+                    (s_src, p_src)
+                };
+                #[cfg(feature = "diagnostics")]
+                eprintln!("[booklid] switching {:?} -> {:?} ({:?})", from, to, reason);
+                let _ = events_c.send(Event::SourceSwitched { from, to, reason });
+            };
+            let emit = |s: AngleSample| {
+                *latest_c.lock().unwrap() = Some(s);
+                let _ = tx_c.send(s);
+            };
+
+            let mut last_p: Option<f32> = None;
+            let mut last_p_at = Instant::now();
+            let mut changed_at = Instant::now();
+            // Cross-check range seen since the primary last changed.
+            let mut motion: Option<(f32, f32)> = None;
+            let mut tick = time::interval(policy.stall_after.unwrap_or(policy.constant_for) / 4);
+            let (mut p_done, mut s_done) = (false, false);
+
+            while !(p_done && s_done) {
+                tokio::select! {
+                    p = p_stream.next(), if !p_done => {
+                        let Some(p) = p else {
+                            p_done = true;
+                            if !on_secondary_c.load(Ordering::Relaxed) {
+                                switch(true, SwitchReason::Stalled);
+                            }
+                            continue;
+                        };
+                        let now = Instant::now();
+                        last_p_at = now;
+                        let changed = last_p.is_none_or(|v| (p.angle_deg - v).abs() > EPS);
+                        last_p = Some(p.angle_deg);
+                        if changed {
+                            changed_at = now;
+                            motion = None;
+                        }
+                        if on_secondary_c.load(Ordering::Relaxed) {
+                            if changed {
+                                switch(false, SwitchReason::Recovered);
+                                emit(p);
+                            }
+                            continue;
+                        }
+                        let moved = motion.is_some_and(|(lo, hi)| hi - lo >= policy.min_motion);
+                        if now - changed_at >= policy.constant_for && moved {
+                            switch(true, SwitchReason::Constant);
+                            continue;
+                        }
+                        emit(p);
+                    }
+                    s = s_stream.next(), if !s_done => {
+                        let Some(s) = s else {
+                            s_done = true;
+                            continue;
+                        };
+                        let v = s.angle_deg;
+                        motion = Some(motion.map_or((v, v), |(lo, hi)| (lo.min(v), hi.max(v))));
+                        if on_secondary_c.load(Ordering::Relaxed) {
+                            emit(s);
+                        }
+                    }
+                    _ = tick.tick() => {
+                        let quiet = last_p_at.elapsed();
+                        let moved = motion.is_some_and(|(lo, hi)| hi - lo >= policy.min_motion);
+                        if !on_secondary_c.load(Ordering::Relaxed)
+                            && (policy.stall_after.is_some_and(|s| quiet >= s)
+                                || quiet >= policy.constant_for && moved)
+                        {
+                            switch(true, SwitchReason::Stalled);
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            primary,
+            secondary,
+            on_secondary,
+            latest,
+            tx,
+            events,
+            _task: task,
+        }
+    }
+
+    fn active(&self) -> &DynDevice {
+        if self.on_secondary.load(Ordering::Relaxed) {
+            &self.secondary
+        } else {
+            &self.primary
+        }
+    }
+}
+
+impl AngleDevice for Failover {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap()
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn set_smoothing(&self, alpha: f32) {
+        self.primary.set_smoothing(alpha);
+        self.secondary.set_smoothing(alpha);
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.primary.set_filter(filter);
+        self.secondary.set_filter(filter);
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.primary.set_outlier_rejection(outliers);
+        self.secondary.set_outlier_rejection(outliers);
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.primary.set_paused(paused);
+        self.secondary.set_paused(paused);
+    }
+
+    fn confidence(&self) -> f32 {
+        self.active().confidence()
+    }
+
+    fn info(&self) -> DeviceInfo {
+        self.primary.info()
+    }
+
+    fn hardware_id(&self) -> Option<String> {
+        self.primary.hardware_id()
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        self.active().is_reconnecting()
+    }
+
+    fn events(&self) -> BoxStream<'static, Event> {
+        BroadcastStream::new(self.events.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    /// Light from either sensor (at most one of a pair is usually ALS).
+    fn subscribe_lux(&self) -> BoxStream<'static, LightSample> {
+        futures_util::stream::select(self.primary.subscribe_lux(), self.secondary.subscribe_lux())
+            .boxed()
+    }
+
+    #[cfg(feature = "hid_debug")]
+    fn subscribe_raw_reports(&self) -> BoxStream<'static, crate::hid::RawReport> {
+        futures_util::stream::select(
+            self.primary.subscribe_raw_reports(),
+            self.secondary.subscribe_raw_reports(),
+        )
+        .boxed()
+    }
+}
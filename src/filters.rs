@@ -0,0 +1,422 @@
+//! Signal processing used by the backends' sampling loops: optional outlier
+//! rejection, then the default EMA or a 1-D Kalman filter over angle and
+//! angular velocity, plus the light-sensor baseline and the variance-based
+//! confidence. The building blocks are pure and usable on their own, e.g.
+//! to post-process a recording:
+//!
+//! ```
+//! use booklid_rust::filters::{Baseline, RollingVariance, bellows, stability};
+//!
+//! let mut baseline = Baseline::new(10.0);
+//! let mut var = RollingVariance::default();
+//! for lux in [10.0, 10.5, 9.8, 40.0] {
+//!     let n = bellows(baseline.update(lux), 0.02);
+//!     let confidence = stability(var.push(n), 20.0);
+//!     assert!((0.0..=1.0).contains(&n) && (0.0..=1.0).contains(&confidence));
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// How backends smooth readings, see `OpenConfig::filter` and
+/// `AngleClient::set_filter`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Filter {
+    /// Exponential moving average with factor `alpha` in 0..1 (1.0 = no
+    /// smoothing). Simple, but lags a moving lid as much as it damps jitter.
+    Ema { alpha: f32 },
+    /// EMA specified by its time constant in seconds: the factor for each
+    /// sample is `1 - exp(-dt / tau_s)` over the actual time since the last
+    /// one, so the same `tau_s` smooths alike at 10 Hz and 120 Hz and
+    /// through irregular sample spacing. 0 disables smoothing.
+    TimeConstant { tau_s: f32 },
+    /// Constant-velocity Kalman filter: tracks angle and velocity, so it
+    /// follows a moving lid without lag while still averaging out jitter.
+    /// `r` is the sensor's jitter variance in °²; `q` is how freely the
+    /// velocity may change (process noise, (°/s²)²·s). Raise `q` or lower
+    /// `r` to follow faster; e.g. `q: 500.0, r: 1.0` for a hinge sensor.
+    Kalman { q: f32, r: f32 },
+}
+
+impl Default for Filter {
+    /// EMA with alpha 0.25, the backends' default.
+    fn default() -> Self {
+        Filter::Ema { alpha: 0.25 }
+    }
+}
+
+impl Filter {
+    pub(crate) fn is_valid(&self) -> bool {
+        match *self {
+            Filter::Ema { alpha } => alpha.is_finite(),
+            Filter::TimeConstant { tau_s } => tau_s.is_finite() && tau_s >= 0.0,
+            Filter::Kalman { q, r } => q.is_finite() && q >= 0.0 && r.is_finite() && r > 0.0,
+        }
+    }
+}
+
+/// Drops readings that sit far from the median of the last `window`
+/// readings, so glitches (a HID report of 0 or 65535) never reach the
+/// smoother. A reading is an outlier when it is more than `max_deviation`
+/// robust standard deviations (1.4826 × MAD) from the median, or
+/// `min_spread` when that is larger, which keeps a still lid's tiny jitter
+/// from counting. Outliers still enter the window, so a real jump is
+/// accepted once it persists for about half of it. Until three readings are
+/// in, readings are taken as they come. See `OpenConfig::reject_outliers`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct OutlierRejection {
+    /// Readings the median and MAD are taken over (at least 3).
+    pub window: usize,
+    pub max_deviation: f32,
+    /// In the source's units: degrees, or 0..1 for normalized sources.
+    pub min_spread: f32,
+}
+
+impl Default for OutlierRejection {
+    /// Median of 5, 3 deviations, never closer than 5.
+    fn default() -> Self {
+        Self {
+            window: 5,
+            max_deviation: 3.0,
+            min_spread: 5.0,
+        }
+    }
+}
+
+impl OutlierRejection {
+    pub fn window(mut self, n: usize) -> Self {
+        self.window = n;
+        self
+    }
+    pub fn max_deviation(mut self, k: f32) -> Self {
+        self.max_deviation = k;
+        self
+    }
+    pub fn min_spread(mut self, v: f32) -> Self {
+        self.min_spread = v;
+        self
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        self.window >= 3
+            && self.max_deviation.is_finite()
+            && self.max_deviation > 0.0
+            && self.min_spread.is_finite()
+            && self.min_spread >= 0.0
+    }
+}
+
+/// A complete smoothing setup, applied at once with
+/// `AngleClient::set_filter_params`, e.g. to switch an interactive
+/// calibration screen to `responsive()` and back to `stable()` without
+/// reopening the device.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct FilterParams {
+    pub filter: Filter,
+    /// `None` turns outlier rejection off.
+    pub outliers: Option<OutlierRejection>,
+}
+
+impl FilterParams {
+    /// Follows the lid closely: 30 ms time constant, no outlier rejection
+    /// (which would hold back a real jump for a few samples).
+    pub fn responsive() -> Self {
+        Self {
+            filter: Filter::TimeConstant { tau_s: 0.03 },
+            outliers: None,
+        }
+    }
+
+    /// Steady readings: 300 ms time constant and default outlier rejection.
+    pub fn stable() -> Self {
+        Self {
+            filter: Filter::TimeConstant { tau_s: 0.3 },
+            outliers: Some(OutlierRejection::default()),
+        }
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+    pub fn reject_outliers(mut self, outliers: Option<OutlierRejection>) -> Self {
+        self.outliers = outliers;
+        self
+    }
+}
+
+/// One exponential-moving-average step from `prev` toward `x`; `alpha` is
+/// clamped to 0..1 (1.0 = no smoothing).
+pub fn ema(prev: f32, x: f32, alpha: f32) -> f32 {
+    prev + alpha.clamp(0.0, 1.0) * (x - prev)
+}
+
+/// High-pass over a slowly drifting signal: tracks a slow EMA baseline and
+/// returns each reading's offset from it. The light-sensor backends use it
+/// to remove ambient drift from lux readings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Baseline {
+    value: f32,
+    rate: f32,
+}
+
+impl Baseline {
+    /// Starting at `initial`, following at 0.005 per reading.
+    pub fn new(initial: f32) -> Self {
+        Self {
+            value: initial,
+            rate: 0.005,
+        }
+    }
+    /// EMA factor of the baseline per reading.
+    pub fn rate(mut self, rate: f32) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Moves the baseline toward `x` and returns `x` minus the new baseline.
+    pub fn update(&mut self, x: f32) -> f32 {
+        self.value = ema(self.value, x, self.rate);
+        x - self.value
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+}
+
+/// Maps a `Baseline` offset into the 0..1 "bellows" range the light-sensor
+/// sources report: 0.5 at the baseline, scaled by `gain`, clamped.
+pub fn bellows(offset: f32, gain: f32) -> f32 {
+    (offset * gain + 0.5).clamp(0.0, 1.0)
+}
+
+/// Population variance over the last `capacity` values.
+#[derive(Clone, Debug)]
+pub struct RollingVariance {
+    buf: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl Default for RollingVariance {
+    /// The backends' 64-sample window.
+    fn default() -> Self {
+        Self::new(64)
+    }
+}
+
+impl RollingVariance {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            buf: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds `x`, dropping the oldest value when full; returns the variance.
+    pub fn push(&mut self, x: f32) -> f32 {
+        if self.buf.len() == self.capacity {
+            self.buf.pop_front();
+        }
+        self.buf.push_back(x);
+        self.variance()
+    }
+
+    pub fn mean(&self) -> f32 {
+        if self.buf.is_empty() {
+            return 0.0;
+        }
+        self.buf.iter().sum::<f32>() / self.buf.len() as f32
+    }
+
+    /// 0 while empty.
+    pub fn variance(&self) -> f32 {
+        if self.buf.is_empty() {
+            return 0.0;
+        }
+        let m = self.mean();
+        self.buf.iter().map(|v| (v - m) * (v - m)).sum::<f32>() / self.buf.len() as f32
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+}
+
+/// Confidence from a `RollingVariance`: `1 / (1 + gain × variance)`, so a
+/// steady signal scores 1. `gain` sets how much jitter halves it (20 for
+/// 0..1 light sources, 0.02–0.05 for degrees).
+pub fn stability(variance: f32, gain: f32) -> f32 {
+    (1.0 / (1.0 + gain * variance)).clamp(0.0, 1.0)
+}
+
+/// Settings shared between a backend handle and its sampling loop.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct Stages {
+    pub(crate) filter: Filter,
+    pub(crate) outliers: Option<OutlierRejection>,
+}
+
+/// Initial velocity variance of a Kalman filter, (°/s)²: nothing is known
+/// about the motion yet.
+const KALMAN_V0: f32 = 1.0e4;
+
+/// Per-loop filter state. Switching `Filter` kinds carries the current
+/// estimate over, so the output does not jump.
+#[derive(Default)]
+pub(crate) struct Smoother {
+    state: Option<State>,
+    recent: VecDeque<f32>,
+}
+
+enum State {
+    /// Both EMA forms, with the time of the last reading.
+    Ema {
+        value: f32,
+        at: Instant,
+    },
+    Kalman(Kalman),
+}
+
+impl Smoother {
+    /// Run reading `z` taken at `at` through `stages`; `None` when it is
+    /// not finite or rejected as an outlier, and should not be emitted.
+    pub(crate) fn apply(&mut self, stages: Stages, z: f32, at: Instant) -> Option<f32> {
+        // One NaN in the filter state would make every later output NaN.
+        if !z.is_finite() {
+            return None;
+        }
+        match stages.outliers {
+            Some(o) if self.is_outlier(o, z) => None,
+            Some(_) => Some(self.smooth(stages.filter, z, at)),
+            None => {
+                self.recent.clear();
+                Some(self.smooth(stages.filter, z, at))
+            }
+        }
+    }
+
+    /// Checks `z` against the window, then adds it.
+    fn is_outlier(&mut self, o: OutlierRejection, z: f32) -> bool {
+        if !z.is_finite() {
+            return true;
+        }
+        let out = self.recent.len() >= 3 && {
+            let mid = median(self.recent.iter().copied().collect());
+            let mad = median(self.recent.iter().map(|v| (v - mid).abs()).collect());
+            (z - mid).abs() > (o.max_deviation * 1.4826 * mad).max(o.min_spread)
+        };
+        while self.recent.len() >= o.window.max(1) {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(z);
+        out
+    }
+
+    fn smooth(&mut self, filter: Filter, z: f32, at: Instant) -> f32 {
+        match (filter, &mut self.state) {
+            (Filter::Ema { alpha }, Some(State::Ema { value, at: last })) => {
+                *value = ema(*value, z, alpha);
+                *last = at;
+                *value
+            }
+            (Filter::TimeConstant { tau_s }, Some(State::Ema { value, at: last })) => {
+                let dt = at.saturating_duration_since(*last).as_secs_f32();
+                let alpha = if tau_s > 0.0 {
+                    1.0 - (-dt / tau_s).exp()
+                } else {
+                    1.0
+                };
+                *value = ema(*value, z, alpha);
+                *last = (*last).max(at);
+                *value
+            }
+            (Filter::Kalman { q, r }, Some(State::Kalman(k))) => k.update(q, r, z, at),
+            (filter, state) => {
+                let start = match state {
+                    Some(State::Ema { value, .. }) => *value,
+                    Some(State::Kalman(k)) => k.x[0],
+                    None => z,
+                };
+                *state = Some(match filter {
+                    Filter::Ema { .. } | Filter::TimeConstant { .. } => {
+                        State::Ema { value: start, at }
+                    }
+                    Filter::Kalman { r, .. } => State::Kalman(Kalman::new(start, r, at)),
+                });
+                self.smooth(filter, z, at)
+            }
+        }
+    }
+}
+
+/// State `x = [angle, velocity]` with covariance `p`.
+struct Kalman {
+    x: [f32; 2],
+    p: [[f32; 2]; 2],
+    at: Instant,
+}
+
+impl Kalman {
+    fn new(angle: f32, r: f32, at: Instant) -> Self {
+        Self {
+            x: [angle, 0.0],
+            p: [[r.max(f32::EPSILON), 0.0], [0.0, KALMAN_V0]],
+            at,
+        }
+    }
+
+    fn update(&mut self, q: f32, r: f32, z: f32, at: Instant) -> f32 {
+        let (q, r) = (q.max(0.0), r.max(f32::EPSILON));
+        let dt = at.saturating_duration_since(self.at).as_secs_f32();
+        self.at = self.at.max(at);
+
+        // Predict: constant velocity, white-noise acceleration.
+        let [[p00, p01], [p10, p11]] = self.p;
+        self.x[0] += dt * self.x[1];
+        let (dt2, dt3) = (dt * dt, dt * dt * dt);
+        let p00 = p00 + dt * (p01 + p10) + dt2 * p11 + q * dt3 / 3.0;
+        let p01 = p01 + dt * p11 + q * dt2 / 2.0;
+        let p10 = p10 + dt * p11 + q * dt2 / 2.0;
+        let p11 = p11 + q * dt;
+
+        // Update with the measured angle.
+        let s = p00 + r;
+        let (k0, k1) = (p00 / s, p10 / s);
+        let y = z - self.x[0];
+        self.x[0] += k0 * y;
+        self.x[1] += k1 * y;
+        self.p = [
+            [(1.0 - k0) * p00, (1.0 - k0) * p01],
+            [p10 - k1 * p00, p11 - k1 * p01],
+        ];
+        self.x[0]
+    }
+}
+
+fn median(mut v: Vec<f32>) -> f32 {
+    v.sort_by(f32::total_cmp);
+    let n = v.len();
+    if n % 2 == 1 {
+        v[n / 2]
+    } else {
+        (v[n / 2 - 1] + v[n / 2]) / 2.0
+    }
+}
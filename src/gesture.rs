@@ -0,0 +1,141 @@
+//! Rapid-close detection from `AngleSample::velocity_deg_s`, so apps can save
+//! state in the moment between a lid being slammed and the OS suspending.
+
+use crate::AngleSample;
+
+/// A fast close, see `GestureDetector`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Gesture {
+    /// The lid started closing faster than `GestureConfig::closing_rate_deg_s`
+    /// while still open; reported on the first such sample.
+    ClosingFast { angle_deg: f32, velocity_deg_s: f32 },
+    /// That close reached `GestureConfig::closed_below`. `peak_velocity_deg_s`
+    /// is the fastest closing speed seen on the way (as a positive rate).
+    SlamDetected { peak_velocity_deg_s: f32 },
+}
+
+/// Rates and bounds for `GestureDetector`. Angles are in degrees in the
+/// `convention` frame; closing means moving toward 0°.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct GestureConfig {
+    /// Closing speed (°/s, positive) that counts as fast.
+    pub closing_rate_deg_s: f32,
+    /// At or below this angle the lid counts as shut.
+    pub closed_below: f32,
+    /// Below this sample confidence samples are ignored.
+    pub min_confidence: f32,
+}
+
+impl Default for GestureConfig {
+    /// Fast from 120 °/s, shut at 10° or less, confidence 0.5.
+    fn default() -> Self {
+        Self {
+            closing_rate_deg_s: 120.0,
+            closed_below: 10.0,
+            min_confidence: 0.5,
+        }
+    }
+}
+
+impl GestureConfig {
+    pub fn closing_rate(mut self, deg_s: f32) -> Self {
+        self.closing_rate_deg_s = deg_s;
+        self
+    }
+    pub fn closed_below(mut self, deg: f32) -> Self {
+        self.closed_below = deg;
+        self
+    }
+    pub fn min_confidence(mut self, c: f32) -> Self {
+        self.min_confidence = c;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum State {
+    Idle,
+    /// `ClosingFast` reported; fastest closing rate so far.
+    Closing {
+        peak: f32,
+    },
+    /// Shut; nothing is reported until the lid opens again.
+    Closed,
+}
+
+/// Turns samples into `Gesture`s: `ClosingFast` once a close passes the
+/// rate, then `SlamDetected` if it reaches shut. A close that slows to half
+/// the rate before shutting is dropped without a `SlamDetected`; one that
+/// shuts between two samples is reported as `SlamDetected` alone.
+pub struct GestureDetector {
+    cfg: GestureConfig,
+    state: State,
+}
+
+impl GestureDetector {
+    pub fn new(cfg: GestureConfig) -> Self {
+        Self {
+            cfg,
+            state: State::Idle,
+        }
+    }
+
+    /// Feed one sample; returns the gesture it completes, if any.
+    pub fn push(&mut self, s: &AngleSample) -> Option<Gesture> {
+        if !s.is_degrees()
+            || !s.angle_deg.is_finite()
+            || !s.velocity_deg_s.is_finite()
+            || s.confidence < self.cfg.min_confidence
+        {
+            return None;
+        }
+        let rate = self.cfg.closing_rate_deg_s.abs();
+        let closing = -s.velocity_deg_s;
+        let shut = s.angle_deg <= self.cfg.closed_below;
+        match self.state {
+            State::Idle if shut => {
+                // Shut within one sample: no chance to warn first.
+                self.state = State::Closed;
+                (closing >= rate).then_some(Gesture::SlamDetected {
+                    peak_velocity_deg_s: closing,
+                })
+            }
+            State::Idle if closing >= rate => {
+                self.state = State::Closing { peak: closing };
+                Some(Gesture::ClosingFast {
+                    angle_deg: s.angle_deg,
+                    velocity_deg_s: s.velocity_deg_s,
+                })
+            }
+            State::Idle => None,
+            State::Closing { peak } => {
+                let peak = peak.max(closing);
+                if shut {
+                    self.state = State::Closed;
+                    Some(Gesture::SlamDetected {
+                        peak_velocity_deg_s: peak,
+                    })
+                } else {
+                    self.state = if closing < rate / 2.0 {
+                        State::Idle
+                    } else {
+                        State::Closing { peak }
+                    };
+                    None
+                }
+            }
+            State::Closed => {
+                if !shut {
+                    self.state = State::Idle;
+                }
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,1052 @@
+//! HID hinge sampling written against the `HidTransport` trait instead of
+//! `hidapi` directly. With feature `mac_hid_feature`, `open()` uses the
+//! hidapi transport; tests and custom integrations can supply scripted
+//! devices on any platform.
+
+use crate::filters::{Filter, OutlierRejection, Smoother, Stages};
+use crate::hid_descriptor::{self, ReportKind};
+use crate::quirks::{self, MAX_REPORT_LEN, ReportFormat, UserQuirks};
+use crate::types::Velocity;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, Backoff, DeviceInfo, Error, Result, Source, TaskGuard,
+    persist,
+};
+use futures_util::{StreamExt, stream::BoxStream};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+use tokio::{
+    sync::{broadcast, mpsc},
+    time::{self, Duration},
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+const APPLE_VID: u16 = 0x05AC;
+const APPLE_HINGE_PID: u16 = 0x8104;
+/// HID Usage Page "Sensor" / Usage "Orientation".
+const USAGE_PAGE_SENSOR: u16 = 0x20;
+const USAGE_ORIENTATION: u16 = 0x008A;
+
+/// One enumerated HID device.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HidDeviceInfo {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub usage_page: u16,
+    pub usage: u16,
+    /// Transport-specific handle used by `open()` (e.g. the hidraw path).
+    pub path: String,
+    pub serial_number: Option<String>,
+}
+
+impl HidDeviceInfo {
+    /// `hid:<vid>:<pid>[:<serial>]`, see `AngleDevice::hardware_id`. Unlike
+    /// `path`, it survives re-plugging and reboots.
+    pub fn hardware_id(&self) -> String {
+        let id = format!("hid:{:04x}:{:04x}", self.vendor_id, self.product_id);
+        match self.serial_number.as_deref().filter(|s| !s.is_empty()) {
+            Some(serial) => format!("{id}:{serial}"),
+            None => id,
+        }
+    }
+}
+
+/// The HID calls `HidAngle` makes.
+pub trait HidTransport: Send + 'static {
+    type Device: Send + 'static;
+
+    /// Current device list, re-scanned on every call.
+    fn enumerate(&mut self) -> Result<Vec<HidDeviceInfo>>;
+    fn open(&mut self, info: &HidDeviceInfo) -> Result<Self::Device>;
+    /// `buf[0]` holds the report id; returns the number of bytes read.
+    fn get_feature_report(&mut self, dev: &Self::Device, buf: &mut [u8]) -> Result<usize>;
+    /// Input report read; `Ok(0)` on timeout.
+    fn read(&mut self, dev: &Self::Device, buf: &mut [u8], timeout_ms: i32) -> Result<usize>;
+    /// The device's report descriptor; empty when the transport cannot
+    /// read it.
+    fn report_descriptor(&mut self, _dev: &Self::Device) -> Result<Vec<u8>> {
+        Ok(Vec::new())
+    }
+    /// A transport of its own for the `ReadMode::Input` reader thread, so
+    /// its blocking `read`s don't hold this one's lock. `None` (the
+    /// default) reads through this one, locking it for each read.
+    fn input_handle(&mut self) -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+/// Hinge angle from a 3-byte feature report: id, then a little-endian u16.
+/// The count is taken as degrees; add a `quirks::HidQuirk` for hinges with
+/// another layout or scale, or fit a `calibrate::AngleCalibration` where it
+/// is not linear.
+pub fn parse_angle(report: &[u8; 3]) -> f32 {
+    u16::from_le_bytes([report[1], report[2]]) as f32
+}
+
+/// Hinge angle from the bytes a feature-report read returned: `None` when
+/// the report is shorter than 3 bytes or carries a different id.
+pub fn parse_feature_report(report: &[u8], report_id: u8) -> Option<f32> {
+    ReportFormat::with_report_id(report_id).parse(report)
+}
+
+fn read_angle<T: HidTransport>(t: &mut T, dev: &T::Device, format: &ReportFormat) -> Result<f32> {
+    let (buf, n) = read_feature(t, dev, format)?;
+    angle_in(format, &buf[..n])
+}
+
+/// Feature report `format.report_id` and its length as read.
+fn read_feature<T: HidTransport>(
+    t: &mut T,
+    dev: &T::Device,
+    format: &ReportFormat,
+) -> Result<([u8; MAX_REPORT_LEN], usize)> {
+    let mut buf = [0u8; MAX_REPORT_LEN];
+    let len = format.report_len().min(MAX_REPORT_LEN);
+    buf[0] = format.report_id;
+    let n = t.get_feature_report(dev, &mut buf[..len])?;
+    Ok((buf, n.min(len)))
+}
+
+fn angle_in(format: &ReportFormat, report: &[u8]) -> Result<f32> {
+    format.parse(report).ok_or_else(|| {
+        Error::Backend(format!(
+            "hid: short or mismatched feature report {} ({} bytes)",
+            format.report_id,
+            report.len()
+        ))
+    })
+}
+
+/// A HID report as the backend read it, see
+/// `AngleDevice::subscribe_raw_reports` (feature `hid_debug`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawReport {
+    pub timestamp: Instant,
+    /// `Feature` when polled, `Input` in `ReadMode::Input`.
+    pub kind: ReportKind,
+    /// As the transport returned them: the report id first, except for
+    /// input reports of devices that do not number their reports.
+    pub bytes: Vec<u8>,
+}
+
+/// Send `bytes` to raw report subscribers, if there are any.
+fn publish_raw(raw: &broadcast::Sender<RawReport>, kind: ReportKind, bytes: &[u8]) {
+    if raw.receiver_count() > 0 {
+        let _ = raw.send(RawReport {
+            timestamp: Instant::now(),
+            kind,
+            bytes: bytes.to_vec(),
+        });
+    }
+}
+
+/// Where `dev`'s report descriptor puts the hinge angle, see
+/// `hid_descriptor::find_angle_field`.
+pub fn descriptor_format<T: HidTransport>(t: &mut T, dev: &T::Device) -> Option<ReportFormat> {
+    let desc = t.report_descriptor(dev).ok()?;
+    hid_descriptor::find_angle_field(&hid_descriptor::parse(&desc))?.format()
+}
+
+/// Open the most likely hinge sensor among `t.enumerate()`.
+pub fn find_hinge<T: HidTransport>(t: &mut T) -> Option<T::Device> {
+    find_hinge_info(t).map(|(h, _)| h)
+}
+
+/// Like `find_hinge`, also returning which device was opened.
+pub fn find_hinge_info<T: HidTransport>(t: &mut T) -> Option<(T::Device, HidDeviceInfo)> {
+    find_hinge_with(t, quirks::user())
+}
+
+/// `find_hinge_info` with `user`'s quirks in place of the user file's.
+fn find_hinge_with<T: HidTransport>(
+    t: &mut T,
+    user: &UserQuirks,
+) -> Option<(T::Device, HidDeviceInfo)> {
+    let devices = t.enumerate().ok()?;
+
+    // 0) Known hardware from the quirks table.
+    for info in &devices {
+        if quirks::hid_lookup_for(user, info).is_some()
+            && let Ok(h) = t.open(info)
+        {
+            #[cfg(feature = "diagnostics")]
+            eprintln!(
+                "[booklid] matched quirk: vid={:#06x} pid={:#06x}",
+                info.vendor_id, info.product_id
+            );
+            return Some((h, info.clone()));
+        }
+    }
+
+    // 1) Best: Usage Page = Sensor (0x20) + Usage = Orientation (0x008A)
+    for info in &devices {
+        if info.usage_page == USAGE_PAGE_SENSOR
+            && info.usage == USAGE_ORIENTATION
+            && let Ok(h) = t.open(info)
+        {
+            #[cfg(feature = "diagnostics")]
+            eprintln!(
+                "[booklid] matched Sensor/Orientation: vid={:#06x} pid={:#06x}",
+                info.vendor_id, info.product_id
+            );
+            return Some((h, info.clone()));
+        }
+    }
+
+    // 2) Fallback: Apple VID + commonly-seen PID (0x8104)
+    for info in &devices {
+        if info.vendor_id == APPLE_VID
+            && info.product_id == APPLE_HINGE_PID
+            && let Ok(h) = t.open(info)
+        {
+            #[cfg(feature = "diagnostics")]
+            eprintln!("[booklid] matched Apple VID/PID 0x05AC/0x8104 (fallback).");
+            return Some((h, info.clone()));
+        }
+    }
+
+    // 3) Last resort: any Apple device that responds to Feature Report #1
+    for info in &devices {
+        if info.vendor_id == APPLE_VID
+            && let Ok(h) = t.open(info)
+            && read_angle(t, &h, &ReportFormat::default()).is_ok()
+        {
+            #[cfg(feature = "diagnostics")]
+            eprintln!(
+                "[booklid] using Apple device responding to Feature#1: pid={:#06x}",
+                info.product_id
+            );
+            return Some((h, info.clone()));
+        }
+    }
+
+    None
+}
+
+/// Pick the report id among `ids` whose values look most like a moving
+/// hinge: all within 0..=180°, spanning at least 10°, highest variance wins.
+/// Samples each id for `dur` (blocking).
+pub fn probe_report_id<T: HidTransport>(
+    t: &mut T,
+    dev: &T::Device,
+    ids: impl IntoIterator<Item = u8>,
+    dur: Duration,
+) -> Option<u8> {
+    probe_scored(t, dev, ids, dur).map(|(id, _)| id)
+}
+
+/// `probe_report_id` with the winner's variance, to compare devices.
+fn probe_scored<T: HidTransport>(
+    t: &mut T,
+    dev: &T::Device,
+    ids: impl IntoIterator<Item = u8>,
+    dur: Duration,
+) -> Option<(u8, f32)> {
+    fn score(samples: &[f32]) -> Option<(f32, f32, f32)> {
+        if samples.is_empty() {
+            return None;
+        }
+        let min = samples.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = samples.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        // Bounds check (simple hinge-ish range)
+        if !(min >= 0.0 && max <= 180.0) {
+            return None;
+        }
+        let range = max - min;
+        if range < 10.0 {
+            return None;
+        } // needs some movement
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        let var = samples.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / samples.len() as f32;
+        Some((range, var, mean))
+    }
+
+    let mut best: Option<(u8, f32, f32, f32)> = None; // (id, range, var, mean)
+
+    for id in ids {
+        let t_end = Instant::now() + dur;
+        let mut vals: Vec<f32> = Vec::with_capacity(64);
+        while Instant::now() < t_end {
+            if let Ok(v) = read_angle(t, dev, &ReportFormat::with_report_id(id)) {
+                vals.push(v);
+            }
+            // small pause to avoid hammering (no async here)
+            std::thread::sleep(Duration::from_millis(8));
+        }
+
+        if let Some((range, var, mean)) = score(&vals) {
+            #[cfg(feature = "diagnostics")]
+            eprintln!(
+                "[booklid] discovery id={}: range={:.1} var={:.2} mean={:.1}",
+                id, range, var, mean
+            );
+            match best {
+                None => best = Some((id, range, var, mean)),
+                Some((_, _, best_var, _)) if var > best_var => best = Some((id, range, var, mean)),
+                _ => {}
+            }
+        } else {
+            #[cfg(feature = "diagnostics")]
+            eprintln!("[booklid] discovery id={} rejected", id);
+        }
+    }
+
+    best.map(|(id, _, var, _)| (id, var))
+}
+
+/// Probe `ids` on every plausible hinge device (Sensor usage page or Apple
+/// vendor id) and return the device and report id that score best by the
+/// rules of `probe_report_id`. Samples each pair for `dur` (blocking), so
+/// with several candidates this takes seconds; see `DiscoveryCache`.
+pub fn discover<T: HidTransport>(
+    t: &mut T,
+    ids: impl IntoIterator<Item = u8> + Clone,
+    dur: Duration,
+) -> Option<(T::Device, HidDeviceInfo, u8)> {
+    let devices = t.enumerate().ok()?;
+    let mut seen: Vec<&str> = Vec::new();
+    let mut best: Option<(T::Device, &HidDeviceInfo, u8, f32)> = None;
+    for info in &devices {
+        // One device can be listed once per usage.
+        if !is_candidate(info) || seen.contains(&info.path.as_str()) {
+            continue;
+        }
+        seen.push(&info.path);
+        let Ok(h) = t.open(info) else { continue };
+        #[cfg(feature = "diagnostics")]
+        eprintln!(
+            "[booklid] discovery: probing vid={:#06x} pid={:#06x} usage={:#06x}/{:#06x}",
+            info.vendor_id, info.product_id, info.usage_page, info.usage
+        );
+        if let Some((id, var)) = probe_scored(t, &h, ids.clone(), dur)
+            && best.as_ref().is_none_or(|b| var > b.3)
+        {
+            best = Some((h, info, id, var));
+        }
+    }
+    best.map(|(h, info, id, _)| (h, info.clone(), id))
+}
+
+/// Whether `discover` probes `info`: the Sensor usage page or Apple's
+/// vendor id.
+fn is_candidate(info: &HidDeviceInfo) -> bool {
+    info.usage_page == USAGE_PAGE_SENSOR || info.vendor_id == APPLE_VID
+}
+
+/// The device and report id `discover` settled on. The usage tells apart
+/// the collections one device lists; entries saved before it was kept
+/// read as 0/0 and match nothing, so discovery runs again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiscoveredHinge {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    #[serde(default)]
+    pub usage_page: u16,
+    #[serde(default)]
+    pub usage: u16,
+    pub report_id: u8,
+}
+
+impl DiscoveredHinge {
+    pub fn new(info: &HidDeviceInfo, report_id: u8) -> Self {
+        Self {
+            vendor_id: info.vendor_id,
+            product_id: info.product_id,
+            usage_page: info.usage_page,
+            usage: info.usage,
+            report_id,
+        }
+    }
+
+    pub fn matches(&self, info: &HidDeviceInfo) -> bool {
+        info.vendor_id == self.vendor_id
+            && info.product_id == self.product_id
+            && info.usage_page == self.usage_page
+            && info.usage == self.usage
+    }
+}
+
+/// A device and report id to read instead of searching, see
+/// `OpenConfig::hid_override`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HidOverride {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub usage_page: u16,
+    pub usage: u16,
+    pub report_id: u8,
+}
+
+impl HidOverride {
+    pub fn matches(&self, info: &HidDeviceInfo) -> bool {
+        info.vendor_id == self.vendor_id
+            && info.product_id == self.product_id
+            && info.usage_page == self.usage_page
+            && info.usage == self.usage
+    }
+}
+
+/// Where `HidAngle` remembers the `discover` winner between opens. The
+/// built-in backends keep it in the persisted state.
+pub trait DiscoveryCache: Send + Sync + 'static {
+    fn load(&self) -> Option<DiscoveredHinge>;
+    /// `None` forgets the entry.
+    fn store(&self, hinge: Option<DiscoveredHinge>);
+}
+
+/// Keeps the discovery winner in the persisted state (alongside saved
+/// calibrations), as the built-in backends do when `persistence` is on.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PersistedDiscovery;
+
+impl DiscoveryCache for PersistedDiscovery {
+    fn load(&self) -> Option<DiscoveredHinge> {
+        persist::load().hid_discovery
+    }
+
+    fn store(&self, hinge: Option<DiscoveredHinge>) {
+        let mut state = persist::load();
+        state.hid_discovery = hinge;
+        persist::store(&state).ok();
+    }
+}
+
+/// How `HidAngle` finds the report id when neither a quirk nor the report
+/// descriptor gives the format. A plain `bool` turns probing on or off
+/// without a cache.
+#[derive(Clone)]
+pub struct Discovery {
+    /// Scan candidates with `discover`; report 1 is read without.
+    pub probe: bool,
+    /// How long each (device, report id) pair is sampled.
+    pub window: Duration,
+    /// Consulted before probing and updated with the winner.
+    pub cache: Option<Arc<dyn DiscoveryCache>>,
+    /// Read only this device, skipping the quirk-driven search, the cache,
+    /// and probing.
+    pub device: Option<HidOverride>,
+    /// User quirks to match devices against; `None` is `quirks::user()`.
+    pub quirks: Option<Arc<UserQuirks>>,
+}
+
+impl Default for Discovery {
+    fn default() -> Self {
+        Self {
+            probe: false,
+            window: Duration::from_millis(400),
+            cache: None,
+            device: None,
+            quirks: None,
+        }
+    }
+}
+
+impl Discovery {
+    fn user_quirks(&self) -> &UserQuirks {
+        self.quirks.as_deref().unwrap_or_else(|| quirks::user())
+    }
+}
+
+impl From<bool> for Discovery {
+    fn from(probe: bool) -> Self {
+        Self {
+            probe,
+            ..Self::default()
+        }
+    }
+}
+
+/// Feature report ids `discover` tries.
+const DISCOVERY_IDS: std::ops::RangeInclusive<u8> = 1..=8;
+
+/// A device to sample and how to read it.
+struct Selected<D> {
+    dev: D,
+    info: HidDeviceInfo,
+    format: ReportFormat,
+    /// Set when the device came from discovery, so a re-open returns to it.
+    discovered: Option<DiscoveredHinge>,
+}
+
+/// The quirk or descriptor format for `dev`, if either knows it.
+fn known_format<T: HidTransport>(
+    t: &mut T,
+    dev: &T::Device,
+    info: &HidDeviceInfo,
+    user: &UserQuirks,
+) -> Option<ReportFormat> {
+    quirks::hid_lookup_for(user, info).or_else(|| descriptor_format(t, dev))
+}
+
+/// Open the first device `pred` accepts, if one is present.
+fn open_matching<T: HidTransport>(
+    t: &mut T,
+    pred: impl Fn(&HidDeviceInfo) -> bool,
+) -> Option<(T::Device, HidDeviceInfo)> {
+    let info = t.enumerate().ok()?.into_iter().find(|i| pred(i))?;
+    Some((t.open(&info).ok()?, info))
+}
+
+/// The `Error::PermissionDenied` opening a hinge candidate (the override,
+/// or a quirk, sensor or Apple device) fails with, if any: a sensor the OS
+/// refuses is not worth retrying like a missing one.
+fn open_denied<T: HidTransport>(t: &mut T, discovery: &Discovery) -> Option<Error> {
+    let devices = t.enumerate().ok()?;
+    devices
+        .iter()
+        .filter(|i| match &discovery.device {
+            Some(o) => o.matches(i),
+            None => quirks::hid_lookup_for(discovery.user_quirks(), i).is_some() || is_candidate(i),
+        })
+        .find_map(|info| match t.open(info) {
+            Err(e @ Error::PermissionDenied { .. }) => Some(e),
+            _ => None,
+        })
+}
+
+/// Whether `select` could find a device, without probing report ids.
+fn hinge_present<T: HidTransport>(t: &mut T, discovery: &Discovery) -> bool {
+    if let Some(o) = &discovery.device {
+        return open_matching(t, |i| o.matches(i)).is_some();
+    }
+    let cached = discovery.cache.as_ref().and_then(|c| c.load());
+    cached.is_some_and(|h| open_matching(t, |i| h.matches(i)).is_some())
+        || find_hinge_with(t, discovery.user_quirks()).is_some()
+        || discovery.probe && open_matching(t, is_candidate).is_some()
+}
+
+/// The `discovery.device` override alone if set. Otherwise the cached
+/// discovery winner if it is present and still answers, else
+/// `find_hinge_info` with its known format, else the `discover` winner
+/// (cached for next time), else report 1 on the `find_hinge_info` device.
+fn select<T: HidTransport>(t: &mut T, discovery: &Discovery) -> Option<Selected<T::Device>> {
+    if let Some(o) = &discovery.device {
+        let (dev, info) = open_matching(t, |i| o.matches(i))?;
+        // A quirk or descriptor still gives the layout of the chosen report.
+        let format = known_format(t, &dev, &info, discovery.user_quirks())
+            .filter(|f| f.report_id == o.report_id)
+            .unwrap_or(ReportFormat::with_report_id(o.report_id));
+        return Some(Selected {
+            dev,
+            info,
+            format,
+            discovered: None,
+        });
+    }
+    if let Some(cache) = &discovery.cache
+        && let Some(hinge) = cache.load()
+        && let Some((dev, info)) = open_matching(t, |i| hinge.matches(i))
+    {
+        let format = known_format(t, &dev, &info, discovery.user_quirks())
+            .unwrap_or(ReportFormat::with_report_id(hinge.report_id));
+        if read_angle(t, &dev, &format).is_ok() {
+            #[cfg(feature = "diagnostics")]
+            eprintln!("[booklid] using cached discovery: {:?}", hinge);
+            return Some(Selected {
+                dev,
+                info,
+                format,
+                discovered: Some(hinge),
+            });
+        }
+        // Present but silent: the cached id is wrong for this device now.
+        cache.store(None);
+    }
+
+    // Without a known format the probe gets its chance, even when no
+    // device answers report 1.
+    let found = find_hinge_with(t, discovery.user_quirks());
+    if let Some((dev, info)) = &found
+        && let Some(format) = known_format(t, dev, info, discovery.user_quirks())
+    {
+        let (dev, info) = found?;
+        return Some(Selected {
+            dev,
+            info,
+            format,
+            discovered: None,
+        });
+    }
+    if discovery.probe
+        && let Some((dev, info, id)) = discover(t, DISCOVERY_IDS, discovery.window)
+    {
+        let hinge = DiscoveredHinge::new(&info, id);
+        if let Some(cache) = &discovery.cache {
+            cache.store(Some(hinge));
+        }
+        let format = known_format(t, &dev, &info, discovery.user_quirks())
+            .unwrap_or(ReportFormat::with_report_id(id));
+        return Some(Selected {
+            dev,
+            info,
+            format,
+            discovered: Some(hinge),
+        });
+    }
+    let (dev, info) = found?;
+    Some(Selected {
+        dev,
+        info,
+        format: ReportFormat::default(),
+        discovered: None,
+    })
+}
+
+/// How `HidAngle` reads the chosen report.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ReadMode {
+    /// `get_feature_report` at the sample rate.
+    #[default]
+    Poll,
+    /// Input reports as the device sends them, read on a blocking thread;
+    /// the sample rate is the device's.
+    Input,
+}
+
+/// Timeout of one blocking read in `ReadMode::Input`, so the reader notices
+/// when it is no longer wanted.
+const INPUT_READ_TIMEOUT_MS: i32 = 100;
+
+/// Read input reports with `read` (buffer, timeout in ms) on a thread and
+/// forward the values of those matching `format` (every report goes to
+/// `raw`). Ends after forwarding a read error, or once the receiver is
+/// dropped.
+fn spawn_input_reader(
+    mut read: impl FnMut(&mut [u8], i32) -> Result<usize> + Send + 'static,
+    format: ReportFormat,
+    raw: broadcast::Sender<RawReport>,
+) -> mpsc::Receiver<Result<f32>> {
+    let (tx, rx) = mpsc::channel(64);
+    // If the thread cannot start, `tx` is dropped and the receiver ends.
+    let _ = std::thread::Builder::new()
+        .name("booklid-hid-input".into())
+        .spawn(move || {
+            let mut buf = [0u8; MAX_REPORT_LEN];
+            // Unnumbered reports arrive without the id byte.
+            let start = usize::from(format.report_id == 0);
+            while !tx.is_closed() {
+                let value = match read(&mut buf[start..], INPUT_READ_TIMEOUT_MS) {
+                    Ok(0) => continue,
+                    Ok(n) => {
+                        let report = &buf[..start + n];
+                        publish_raw(&raw, ReportKind::Input, &report[start..]);
+                        match format.parse(report) {
+                            Some(v) => Ok(v),
+                            // Another report of the device.
+                            None => continue,
+                        }
+                    }
+                    Err(e) => Err(e),
+                };
+                let failed = value.is_err();
+                if tx.blocking_send(value).is_err() || failed {
+                    break;
+                }
+            }
+        });
+    rx
+}
+
+/// Where `HidAngle` gets its next reading from.
+enum Reader<D> {
+    Poll(D),
+    Input(mpsc::Receiver<Result<f32>>),
+}
+
+impl<D: Send + 'static> Reader<D> {
+    /// `locked` is `transport`, locked by the caller.
+    fn start<T: HidTransport<Device = D>>(
+        mode: ReadMode,
+        transport: &Arc<Mutex<T>>,
+        locked: &mut T,
+        dev: D,
+        format: ReportFormat,
+        raw: &broadcast::Sender<RawReport>,
+    ) -> Self {
+        if mode == ReadMode::Poll {
+            return Reader::Poll(dev);
+        }
+        let raw = raw.clone();
+        Reader::Input(match locked.input_handle() {
+            Some(mut t) => spawn_input_reader(move |buf, ms| t.read(&dev, buf, ms), format, raw),
+            None => {
+                let transport = transport.clone();
+                spawn_input_reader(
+                    move |buf, ms| {
+                        transport
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .read(&dev, buf, ms)
+                    },
+                    format,
+                    raw,
+                )
+            }
+        })
+    }
+}
+
+pub struct HidAngle {
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
+    reconnecting: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    /// Of the device last opened.
+    hardware_id: Arc<Mutex<Option<String>>>,
+    #[cfg_attr(not(feature = "hid_debug"), allow(dead_code))]
+    raw: broadcast::Sender<RawReport>,
+}
+
+impl HidAngle {
+    /// Sample the hinge found on `transport` at `hz`. A device in the quirks
+    /// table is read with its `ReportFormat`, else as its report descriptor
+    /// places the angle (`descriptor_format`); `discovery.device` names the
+    /// device and report id outright. Otherwise a device and report
+    /// id remembered in `discovery.cache` are used, or with
+    /// `discovery.probe` chosen by `discover` over ids 1..=8 (and cached);
+    /// report 1 is used without. Stops looking once opening a candidate
+    /// fails with `Error::PermissionDenied`. Must be called from within a
+    /// Tokio runtime.
+    pub fn with_transport<T: HidTransport>(
+        transport: T,
+        hz: f32,
+        discovery: impl Into<Discovery>,
+        backoff: Backoff,
+    ) -> Self {
+        Self::with_transport_mode(transport, hz, discovery, ReadMode::Poll, backoff)
+    }
+
+    /// `with_transport_mode` once a hinge is found on `transport`, else
+    /// `Err` right away, so the caller can fall back to other sources
+    /// instead of waiting on a sensor this machine does not have;
+    /// `Error::PermissionDenied` if the transport refused to open one. The
+    /// lookup runs on a blocking thread; it opens devices but does not
+    /// probe report ids.
+    pub async fn open_transport<T: HidTransport>(
+        mut transport: T,
+        hz: f32,
+        discovery: impl Into<Discovery>,
+        mode: ReadMode,
+        backoff: Backoff,
+    ) -> Result<Self> {
+        let discovery = discovery.into();
+        let check = discovery.clone();
+        let transport = tokio::task::spawn_blocking(move || {
+            if hinge_present(&mut transport, &check) {
+                return Ok(transport);
+            }
+            Err(open_denied(&mut transport, &check)
+                .unwrap_or_else(|| Error::Backend("hid: no hinge sensor found".into())))
+        })
+        .await
+        .map_err(|e| Error::Backend(format!("hid: lookup failed: {e}")))??;
+        Ok(Self::with_transport_mode(
+            transport, hz, discovery, mode, backoff,
+        ))
+    }
+
+    /// `with_transport`, reading reports per `mode`; with `ReadMode::Input`
+    /// `hz` is unused.
+    pub fn with_transport_mode<T: HidTransport>(
+        transport: T,
+        hz: f32,
+        discovery: impl Into<Discovery>,
+        mode: ReadMode,
+        backoff: Backoff,
+    ) -> Self {
+        let discovery = discovery.into();
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let stages = Arc::new(Mutex::new(Stages::default()));
+        // Until the hinge is found the device is effectively reconnecting.
+        let reconnecting = Arc::new(AtomicBool::new(true));
+        let paused = Arc::new(AtomicBool::new(false));
+        let hardware_id = Arc::new(Mutex::new(None));
+        let transport = Arc::new(Mutex::new(transport));
+
+        let latest_c = Arc::clone(&latest);
+        let tx_c = tx.clone();
+        let stages_c = Arc::clone(&stages);
+        let reconnecting_c = Arc::clone(&reconnecting);
+        let paused_c = Arc::clone(&paused);
+        let hardware_id_c = Arc::clone(&hardware_id);
+        let (raw, _rx) = broadcast::channel::<RawReport>(64);
+        let raw_c = raw.clone();
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let stages_c = stages_c.clone();
+            let reconnecting_c = reconnecting_c.clone();
+            let paused_c = paused_c.clone();
+            let hardware_id_c = hardware_id_c.clone();
+            let raw_c = raw_c.clone();
+            let transport = transport.clone();
+            let discovery = discovery.clone();
+            async move {
+                // Retry (per the backoff policy) until we have a device.
+                let mut attempt = 0u32;
+                let Selected {
+                    dev: hid,
+                    mut format,
+                    discovered,
+                    ..
+                } = loop {
+                    // Probing samples for seconds: off the runtime's workers.
+                    let selected = {
+                        let transport = transport.clone();
+                        let discovery = discovery.clone();
+                        tokio::task::spawn_blocking(move || {
+                            select(
+                                &mut *transport.lock().unwrap_or_else(PoisonError::into_inner),
+                                &discovery,
+                            )
+                        })
+                        .await
+                        .ok()
+                        .flatten()
+                    };
+                    if let Some(found) = selected {
+                        #[cfg(feature = "diagnostics")]
+                        eprintln!("[booklid] hinge sensor opened.");
+                        *hardware_id_c.lock().unwrap_or_else(PoisonError::into_inner) =
+                            Some(found.info.hardware_id());
+                        break found;
+                    }
+                    if let Some(_e) = open_denied(
+                        &mut *transport.lock().unwrap_or_else(PoisonError::into_inner),
+                        &discovery,
+                    ) {
+                        #[cfg(feature = "diagnostics")]
+                        eprintln!("[booklid] {_e}; not retrying");
+                        return;
+                    }
+                    #[cfg(feature = "diagnostics")]
+                    eprintln!("[booklid] hinge not found yet; retrying…");
+                    let Some(d) = backoff.delay(attempt) else {
+                        #[cfg(feature = "diagnostics")]
+                        eprintln!("[booklid] giving up on hinge after {} attempts", attempt);
+                        return;
+                    };
+                    attempt += 1;
+                    time::sleep(d).await;
+                };
+                attempt = 0;
+
+                #[cfg(feature = "diagnostics")]
+                eprintln!("[booklid] using {:?} report ID {}", mode, format.report_id);
+
+                // Some devices like a first “poke”
+                let _ = read_angle(
+                    &mut *transport.lock().unwrap_or_else(PoisonError::into_inner),
+                    &hid,
+                    &format,
+                );
+                let mut reader = Reader::start(
+                    mode,
+                    &transport,
+                    &mut *transport.lock().unwrap_or_else(PoisonError::into_inner),
+                    hid,
+                    format,
+                    &raw_c,
+                );
+
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let target_hz = if hz.is_finite() && hz > 0.0 { hz } else { 60.0 };
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / target_hz));
+
+                loop {
+                    let read = match &mut reader {
+                        Reader::Poll(hid) => {
+                            interval.tick().await;
+                            if paused_c.load(Ordering::Relaxed) {
+                                continue;
+                            }
+                            read_feature(
+                                &mut *transport.lock().unwrap_or_else(PoisonError::into_inner),
+                                hid,
+                                &format,
+                            )
+                            .and_then(|(buf, n)| {
+                                publish_raw(&raw_c, ReportKind::Feature, &buf[..n]);
+                                angle_in(&format, &buf[..n])
+                            })
+                        }
+                        Reader::Input(rx) => rx.recv().await.unwrap_or_else(|| {
+                            Err(Error::Backend("hid: input reader stopped".into()))
+                        }),
+                    };
+                    // Input reports keep arriving; drop them while paused.
+                    if read.is_ok() && paused_c.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    match read {
+                        // A glitch, not a lost device.
+                        Ok(angle_deg) if !format.in_range(angle_deg) => {}
+                        Ok(angle_deg) => {
+                            reconnecting_c.store(false, Ordering::Relaxed);
+                            attempt = 0;
+
+                            let now = Instant::now();
+                            let Some(s) = smoother.apply(
+                                *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                                angle_deg,
+                                now,
+                            ) else {
+                                continue;
+                            };
+
+                            let sample = AngleSample {
+                                angle_deg: s,
+                                raw: angle_deg,
+                                timestamp: now,
+                                source: Source::HingeFeature,
+                                confidence: 1.0,
+                                velocity_deg_s: velocity.update(angle_deg, now),
+                            };
+
+                            *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                            let _ = tx_c.send(sample);
+                        }
+                        Err(_) => {
+                            reconnecting_c.store(true, Ordering::Relaxed);
+                            #[cfg(feature = "diagnostics")]
+                            eprintln!("[booklid] read failed; attempting re-open…");
+                            {
+                                let mut t =
+                                    transport.lock().unwrap_or_else(PoisonError::into_inner);
+                                // Back to the chosen or discovered device when
+                                // it returns.
+                                let found = match &discovery.device {
+                                    Some(o) => open_matching(&mut *t, |i| o.matches(i)),
+                                    None => discovered
+                                        .and_then(|d| open_matching(&mut *t, |i| d.matches(i)))
+                                        .or_else(|| {
+                                            find_hinge_with(&mut *t, discovery.user_quirks())
+                                        }),
+                                };
+                                if let Some((h, info)) = found {
+                                    *hardware_id_c.lock().unwrap_or_else(PoisonError::into_inner) =
+                                        Some(info.hardware_id());
+                                    // Another device may have come back; keep
+                                    // the probed id unless it is known or described.
+                                    if discovery.device.is_none()
+                                        && let Some(f) = known_format(
+                                            &mut *t,
+                                            &h,
+                                            &info,
+                                            discovery.user_quirks(),
+                                        )
+                                    {
+                                        format = f;
+                                    }
+                                    let _ = read_angle(&mut *t, &h, &format);
+                                    reader =
+                                        Reader::start(mode, &transport, &mut *t, h, format, &raw_c);
+                                }
+                            }
+                            let Some(d) = backoff.delay(attempt) else {
+                                #[cfg(feature = "diagnostics")]
+                                eprintln!("[booklid] giving up re-open after {} attempts", attempt);
+                                return;
+                            };
+                            attempt += 1;
+                            time::sleep(d).await;
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            latest,
+            tx,
+            task,
+            stages,
+            reconnecting,
+            paused,
+            hardware_id,
+            raw,
+        }
+    }
+}
+
+impl AngleDevice for HidAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn set_smoothing(&self, alpha: f32) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = Filter::Ema { alpha };
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = filter;
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .outliers = outliers;
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    fn confidence(&self) -> f32 {
+        1.0
+    }
+
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(Source::HingeFeature, "mac_hid_feature")
+    }
+
+    fn hardware_id(&self) -> Option<String> {
+        self.hardware_id
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        self.reconnecting.load(Ordering::Relaxed)
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
+    }
+
+    #[cfg(feature = "hid_debug")]
+    fn subscribe_raw_reports(&self) -> BoxStream<'static, RawReport> {
+        BroadcastStream::new(self.raw.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+}
@@ -0,0 +1,272 @@
+//! HID report descriptor parsing, to find the hinge angle's exact place in
+//! a feature report instead of probing report ids (see
+//! `hid::descriptor_format`).
+//!
+//! Only what locating a value needs is tracked: usages, report ids, sizes,
+//! logical ranges, and units. Long items are skipped.
+
+use crate::quirks::{Layout, ReportFormat};
+
+/// HID usage page "Sensor".
+pub const USAGE_PAGE_SENSOR: u16 = 0x20;
+
+/// Sensor-page data fields that carry an angle without declaring a unit:
+/// orientation tilt X/Y/Z and the first custom value (used by hinge angle
+/// sensors that report as custom sensors).
+pub const ANGLE_USAGES: &[u16] = &[0x047F, 0x0480, 0x0481, 0x0544];
+
+/// Usages kept per main item; descriptors come from the device, so a huge
+/// usage range is not expanded in full.
+const MAX_USAGES: usize = 1024;
+
+/// HID unit codes for rotation: radians (SI) and degrees (English).
+const UNIT_RADIANS: u32 = 0x12;
+const UNIT_DEGREES: u32 = 0x14;
+
+/// Which kind of report a field is in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportKind {
+    Input,
+    Output,
+    Feature,
+}
+
+/// One value in a report, as described by the descriptor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReportField {
+    pub kind: ReportKind,
+    /// 0 when the device does not number its reports.
+    pub report_id: u8,
+    pub usage_page: u16,
+    pub usage: u16,
+    /// Position after the report id byte.
+    pub bit_offset: u32,
+    pub bit_size: u32,
+    pub logical_min: i64,
+    pub logical_max: i64,
+    /// HID unit code, 0 for none.
+    pub unit: u32,
+    /// Power of ten applied to the value.
+    pub unit_exponent: i8,
+}
+
+impl ReportField {
+    /// Declared in degrees or radians.
+    pub fn is_rotation(&self) -> bool {
+        matches!(self.unit, UNIT_DEGREES | UNIT_RADIANS)
+    }
+
+    /// Degrees per count, from the unit and exponent (a field without a
+    /// unit is taken as degrees).
+    pub fn scale(&self) -> f32 {
+        let per_unit = if self.unit == UNIT_RADIANS {
+            180.0 / std::f32::consts::PI
+        } else {
+            1.0
+        };
+        10f32.powi(self.unit_exponent.into()) * per_unit
+    }
+
+    /// How to read this field with `hid::HidAngle`; `None` when it is wider
+    /// than 32 bits.
+    pub fn format(&self) -> Option<ReportFormat> {
+        if self.bit_size == 0 || self.bit_size > 32 {
+            return None;
+        }
+        let layout = Layout::Bits {
+            shift: (self.bit_offset % 8) as u8,
+            size: self.bit_size as u8,
+            signed: self.logical_min < 0,
+        };
+        let scale = self.scale();
+        let d = ReportFormat::with_report_id(self.report_id);
+        let (min_deg, max_deg) = if self.logical_min < self.logical_max {
+            let (a, b) = (
+                self.logical_min as f32 * scale,
+                self.logical_max as f32 * scale,
+            );
+            (a.min(b), a.max(b))
+        } else {
+            (d.min_deg, d.max_deg)
+        };
+        let format = ReportFormat {
+            offset: 1 + (self.bit_offset / 8) as usize,
+            layout,
+            scale,
+            min_deg,
+            max_deg,
+            ..d
+        };
+        format.is_valid().then_some(format)
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct Globals {
+    usage_page: u16,
+    logical_min: i64,
+    logical_max: i64,
+    /// `logical_max` read as unsigned, for ranges like 0..0xFFFF.
+    logical_max_unsigned: i64,
+    unit_exponent: i8,
+    unit: u32,
+    report_size: u32,
+    report_id: u8,
+    report_count: u32,
+}
+
+/// Every variable, non-constant value in `desc`, in descriptor order.
+/// Parsing stops at the first truncated item.
+pub fn parse(desc: &[u8]) -> Vec<ReportField> {
+    let mut fields = Vec::new();
+    let mut g = Globals::default();
+    let mut stack: Vec<Globals> = Vec::new();
+    // (page, usage); page 0 means "the current usage page".
+    let mut usages: Vec<(u16, u16)> = Vec::new();
+    let mut usage_min: Option<(u16, u16)> = None;
+    // Next free bit per (kind, report id).
+    let mut offsets: Vec<(ReportKind, u8, u32)> = Vec::new();
+
+    let mut i = 0;
+    while let Some(&prefix) = desc.get(i) {
+        if prefix == 0xFE {
+            // Long item: size, tag, data.
+            let Some(&len) = desc.get(i + 1) else { break };
+            i += 3 + len as usize;
+            continue;
+        }
+        let len = match prefix & 0x3 {
+            3 => 4,
+            n => n as usize,
+        };
+        let Some(data) = desc.get(i + 1..i + 1 + len) else {
+            break;
+        };
+        i += 1 + len;
+        let unsigned = data
+            .iter()
+            .rev()
+            .fold(0u32, |acc, b| (acc << 8) | u32::from(*b));
+        let signed = match len {
+            1 => i64::from(unsigned as u8 as i8),
+            2 => i64::from(unsigned as u16 as i16),
+            4 => i64::from(unsigned as i32),
+            _ => 0,
+        };
+        let tag = prefix >> 4;
+        match (prefix >> 2) & 0x3 {
+            // Main
+            0 => {
+                let kind = match tag {
+                    0x8 => Some(ReportKind::Input),
+                    0x9 => Some(ReportKind::Output),
+                    0xB => Some(ReportKind::Feature),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    let slot = match offsets
+                        .iter()
+                        .position(|(k, id, _)| *k == kind && *id == g.report_id)
+                    {
+                        Some(p) => p,
+                        None => {
+                            offsets.push((kind, g.report_id, 0));
+                            offsets.len() - 1
+                        }
+                    };
+                    let start = offsets[slot].2;
+                    let constant = unsigned & 0x1 != 0;
+                    let variable = unsigned & 0x2 != 0;
+                    if !constant && variable {
+                        let logical_max = if g.logical_min >= 0 && g.logical_max < 0 {
+                            g.logical_max_unsigned
+                        } else {
+                            g.logical_max
+                        };
+                        // Values past the last usage repeat it; they are not
+                        // listed.
+                        let listed = g.report_count.min(usages.len() as u32);
+                        for (n, &(page, usage)) in (0..listed).zip(&usages) {
+                            fields.push(ReportField {
+                                kind,
+                                report_id: g.report_id,
+                                usage_page: if page == 0 { g.usage_page } else { page },
+                                usage,
+                                bit_offset: start.saturating_add(n.saturating_mul(g.report_size)),
+                                bit_size: g.report_size,
+                                logical_min: g.logical_min,
+                                logical_max,
+                                unit: g.unit,
+                                unit_exponent: g.unit_exponent,
+                            });
+                        }
+                    }
+                    offsets[slot].2 =
+                        start.saturating_add(g.report_size.saturating_mul(g.report_count));
+                }
+                usages.clear();
+                usage_min = None;
+            }
+            // Global
+            1 => match tag {
+                0x0 => g.usage_page = unsigned as u16,
+                0x1 => g.logical_min = signed,
+                0x2 => {
+                    g.logical_max = signed;
+                    g.logical_max_unsigned = i64::from(unsigned);
+                }
+                0x5 => {
+                    // A 4-bit two's complement nibble.
+                    let e = (unsigned & 0xF) as i8;
+                    g.unit_exponent = if e > 7 { e - 16 } else { e };
+                }
+                0x6 => g.unit = unsigned,
+                0x7 => g.report_size = unsigned,
+                0x8 => g.report_id = unsigned as u8,
+                0x9 => g.report_count = unsigned,
+                0xA => stack.push(g),
+                0xB => {
+                    if let Some(top) = stack.pop() {
+                        g = top;
+                    }
+                }
+                _ => {}
+            },
+            // Local
+            2 => {
+                // A 4-byte usage carries its own page in the high half.
+                let usage = if len == 4 {
+                    ((unsigned >> 16) as u16, unsigned as u16)
+                } else {
+                    (0, unsigned as u16)
+                };
+                match tag {
+                    0x0 if usages.len() < MAX_USAGES => usages.push(usage),
+                    0x1 => usage_min = Some(usage),
+                    0x2 => {
+                        if let Some((page, min)) = usage_min.take() {
+                            let room = MAX_USAGES - usages.len();
+                            usages.extend((min..=usage.1).take(room).map(|u| (page, u)));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+/// The feature field most likely to be the hinge angle: a Sensor-page data
+/// field declared in degrees or radians, else one of `ANGLE_USAGES`.
+/// Sensitivity, minimum, and other modified data fields are skipped.
+pub fn find_angle_field(fields: &[ReportField]) -> Option<ReportField> {
+    let data = fields.iter().filter(|f| {
+        f.kind == ReportKind::Feature && f.usage_page == USAGE_PAGE_SENSOR && f.usage & 0xF000 == 0
+    });
+    data.clone()
+        .find(|f| f.is_rotation())
+        .or_else(|| data.clone().find(|f| ANGLE_USAGES.contains(&f.usage)))
+        .copied()
+}
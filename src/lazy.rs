@@ -0,0 +1,208 @@
+//! A device that starts without a backend and attaches one in the
+//! background (see `open_lazy`). Until then it reports `is_acquiring()`;
+//! open attempts are retried per `OpenConfig::reconnect`.
+
+use crate::client::{DynDevice, Output};
+use crate::filters::Stages;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, DeviceInfo, Error, Event, Filter, InitConfig,
+    LightSample, OutlierRejection, Progress, Source, TaskGuard, select_device,
+};
+use futures_util::{StreamExt, stream::BoxStream};
+use std::sync::{
+    Arc, Mutex, RwLock,
+    atomic::{AtomicBool, Ordering},
+};
+use tokio::{sync::broadcast, time};
+use tokio_stream::wrappers::BroadcastStream;
+
+pub(crate) struct LazyAngle {
+    dev: Arc<RwLock<Option<DynDevice>>>,
+    /// Filter settings, applied to each backend as it attaches.
+    stages: Arc<Mutex<Stages>>,
+    /// Likewise `set_paused`.
+    paused: Arc<AtomicBool>,
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    lux: broadcast::Sender<LightSample>,
+    events: broadcast::Sender<Event>,
+    restarts: broadcast::Sender<u32>,
+    _task: TaskGuard,
+}
+
+impl LazyAngle {
+    /// `output` is the client's; its axis is set for the attached source.
+    /// Must be called from within a Tokio runtime.
+    pub(crate) fn new(cfg: InitConfig, output: Arc<RwLock<Output>>) -> Self {
+        let dev: Arc<RwLock<Option<DynDevice>>> = Arc::new(RwLock::new(None));
+        let stages = Arc::new(Mutex::new(Stages {
+            filter: cfg.filter,
+            outliers: cfg.outlier_rejection,
+        }));
+        let paused = Arc::new(AtomicBool::new(false));
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let (lux, _rx) = broadcast::channel::<LightSample>(256);
+        let (events, _rx) = broadcast::channel::<Event>(16);
+        let (restarts, _rx) = broadcast::channel::<u32>(16);
+
+        let dev_c = Arc::clone(&dev);
+        let stages_c = Arc::clone(&stages);
+        let paused_c = Arc::clone(&paused);
+        let latest_c = Arc::clone(&latest);
+        let tx_c = tx.clone();
+        let lux_c = lux.clone();
+        let events_c = events.clone();
+        let restarts_c = restarts.clone();
+
+        let task = TaskGuard::spawn(async move {
+            let backoff = cfg.reconnect;
+            let mut attempt = 0u32;
+            let sel = loop {
+                match select_device(cfg.clone(), &Progress::default()).await {
+                    Ok(sel) => break sel,
+                    // Retrying cannot get past the OS refusing access.
+                    Err(_e @ Error::PermissionDenied { .. }) => {
+                        #[cfg(feature = "diagnostics")]
+                        eprintln!("[booklid] {_e}; giving up");
+                        return;
+                    }
+                    Err(_) => {
+                        let Some(d) = backoff.delay(attempt) else {
+                            #[cfg(feature = "diagnostics")]
+                            eprintln!("[booklid] no backend after {} attempts; giving up", attempt);
+                            return;
+                        };
+                        attempt += 1;
+                        time::sleep(d).await;
+                    }
+                }
+            };
+
+            let source = sel.dev.info().source;
+            output.write().unwrap().axis = sel.axis.map(|a| (source, a));
+            let stages = *stages_c.lock().unwrap();
+            sel.dev.set_filter(stages.filter);
+            sel.dev.set_outlier_rejection(stages.outliers);
+            sel.dev.set_paused(paused_c.load(Ordering::Relaxed));
+            let mut stream = sel.dev.subscribe();
+            let mut dev_lux = sel.dev.subscribe_lux();
+            let mut dev_restarts = sel.dev.restarts();
+            let mut dev_events = sel.dev.events();
+            *dev_c.write().unwrap() = Some(sel.dev);
+            let _ = events_c.send(Event::Attached { source });
+
+            loop {
+                tokio::select! {
+                    s = stream.next() => {
+                        let Some(s) = s else { break };
+                        *latest_c.lock().unwrap() = Some(s);
+                        let _ = tx_c.send(s);
+                    }
+                    Some(l) = dev_lux.next() => {
+                        let _ = lux_c.send(l);
+                    }
+                    Some(n) = dev_restarts.next() => {
+                        let _ = restarts_c.send(n);
+                    }
+                    Some(ev) = dev_events.next() => {
+                        let _ = events_c.send(ev);
+                    }
+                }
+            }
+        });
+
+        Self {
+            dev,
+            stages,
+            paused,
+            latest,
+            tx,
+            lux,
+            events,
+            restarts,
+            _task: task,
+        }
+    }
+
+    fn with_dev<R>(&self, f: impl FnOnce(&DynDevice) -> R) -> Option<R> {
+        self.dev.read().unwrap().as_ref().map(f)
+    }
+}
+
+impl AngleDevice for LazyAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap()
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn subscribe_lux(&self) -> BoxStream<'static, LightSample> {
+        BroadcastStream::new(self.lux.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    /// Empty until a backend is attached.
+    #[cfg(feature = "hid_debug")]
+    fn subscribe_raw_reports(&self) -> BoxStream<'static, crate::hid::RawReport> {
+        self.with_dev(|d| d.subscribe_raw_reports())
+            .unwrap_or_else(|| futures_util::stream::empty().boxed())
+    }
+
+    fn set_smoothing(&self, alpha: f32) {
+        self.set_filter(Filter::Ema { alpha });
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages.lock().unwrap().filter = filter;
+        self.with_dev(|d| d.set_filter(filter));
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages.lock().unwrap().outliers = outliers;
+        self.with_dev(|d| d.set_outlier_rejection(outliers));
+    }
+
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+        self.with_dev(|d| d.set_paused(paused));
+    }
+
+    fn confidence(&self) -> f32 {
+        self.with_dev(|d| d.confidence()).unwrap_or(0.0)
+    }
+
+    fn info(&self) -> DeviceInfo {
+        self.with_dev(|d| d.info())
+            .unwrap_or(DeviceInfo::new(Source::Other("acquiring"), "acquiring"))
+    }
+
+    fn hardware_id(&self) -> Option<String> {
+        self.with_dev(|d| d.hardware_id()).flatten()
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        self.with_dev(|d| d.is_reconnecting()).unwrap_or(false)
+    }
+
+    fn is_acquiring(&self) -> bool {
+        self.dev.read().unwrap().is_none()
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        BroadcastStream::new(self.restarts.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn events(&self) -> BoxStream<'static, Event> {
+        BroadcastStream::new(self.events.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+}
@@ -9,26 +9,111 @@ mod backend_hidapi;
 mod backend_linux;
 #[cfg(feature = "mac_als")]
 mod backend_mac_als;
+#[cfg(all(target_os = "macos", feature = "mac_iokit_raw"))]
+mod backend_mac_iokit;
+#[cfg(all(target_os = "macos", feature = "mac_smc"))]
+mod backend_mac_smc;
 #[cfg(feature = "mock")]
 mod backend_mock;
+#[cfg(feature = "record")]
+mod backend_replay;
 #[cfg(all(target_os = "windows", feature = "win_sensors"))]
 mod backend_win;
 
+pub mod calibrate;
+mod client;
+#[cfg(feature = "serde")]
+mod config_file;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+pub mod convention;
+#[cfg(all(unix, feature = "daemon"))]
+pub mod daemon;
+pub mod events;
+#[cfg(feature = "arrow")]
+mod export;
+mod failover;
+pub mod filters;
+pub mod gesture;
+pub mod hid;
+pub mod hid_descriptor;
+mod lazy;
+pub mod lid_switch;
+#[cfg(target_os = "linux")]
+pub mod linux;
+#[cfg(all(
+    target_os = "macos",
+    any(
+        feature = "mac_als",
+        feature = "mac_iokit_raw",
+        feature = "mac_smc",
+        feature = "mac_power"
+    )
+))]
+mod mac;
+mod permissions;
 mod persist;
+#[cfg(feature = "plugins")]
+pub mod plugin;
+pub mod posture;
+pub mod quirks;
+#[cfg(feature = "record")]
+pub mod record;
+mod registry;
+mod self_test;
+pub mod sleep;
+mod soak;
+mod stream_ext;
+pub mod win;
 
 pub mod types;
-pub use crate::types::{AngleSample, Error, Result, Source};
+pub use crate::client::{AngleClient, Degraded, Health, Stats, SubscriberId, SubscriberLag};
+#[cfg(feature = "serde")]
+pub use crate::config_file::ConfigWatch;
+pub use crate::events::{Crossing, LidEvent, LidEventConfig, Threshold, ThresholdWatcher};
+pub use crate::filters::{Filter, FilterParams, OutlierRejection};
+pub use crate::gesture::{Gesture, GestureConfig};
+pub use crate::lid_switch::{
+    AutoCalibration, AutoCalibrationConfig, LidSwitch, LidSwitchAngle, LidSwitchState,
+};
+pub use crate::permissions::{PermissionStatus, check_permissions};
+pub use crate::posture::{Posture, PostureConfig};
+pub use crate::registry::{BackendContext, BackendFactory, register_backend};
+pub use crate::self_test::{SelfTestFailure, SelfTestReport};
+pub use crate::sleep::{PowerEvent, PowerNotifications};
+pub use crate::soak::{SoakReport, SoakSnapshot, soak};
+pub use crate::stream_ext::AngleStreamExt;
+pub use crate::types::{
+    Aggregate, AngleSample, Annotation, AxisTransform, Backoff, Error, Event, Histogram, InitEvent,
+    LightSample, OutputMap, PowerCost, Result, SampleRate, Source, SourceId, SwitchReason, Zone,
+};
 
+use crate::client::DynDevice;
+use futures_util::StreamExt;
+use futures_util::future::BoxFuture;
 use futures_util::stream::BoxStream;
 use once_cell::sync::Lazy;
-use std::time::Duration;
+use std::path::PathBuf;
+use std::sync::{
+    Arc, Mutex, RwLock,
+    atomic::{AtomicU32, Ordering},
+};
+use std::time::{Duration, Instant};
+use tokio_stream::wrappers::UnboundedReceiverStream;
+pub use tokio_util::sync::CancellationToken;
 
 pub type AngleStream = BoxStream<'static, AngleSample>;
-pub type AngleClient = Box<dyn AngleDevice + Send + Sync>;
 
 const HAS_BACKENDS: bool = cfg!(any(
     feature = "mac_hid_feature",
     feature = "mac_als",
+    all(target_os = "macos", feature = "mac_iokit_raw"),
+    all(target_os = "macos", feature = "mac_smc"),
+    all(target_os = "macos", feature = "mac_clamshell"),
+    all(target_os = "windows", feature = "win_lid_switch"),
+    all(target_os = "linux", feature = "linux_libinput"),
+    all(target_os = "linux", feature = "linux_upower"),
+    all(target_os = "linux", feature = "linux_acpi_lid"),
     feature = "mock",
     all(target_os = "windows", feature = "win_sensors"),
     all(
@@ -39,60 +124,384 @@ const HAS_BACKENDS: bool = cfg!(any(
 
 // ===== Device info =====
 
+/// Built with `DeviceInfo::new`, so fields can be added without breaking
+/// backends outside this crate.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
 pub struct DeviceInfo {
     pub source: Source,
     pub note: &'static str,
+    /// The rate the sensor itself samples at, as read back after asking for
+    /// the configured one; `None` where the backend can't tell.
+    pub sample_hz: Option<f32>,
+    /// Samples carry the sensor's own timestamps, whatever the source's
+    /// `Capabilities` default.
+    pub hardware_timestamps: bool,
+}
+
+impl DeviceInfo {
+    pub fn new(source: Source, note: &'static str) -> Self {
+        Self {
+            source,
+            note,
+            sample_hz: None,
+            hardware_timestamps: false,
+        }
+    }
+
+    pub fn with_sample_hz(mut self, hz: Option<f32>) -> Self {
+        self.sample_hz = hz;
+        self
+    }
+
+    pub fn with_hardware_timestamps(mut self, on: bool) -> Self {
+        self.hardware_timestamps = on;
+        self
+    }
+
+    /// The source's capabilities, with `hardware_timestamps` also set when
+    /// this device has them.
+    pub fn capabilities(&self) -> Capabilities {
+        let mut caps = Capabilities::for_source(self.source);
+        caps.hardware_timestamps |= self.hardware_timestamps;
+        caps
+    }
+}
+
+/// What a source can provide, so consumers can adapt without matching on `Source`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities {
+    /// Physical hinge angle in degrees (not a tilt estimate).
+    pub true_degrees: bool,
+    /// Values are a normalized 0..1 control (ALS-style), not degrees.
+    pub normalized: bool,
+    /// Samples carry angular velocity.
+    pub velocity: bool,
+    /// Timestamps come from the sensor rather than the sampling loop.
+    pub hardware_timestamps: bool,
+    /// Updates are pushed by the OS/sensor instead of polled.
+    pub event_driven: bool,
+}
+
+impl Capabilities {
+    pub fn for_source(source: Source) -> Self {
+        Self {
+            true_degrees: matches!(
+                source,
+                Source::HingeFeature
+                    | Source::HingeHid
+                    | Source::HingeIOKit
+                    | Source::HingeSMC
+                    | Source::WinHinge
+                    | Source::LinuxHinge
+                    | Source::Mock
+            ),
+            normalized: !source.is_degrees(),
+            velocity: !source.is_lid_switch(),
+            hardware_timestamps: false,
+            event_driven: matches!(
+                source,
+                Source::WinHinge
+                    | Source::WinPosture
+                    | Source::WinLidSwitch
+                    | Source::LinuxLidSwitch
+                    | Source::LinuxUPowerLid
+            ),
+        }
+    }
+}
+
+/// One backend open attempt during selection, in probe order.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Probe {
+    pub source: Source,
+    pub elapsed: Duration,
+    pub ok: bool,
 }
 
 // ===== Trait =====
 
+/// The backend contract. Built-in sensors implement it, and so can external
+/// crates (see `register_backend`). Samples should already be in the
+/// `convention` frame; the client applies axis correction, output mapping,
+/// and the confidence gate on top.
 pub trait AngleDevice: Send + Sync {
+    /// Most recent (smoothed) sample, if any.
     fn latest(&self) -> Option<AngleSample>;
+    /// Every sample from now on; ends when the device stops for good.
     fn subscribe(&self) -> AngleStream;
+    /// EMA factor in 0..1 for subsequent samples (1.0 = no smoothing).
     fn set_smoothing(&self, alpha: f32);
+    /// Smoothing for subsequent samples. Built-in backends support every
+    /// `Filter`; by default only `Filter::Ema` is applied (via
+    /// `set_smoothing`) and other filters are ignored.
+    fn set_filter(&self, filter: Filter) {
+        if let Filter::Ema { alpha } = filter {
+            self.set_smoothing(alpha);
+        }
+    }
+    /// Drop outlying readings before smoothing (`None` turns it off).
+    /// Built-in backends support it; the default ignores it.
+    fn set_outlier_rejection(&self, _outliers: Option<OutlierRejection>) {}
+    /// Stop reading the sensor until called with `false`, e.g. while the
+    /// system sleeps (see `AngleClient::follow_power`). The HID and macOS
+    /// backends support it; the default ignores it.
+    fn set_paused(&self, _paused: bool) {}
+    /// Filter and outlier rejection together, see `FilterParams`.
+    fn set_filter_params(&self, params: FilterParams) {
+        self.set_filter(params.filter);
+        self.set_outlier_rejection(params.outliers);
+    }
+    /// Current confidence in 0..1.
     fn confidence(&self) -> f32;
     fn info(&self) -> DeviceInfo;
+
+    /// Stable identity of the sensor hardware (HID vid/pid/serial, IIO
+    /// device name, Windows sensor id), which keys saved calibrations;
+    /// `None` when the backend cannot tell sensors apart.
+    fn hardware_id(&self) -> Option<String> {
+        None
+    }
+
+    /// True while the backend is re-opening its sensor.
+    fn is_reconnecting(&self) -> bool {
+        false
+    }
+
+    /// True while no backend is attached yet (see `open_lazy`).
+    fn is_acquiring(&self) -> bool {
+        false
+    }
+
+    /// Running restart count, yielded each time a panicked sampler is restarted.
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        futures_util::stream::empty().boxed()
+    }
+
+    /// Device-originated events (e.g. `Event::SourceSwitched`), forwarded to
+    /// `AngleClient::events()`.
+    fn events(&self) -> BoxStream<'static, Event> {
+        futures_util::stream::empty().boxed()
+    }
+
+    /// Illuminance readings from ambient-light sources; empty for the rest.
+    fn subscribe_lux(&self) -> BoxStream<'static, LightSample> {
+        futures_util::stream::empty().boxed()
+    }
+
+    /// Reports read by the HID hinge backend, bytes as received; empty for
+    /// the rest.
+    #[cfg(feature = "hid_debug")]
+    fn subscribe_raw_reports(&self) -> BoxStream<'static, hid::RawReport> {
+        futures_util::stream::empty().boxed()
+    }
+
+    /// Take `samples` readings and check that they are plausible, not
+    /// jittery, and arrive at a usable rate; see `SelfTestReport`.
+    fn self_test(&self, samples: usize) -> BoxFuture<'static, SelfTestReport> {
+        Box::pin(self_test::run(
+            self.subscribe(),
+            self.info().source,
+            samples,
+        ))
+    }
 }
 
 // ===== Global Tokio runtime for blocking variants =====
 
-static RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()
-        .expect("failed to init Tokio runtime")
-});
+// Created on first use and re-created after `shutdown()`.
+static RUNTIME: Lazy<Mutex<Option<tokio::runtime::Runtime>>> = Lazy::new(|| Mutex::new(None));
+
+pub(crate) fn runtime() -> tokio::runtime::Handle {
+    RUNTIME
+        .lock()
+        .unwrap()
+        .get_or_insert_with(|| {
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .expect("failed to init Tokio runtime")
+        })
+        .handle()
+        .clone()
+}
 
 // ===== OpenConfig (1.0) =====
 
+/// With the `serde` feature, missing fields take their `OpenConfig::default()`
+/// values and `fail_after` is written as milliseconds.
 #[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
 pub struct OpenConfig {
-    pub hz: f32,
+    pub rate: SampleRate,
     pub smoothing_alpha: f32,
+    /// Smoothing filter; `None` is an EMA with `smoothing_alpha`.
+    pub filter: Option<Filter>,
+    /// Drop glitches before smoothing, see `OutlierRejection`.
+    pub outlier_rejection: Option<OutlierRejection>,
     pub min_confidence: f32,
     pub prefer_sources: Vec<Source>,
+    /// Probe `prefer_sources` only, see `OpenConfig::only_preferred`.
+    pub only_preferred: bool,
     pub disable_backends: Vec<Source>,
+    /// Per-source weights (default 1.0; 0 never opens the source), combined
+    /// with persisted open success rates to order the probe chain.
+    pub source_weights: Vec<(Source, f32)>,
     pub discovery: bool,
+    /// Read this HID device and report id instead of searching, see
+    /// `OpenConfig::hid_override`.
+    pub hid_override: Option<hid::HidOverride>,
+    /// Poll feature reports (the default) or read input reports as they
+    /// arrive.
+    pub hid_read_mode: hid::ReadMode,
     pub allow_mock: bool,
     pub diagnostics: bool,
+    #[cfg_attr(feature = "serde", serde(with = "duration_ms"))]
     pub fail_after: Duration,
     pub persistence: bool,
+    pub reconnect: Backoff,
+    /// Attach to a running daemon (feature `daemon`) instead of opening backends.
+    pub attach_daemon: bool,
+    /// Keep an online angle histogram, see `AngleClient::histogram()`.
+    pub histogram: Option<HistogramConfig>,
+    /// Clamp/scale emitted degree values, applied after smoothing.
+    pub output_map: Option<OutputMap>,
+    /// Invert/offset correction; `None` uses the `quirks` entry for this
+    /// machine, if any.
+    pub axis: Option<AxisTransform>,
+    /// Named ranges for `AngleClient::subscribe_zones`, see `OpenConfig::zone`.
+    pub zones: Vec<Zone>,
+    /// Keep the runner-up backend open at a low rate and switch to it as soon
+    /// as the chosen one stalls (ten sample periods without a sample) or
+    /// ends. Only a backend in the same unit, degrees or normalized, is kept.
+    pub warm_standby: bool,
+    /// Rank sources by accuracy per unit of power (see `Source::power_cost`)
+    /// rather than accuracy alone, and skip the extra sensors that warm
+    /// standby and the Windows hinge cross-check keep open.
+    pub prefer_low_power: bool,
+    /// Per-source confidence scales, e.g. fitted by
+    /// `calibrate::fit_confidence`; 1.0 (the default) leaves a source as is.
+    pub confidence_scales: Vec<(Source, f32)>,
+    /// Per-source reading-to-degrees maps, see `OpenConfig::calibration`.
+    pub calibrations: Vec<calibrate::AngleCalibration>,
+    /// `AngleClient::is_closed` turns true at or below this angle...
+    pub lid_closed_below: f32,
+    /// ...and `is_open` at or above this one; in between the previous state
+    /// holds.
+    pub lid_opened_above: f32,
+    /// Follow this machine's sleep/wake and clamshell notifications, see
+    /// `AngleClient::follow_power`.
+    pub power_notifications: bool,
+    /// Anchor the angle at 0° on this machine's lid switch, see
+    /// `OpenConfig::auto_calibration`.
+    pub auto_calibration: Option<AutoCalibrationConfig>,
+    /// Report thresholds for the WinRT sensors, see `OpenConfig::win_reporting`.
+    pub win_reporting: win::WinReporting,
+    /// Read Linux sysfs accelerometers through their IIO buffer, see
+    /// `OpenConfig::linux_iio_buffer`.
+    pub linux_iio_buffer: bool,
+    /// Linux sysfs root, see `OpenConfig::sysfs_root`.
+    pub sysfs_root: Option<PathBuf>,
+    /// Linux IIO device directory, see `OpenConfig::dev_root`.
+    pub dev_root: Option<PathBuf>,
+    /// User quirks file, see `OpenConfig::quirks_file`.
+    pub quirks_file: Option<PathBuf>,
+}
+
+/// Bins for `OpenConfig::histogram`. With `persist` (and `persistence` on),
+/// counts are saved when the client closes and resumed on the next open if
+/// the layout matches.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistogramConfig {
+    pub range: std::ops::RangeInclusive<f32>,
+    pub bins: usize,
+    pub persist: bool,
+}
+
+impl Default for HistogramConfig {
+    /// 36 five-degree bins over 0..=180°, persisted.
+    fn default() -> Self {
+        Self {
+            range: 0.0..=180.0,
+            bins: 36,
+            persist: true,
+        }
+    }
+}
+
+impl HistogramConfig {
+    pub(crate) fn is_valid(&self) -> bool {
+        let (lo, hi) = (*self.range.start(), *self.range.end());
+        lo.is_finite() && hi.is_finite() && lo < hi
+    }
+}
+
+impl Default for OpenConfig {
+    fn default() -> Self {
+        Self::new(SampleRate::hz(60.0))
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_u64(d.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Duration, D::Error> {
+        u64::deserialize(d).map(Duration::from_millis)
+    }
 }
 
 impl OpenConfig {
-    pub fn new(hz: f32) -> Self {
+    pub fn new(rate: SampleRate) -> Self {
         Self {
-            hz,
+            rate,
             smoothing_alpha: 0.25,
+            filter: None,
+            outlier_rejection: None,
             min_confidence: 0.70,
             prefer_sources: vec![],
+            only_preferred: false,
             disable_backends: vec![],
+            source_weights: vec![],
             discovery: true,
+            hid_override: None,
+            hid_read_mode: hid::ReadMode::Poll,
             allow_mock: false,
             diagnostics: false,
             fail_after: Duration::from_secs(3),
             persistence: true,
+            reconnect: Backoff::default(),
+            attach_daemon: true,
+            histogram: None,
+            output_map: None,
+            axis: None,
+            zones: vec![],
+            warm_standby: false,
+            prefer_low_power: false,
+            confidence_scales: vec![],
+            calibrations: vec![],
+            lid_closed_below: 10.0,
+            lid_opened_above: 20.0,
+            power_notifications: true,
+            auto_calibration: Some(AutoCalibrationConfig::default()),
+            win_reporting: win::WinReporting::default(),
+            linux_iio_buffer: false,
+            sysfs_root: None,
+            dev_root: None,
+            quirks_file: None,
         }
     }
 
@@ -100,6 +509,26 @@ impl OpenConfig {
         self.smoothing_alpha = a;
         self
     }
+    /// Smooth with time constant `tau` instead of a per-sample factor, so
+    /// the result does not depend on the sample rate; see
+    /// `Filter::TimeConstant`.
+    pub fn smoothing_tau(self, tau: Duration) -> Self {
+        self.filter(Filter::TimeConstant {
+            tau_s: tau.as_secs_f32(),
+        })
+    }
+    /// Smooth with `f` instead of an EMA, e.g.
+    /// `Filter::Kalman { q: 500.0, r: 1.0 }`.
+    pub fn filter(mut self, f: Filter) -> Self {
+        self.filter = Some(f);
+        self
+    }
+    /// Drop readings far from the recent median before smoothing, e.g.
+    /// `OutlierRejection::default()` for a sensor that glitches to 0.
+    pub fn reject_outliers(mut self, o: OutlierRejection) -> Self {
+        self.outlier_rejection = Some(o);
+        self
+    }
     pub fn min_confidence(mut self, m: f32) -> Self {
         self.min_confidence = m;
         self
@@ -108,14 +537,56 @@ impl OpenConfig {
         self.prefer_sources = v;
         self
     }
+    /// Probe only the `prefer` sources, as if every other one were
+    /// disabled (including the extra sensors warm standby and the Windows
+    /// hinge cross-check would open).
+    pub fn only_preferred(mut self, on: bool) -> Self {
+        self.only_preferred = on;
+        self
+    }
     pub fn disable(mut self, v: Vec<Source>) -> Self {
         self.disable_backends = v;
         self
     }
+    /// Weight `source` when ordering probes, e.g. hinge 3.0, tilt 1.5, ALS
+    /// 0.0 for "prefer hinge strongly, tilt mildly, never ALS".
+    pub fn weight(mut self, source: Source, w: f32) -> Self {
+        self.source_weights.retain(|(s, _)| *s != source);
+        self.source_weights.push((source, w));
+        self
+    }
     pub fn discovery(mut self, on: bool) -> Self {
         self.discovery = on;
         self
     }
+    /// Point the HID hinge backend at one device, e.g. an external hinge
+    /// sensor or an Apple PID the search does not know, and read the angle
+    /// from feature report `report_id` without any discovery.
+    pub fn hid_override(
+        mut self,
+        vendor_id: u16,
+        product_id: u16,
+        usage_page: u16,
+        usage: u16,
+        report_id: u8,
+    ) -> Self {
+        self.hid_override = Some(hid::HidOverride {
+            vendor_id,
+            product_id,
+            usage_page,
+            usage,
+            report_id,
+        });
+        self
+    }
+    /// `hid::ReadMode::Input` follows the hinge through input reports
+    /// instead of polling a feature report at the sample rate, which costs
+    /// less CPU and keeps fast motion between ticks; for sensors that send
+    /// them.
+    pub fn hid_read_mode(mut self, mode: hid::ReadMode) -> Self {
+        self.hid_read_mode = mode;
+        self
+    }
     pub fn allow_mock(mut self, ok: bool) -> Self {
         self.allow_mock = ok;
         self
@@ -132,11 +603,133 @@ impl OpenConfig {
         self.persistence = on;
         self
     }
+    pub fn reconnect(mut self, b: Backoff) -> Self {
+        self.reconnect = b;
+        self
+    }
+    pub fn attach_daemon(mut self, on: bool) -> Self {
+        self.attach_daemon = on;
+        self
+    }
+    pub fn warm_standby(mut self, on: bool) -> Self {
+        self.warm_standby = on;
+        self
+    }
+    pub fn prefer_low_power(mut self, on: bool) -> Self {
+        self.prefer_low_power = on;
+        self
+    }
+    pub fn histogram(mut self, h: HistogramConfig) -> Self {
+        self.histogram = Some(h);
+        self
+    }
+    /// Rescale `source`'s confidence so it means the same error bound as on
+    /// other sources: above 1.0 lowers it, below 1.0 raises it. Fit `scale`
+    /// with `calibrate::fit_confidence` (feature `record`).
+    pub fn confidence_scale(mut self, source: Source, scale: f32) -> Self {
+        self.confidence_scales.retain(|(s, _)| *s != source);
+        self.confidence_scales.push((source, scale));
+        self
+    }
+    /// Map `cal.source`'s readings to degrees, replacing any calibration
+    /// for it saved with `calibrate::save` (which applies by default with
+    /// `persistence` on). One tied to a sensor (`cal.hardware_id`) only
+    /// applies to that sensor; one that is not applies to any.
+    pub fn calibration(mut self, cal: calibrate::AngleCalibration) -> Self {
+        self.calibrations.retain(|c| c.source != cal.source);
+        self.calibrations.push(cal);
+        self
+    }
+    /// Angles for `AngleClient::is_closed` / `is_open`; the gap between them
+    /// keeps the state from flapping near the boundary.
+    pub fn lid_thresholds(mut self, closed_below: f32, opened_above: f32) -> Self {
+        self.lid_closed_below = closed_below;
+        self.lid_opened_above = opened_above;
+        self
+    }
+    /// Pause sampling across system sleep and report the clamshell closing
+    /// (default on; macOS with feature `mac_power`, see `sleep`).
+    pub fn power_notifications(mut self, on: bool) -> Self {
+        self.power_notifications = on;
+        self
+    }
+    /// While `lid_switch::platform()` reports the lid shut, anchor the
+    /// angle at 0° (see `AngleClient::auto_calibrate`) from open until
+    /// `close()`. Default on with `AutoCalibrationConfig::default()`;
+    /// `None` turns it off.
+    pub fn auto_calibration(mut self, cfg: Option<AutoCalibrationConfig>) -> Self {
+        self.auto_calibration = cfg;
+        self
+    }
+    /// Have the Windows hinge, inclinometer and light sensors report only
+    /// changes past these thresholds, so a still lid stops waking the
+    /// process (e.g. `WinReporting::low_power()`). The default reports
+    /// every change.
+    pub fn win_reporting(mut self, r: win::WinReporting) -> Self {
+        self.win_reporting = r;
+        self
+    }
+    /// Read the Linux tilt and hinge accelerometers from `/dev/iio:deviceN`
+    /// (every scan, with its hardware timestamp) instead of polling their
+    /// attributes. Off by default: while a buffer is on, drivers refuse
+    /// direct reads, so iio-sensor-proxy and auto-rotate stop working until
+    /// the device is dropped, and a killed process leaves it on.
+    pub fn linux_iio_buffer(mut self, on: bool) -> Self {
+        self.linux_iio_buffer = on;
+        self
+    }
+    /// Read IIO, hwmon, and DMI nodes under `root` instead of
+    /// `linux::sysfs_root()` (`$BOOKLID_SYSFS_ROOT`, else `/sys`), e.g. a
+    /// fixture tree in tests. Ignored off Linux.
+    pub fn sysfs_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.sysfs_root = Some(root.into());
+        self
+    }
+    /// Open IIO character devices from `root` instead of `linux::dev_root()`
+    /// (`$BOOKLID_DEV_ROOT`, else `/dev`). Ignored off Linux.
+    pub fn dev_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.dev_root = Some(root.into());
+        self
+    }
+    /// Read user quirks from `path` instead of `quirks::user_file_path()`.
+    /// Unlike that file, which is ignored when broken, one named here fails
+    /// the open if it does not load.
+    pub fn quirks_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.quirks_file = Some(path.into());
+        self
+    }
+    pub fn output_map(mut self, m: OutputMap) -> Self {
+        self.output_map = Some(m);
+        self
+    }
+    pub fn axis(mut self, a: AxisTransform) -> Self {
+        self.axis = Some(a);
+        self
+    }
+    /// Add a zone for `AngleClient::subscribe_zones`; where zones overlap,
+    /// the first added wins.
+    pub fn zone(mut self, name: impl Into<String>, range: std::ops::RangeInclusive<f32>) -> Self {
+        self.zones.push(Zone::new(name, range));
+        self
+    }
+
+    /// `filter`, else an EMA with `smoothing_alpha`.
+    pub(crate) fn smoothing_filter(&self) -> Filter {
+        self.filter.unwrap_or(Filter::Ema {
+            alpha: self.smoothing_alpha,
+        })
+    }
+
+    /// The quirk axis for `source` under this config's `quirks_file` and
+    /// `sysfs_root`; `None` also when the quirks file fails to load.
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) fn quirk_axis(&self, source: Source) -> Option<AxisTransform> {
+        let user = quirks::user_for(self.quirks_file.as_deref()).ok()?;
+        let model = quirks::machine_model_under(self.sysfs_root.as_deref());
+        quirks::lookup_for(&user, model.as_deref(), source)
+    }
 
     pub fn validate(mut self) -> Result<Self> {
-        if self.hz <= 0.0 {
-            return Err(Error::Other("hz must be > 0".into()));
-        }
         self.smoothing_alpha = self.smoothing_alpha.clamp(0.0, 1.0);
         self.min_confidence = self.min_confidence.clamp(0.0, 1.0);
         if self
@@ -148,145 +741,449 @@ impl OpenConfig {
                 "prefer_sources intersects disable_backends".into(),
             ));
         }
+        if self
+            .source_weights
+            .iter()
+            .any(|(_, w)| !w.is_finite() || *w < 0.0)
+        {
+            return Err(Error::Other(
+                "source weights must be finite and >= 0".into(),
+            ));
+        }
+        if self
+            .prefer_sources
+            .iter()
+            .any(|s| source_weight(&self.source_weights, *s) == 0.0)
+        {
+            return Err(Error::Other("prefer_sources has a zero weight".into()));
+        }
+        if self.only_preferred && self.prefer_sources.is_empty() {
+            return Err(Error::Other("only_preferred needs prefer_sources".into()));
+        }
+        if self
+            .confidence_scales
+            .iter()
+            .any(|(_, k)| !k.is_finite() || *k < 0.0)
+        {
+            return Err(Error::Other(
+                "confidence scales must be finite and >= 0".into(),
+            ));
+        }
+        if !self.calibrations.iter().all(|c| c.is_valid()) {
+            return Err(Error::Other("invalid calibration points".into()));
+        }
+        if !self.win_reporting.is_valid() {
+            return Err(Error::Other(
+                "win_reporting thresholds must be finite and >= 0".into(),
+            ));
+        }
+        if !(self.lid_closed_below.is_finite()
+            && self.lid_opened_above.is_finite()
+            && self.lid_closed_below < self.lid_opened_above)
+        {
+            return Err(Error::Other(
+                "lid thresholds must be finite with closed_below < opened_above".into(),
+            ));
+        }
+        if self.filter.is_some_and(|f| !f.is_valid()) {
+            return Err(Error::Other(
+                "filter parameters must be finite (Kalman: q >= 0, r > 0)".into(),
+            ));
+        }
+        if self.outlier_rejection.is_some_and(|o| !o.is_valid()) {
+            return Err(Error::Other(
+                "outlier rejection needs a window of 3+ and finite, positive bounds".into(),
+            ));
+        }
+        if self.output_map.as_ref().is_some_and(|m| !m.is_valid()) {
+            return Err(Error::Other("output_map bounds must be finite".into()));
+        }
+        if self.axis.is_some_and(|a| !a.offset_deg.is_finite()) {
+            return Err(Error::Other("axis offset must be finite".into()));
+        }
+        if !self.zones.iter().all(Zone::is_valid) {
+            return Err(Error::Other("zone bounds must be finite".into()));
+        }
+        if self.histogram.as_ref().is_some_and(|h| !h.is_valid()) {
+            return Err(Error::Other(
+                "histogram range must be finite with start < end".into(),
+            ));
+        }
         Ok(self)
     }
 }
 
 // ===== Internal init config =====
 
+#[derive(Clone)]
 struct InitConfig {
-    hz: f32,
-    smoothing_alpha: f32,
+    rate: SampleRate,
+    filter: Filter,
+    outlier_rejection: Option<OutlierRejection>,
     min_confidence: f32,
     prefer_sources: Vec<Source>,
+    only_preferred: bool,
     disable_backends: Vec<Source>,
+    source_weights: Vec<(Source, f32)>,
 
     #[cfg_attr(not(feature = "mac_hid_feature"), allow(dead_code))]
     discovery: bool,
+    #[cfg_attr(not(feature = "mac_hid_feature"), allow(dead_code))]
+    hid_override: Option<hid::HidOverride>,
+    #[cfg_attr(not(feature = "mac_hid_feature"), allow(dead_code))]
+    hid_read_mode: hid::ReadMode,
 
     #[cfg_attr(not(feature = "mock"), allow(dead_code))]
     allow_mock: bool,
 
     diagnostics: bool,
+    fail_after: Duration,
     persistence: bool,
+    reconnect: Backoff,
+
+    #[cfg_attr(not(all(unix, feature = "daemon")), allow(dead_code))]
+    attach_daemon: bool,
+    histogram: Option<HistogramConfig>,
+    output_map: Option<OutputMap>,
+    axis: Option<AxisTransform>,
+    zones: Vec<Zone>,
+    warm_standby: bool,
+    prefer_low_power: bool,
+    confidence_scales: Vec<(Source, f32)>,
+    calibrations: Vec<calibrate::AngleCalibration>,
+    lid_thresholds: (f32, f32),
+    power_notifications: bool,
+    auto_calibration: Option<AutoCalibrationConfig>,
+    win_reporting: win::WinReporting,
+    linux_iio_buffer: bool,
+    sysfs_root: Option<PathBuf>,
+    dev_root: Option<PathBuf>,
+    quirks: Arc<quirks::UserQuirks>,
 }
 
 impl InitConfig {
     fn from_open(cfg: OpenConfig) -> Result<Self> {
-        let cfg = cfg.validate()?;
+        let mut cfg = cfg.validate()?;
+        let diagnostics =
+            cfg.diagnostics || std::env::var("BOOKLID_DIAGNOSTICS").ok().as_deref() == Some("1");
+        if diagnostics
+            && cfg.quirks_file.is_none()
+            && let Some(e) = quirks::user_file_error()
+        {
+            eprintln!("booklid: {e}");
+        }
+        let user_quirks = quirks::user_for(cfg.quirks_file.as_deref())?;
+        user_quirks.apply_sources(&mut cfg.prefer_sources, &mut cfg.disable_backends);
         Ok(Self {
-            hz: cfg.hz,
-            smoothing_alpha: cfg.smoothing_alpha,
+            rate: cfg.rate,
+            filter: cfg.smoothing_filter(),
+            outlier_rejection: cfg.outlier_rejection,
             min_confidence: cfg.min_confidence,
             prefer_sources: cfg.prefer_sources,
+            only_preferred: cfg.only_preferred,
             disable_backends: cfg.disable_backends,
+            source_weights: cfg.source_weights,
             discovery: cfg.discovery,
+            hid_override: cfg.hid_override,
+            hid_read_mode: cfg.hid_read_mode,
             allow_mock: cfg.allow_mock && cfg!(feature = "mock"),
-            diagnostics: cfg.diagnostics
-                || std::env::var("BOOKLID_DIAGNOSTICS").ok().as_deref() == Some("1"),
+            diagnostics,
+            fail_after: cfg.fail_after,
             persistence: cfg.persistence,
+            reconnect: cfg.reconnect,
+            attach_daemon: cfg.attach_daemon,
+            histogram: cfg.histogram,
+            output_map: cfg.output_map,
+            axis: cfg.axis,
+            zones: cfg.zones,
+            warm_standby: cfg.warm_standby,
+            prefer_low_power: cfg.prefer_low_power,
+            confidence_scales: cfg.confidence_scales,
+            calibrations: cfg.calibrations,
+            lid_thresholds: (cfg.lid_closed_below, cfg.lid_opened_above),
+            power_notifications: cfg.power_notifications,
+            auto_calibration: cfg.auto_calibration,
+            win_reporting: cfg.win_reporting,
+            linux_iio_buffer: cfg.linux_iio_buffer,
+            sysfs_root: cfg.sysfs_root,
+            dev_root: cfg.dev_root,
+            quirks: user_quirks,
         })
     }
 }
 
-// ===== Desktop guard =====
+// ===== Backend task guard =====
 
-fn desktop_guard() -> bool {
-    std::env::var("BOOKLID_DESKTOP").ok().as_deref() == Some("1")
+/// Owns a backend's sampling task and aborts it on drop, so dropping a device
+/// (including one opened by a cancelled probe) stops its sampler.
+pub(crate) struct TaskGuard {
+    handle: tokio::task::JoinHandle<()>,
+    restarts: Arc<Restarts>,
 }
 
-// ===== Confidence gate =====
+/// Restart accounting for a supervised sampler.
+struct Restarts {
+    count: AtomicU32,
+    tx: tokio::sync::broadcast::Sender<u32>,
+}
 
-mod gating {
-    use super::*;
-    use std::sync::atomic::{AtomicBool, Ordering};
+impl Restarts {
+    fn new() -> Self {
+        Self {
+            count: AtomicU32::new(0),
+            tx: tokio::sync::broadcast::channel(16).0,
+        }
+    }
 
-    pub struct Gated {
-        inner: AngleClient,
-        live: AtomicBool,
-        min: f32,
-        drop: f32,
+    fn record(&self) -> u32 {
+        let n = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        let _ = self.tx.send(n);
+        n
     }
+}
 
-    impl Gated {
-        pub fn wrap(inner: AngleClient, min: f32) -> AngleClient {
-            let drop = (min - 0.05).clamp(0.0, 1.0);
-            Box::new(Self {
-                inner,
-                live: AtomicBool::new(false),
-                min,
-                drop,
-            })
+impl TaskGuard {
+    pub(crate) fn spawn<F>(fut: F) -> Self
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            handle: tokio::spawn(fut),
+            restarts: Arc::new(Restarts::new()),
         }
+    }
+}
 
-        fn bump(&self) {
-            let c = self.inner.confidence();
-            let live = self.live.load(Ordering::Relaxed);
-            if !live && c >= self.min {
-                self.live.store(true, Ordering::Relaxed);
-            } else if live && c < self.drop {
-                self.live.store(false, Ordering::Relaxed);
+// Only sensor backends are supervised; replay and daemon tasks just run.
+impl TaskGuard {
+    /// Run `make()` and start a fresh sampler (after `backoff`) whenever it
+    /// panics. A sampler that returns normally is not restarted; neither is
+    /// one that panics after the backoff's attempts are used up. A panic can
+    /// leave the state `make` shares between runs poisoned, so samplers lock
+    /// it with `unwrap_or_else(PoisonError::into_inner)`.
+    pub(crate) fn supervised<F, Fut>(backoff: Backoff, make: F) -> Self
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        let restarts = Arc::new(Restarts::new());
+        let restarts_c = Arc::clone(&restarts);
+        let handle = tokio::spawn(async move {
+            let mut attempt = 0u32;
+            loop {
+                let started = Instant::now();
+                // Dropping the inner guard (supervisor aborted) stops the sampler too.
+                let mut inner = TaskGuard::spawn(make());
+                match (&mut inner.handle).await {
+                    Err(e) if e.is_panic() => {}
+                    _ => return,
+                }
+                let _n = restarts_c.record();
+                #[cfg(feature = "diagnostics")]
+                eprintln!("[booklid] sampler panicked; restart #{}", _n);
+                // A sampler that ran for a while earns a fresh backoff.
+                if started.elapsed() >= backoff.max {
+                    attempt = 0;
+                }
+                let Some(d) = backoff.delay(attempt) else {
+                    return;
+                };
+                attempt += 1;
+                tokio::time::sleep(d).await;
             }
-        }
+        });
+        Self { handle, restarts }
     }
 
-    impl AngleDevice for Gated {
-        fn latest(&self) -> Option<AngleSample> {
-            self.bump();
-            if self.live.load(Ordering::Relaxed) {
-                self.inner.latest()
-            } else {
-                None
-            }
-        }
-        fn subscribe(&self) -> AngleStream {
-            self.inner.subscribe()
-        }
-        fn set_smoothing(&self, a: f32) {
-            self.inner.set_smoothing(a)
-        }
-        fn confidence(&self) -> f32 {
-            self.inner.confidence()
-        }
-        fn info(&self) -> DeviceInfo {
-            self.inner.info()
+    pub(crate) fn restarts(&self) -> BoxStream<'static, u32> {
+        tokio_stream::wrappers::BroadcastStream::new(self.restarts.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+}
+
+impl Drop for TaskGuard {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+// ===== Desktop guard =====
+
+/// Optional sink for `InitEvent`s; a no-op unless `open_with_progress` set it.
+#[derive(Default)]
+struct Progress(Option<tokio::sync::mpsc::UnboundedSender<InitEvent>>);
+
+impl Progress {
+    fn send(&self, e: InitEvent) {
+        if let Some(tx) = &self.0 {
+            let _ = tx.send(e);
         }
     }
 }
 
-use gating::Gated;
+/// Built-in sources in default probe order, before weights and history.
+const BUILTIN_ORDER: [Source; 17] = [
+    Source::HingeFeature,
+    Source::HingeHid,
+    Source::HingeIOKit,
+    Source::HingeSMC,
+    Source::ALS,
+    Source::WinHinge,
+    Source::WinPosture,
+    Source::WinTilt,
+    Source::WinALS,
+    Source::LinuxHinge,
+    Source::LinuxTilt,
+    Source::LinuxALS,
+    Source::MacClamshell,
+    Source::WinLidSwitch,
+    Source::LinuxLidSwitch,
+    Source::LinuxUPowerLid,
+    Source::LinuxAcpiLid,
+];
+
+/// Backends probed at once during init.
+const PROBE_CONCURRENCY: usize = 3;
+/// Sample rate of a warm-standby backend (`OpenConfig::warm_standby`).
+const STANDBY_HZ: f32 = 2.0;
+
+fn desktop_guard() -> bool {
+    std::env::var("BOOKLID_DESKTOP").ok().as_deref() == Some("1")
+}
 
 // ===== Unified init =====
 
-async fn init_all(cfg: InitConfig) -> Result<AngleClient> {
-    let InitConfig {
-        #[cfg_attr(
-            not(any(
-                feature = "mac_hid_feature",
-                feature = "mac_als",
-                feature = "mock",
-                all(target_os = "windows", feature = "win_sensors"),
-                all(
-                    target_os = "linux",
-                    any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
-                )
-            )),
-            allow(unused_variables)
-        )]
-        hz,
-        smoothing_alpha,
+async fn init_all(cfg: InitConfig, progress: Progress) -> Result<AngleClient> {
+    let (rate, min_confidence, persistence) = (cfg.rate, cfg.min_confidence, cfg.persistence);
+    let (histogram, output_map) = (cfg.histogram.clone(), cfg.output_map.clone());
+    let (confidence_scales, lid) = (cfg.confidence_scales.clone(), cfg.lid_thresholds);
+    let (power_notifications, auto_calibration) = (cfg.power_notifications, cfg.auto_calibration);
+    let (calibrations, zones) = (cfg.calibrations.clone(), cfg.zones.clone());
+    let sel = select_device(cfg, &progress).await?;
+    let client = AngleClient::new(
+        sel.dev,
+        rate,
         min_confidence,
+        sel.axis,
+        output_map,
+        sel.probes,
+    );
+    client.set_confidence_scales(confidence_scales);
+    client.resolve_calibrations(calibrations, persistence);
+    client.set_lid_thresholds(lid.0, lid.1)?;
+    client.set_zones(zones);
+    enable_histogram(&client, histogram, persistence);
+    enable_power_notifications(&client, power_notifications);
+    enable_auto_calibration(&client, auto_calibration);
+    Ok(client)
+}
+
+/// An opened backend, ready to be wrapped in an `AngleClient`.
+struct Selected {
+    dev: DynDevice,
+    /// Explicit axis, else this machine's quirk for the chosen source.
+    axis: Option<AxisTransform>,
+    probes: Vec<Probe>,
+}
+
+/// Attach to the daemon or probe local backends; records open outcomes when
+/// persistence is on.
+async fn select_device(cfg: InitConfig, progress: &Progress) -> Result<Selected> {
+    let InitConfig {
+        rate,
+        filter,
+        outlier_rejection,
         prefer_sources,
+        only_preferred,
         disable_backends,
+        source_weights,
         #[cfg_attr(not(feature = "mac_hid_feature"), allow(unused_variables))]
         discovery,
+        hid_override,
+        hid_read_mode,
         #[cfg_attr(not(feature = "mock"), allow(unused_variables))]
         allow_mock,
         diagnostics,
+        fail_after,
         persistence,
+        reconnect,
+        #[cfg_attr(not(all(unix, feature = "daemon")), allow(unused_variables))]
+        attach_daemon,
+        axis,
+        warm_standby,
+        prefer_low_power,
+        win_reporting,
+        linux_iio_buffer,
+        sysfs_root,
+        dev_root,
+        quirks: user_quirks,
+        ..
     } = cfg;
 
-    if !HAS_BACKENDS {
+    let hz = rate.as_hz();
+
+    let mut probes: Vec<Probe> = Vec::new();
+
+    // Sources this config lets through, daemon or local.
+    let allowed = |s: &Source| {
+        !disable_backends.contains(s)
+            && source_weight(&source_weights, *s) > 0.0
+            && (!only_preferred || prefer_sources.contains(s))
+    };
+
+    // A running daemon already owns the sensors; share it instead of polling.
+    #[cfg(all(unix, feature = "daemon"))]
+    if attach_daemon {
+        let started = Instant::now();
+        let path = daemon::socket_path();
+        if let Ok(dev) = daemon::RemoteAngle::connect(&path).await {
+            let src = dev.info().source;
+            if !allowed(&src) || (src == Source::Mock && !allow_mock) {
+                probes.push(Probe {
+                    source: src,
+                    elapsed: started.elapsed(),
+                    ok: false,
+                });
+                progress.send(InitEvent::SourceFailed {
+                    source: src,
+                    reason: "daemon source not allowed by this config".into(),
+                });
+                if diagnostics {
+                    eprintln!(
+                        "booklid: skipped daemon={} source={:?}",
+                        path.display(),
+                        src
+                    );
+                }
+            } else {
+                probes.push(Probe {
+                    source: src,
+                    elapsed: started.elapsed(),
+                    ok: true,
+                });
+                progress.send(InitEvent::SourceSelected(src));
+                dev.set_filter(filter);
+                dev.set_outlier_rejection(outlier_rejection);
+                if diagnostics {
+                    eprintln!(
+                        "booklid: attached daemon={} source={:?}",
+                        path.display(),
+                        src
+                    );
+                }
+                // The daemon already applied this machine's quirks.
+                return Ok(Selected {
+                    dev: Box::new(dev),
+                    axis,
+                    probes,
+                });
+            }
+        }
+    }
+
+    let registered = registry::sources();
+    if !HAS_BACKENDS && registered.is_empty() {
         return Err(Error::Backend(
             "no backends enabled; enable platform features".into(),
         ));
@@ -294,32 +1191,29 @@ async fn init_all(cfg: InitConfig) -> Result<AngleClient> {
 
     let mut tried = Vec::new();
 
-    // Persistence: try last source first
-    let persisted = if persistence {
-        persist::load().last_source
+    let mut state = if persistence {
+        persist::load()
     } else {
-        None
+        persist::PersistedState::default()
     };
 
-    let mut order: Vec<Source> = vec![
-        Source::HingeFeature,
-        Source::HingeHid,
-        Source::ALS,
-        Source::WinHinge,
-        Source::WinTilt,
-        Source::WinALS,
-        Source::LinuxTilt,
-        Source::LinuxALS,
-        Source::Mock,
-    ];
-
-    order.retain(|s| !disable_backends.contains(s));
-    if let Some(p) = persisted {
-        if order.contains(&p) {
-            order.retain(|s| s != &p);
-            order.insert(0, p);
+    let mut order = BUILTIN_ORDER.to_vec();
+    order.extend(registered.into_iter().filter(|s| *s != Source::Mock));
+    order.push(Source::Mock);
+
+    order.retain(allowed);
+    // Weight scaled by past open success (0.5 with no history, so weights
+    // alone decide until outcomes are recorded). Stable: ties keep the
+    // default order.
+    let rank = |s: &Source| {
+        let r = source_weight(&source_weights, *s) * (0.5 + state.success_rate(*s));
+        if prefer_low_power {
+            r * low_power_rank(*s)
+        } else {
+            r
         }
-    }
+    };
+    order.sort_by(|a, b| rank(b).total_cmp(&rank(a)));
     for p in prefer_sources.iter().rev() {
         if order.contains(p) {
             order.retain(|s| s != p);
@@ -327,116 +1221,731 @@ async fn init_all(cfg: InitConfig) -> Result<AngleClient> {
         }
     }
 
-    let _guard = desktop_guard();
+    let opts = BuiltinOpts {
+        #[cfg(reconnecting_backends)]
+        reconnect,
+        discovery,
+        persistence,
+        hid_override,
+        hid_read_mode,
+        allow_mock,
+        desktop: desktop_guard(),
+        failover_tilt: !prefer_low_power
+            && !disable_backends.contains(&Source::WinTilt)
+            && (!only_preferred || prefer_sources.contains(&Source::WinTilt)),
+        win_reporting,
+        linux_iio_buffer,
+        sysfs_root: sysfs_root.clone(),
+        dev_root,
+        quirks: Arc::clone(&user_quirks),
+    };
+    let open_source = move |src: Source, hz: f32| {
+        let opts = opts.clone();
+        async move {
+            let started = Instant::now();
+            let dev = match open_builtin(src, hz, opts).await {
+                Some(dev) => dev,
+                None => match registry::find(src) {
+                    Some(f) => f.open(BackendContext { hz, reconnect }).await,
+                    None => Err(Error::Backend(format!(
+                        "{src:?} is not available in this build or environment"
+                    ))),
+                },
+            };
+            (src, started.elapsed(), dev)
+        }
+    };
+
+    // Probe up to PROBE_CONCURRENCY sources at once, in rank order. A source
+    // is chosen once every higher-ranked one has failed; at the deadline the
+    // best success so far wins. Dropping the losers stops their samplers.
+    let deadline = tokio::time::Instant::now() + fail_after;
+    let mut queue = order.iter().copied();
+    let mut running = futures_util::stream::FuturesUnordered::new();
+    let mut results: Vec<(Source, Option<Option<DynDevice>>)> =
+        order.iter().map(|s| (*s, None)).collect();
+    // Reported instead of `NoBackend` if nothing opens: it says what to fix.
+    let mut denied = None;
+    loop {
+        while running.len() < PROBE_CONCURRENCY
+            && let Some(src) = queue.next()
+        {
+            tried.push(src);
+            progress.send(InitEvent::ProbingSource(src));
+            running.push(open_source(src, hz));
+        }
+        // Decided once the best unfailed source has finished.
+        if let Some((_, Some(_))) = results.iter().find(|(_, r)| !matches!(r, Some(None))) {
+            break;
+        }
+        match tokio::time::timeout_at(deadline, running.next()).await {
+            Ok(Some((src, elapsed, dev))) => {
+                probes.push(Probe {
+                    source: src,
+                    elapsed,
+                    ok: dev.is_ok(),
+                });
+                let dev = dev
+                    .map_err(|e| {
+                        progress.send(InitEvent::SourceFailed {
+                            source: src,
+                            reason: e.to_string(),
+                        });
+                        if let Error::PermissionDenied { hint, .. } = e {
+                            denied.get_or_insert(Error::PermissionDenied { sensor: src, hint });
+                        }
+                    })
+                    .ok();
+                if let Some(slot) = results.iter_mut().find(|(s, _)| *s == src) {
+                    slot.1 = Some(dev);
+                }
+            }
+            Ok(None) => break,
+            Err(_) => {
+                // Out of budget: whatever is still opening counts as failed.
+                for (src, r) in &results {
+                    if r.is_none() && tried.contains(src) {
+                        probes.push(Probe {
+                            source: *src,
+                            elapsed: fail_after,
+                            ok: false,
+                        });
+                        progress.send(InitEvent::SourceFailed {
+                            source: *src,
+                            reason: format!("timed out after {fail_after:?}"),
+                        });
+                    }
+                }
+                break;
+            }
+        }
+    }
+    drop(running);
+
+    let chosen = results
+        .iter_mut()
+        .position(|(_, r)| matches!(r, Some(Some(_))));
+    if let Some(i) = chosen {
+        let src = results[i].0;
+        let mut dev = results[i].1.take().flatten().expect("chosen probe opened");
 
-    for src in order {
-        tried.push(src);
+        if warm_standby && !prefer_low_power {
+            // Reopen the best remaining source that did not fail at a low
+            // rate; full-rate probes that lost are dropped with `results`.
+            // Only a source in the same unit can stand in for the primary.
+            let standby_hz = hz.min(STANDBY_HZ);
+            let candidates: Vec<Source> = results[i + 1..]
+                .iter()
+                .filter(|(s, r)| !matches!(r, Some(None)) && s.is_degrees() == src.is_degrees())
+                .map(|(s, _)| *s)
+                .collect();
+            drop(results);
+            for s in candidates {
+                let left = deadline.saturating_duration_since(tokio::time::Instant::now());
+                if let Ok((_, _, Ok(standby))) =
+                    tokio::time::timeout(left, open_source(s, standby_hz)).await
+                {
+                    if diagnostics {
+                        eprintln!("booklid: warm standby={:?} at {} Hz", s, standby_hz);
+                    }
+                    let mut policy = failover::FailoverPolicy::for_rate(hz);
+                    // A still lid may leave the WinRT sensors quiet.
+                    if matches!(src, Source::WinHinge | Source::WinTilt | Source::WinALS) {
+                        policy = policy.quiet_read(win_reporting.quiet_read);
+                    }
+                    dev = Box::new(failover::Failover::new(dev, standby, policy));
+                    break;
+                }
+            }
+        }
 
-        // IMPORTANT: unify all backend returns into a single concrete type:
-        // Option<AngleClient> (boxed trait object).
-        let dev: Option<AngleClient> = match src {
-            #[cfg(feature = "mac_hid_feature")]
-            Source::HingeFeature if !_guard => backend_hidapi::HidAngle::open(hz)
-                .await
-                .ok()
-                .map(|d| Box::new(d) as AngleClient),
+        progress.send(InitEvent::SourceSelected(src));
+        dev.set_filter(filter);
+        dev.set_outlier_rejection(outlier_rejection);
+
+        if persistence {
+            for p in &probes {
+                state.record_open(p.source, p.ok);
+            }
+            state.last_source = Some(src);
+            persist::store(&state).ok();
+        }
+
+        if diagnostics {
+            eprintln!(
+                "booklid: chosen={:?} tried={:?} probes=[{}]",
+                src,
+                tried,
+                fmt_probes(&probes)
+            );
+        }
+        return Ok(Selected {
+            dev,
+            axis: axis.or_else(|| {
+                let model = quirks::machine_model_under(sysfs_root.as_deref());
+                quirks::lookup_for(&user_quirks, model.as_deref(), src)
+            }),
+            probes,
+        });
+    }
+
+    if persistence {
+        for p in &probes {
+            state.record_open(p.source, p.ok);
+        }
+        persist::store(&state).ok();
+    }
+    if diagnostics {
+        eprintln!("booklid: no backend; probes=[{}]", fmt_probes(&probes));
+    }
+    Err(denied.unwrap_or(Error::NoBackend { tried }))
+}
 
-            #[cfg(feature = "mac_hid_feature")]
-            Source::HingeHid if !_guard => backend_hidapi::HidAngle::open_with(hz, discovery)
+/// What the built-in backends need besides the source and rate.
+#[derive(Clone)]
+pub(crate) struct BuiltinOpts {
+    #[cfg(reconnecting_backends)]
+    pub(crate) reconnect: Backoff,
+    #[cfg_attr(not(feature = "mac_hid_feature"), allow(dead_code))]
+    pub(crate) discovery: bool,
+    /// Cache the HID discovery winner in the persisted state.
+    #[cfg_attr(not(feature = "mac_hid_feature"), allow(dead_code))]
+    pub(crate) persistence: bool,
+    #[cfg_attr(not(feature = "mac_hid_feature"), allow(dead_code))]
+    pub(crate) hid_override: Option<hid::HidOverride>,
+    #[cfg_attr(not(feature = "mac_hid_feature"), allow(dead_code))]
+    pub(crate) hid_read_mode: hid::ReadMode,
+    #[cfg_attr(not(feature = "mock"), allow(dead_code))]
+    pub(crate) allow_mock: bool,
+    /// `BOOKLID_DESKTOP=1`: leave the macOS hinge to registered backends.
+    #[cfg_attr(
+        not(any(
+            feature = "mac_hid_feature",
+            all(target_os = "macos", feature = "mac_iokit_raw"),
+            all(target_os = "macos", feature = "mac_smc")
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) desktop: bool,
+    /// Cross-check the Windows hinge against the inclinometer.
+    #[cfg_attr(
+        not(all(target_os = "windows", feature = "win_sensors")),
+        allow(dead_code)
+    )]
+    pub(crate) failover_tilt: bool,
+    #[cfg_attr(
+        not(all(target_os = "windows", feature = "win_sensors")),
+        allow(dead_code)
+    )]
+    pub(crate) win_reporting: win::WinReporting,
+    #[cfg_attr(
+        not(all(target_os = "linux", feature = "linux_iio_sys")),
+        allow(dead_code)
+    )]
+    pub(crate) linux_iio_buffer: bool,
+    #[cfg_attr(
+        not(all(target_os = "linux", feature = "linux_iio_sys")),
+        allow(dead_code)
+    )]
+    pub(crate) sysfs_root: Option<PathBuf>,
+    #[cfg_attr(
+        not(all(target_os = "linux", feature = "linux_iio_sys")),
+        allow(dead_code)
+    )]
+    pub(crate) dev_root: Option<PathBuf>,
+    /// The open's user quirks, see `OpenConfig::quirks_file`.
+    #[cfg_attr(
+        not(any(
+            feature = "mac_hid_feature",
+            all(target_os = "windows", feature = "win_sensors")
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) quirks: Arc<quirks::UserQuirks>,
+}
+
+/// Open `src` with its built-in backend, or `None` when this build has no
+/// built-in backend for it (registered factories get a turn then).
+#[cfg(builtin_backends)]
+pub(crate) async fn open_builtin(
+    src: Source,
+    hz: f32,
+    opts: BuiltinOpts,
+) -> Option<Result<DynDevice>> {
+    Some(match src {
+        #[cfg(feature = "mac_hid_feature")]
+        Source::HingeFeature | Source::HingeHid if !opts.desktop => {
+            let discovery = hid::Discovery {
+                probe: src == Source::HingeFeature || opts.discovery,
+                cache: opts
+                    .persistence
+                    .then(|| Arc::new(hid::PersistedDiscovery) as Arc<dyn hid::DiscoveryCache>),
+                device: opts.hid_override,
+                quirks: Some(opts.quirks),
+                ..hid::Discovery::default()
+            };
+            hid::HidAngle::open_with_mode(hz, discovery, opts.hid_read_mode, opts.reconnect)
                 .await
-                .ok()
-                .map(|d| Box::new(d) as AngleClient),
+                .map(|d| Box::new(d) as DynDevice)
+        }
 
-            #[cfg(feature = "mac_als")]
-            Source::ALS => backend_mac_als::AlsAngle::open(hz)
+        #[cfg(all(target_os = "macos", feature = "mac_iokit_raw"))]
+        Source::HingeIOKit if !opts.desktop => {
+            backend_mac_iokit::IoKitAngle::open(hz, opts.reconnect)
                 .await
-                .ok()
-                .map(|d| Box::new(d) as AngleClient),
+                .map(|d| Box::new(d) as DynDevice)
+        }
+
+        #[cfg(all(target_os = "macos", feature = "mac_smc"))]
+        Source::HingeSMC if !opts.desktop => backend_mac_smc::SmcAngle::open(hz, opts.reconnect)
+            .await
+            .map(|d| Box::new(d) as DynDevice),
+
+        #[cfg(feature = "mac_als")]
+        Source::ALS => backend_mac_als::AlsAngle::open(hz, opts.reconnect)
+            .await
+            .map(|d| Box::new(d) as DynDevice),
+
+        #[cfg(all(target_os = "macos", feature = "mac_clamshell"))]
+        Source::MacClamshell => lid_switch::IoKitClamshell::find()
+            .ok_or_else(|| Error::Backend("clamshell: AppleClamshellState not published".into()))
+            .and_then(|c| LidSwitchAngle::open(c, Source::MacClamshell, hz, opts.reconnect))
+            .map(|d| Box::new(d) as DynDevice),
+
+        #[cfg(all(target_os = "windows", feature = "win_sensors"))]
+        Source::WinHinge => {
+            let hinge =
+                match win::WinAngle::open_hinge(hz, opts.reconnect, opts.win_reporting).await {
+                    // No hinge sensor: some Surfaces expose it as a custom sensor,
+                    // and a lid and a base accelerometer still give the angle.
+                    Err(e) => {
+                        let hinges = quirks::win_custom_hinges_for(&opts.quirks);
+                        match win::WinAngle::open_custom_hinge_in(
+                            hz,
+                            opts.reconnect,
+                            opts.win_reporting,
+                            hinges,
+                        )
+                        .await
+                        {
+                            Err(_) => win::WinAngle::open_accel_hinge(hz, opts.reconnect)
+                                .await
+                                .map_err(|_| e),
+                            hinge => hinge,
+                        }
+                    }
+                    hinge => hinge,
+                };
+            match hinge {
+                Ok(hinge) => {
+                    let hinge = Box::new(hinge) as DynDevice;
+                    // Cross-check against the inclinometer when it's allowed.
+                    let tilt = if opts.failover_tilt {
+                        win::WinAngle::open_tilt(hz, opts.reconnect, opts.win_reporting)
+                            .await
+                            .ok()
+                    } else {
+                        None
+                    };
+                    Ok(match tilt {
+                        Some(tilt) => Box::new(failover::Failover::new(
+                            hinge,
+                            Box::new(tilt),
+                            failover::FailoverPolicy::for_rate(hz)
+                                .quiet_read(opts.win_reporting.quiet_read),
+                        )) as DynDevice,
+                        None => hinge,
+                    })
+                }
+                Err(e) => Err(e),
+            }
+        }
+
+        #[cfg(all(target_os = "windows", feature = "win_posture"))]
+        Source::WinPosture => win::WinAngle::open_posture(hz, opts.reconnect)
+            .await
+            .map(|d| Box::new(d) as DynDevice),
+
+        #[cfg(all(target_os = "windows", feature = "win_sensors"))]
+        Source::WinTilt => win::WinAngle::open_tilt(hz, opts.reconnect, opts.win_reporting)
+            .await
+            .map(|d| Box::new(d) as DynDevice),
+
+        #[cfg(all(target_os = "windows", feature = "win_sensors"))]
+        Source::WinALS => win::WinAngle::open_als(hz, opts.reconnect, opts.win_reporting)
+            .await
+            .map(|d| Box::new(d) as DynDevice),
 
-            #[cfg(all(target_os = "windows", feature = "win_sensors"))]
-            Source::WinHinge => backend_win::WinAngle::open_hinge(hz)
+        #[cfg(all(target_os = "windows", feature = "win_lid_switch"))]
+        Source::WinLidSwitch => {
+            lid_switch::NotifiedLidSwitch::windows_within(Duration::from_millis(100))
                 .await
-                .ok()
-                .map(|d| Box::new(d) as AngleClient),
+                .ok_or_else(|| Error::Backend("lid switch: Windows reported no lid state".into()))
+                .and_then(|l| LidSwitchAngle::open(l, Source::WinLidSwitch, hz, opts.reconnect))
+                .map(|d| Box::new(d) as DynDevice)
+        }
 
-            #[cfg(all(target_os = "windows", feature = "win_sensors"))]
-            Source::WinTilt => backend_win::WinAngle::open_tilt(hz)
+        #[cfg(all(target_os = "linux", feature = "linux_libinput"))]
+        Source::LinuxLidSwitch => {
+            tokio::task::spawn_blocking(lid_switch::NotifiedLidSwitch::libinput)
                 .await
                 .ok()
-                .map(|d| Box::new(d) as AngleClient),
+                .flatten()
+                .ok_or_else(|| Error::Backend("lid switch: libinput found no lid switch".into()))
+                .and_then(|l| LidSwitchAngle::open(l, Source::LinuxLidSwitch, hz, opts.reconnect))
+                .map(|d| Box::new(d) as DynDevice)
+        }
 
-            #[cfg(all(target_os = "windows", feature = "win_sensors"))]
-            Source::WinALS => backend_win::WinAngle::open_als(hz)
+        #[cfg(all(target_os = "linux", feature = "linux_upower"))]
+        Source::LinuxUPowerLid => {
+            tokio::task::spawn_blocking(lid_switch::NotifiedLidSwitch::upower)
                 .await
                 .ok()
-                .map(|d| Box::new(d) as AngleClient),
+                .flatten()
+                .ok_or_else(|| Error::Backend("lid switch: UPower reported no lid".into()))
+                .and_then(|l| LidSwitchAngle::open(l, Source::LinuxUPowerLid, hz, opts.reconnect))
+                .map(|d| Box::new(d) as DynDevice)
+        }
 
-            #[cfg(all(
-                target_os = "linux",
-                any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
-            ))]
-            Source::LinuxTilt => backend_linux::LinuxAngle::open_tilt(hz)
+        #[cfg(all(target_os = "linux", feature = "linux_acpi_lid"))]
+        Source::LinuxAcpiLid => lid_switch::AcpiLid::find()
+            .ok_or_else(|| Error::Backend("lid switch: no /proc/acpi/button/lid".into()))
+            .and_then(|l| {
+                LidSwitchAngle::open_with_confidence(
+                    l,
+                    Source::LinuxAcpiLid,
+                    lid_switch::ACPI_LID_CONFIDENCE,
+                    hz,
+                    opts.reconnect,
+                )
+            })
+            .map(|d| Box::new(d) as DynDevice),
+
+        #[cfg(all(
+            target_os = "linux",
+            any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
+        ))]
+        Source::LinuxHinge => {
+            let roots = linux::Roots::new(opts.sysfs_root, opts.dev_root);
+            backend_linux::LinuxAngle::open_hinge(hz, opts.reconnect, opts.linux_iio_buffer, roots)
                 .await
-                .ok()
-                .map(|d| Box::new(d) as AngleClient),
+                .map(|d| Box::new(d) as DynDevice)
+        }
 
-            #[cfg(all(
-                target_os = "linux",
-                any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
-            ))]
-            Source::LinuxALS => backend_linux::LinuxAngle::open_als(hz)
+        #[cfg(all(
+            target_os = "linux",
+            any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
+        ))]
+        Source::LinuxTilt => {
+            let roots = linux::Roots::new(opts.sysfs_root, opts.dev_root);
+            backend_linux::LinuxAngle::open_tilt(hz, opts.reconnect, opts.linux_iio_buffer, roots)
                 .await
-                .ok()
-                .map(|d| Box::new(d) as AngleClient),
+                .map(|d| Box::new(d) as DynDevice)
+        }
 
-            #[cfg(feature = "mock")]
-            Source::Mock if allow_mock => backend_mock::MockAngle::open(hz)
+        #[cfg(all(
+            target_os = "linux",
+            any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
+        ))]
+        Source::LinuxALS => {
+            let roots = linux::Roots::new(opts.sysfs_root, opts.dev_root);
+            backend_linux::LinuxAngle::open_als(hz, opts.reconnect, roots)
                 .await
-                .ok()
-                .map(|d| Box::new(d) as AngleClient),
+                .map(|d| Box::new(d) as DynDevice)
+        }
 
-            _ => None,
-        };
+        #[cfg(feature = "mock")]
+        Source::Mock if opts.allow_mock => backend_mock::MockAngle::open(hz)
+            .await
+            .map(|d| Box::new(d) as DynDevice),
 
-        if let Some(dev) = dev {
-            dev.set_smoothing(smoothing_alpha);
-            let dev = Gated::wrap(dev, min_confidence);
+        _ => return None,
+    })
+}
 
-            if persistence {
-                persist::store(&persist::PersistedState {
-                    last_source: Some(src),
-                })
-                .ok();
-            }
+#[cfg(not(builtin_backends))]
+pub(crate) async fn open_builtin(
+    _src: Source,
+    _hz: f32,
+    _opts: BuiltinOpts,
+) -> Option<Result<DynDevice>> {
+    None
+}
 
-            if diagnostics {
-                eprintln!("booklid: chosen={:?} tried={:?}", src, tried);
-            }
-            return Ok(dev);
+/// Accuracy per unit of power for `OpenConfig::prefer_low_power`: a true
+/// hinge beats a tilt estimate beats a normalized ALS control, and each
+/// step up in power cost roughly halves a source's appeal. `Mock` ranks
+/// last so it never displaces a real sensor.
+fn low_power_rank(source: Source) -> f32 {
+    if source == Source::Mock {
+        return 0.0;
+    }
+    let caps = Capabilities::for_source(source);
+    let accuracy = if caps.true_degrees {
+        1.0
+    } else if caps.normalized {
+        0.15
+    } else {
+        0.5
+    };
+    let cost = registry::find(source).map_or_else(|| source.power_cost(), |f| f.power_cost());
+    let power = match cost {
+        PowerCost::Low => 1.0,
+        PowerCost::Moderate => 0.5,
+        PowerCost::High => 0.25,
+    };
+    accuracy * power
+}
+
+/// Configured weight for `source`, 1.0 if unset.
+fn source_weight(weights: &[(Source, f32)], source: Source) -> f32 {
+    weights
+        .iter()
+        .find(|(s, _)| *s == source)
+        .map_or(1.0, |(_, w)| *w)
+}
+
+/// Calibrations for a client on `source`'s `hardware_id`: the `explicit`
+/// ones that fit it (or are not tied to a sensor), else (with
+/// `persistence`) the saved match.
+pub(crate) fn calibrations_for(
+    explicit: &[calibrate::AngleCalibration],
+    persistence: bool,
+    source: Source,
+    hardware_id: Option<&str>,
+) -> Vec<calibrate::AngleCalibration> {
+    let mut all: Vec<_> = explicit
+        .iter()
+        .filter(|c| c.source != source || c.hardware_id.is_none() || c.matches(source, hardware_id))
+        .cloned()
+        .collect();
+    if persistence && all.iter().all(|c| c.source != source) {
+        all.extend(calibrate::saved_for(source, hardware_id));
+    }
+    all
+}
+
+fn enable_histogram(client: &AngleClient, cfg: Option<HistogramConfig>, persistence: bool) {
+    let Some(cfg) = cfg else {
+        return;
+    };
+    let mut h = Histogram::new(cfg.range, cfg.bins);
+    let persist = cfg.persist && persistence;
+    if persist {
+        if let Some(saved) = persist::load().histogram.filter(|s| s.same_layout(&h)) {
+            h = saved;
         }
     }
+    client.enable_histogram(h, persist);
+}
 
-    Err(Error::NoBackend { tried })
+fn enable_power_notifications(client: &AngleClient, on: bool) {
+    if let Some(n) = on.then(sleep::platform).flatten() {
+        client.follow_power(n);
+    }
+}
+
+fn enable_auto_calibration(client: &AngleClient, cfg: Option<AutoCalibrationConfig>) {
+    if let Some(cfg) = cfg {
+        client.keep_auto_calibration(cfg);
+    }
+}
+
+fn fmt_probes(probes: &[Probe]) -> String {
+    probes
+        .iter()
+        .map(|p| {
+            format!(
+                "{:?}:{}ms{}",
+                p.source,
+                p.elapsed.as_millis(),
+                if p.ok { "" } else { "(fail)" }
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 // ===== Public API =====
 
-pub async fn open(hz: f32) -> Result<AngleClient> {
-    open_with_config(OpenConfig::new(hz)).await
+pub async fn open(rate: SampleRate) -> Result<AngleClient> {
+    open_with_config(OpenConfig::new(rate)).await
 }
 
+/// Open with `cfg`. When no backend opens this is `Error::NoBackend`, or
+/// `Error::PermissionDenied` if a sensor is present but the OS refused it.
 pub async fn open_with_config(cfg: OpenConfig) -> Result<AngleClient> {
     let init = InitConfig::from_open(cfg)?;
-    init_all(init).await
+    init_all(init, Progress::default()).await
 }
 
-pub fn open_blocking(hz: f32) -> Result<AngleClient> {
-    open_blocking_with_config(OpenConfig::new(hz))
+/// Lid angles in degrees at `rate`, for scripts and demos: opens the best
+/// degree source with default settings and yields each gated sample's
+/// `angle_deg`. Sources that carry no degrees (ALS, lid switches) are
+/// skipped, and nothing is persisted between runs. The stream is empty if
+/// nothing can be opened (enable `diagnostics` to see why) and ends if the
+/// backend closes; use `open_with_config` for control over any of this.
+pub fn watch_degrees(rate: SampleRate) -> impl futures_core::Stream<Item = f32> + Send + 'static {
+    let cfg = OpenConfig::new(rate).persistence(false).disable(
+        BUILTIN_ORDER
+            .into_iter()
+            .filter(|s| !s.is_degrees())
+            .collect(),
+    );
+    futures_util::stream::once(open_with_config(cfg)).flat_map(|opened| match opened {
+        Ok(client) => {
+            let samples = client.subscribe();
+            // Keep the client alive for as long as the stream is.
+            samples
+                .filter(|s| std::future::ready(s.is_degrees()))
+                .map(move |s| {
+                    let _ = &client;
+                    s.angle_deg
+                })
+                .boxed()
+        }
+        Err(_e) => {
+            #[cfg(feature = "diagnostics")]
+            eprintln!("[booklid] watch_degrees: {_e}");
+            futures_util::stream::empty().boxed()
+        }
+    })
+}
+
+/// Like `open_with_config`, but gives up with `Error::Cancelled` as soon as
+/// `token` is cancelled. In-flight probes are dropped, which also stops any
+/// sampler a backend had already started.
+pub async fn open_with_config_cancellable(
+    cfg: OpenConfig,
+    token: CancellationToken,
+) -> Result<AngleClient> {
+    let init = InitConfig::from_open(cfg)?;
+    tokio::select! {
+        biased;
+        _ = token.cancelled() => Err(Error::Cancelled),
+        r = init_all(init, Progress::default()) => r,
+    }
+}
+
+/// Return a client immediately and attach a backend in the background.
+/// Until one opens, `latest()` is `None` and `health()` is
+/// `Health::Acquiring`; failed attempts are retried per `cfg.reconnect`
+/// (but not after `Error::PermissionDenied`), and
+/// `Event::Attached` is emitted once a backend is in use. `probes()` stays
+/// empty. Called outside a Tokio runtime, the client runs on the internal one
+/// the blocking API uses (see `shutdown()`).
+pub fn open_lazy(cfg: OpenConfig) -> Result<AngleClient> {
+    let init = InitConfig::from_open(cfg)?;
+    let rt = tokio::runtime::Handle::try_current().unwrap_or_else(|_| runtime());
+    let _rt = rt.enter();
+    let (rate, min_confidence, persistence) = (init.rate, init.min_confidence, init.persistence);
+    let (histogram, output_map) = (init.histogram.clone(), init.output_map.clone());
+    let (lid, power_notifications) = (init.lid_thresholds, init.power_notifications);
+    let auto_calibration = init.auto_calibration;
+    let (calibrations, zones) = (init.calibrations.clone(), init.zones.clone());
+    // `LazyAngle` sets the axis once the source is known.
+    let output = Arc::new(RwLock::new(client::Output {
+        axis: None,
+        map: output_map,
+        confidence: init.confidence_scales.clone(),
+        calibrations: Vec::new(),
+    }));
+    let dev = lazy::LazyAngle::new(init, Arc::clone(&output));
+    let client = AngleClient::with_output(Box::new(dev), rate, min_confidence, output, Vec::new());
+    client.resolve_calibrations(calibrations, persistence);
+    client.set_lid_thresholds(lid.0, lid.1)?;
+    client.set_zones(zones);
+    enable_histogram(&client, histogram, persistence);
+    enable_power_notifications(&client, power_notifications);
+    enable_auto_calibration(&client, auto_calibration);
+    Ok(client)
+}
+
+/// Like `open_with_config`, plus a stream of `InitEvent`s reporting each
+/// probe as it starts, fails, or is selected. The stream ends once the
+/// returned future completes; drive the future to make progress.
+pub fn open_with_progress(
+    cfg: OpenConfig,
+) -> (
+    BoxStream<'static, InitEvent>,
+    BoxFuture<'static, Result<AngleClient>>,
+) {
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let fut = async move {
+        let init = InitConfig::from_open(cfg)?;
+        init_all(init, Progress(Some(tx))).await
+    };
+    (UnboundedReceiverStream::new(rx).boxed(), Box::pin(fut))
+}
+
+/// Play back a recording (see `record`) as a client. Samples arrive with the
+/// recorded spacing and confidence; `cfg` supplies the rate used for health
+/// checks and the confidence gate. Recorded values are already smoothed, so
+/// they replay verbatim; call `set_smoothing` to smooth further.
+#[cfg(feature = "record")]
+pub async fn open_replay(
+    path: impl AsRef<std::path::Path>,
+    cfg: OpenConfig,
+) -> Result<AngleClient> {
+    open_replay_with_control(path, cfg)
+        .await
+        .map(|(client, _)| client)
+}
+
+/// Like `open_replay`, also returning a `record::ReplayControl` to change the
+/// playback rate, pause, or seek, e.g. to scrub through an incident while
+/// debugging a consumer.
+#[cfg(feature = "record")]
+pub async fn open_replay_with_control(
+    path: impl AsRef<std::path::Path>,
+    cfg: OpenConfig,
+) -> Result<(AngleClient, record::ReplayControl)> {
+    let rec = record::Recording::load(path)?;
+    let cfg = cfg.validate()?;
+    let dev = backend_replay::ReplayAngle::open(rec);
+    let control = dev.control();
+    let client = AngleClient::new(
+        Box::new(dev),
+        cfg.rate,
+        cfg.min_confidence,
+        cfg.axis,
+        cfg.output_map,
+        Vec::new(),
+    );
+    client.set_confidence_scales(cfg.confidence_scales);
+    client.set_calibrations(cfg.calibrations);
+    client.set_lid_thresholds(cfg.lid_closed_below, cfg.lid_opened_above)?;
+    client.set_zones(cfg.zones);
+    Ok((client, control))
+}
+
+pub fn open_blocking(rate: SampleRate) -> Result<AngleClient> {
+    open_blocking_with_config(OpenConfig::new(rate))
 }
 
 pub fn open_blocking_with_config(cfg: OpenConfig) -> Result<AngleClient> {
     let init = InitConfig::from_open(cfg)?;
-    RUNTIME.block_on(init_all(init))
+    runtime().block_on(init_all(init, Progress::default()))
+}
+
+/// Close every open client as `AngleClient::close` does (backends stop,
+/// subscriber streams end, a `HistogramConfig::persist` histogram is saved)
+/// and shut down the internal runtime used by the blocking API. Outside a
+/// runtime this waits up to a second for its tasks; called from inside one
+/// it returns without waiting.
+///
+/// Safe to call more than once; a later `open_blocking*` starts a fresh runtime.
+pub fn shutdown() {
+    client::close_all();
+    let rt = RUNTIME.lock().unwrap().take();
+    if let Some(rt) = rt {
+        if tokio::runtime::Handle::try_current().is_ok() {
+            // Blocking on shutdown from inside a runtime would panic.
+            rt.shutdown_background();
+        } else {
+            rt.shutdown_timeout(Duration::from_secs(1));
+        }
+    }
 }
 
 pub fn clear_persisted_state() -> Result<()> {
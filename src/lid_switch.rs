@@ -0,0 +1,1037 @@
+//! Platform lid switches, and automatic zero calibration from them: while
+//! the switch reports the lid shut, the client's axis offset is anchored so
+//! the reading is 0°, and refined on every later close.
+//!
+//! Clients do this on `platform()` from open (`OpenConfig::auto_calibration`);
+//! for another switch:
+//!
+//! ```ignore
+//! let _auto = dev.auto_calibrate(my_switch, AutoCalibrationConfig::default());
+//! // ...keep `_auto` alive as long as it should run...
+//! ```
+//!
+//! Linux's ACPI lid button (else libinput's lid switch, with feature
+//! `linux_libinput`, or UPower's `LidIsClosed`, with `linux_upower`),
+//! macOS's `AppleClamshellState` and Windows' `GUID_LIDSWITCH_STATE_CHANGE`
+//! notifications are built in; implement `LidSwitch` for other platforms. Many machines suspend when the lid
+//! shuts, so anchors mostly come from closed-lid (external display) use.
+//!
+//! `LidSwitchAngle` also serves a switch as a device of its own
+//! (`Source::MacClamshell`, `Source::WinLidSwitch`,
+//! `Source::LinuxLidSwitch`, `Source::LinuxUPowerLid`,
+//! `Source::LinuxAcpiLid`), for an open/closed signal where no angle sensor
+//! is available.
+
+use crate::client::WeakClient;
+use crate::{
+    AngleClient, AngleDevice, AngleSample, AngleStream, Backoff, DeviceInfo, Result, Source,
+    TaskGuard,
+};
+use futures_util::{StreamExt, stream::BoxStream};
+use std::sync::{
+    Arc, Mutex, PoisonError,
+    atomic::{AtomicBool, AtomicU32, Ordering},
+};
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::{self, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LidSwitchState {
+    Open,
+    Closed,
+}
+
+/// A lid switch the coordinator polls.
+pub trait LidSwitch: Send + Sync + 'static {
+    /// Current state; `Ok(None)` when it is not known right now.
+    fn read(&self) -> Result<Option<LidSwitchState>>;
+
+    /// For switches the OS notifies about: a receiver marked changed on
+    /// every new state, so `LidSwitchAngle` sends it at once rather than on
+    /// its next poll. `None` (the default) for switches that are only read.
+    fn changes(&self) -> Option<watch::Receiver<Option<LidSwitchState>>> {
+        None
+    }
+}
+
+impl LidSwitch for Box<dyn LidSwitch> {
+    fn read(&self) -> Result<Option<LidSwitchState>> {
+        (**self).read()
+    }
+
+    fn changes(&self) -> Option<watch::Receiver<Option<LidSwitchState>>> {
+        (**self).changes()
+    }
+}
+
+/// This machine's lid switch, if one is supported and present.
+pub fn platform() -> Option<Box<dyn LidSwitch>> {
+    #[cfg(target_os = "linux")]
+    if let Some(lid) = AcpiLid::find() {
+        return Some(Box::new(lid));
+    }
+    #[cfg(all(target_os = "linux", feature = "linux_libinput"))]
+    if let Some(lid) = NotifiedLidSwitch::libinput() {
+        return Some(Box::new(lid));
+    }
+    #[cfg(all(target_os = "linux", feature = "linux_upower"))]
+    if let Some(lid) = NotifiedLidSwitch::upower() {
+        return Some(Box::new(lid));
+    }
+    #[cfg(target_os = "macos")]
+    if let Some(lid) = IoKitClamshell::find() {
+        return Some(Box::new(lid));
+    }
+    #[cfg(target_os = "windows")]
+    if let Some(lid) = NotifiedLidSwitch::windows() {
+        return Some(Box::new(lid));
+    }
+    None
+}
+
+/// The ACPI lid button, `/proc/acpi/button/lid/*/state`.
+#[cfg(target_os = "linux")]
+pub struct AcpiLid {
+    path: std::path::PathBuf,
+}
+
+#[cfg(target_os = "linux")]
+impl AcpiLid {
+    pub fn find() -> Option<Self> {
+        crate::linux::glob_under(std::path::Path::new("/proc"), "acpi/button/lid/*/state")
+            .into_iter()
+            .next()
+            .map(Self::at)
+    }
+
+    /// Read the state file at `path`.
+    pub fn at(path: impl Into<std::path::PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+/// Confidence of `Source::LinuxAcpiLid` samples: the legacy state file is
+/// only as fresh as the firmware's last lid notification, which some
+/// machines skip (across suspend, or in VMs).
+pub const ACPI_LID_CONFIDENCE: f32 = 0.5;
+
+#[cfg(target_os = "linux")]
+impl LidSwitch for AcpiLid {
+    fn read(&self) -> Result<Option<LidSwitchState>> {
+        let s = std::fs::read_to_string(&self.path)?;
+        Ok(crate::linux::parse_acpi_lid_state(&s))
+    }
+}
+
+/// `AppleClamshellState` on the IORegistry's `IOPMrootDomain`.
+#[cfg(target_os = "macos")]
+pub struct IoKitClamshell;
+
+#[cfg(target_os = "macos")]
+impl IoKitClamshell {
+    /// `Some` when this Mac publishes the clamshell state.
+    pub fn find() -> Option<Self> {
+        matches!(clamshell_closed(), Ok(Some(_))).then_some(Self)
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl LidSwitch for IoKitClamshell {
+    fn read(&self) -> Result<Option<LidSwitchState>> {
+        Ok(clamshell_closed()?.map(|closed| {
+            if closed {
+                LidSwitchState::Closed
+            } else {
+                LidSwitchState::Open
+            }
+        }))
+    }
+}
+
+/// `AppleClamshellState`: `Ok(None)` when the property is missing.
+#[cfg(target_os = "macos")]
+fn clamshell_closed() -> Result<Option<bool>> {
+    use std::ffi::c_void;
+    type CFTypeRef = *const c_void;
+
+    #[link(name = "IOKit", kind = "framework")]
+    unsafe extern "C" {
+        fn IOServiceMatching(name: *const std::ffi::c_char) -> *mut c_void;
+        fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> u32;
+        fn IORegistryEntryCreateCFProperty(
+            entry: u32,
+            key: CFTypeRef,
+            allocator: CFTypeRef,
+            options: u32,
+        ) -> CFTypeRef;
+        fn IOObjectRelease(object: u32) -> i32;
+    }
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFStringCreateWithCString(
+            allocator: CFTypeRef,
+            s: *const std::ffi::c_char,
+            encoding: u32,
+        ) -> CFTypeRef;
+        fn CFGetTypeID(cf: CFTypeRef) -> usize;
+        fn CFBooleanGetTypeID() -> usize;
+        fn CFBooleanGetValue(b: CFTypeRef) -> u8;
+        fn CFRelease(cf: CFTypeRef);
+    }
+    const UTF8: u32 = 0x0800_0100;
+
+    // SAFETY: the matching dictionary is consumed by
+    // IOServiceGetMatchingService; every object created here is released
+    // once, and the property is only read as a CFBoolean after its type is
+    // checked.
+    unsafe {
+        let matching = IOServiceMatching(c"IOPMrootDomain".as_ptr());
+        if matching.is_null() {
+            return Err(crate::Error::Backend(
+                "clamshell: no matching dictionary".into(),
+            ));
+        }
+        let service = IOServiceGetMatchingService(0, matching);
+        if service == 0 {
+            return Err(crate::Error::Backend(
+                "clamshell: IOPMrootDomain not found".into(),
+            ));
+        }
+        let key =
+            CFStringCreateWithCString(std::ptr::null(), c"AppleClamshellState".as_ptr(), UTF8);
+        let value = if key.is_null() {
+            std::ptr::null()
+        } else {
+            let v = IORegistryEntryCreateCFProperty(service, key, std::ptr::null(), 0);
+            CFRelease(key);
+            v
+        };
+        IOObjectRelease(service);
+        if value.is_null() {
+            return Ok(None);
+        }
+        let closed =
+            (CFGetTypeID(value) == CFBooleanGetTypeID()).then(|| CFBooleanGetValue(value) != 0);
+        CFRelease(value);
+        Ok(closed)
+    }
+}
+
+/// The lid state Windows last reported through
+/// `GUID_LIDSWITCH_STATE_CHANGE` power-setting notifications.
+#[cfg(target_os = "windows")]
+static WIN_LID: once_cell::sync::Lazy<notified::Watcher> =
+    once_cell::sync::Lazy::new(|| notified::Watcher::new(register_lid_notifications));
+
+/// Subscribe `WIN_LID` to lid switch changes, once per process with
+/// `PowerSettingRegisterNotification`; false if Windows refused. The
+/// registration lasts for the life of the process.
+#[cfg(target_os = "windows")]
+fn register_lid_notifications() -> bool {
+    use std::ffi::c_void;
+
+    #[repr(C)]
+    struct Guid {
+        data1: u32,
+        data2: u16,
+        data3: u16,
+        data4: [u8; 8],
+    }
+    type Callback = unsafe extern "system" fn(*const c_void, u32, *const c_void) -> u32;
+    /// `DEVICE_NOTIFY_SUBSCRIBE_PARAMETERS`.
+    #[repr(C)]
+    struct SubscribeParameters {
+        callback: Callback,
+        context: *const c_void,
+    }
+    /// `POWERBROADCAST_SETTING`, whose `data` runs on for `data_length`
+    /// bytes.
+    #[repr(C)]
+    struct PowerBroadcastSetting {
+        power_setting: Guid,
+        data_length: u32,
+        data: [u8; 1],
+    }
+
+    #[link(name = "powrprof")]
+    unsafe extern "system" {
+        fn PowerSettingRegisterNotification(
+            setting: *const Guid,
+            flags: u32,
+            recipient: *const c_void,
+            handle: *mut *mut c_void,
+        ) -> u32;
+    }
+    const DEVICE_NOTIFY_CALLBACK: u32 = 2;
+    const PBT_POWERSETTINGCHANGE: u32 = 0x8013;
+    // {BA3E0F4D-B817-4094-A2D1-D56379E6A0F3}
+    const GUID_LIDSWITCH_STATE_CHANGE: Guid = Guid {
+        data1: 0xBA3E_0F4D,
+        data2: 0xB817,
+        data3: 0x4094,
+        data4: [0xA2, 0xD1, 0xD5, 0x63, 0x79, 0xE6, 0xA0, 0xF3],
+    };
+
+    unsafe extern "system" fn on_setting(
+        _context: *const c_void,
+        kind: u32,
+        setting: *const c_void,
+    ) -> u32 {
+        if kind != PBT_POWERSETTINGCHANGE || setting.is_null() {
+            return 0;
+        }
+        // SAFETY: for PBT_POWERSETTINGCHANGE `setting` points at a
+        // POWERBROADCAST_SETTING with `data_length` bytes of data; the lid
+        // state is a DWORD, 0 closed and 1 open.
+        unsafe {
+            let s = setting.cast::<PowerBroadcastSetting>();
+            if (*s).data_length >= 4 {
+                let open = std::ptr::read_unaligned((*s).data.as_ptr().cast::<u32>()) != 0;
+                WIN_LID.report(Some(if open {
+                    LidSwitchState::Open
+                } else {
+                    LidSwitchState::Closed
+                }));
+            }
+        }
+        0
+    }
+
+    // Leaked: Windows may call back for as long as the process runs.
+    let params: &'static SubscribeParameters = Box::leak(Box::new(SubscribeParameters {
+        callback: on_setting,
+        context: std::ptr::null(),
+    }));
+    let mut handle = std::ptr::null_mut();
+    // SAFETY: the GUID and parameters outlive the registration, and
+    // `handle` is a valid out pointer.
+    let status = unsafe {
+        PowerSettingRegisterNotification(
+            &GUID_LIDSWITCH_STATE_CHANGE,
+            DEVICE_NOTIFY_CALLBACK,
+            std::ptr::from_ref(params).cast(),
+            &mut handle,
+        )
+    };
+    status == 0
+}
+
+/// The lid state libinput last reported on this session's seat; `None`
+/// without a lid switch.
+#[cfg(all(target_os = "linux", feature = "linux_libinput"))]
+static LIBINPUT_LID: once_cell::sync::Lazy<notified::Watcher> =
+    once_cell::sync::Lazy::new(|| notified::Watcher::new(libinput::start));
+
+/// One thread per process runs a libinput context on the seat and keeps
+/// `LIBINPUT_LID` current.
+#[cfg(all(target_os = "linux", feature = "linux_libinput"))]
+mod libinput {
+    use super::{LIBINPUT_LID, LidSwitchState};
+    use input::event::{
+        DeviceEvent, EventTrait, SwitchEvent,
+        switch::{Switch, SwitchState},
+    };
+    use input::{AsRaw, DeviceCapability, Event, Libinput, ffi};
+    use std::ffi::{CStr, CString, c_char, c_int, c_ulong, c_void};
+    use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd};
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::Path;
+
+    const O_ACCMODE: c_int = 0o3;
+    const O_RDONLY: c_int = 0o0;
+    const O_WRONLY: c_int = 0o1;
+    const EIO: c_int = 5;
+    const POLLIN: i16 = 0x1;
+
+    #[repr(C)]
+    struct PollFd {
+        fd: c_int,
+        events: i16,
+        revents: i16,
+    }
+
+    unsafe extern "C" {
+        fn poll(fds: *mut PollFd, nfds: c_ulong, timeout: c_int) -> c_int;
+    }
+
+    #[link(name = "udev")]
+    unsafe extern "C" {
+        fn udev_new() -> *mut c_void;
+        fn udev_unref(udev: *mut c_void) -> *mut c_void;
+    }
+
+    /// Opens a device node for libinput, as this process may: a node it
+    /// cannot read is refused rather than taken over from the session.
+    unsafe extern "C" fn open_restricted(
+        path: *const c_char,
+        flags: c_int,
+        _user_data: *mut c_void,
+    ) -> c_int {
+        // SAFETY: libinput passes a valid path.
+        let path = unsafe { CStr::from_ptr(path) }.to_string_lossy();
+        let mode = flags & O_ACCMODE;
+        let opened = std::fs::OpenOptions::new()
+            .read(mode != O_WRONLY)
+            .write(mode != O_RDONLY)
+            .custom_flags(flags)
+            .open(Path::new(&*path));
+        match opened {
+            Ok(f) => OwnedFd::from(f).into_raw_fd(),
+            Err(e) => -e.raw_os_error().unwrap_or(EIO),
+        }
+    }
+
+    unsafe extern "C" fn close_restricted(fd: c_int, _user_data: *mut c_void) {
+        // SAFETY: libinput hands back an fd it got from `open_restricted`
+        // and no longer uses.
+        drop(unsafe { OwnedFd::from_raw_fd(fd) });
+    }
+
+    /// Starts the thread; false if libinput could not be set up on the seat.
+    pub(super) fn start() -> bool {
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+        let spawned = std::thread::Builder::new()
+            .name("booklid-libinput".into())
+            .spawn(move || {
+                let Some(mut li) = context() else {
+                    let _ = ready_tx.send(false);
+                    return;
+                };
+                let fd = li.as_raw_fd();
+                let mut ready = Some(ready_tx);
+                while li.dispatch().is_ok() {
+                    for event in &mut li {
+                        on_event(event);
+                    }
+                    if let Some(tx) = ready.take() {
+                        let _ = tx.send(true);
+                    }
+                    let mut p = PollFd {
+                        fd,
+                        events: POLLIN,
+                        revents: 0,
+                    };
+                    // SAFETY: `p` is one valid pollfd.
+                    unsafe { poll(&mut p, 1, -1) };
+                }
+                LIBINPUT_LID.stopped();
+            });
+        spawned.is_ok() && ready_rx.recv().unwrap_or(false)
+    }
+
+    /// A udev context for the seat, its devices opened by `open_restricted`.
+    fn context() -> Option<Libinput> {
+        let seat = std::env::var("XDG_SEAT").unwrap_or_else(|_| "seat0".into());
+        let seat = CString::new(seat).ok()?;
+        // Leaked: libinput calls back through it for the life of the
+        // context, which is the life of the process.
+        let interface: &'static ffi::libinput_interface =
+            Box::leak(Box::new(ffi::libinput_interface {
+                open_restricted: Some(open_restricted),
+                close_restricted: Some(close_restricted),
+            }));
+        // SAFETY: `udev` is checked before use and unreferenced once the
+        // context holds its own reference; the context's pointer is checked
+        // and handed to `Libinput`, which takes a reference of its own.
+        unsafe {
+            let udev = udev_new();
+            if udev.is_null() {
+                return None;
+            }
+            let raw =
+                ffi::libinput_udev_create_context(interface, std::ptr::null_mut(), udev.cast());
+            udev_unref(udev);
+            if raw.is_null() {
+                return None;
+            }
+            let li = Libinput::from_raw(raw);
+            ffi::libinput_unref(raw);
+            (ffi::libinput_udev_assign_seat(li.as_raw_mut(), seat.as_ptr()) == 0).then_some(li)
+        }
+    }
+
+    fn on_event(event: Event) {
+        let has_lid = |d: input::Device| {
+            d.has_capability(DeviceCapability::Switch)
+                && d.switch_has_switch(Switch::Lid) == Ok(true)
+        };
+        match event {
+            // Taken as open until libinput reports the switch on.
+            Event::Device(DeviceEvent::Added(e)) if has_lid(e.device()) => {
+                LIBINPUT_LID.report_if_unknown(LidSwitchState::Open);
+            }
+            Event::Device(DeviceEvent::Removed(e)) if has_lid(e.device()) => {
+                LIBINPUT_LID.report(None);
+            }
+            Event::Switch(SwitchEvent::Toggle(t)) if t.switch() == Some(Switch::Lid) => {
+                LIBINPUT_LID.report(Some(if t.switch_state() == SwitchState::On {
+                    LidSwitchState::Closed
+                } else {
+                    LidSwitchState::Open
+                }));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The lid state UPower last reported; `None` without a lid (or UPower).
+#[cfg(all(target_os = "linux", feature = "linux_upower"))]
+static UPOWER_LID: once_cell::sync::Lazy<notified::Watcher> =
+    once_cell::sync::Lazy::new(|| notified::Watcher::new(watch_upower));
+
+/// Read `LidIsClosed` once and keep `UPOWER_LID` current from a thread
+/// following its changes; false if UPower is unreachable or has no lid.
+#[cfg(all(target_os = "linux", feature = "linux_upower"))]
+fn watch_upower() -> bool {
+    use zbus::blocking::{Connection as ZConn, Proxy as ZProxy};
+
+    let store = |closed: bool| {
+        UPOWER_LID.report(Some(if closed {
+            LidSwitchState::Closed
+        } else {
+            LidSwitchState::Open
+        }));
+    };
+    let Some(upower) = ZConn::system().ok().and_then(|conn| {
+        ZProxy::new(
+            &conn,
+            "org.freedesktop.UPower",
+            "/org/freedesktop/UPower",
+            "org.freedesktop.UPower",
+        )
+        .ok()
+    }) else {
+        return false;
+    };
+    if upower.get_property::<bool>("LidIsPresent") != Ok(true) {
+        return false;
+    }
+    // Subscribe before the first read, so no change falls in between.
+    let changes = upower.receive_property_changed::<bool>("LidIsClosed");
+    let Ok(closed) = upower.get_property::<bool>("LidIsClosed") else {
+        return false;
+    };
+    store(closed);
+    std::thread::Builder::new()
+        .name("booklid-upower".into())
+        .spawn(move || {
+            // Held so the property cache the changes come through lives on.
+            let _upower = &upower;
+            for change in changes {
+                if let Ok(closed) = change.get() {
+                    store(closed);
+                }
+            }
+            // The bus connection went away, or UPower restarted.
+            UPOWER_LID.stopped();
+        })
+        .is_ok()
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(
+        target_os = "linux",
+        any(feature = "linux_libinput", feature = "linux_upower")
+    )
+))]
+pub use notified::NotifiedLidSwitch;
+
+/// Lid switches the OS notifies about, kept current by one watcher per
+/// process however many handles there are on them.
+#[cfg(any(
+    target_os = "windows",
+    all(
+        target_os = "linux",
+        any(feature = "linux_libinput", feature = "linux_upower")
+    )
+))]
+mod notified {
+    use super::{LidSwitch, LidSwitchState};
+    use crate::Result;
+    use std::sync::{
+        Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    };
+    use std::time::{Duration, Instant};
+    use tokio::sync::watch;
+
+    /// Shortest time between attempts to start a watcher.
+    const RESTART_EVERY: Duration = Duration::from_secs(5);
+
+    /// A watcher's last report, and whether it runs. The watcher is started
+    /// by the first lookup, and again by a read once it has stopped.
+    pub(super) struct Watcher {
+        state: watch::Sender<Option<LidSwitchState>>,
+        running: AtomicBool,
+        /// When the watcher was last started.
+        tried: Mutex<Option<Instant>>,
+        /// Starts following the lid; false if that can't be done here.
+        start: fn() -> bool,
+    }
+
+    impl Watcher {
+        pub(super) fn new(start: fn() -> bool) -> Self {
+            Self {
+                state: watch::channel(None).0,
+                running: AtomicBool::new(false),
+                tried: Mutex::new(None),
+                start,
+            }
+        }
+
+        /// A new lid state; `None` when the lid is gone.
+        pub(super) fn report(&self, state: Option<LidSwitchState>) {
+            self.state.send_replace(state);
+        }
+
+        /// `report(Some(state))` unless a state is already known.
+        #[cfg(all(target_os = "linux", feature = "linux_libinput"))]
+        pub(super) fn report_if_unknown(&self, state: LidSwitchState) {
+            self.state.send_if_modified(|s| {
+                let unknown = s.is_none();
+                if unknown {
+                    *s = Some(state);
+                }
+                unknown
+            });
+        }
+
+        /// The watcher has ended: the state is unknown until it is started
+        /// again.
+        #[cfg(target_os = "linux")]
+        pub(super) fn stopped(&self) {
+            self.running.store(false, Ordering::Relaxed);
+            self.state.send_replace(None);
+        }
+
+        /// Whether the caller should start the watcher: it is not running
+        /// and was not tried within `RESTART_EVERY`. Taken as running from
+        /// here on.
+        fn claim(&self) -> bool {
+            let mut tried = self.tried.lock().unwrap_or_else(PoisonError::into_inner);
+            if self.running.load(Ordering::Relaxed)
+                || tried.is_some_and(|at| at.elapsed() < RESTART_EVERY)
+            {
+                return false;
+            }
+            *tried = Some(Instant::now());
+            self.running.store(true, Ordering::Relaxed);
+            true
+        }
+
+        fn start(&self) {
+            if !(self.start)() {
+                self.running.store(false, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// A lid switch followed through OS notifications: Windows'
+    /// `GUID_LIDSWITCH_STATE_CHANGE` (`windows`), libinput's switch events
+    /// (`libinput`) or UPower's `LidIsClosed` (`upower`). `read()` returns
+    /// the state last reported and `changes()` follows it. A watcher that
+    /// stops, as UPower's does when UPower restarts, is started again on a
+    /// later read, at most every 5 s.
+    #[derive(Clone, Copy)]
+    pub struct NotifiedLidSwitch {
+        watcher: &'static Watcher,
+    }
+
+    impl NotifiedLidSwitch {
+        /// Start `watcher` if need be; `Some` once it has reported a lid.
+        fn find(watcher: &'static Watcher) -> Option<Self> {
+            if watcher.claim() {
+                watcher.start();
+            }
+            (watcher.running.load(Ordering::Relaxed) && watcher.state.borrow().is_some())
+                .then_some(Self { watcher })
+        }
+
+        /// Windows' lid switch notifications: `Some` once Windows has
+        /// reported the lid state, which it does shortly after the first
+        /// call registers for it on machines with a lid. Does not wait for
+        /// that report; see `windows_within`.
+        #[cfg(target_os = "windows")]
+        pub fn windows() -> Option<Self> {
+            Self::find(&super::WIN_LID)
+        }
+
+        /// `windows`, giving Windows up to `timeout` to report the lid
+        /// state.
+        #[cfg(target_os = "windows")]
+        pub async fn windows_within(timeout: Duration) -> Option<Self> {
+            let watcher: &'static Watcher = &super::WIN_LID;
+            if watcher.claim() {
+                watcher.start();
+            }
+            if !watcher.running.load(Ordering::Relaxed) {
+                return None;
+            }
+            let mut rx = watcher.state.subscribe();
+            tokio::time::timeout(timeout, rx.wait_for(Option::is_some))
+                .await
+                .ok()?
+                .ok()?;
+            Some(Self { watcher })
+        }
+
+        /// The lid switch as libinput sees it on this session's seat
+        /// (`$XDG_SEAT`, else `seat0`): `Some` once libinput has added a
+        /// device with one. libinput only gets the `/dev/input` nodes this
+        /// process can read (group `input`, or a udev `uaccess` rule); the
+        /// session is never taken over from its compositor. Blocks while
+        /// the seat is first set up.
+        #[cfg(all(target_os = "linux", feature = "linux_libinput"))]
+        pub fn libinput() -> Option<Self> {
+            Self::find(&super::LIBINPUT_LID)
+        }
+
+        /// UPower's `LidIsClosed`, followed through its property-change
+        /// signals: `Some` if UPower is running and reports a lid. Needs no
+        /// device access at all, so it works on desktops and servers where
+        /// neither the ACPI button nor `/dev/input` is readable. Blocks on
+        /// the system bus when UPower is first asked.
+        #[cfg(all(target_os = "linux", feature = "linux_upower"))]
+        pub fn upower() -> Option<Self> {
+            Self::find(&super::UPOWER_LID)
+        }
+    }
+
+    impl LidSwitch for NotifiedLidSwitch {
+        fn read(&self) -> Result<Option<LidSwitchState>> {
+            let watcher = self.watcher;
+            if watcher.claim() {
+                // Off the caller's thread: starting may wait on the bus.
+                let spawned = std::thread::Builder::new()
+                    .name("booklid-lid-restart".into())
+                    .spawn(move || watcher.start());
+                if spawned.is_err() {
+                    watcher.running.store(false, Ordering::Relaxed);
+                }
+            }
+            Ok(*watcher.state.borrow())
+        }
+
+        fn changes(&self) -> Option<watch::Receiver<Option<LidSwitchState>>> {
+            Some(self.watcher.state.subscribe())
+        }
+    }
+}
+
+/// Fastest rate a `LidSwitchAngle` polls its switch at.
+const SWITCH_MAX_HZ: f32 = 10.0;
+
+/// A lid switch served as a device: `angle_deg` is 0.0 closed and 1.0 open
+/// (not degrees; `Source::is_lid_switch`), at full confidence. The client
+/// reports `is_closed`/`is_open` and lid events from it directly, and keeps
+/// its samples out of `AngleClient::subscribe`. The state
+/// is sent on every poll, and at once on each change from a switch with
+/// `LidSwitch::changes`.
+pub struct LidSwitchAngle {
+    source: Source,
+    confidence: f32,
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: tokio::sync::broadcast::Sender<AngleSample>,
+    reconnecting: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    task: TaskGuard,
+}
+
+impl LidSwitchAngle {
+    /// Poll `switch` at `hz` (at most 10 Hz), tagging samples with `source`.
+    /// Fails if the first read does; later failures back off per `backoff`
+    /// while the device reports reconnecting. Must be called from within a
+    /// Tokio runtime.
+    pub fn open(switch: impl LidSwitch, source: Source, hz: f32, backoff: Backoff) -> Result<Self> {
+        Self::open_with_confidence(switch, source, 1.0, hz, backoff)
+    }
+
+    /// `open`, with samples tagged `confidence` (clamped to 0..1) for
+    /// switches less trustworthy than the platform's own notifications.
+    pub fn open_with_confidence(
+        switch: impl LidSwitch,
+        source: Source,
+        confidence: f32,
+        hz: f32,
+        backoff: Backoff,
+    ) -> Result<Self> {
+        let confidence = if confidence.is_nan() {
+            0.0
+        } else {
+            confidence.clamp(0.0, 1.0)
+        };
+        switch.read()?;
+        let switch = Arc::new(switch);
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = tokio::sync::broadcast::channel::<AngleSample>(64);
+        let reconnecting = Arc::new(AtomicBool::new(false));
+        let paused = Arc::new(AtomicBool::new(false));
+
+        let latest_c = Arc::clone(&latest);
+        let tx_c = tx.clone();
+        let reconnecting_c = Arc::clone(&reconnecting);
+        let paused_c = Arc::clone(&paused);
+        let hz = if hz.is_finite() && hz > 0.0 {
+            hz.min(SWITCH_MAX_HZ)
+        } else {
+            SWITCH_MAX_HZ
+        };
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let switch = Arc::clone(&switch);
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let reconnecting_c = reconnecting_c.clone();
+            let paused_c = paused_c.clone();
+            async move {
+                let mut tick = time::interval(Duration::from_secs_f32(1.0 / hz));
+                let mut changes = switch.changes();
+                let mut attempt = 0u32;
+                loop {
+                    match changes.as_mut() {
+                        Some(rx) => tokio::select! {
+                            _ = tick.tick() => {}
+                            changed = rx.changed() => {
+                                if changed.is_err() {
+                                    changes = None;
+                                }
+                            }
+                        },
+                        None => {
+                            tick.tick().await;
+                        }
+                    }
+                    if paused_c.load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    let state = match switch.read() {
+                        Ok(state) => state,
+                        Err(_) => {
+                            reconnecting_c.store(true, Ordering::Relaxed);
+                            let Some(d) = backoff.delay(attempt) else {
+                                return;
+                            };
+                            attempt += 1;
+                            time::sleep(d).await;
+                            continue;
+                        }
+                    };
+                    reconnecting_c.store(false, Ordering::Relaxed);
+                    attempt = 0;
+                    let Some(state) = state else { continue };
+                    let open = if state == LidSwitchState::Open {
+                        1.0
+                    } else {
+                        0.0
+                    };
+                    let sample = AngleSample {
+                        angle_deg: open,
+                        raw: open,
+                        timestamp: std::time::Instant::now(),
+                        source,
+                        confidence,
+                        velocity_deg_s: 0.0,
+                    };
+                    *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                    let _ = tx_c.send(sample);
+                }
+            }
+        });
+
+        Ok(Self {
+            source,
+            confidence,
+            latest,
+            tx,
+            reconnecting,
+            paused,
+            task,
+        })
+    }
+}
+
+impl AngleDevice for LidSwitchAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        tokio_stream::wrappers::BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    /// A switch has nothing to smooth.
+    fn set_smoothing(&self, _alpha: f32) {}
+
+    fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    fn confidence(&self) -> f32 {
+        if self.latest().is_some() {
+            self.confidence
+        } else {
+            0.0
+        }
+    }
+
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(self.source, "lid switch")
+    }
+
+    fn is_reconnecting(&self) -> bool {
+        self.reconnecting.load(Ordering::Relaxed)
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
+    }
+}
+
+/// Timing and weighting for `AngleClient::auto_calibrate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct AutoCalibrationConfig {
+    /// How often the switch is read.
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub poll: Duration,
+    /// The switch must report closed this long before anchoring, so the lid
+    /// has come to rest rather than just passed the switch's trip point.
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub settle: Duration,
+    /// Readings moving faster than this (°/s) are not anchored on.
+    pub max_velocity_deg_s: f32,
+    /// Weight of each later anchor against the current offset (the first
+    /// one is taken as is).
+    pub refine: f32,
+}
+
+impl Default for AutoCalibrationConfig {
+    /// Poll every 250 ms, settle 1 s, below 2 °/s, refine by 0.3.
+    fn default() -> Self {
+        Self {
+            poll: Duration::from_millis(250),
+            settle: Duration::from_secs(1),
+            max_velocity_deg_s: 2.0,
+            refine: 0.3,
+        }
+    }
+}
+
+impl AutoCalibrationConfig {
+    pub fn poll(mut self, every: Duration) -> Self {
+        self.poll = every;
+        self
+    }
+    pub fn settle(mut self, d: Duration) -> Self {
+        self.settle = d;
+        self
+    }
+    pub fn max_velocity(mut self, deg_s: f32) -> Self {
+        self.max_velocity_deg_s = deg_s;
+        self
+    }
+    pub fn refine(mut self, weight: f32) -> Self {
+        self.refine = weight;
+        self
+    }
+}
+
+/// Runs `AngleClient::auto_calibrate` until dropped.
+pub struct AutoCalibration {
+    anchors: Arc<AtomicU32>,
+    _task: TaskGuard,
+}
+
+impl AutoCalibration {
+    /// Closes anchored on so far.
+    pub fn anchors(&self) -> u32 {
+        self.anchors.load(Ordering::Relaxed)
+    }
+}
+
+impl AngleClient {
+    /// Anchor the angle at 0° whenever `switch` reports the lid shut: once
+    /// it has been closed for `cfg.settle` and the live reading is still,
+    /// the axis offset is moved so that reading is 0° (see
+    /// `calibrate_zero`), fully the first time and by `cfg.refine` on later
+    /// closes. One anchor per close. Stops once the client is dropped. Must
+    /// be called from within a Tokio runtime. `OpenConfig::auto_calibration`
+    /// runs this on the platform switch from open until `close()`.
+    pub fn auto_calibrate(
+        &self,
+        switch: impl LidSwitch,
+        cfg: AutoCalibrationConfig,
+    ) -> AutoCalibration {
+        let anchors = Arc::new(AtomicU32::new(0));
+        let task = TaskGuard::spawn(anchor_closes(
+            self.downgrade(),
+            switch,
+            cfg,
+            Arc::clone(&anchors),
+        ));
+        AutoCalibration {
+            anchors,
+            _task: task,
+        }
+    }
+
+    /// `auto_calibrate` on `platform()`, if this machine has a switch.
+    pub(crate) fn auto_calibrate_platform(&self, cfg: AutoCalibrationConfig) -> TaskGuard {
+        let client = self.downgrade();
+        TaskGuard::spawn(async move {
+            // Finding the switch may block on the system bus.
+            if let Ok(Some(switch)) = tokio::task::spawn_blocking(platform).await {
+                anchor_closes(client, switch, cfg, Arc::default()).await;
+            }
+        })
+    }
+}
+
+async fn anchor_closes(
+    client: WeakClient,
+    switch: impl LidSwitch,
+    cfg: AutoCalibrationConfig,
+    anchors: Arc<AtomicU32>,
+) {
+    let mut tick = time::interval(cfg.poll.max(Duration::from_millis(1)));
+    // When the current close started, and whether it was used.
+    let mut closed: Option<(Instant, bool)> = None;
+    loop {
+        tick.tick().await;
+        let Some(client) = client.upgrade() else {
+            return;
+        };
+        match switch.read() {
+            Ok(Some(LidSwitchState::Closed)) => {
+                let (since, done) = closed.get_or_insert((Instant::now(), false));
+                if *done || since.elapsed() < cfg.settle {
+                    continue;
+                }
+                let still = client
+                    .latest()
+                    .is_some_and(|s| s.velocity_deg_s.abs() <= cfg.max_velocity_deg_s);
+                if !still {
+                    continue;
+                }
+                let n = anchors.load(Ordering::Relaxed);
+                let weight = if n == 0 { 1.0 } else { cfg.refine };
+                if client.shift_axis(0.0, weight).is_ok() {
+                    *done = true;
+                    anchors.store(n + 1, Ordering::Relaxed);
+                }
+            }
+            Ok(Some(LidSwitchState::Open)) => closed = None,
+            Ok(None) | Err(_) => {}
+        }
+    }
+}
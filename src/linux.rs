@@ -0,0 +1,351 @@
+//! Linux setup helpers.
+
+use crate::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Overrides the sysfs root (default `/sys`) used to discover IIO, hwmon, and
+/// DMI nodes, so the Linux backend can run against a fixture tree.
+pub const SYSFS_ROOT_ENV: &str = "BOOKLID_SYSFS_ROOT";
+
+/// `$BOOKLID_SYSFS_ROOT`, else `/sys`.
+pub fn sysfs_root() -> PathBuf {
+    std::env::var_os(SYSFS_ROOT_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/sys"))
+}
+
+/// Overrides the directory (default `/dev`) holding the IIO character
+/// devices the buffered reads come from, alongside `SYSFS_ROOT_ENV`.
+pub const DEV_ROOT_ENV: &str = "BOOKLID_DEV_ROOT";
+
+/// `$BOOKLID_DEV_ROOT`, else `/dev`.
+pub fn dev_root() -> PathBuf {
+    std::env::var_os(DEV_ROOT_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/dev"))
+}
+
+/// Where an open finds sysfs and the IIO character devices, see
+/// `OpenConfig::sysfs_root` and `OpenConfig::dev_root`.
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    not(all(
+        target_os = "linux",
+        any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
+    )),
+    allow(dead_code)
+)]
+pub(crate) struct Roots {
+    pub(crate) sys: PathBuf,
+    pub(crate) dev: PathBuf,
+}
+
+#[cfg_attr(
+    not(all(
+        target_os = "linux",
+        any(feature = "linux_iio_proxy", feature = "linux_iio_sys")
+    )),
+    allow(dead_code)
+)]
+impl Roots {
+    /// `sys` and `dev`, else `sysfs_root()` and `dev_root()`.
+    pub(crate) fn new(sys: Option<PathBuf>, dev: Option<PathBuf>) -> Self {
+        Self {
+            sys: sys.unwrap_or_else(sysfs_root),
+            dev: dev.unwrap_or_else(dev_root),
+        }
+    }
+}
+
+/// Paths under `root` matching `pattern` (relative, glob syntax), sorted.
+pub(crate) fn glob_under(root: &Path, pattern: &str) -> Vec<PathBuf> {
+    let base = glob::Pattern::escape(&root.to_string_lossy());
+    glob::glob(&format!("{base}/{pattern}"))
+        .map(|g| g.flatten().collect())
+        .unwrap_or_default()
+}
+
+/// One numeric sysfs attribute (`"120\n"`, `"0.009576"`); `None` unless it
+/// parses to a finite number.
+pub fn parse_attr(s: &str) -> Option<f32> {
+    s.trim().parse::<f32>().ok().filter(|v| v.is_finite())
+}
+
+/// An hwmon illuminance attribute in lux. Values above 10 000 are taken to
+/// be millilux (some drivers report those).
+pub fn parse_hwmon_lux(s: &str) -> Option<f32> {
+    let v = parse_attr(s)?;
+    Some(if v > 10_000.0 { v / 1000.0 } else { v })
+}
+
+/// An ACPI lid button's `state` file (`"state:      open\n"`).
+pub fn parse_acpi_lid_state(s: &str) -> Option<crate::lid_switch::LidSwitchState> {
+    use crate::lid_switch::LidSwitchState;
+    match s.trim().strip_prefix("state:")?.trim() {
+        "open" => Some(LidSwitchState::Open),
+        "closed" => Some(LidSwitchState::Closed),
+        _ => None,
+    }
+}
+
+/// The raw `in_accel_*_thresh_{rising,falling}_value` for a threshold
+/// `delta` m/s² above (`rising`) or below `at` m/s², on an axis whose raw
+/// counts are `scale` m/s² each. Rounded away from `at`, so a reading that
+/// has not moved by `delta` can't trip it. `None` unless `scale` is finite
+/// and nonzero.
+pub fn accel_threshold_raw(at: f32, delta: f32, scale: f32, rising: bool) -> Option<i64> {
+    let scale = scale.abs();
+    if !(scale.is_finite() && scale > 0.0) {
+        return None;
+    }
+    let raw = if rising {
+        ((at + delta) / scale).ceil()
+    } else {
+        ((at - delta) / scale).floor()
+    };
+    raw.is_finite().then_some(raw as i64)
+}
+
+/// The rate to ask an IIO device for, given its `sampling_frequency_available`
+/// attribute: the slowest listed rate at or above `hz`, else the fastest; or
+/// `hz` clamped into a `[min step max]` range. `None` if it doesn't parse.
+pub fn pick_sampling_frequency(available: &str, hz: f32) -> Option<f32> {
+    let available = available.trim();
+    if let Some(range) = available.strip_prefix('[') {
+        let range: Vec<f32> = range
+            .strip_suffix(']')?
+            .split_whitespace()
+            .map(parse_attr)
+            .collect::<Option<_>>()?;
+        let [min, _step, max] = range[..] else {
+            return None;
+        };
+        return Some(hz.clamp(min, max.max(min)));
+    }
+    let rates: Vec<f32> = available
+        .split_whitespace()
+        .map(parse_attr)
+        .collect::<Option<_>>()?;
+    let fastest = rates.iter().copied().reduce(f32::max)?;
+    Some(
+        rates
+            .into_iter()
+            .filter(|r| *r >= hz)
+            .reduce(f32::min)
+            .unwrap_or(fastest),
+    )
+}
+
+/// Lid pitch in degrees from an accelerometer reading (any consistent
+/// unit), measured about the y axis over the full turn (-180..=180, past 90°
+/// once the lid leans over face-down); `None` for a zero or non-finite
+/// vector.
+pub fn pitch_from_accel(x: f32, y: f32, z: f32) -> Option<f32> {
+    let (x, y, z) = (x as f64, y as f64, z as f64);
+    let g = (x * x + y * y + z * z).sqrt();
+    if !g.is_finite() || g == 0.0 {
+        return None;
+    }
+    Some((-x).atan2(z).to_degrees() as f32)
+}
+
+/// Lid pitch in degrees for one of iio-sensor-proxy's `AccelerometerTilt`
+/// classes; `None` for `undefined` or an unknown class.
+pub fn parse_tilt_class(s: &str) -> Option<f32> {
+    Some(match s {
+        "face-up" => 0.0,
+        "tilted-up" => 45.0,
+        "vertical" => 90.0,
+        "tilted-down" => 135.0,
+        "face-down" => 180.0,
+        _ => return None,
+    })
+}
+
+/// Confidence cap for `Source::LinuxTilt` samples from iio-sensor-proxy: a
+/// tilt class is a 45° bucket (see `parse_tilt_class`), so a steady one says
+/// little about the exact angle.
+pub const PROXY_TILT_CONFIDENCE: f32 = 0.3;
+
+/// An IIO `mount_matrix` attribute (`"0, 1, 0; -1, 0, 0; 0, 0, 1\n"`), row
+/// by row; `None` unless it is three rows of three finite numbers.
+pub fn parse_mount_matrix(s: &str) -> Option<[[f32; 3]; 3]> {
+    let mut m = [[0.0; 3]; 3];
+    let mut rows = s.trim().split(';');
+    for row in &mut m {
+        let mut cols = rows.next()?.split(',');
+        for v in row.iter_mut() {
+            *v = parse_attr(cols.next()?)?;
+        }
+        if cols.next().is_some() {
+            return None;
+        }
+    }
+    rows.next().is_none().then_some(m)
+}
+
+/// How one channel is stored in an IIO buffer scan, from its
+/// `scan_elements/*_type` attribute (`"le:s12/16>>4"`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScanType {
+    pub big_endian: bool,
+    pub signed: bool,
+    /// Significant bits.
+    pub bits: u32,
+    /// Bits the value is stored in: 8, 16, 32 or 64.
+    pub storage_bits: u32,
+    /// Right shift from the stored word to the value.
+    pub shift: u32,
+    /// Elements of this type in a row (`X2` in the attribute), usually 1.
+    pub repeat: u32,
+}
+
+impl ScanType {
+    /// Bytes the channel takes in a scan.
+    pub fn bytes(&self) -> usize {
+        (self.storage_bits / 8 * self.repeat) as usize
+    }
+
+    /// The (first) value stored at the start of `bytes`; `None` if there
+    /// are too few.
+    pub fn decode(&self, bytes: &[u8]) -> Option<i64> {
+        let word = bytes.get(..(self.storage_bits / 8) as usize)?;
+        let fold = |w: u64, b: &u8| (w << 8) | u64::from(*b);
+        let word = if self.big_endian {
+            word.iter().fold(0, fold)
+        } else {
+            word.iter().rev().fold(0, fold)
+        };
+        let mask = u64::MAX >> (64 - self.bits);
+        let v = (word >> self.shift) & mask;
+        let negative = self.signed && v >> (self.bits - 1) & 1 == 1;
+        Some(if negative {
+            (v | !mask) as i64
+        } else {
+            v as i64
+        })
+    }
+}
+
+/// A `scan_elements/*_type` attribute; `None` unless well formed.
+pub fn parse_scan_type(s: &str) -> Option<ScanType> {
+    let (endian, rest) = s.trim().split_once(':')?;
+    let big_endian = match endian {
+        "be" => true,
+        "le" => false,
+        _ => return None,
+    };
+    let signed = match rest.get(..1)? {
+        "s" => true,
+        "u" => false,
+        _ => return None,
+    };
+    let (bits, rest) = rest[1..].split_once('/')?;
+    let (storage, shift) = rest.split_once(">>")?;
+    let (storage, repeat) = match storage.split_once('X') {
+        Some((storage, repeat)) => (storage, repeat.parse().ok()?),
+        None => (storage, 1),
+    };
+    let t = ScanType {
+        big_endian,
+        signed,
+        bits: bits.parse().ok()?,
+        storage_bits: storage.parse().ok()?,
+        shift: shift.parse().ok()?,
+        repeat,
+    };
+    let valid = matches!(t.storage_bits, 8 | 16 | 32 | 64)
+        && t.bits > 0
+        && t.bits + t.shift <= t.storage_bits
+        && t.repeat > 0;
+    valid.then_some(t)
+}
+
+/// Byte offset of each channel (given in `index` order) in a buffer scan,
+/// and the scan's size: as the kernel lays them out, every channel is
+/// aligned to its own size and the scan to the largest.
+pub fn scan_layout(channels: &[ScanType]) -> (Vec<usize>, usize) {
+    let align = |at: usize, to: usize| at.div_ceil(to) * to;
+    let (mut at, mut largest) = (0, 1);
+    let offsets = channels
+        .iter()
+        .map(|t| {
+            let offset = align(at, t.bytes());
+            at = offset + t.bytes();
+            largest = largest.max(t.bytes());
+            offset
+        })
+        .collect();
+    (offsets, align(at, largest))
+}
+
+/// Where `install_udev_rules()` writes the rules.
+pub const UDEV_RULES_PATH: &str = "/etc/udev/rules.d/70-booklid.rules";
+
+/// Grants the logged-in user (`uaccess`) the IIO sensors and the hidraw
+/// devices the HID backend probes: HID sensor hubs (the Sensor usage page)
+/// and Apple's vendor id. Other hidraw devices, e.g. security keys, keep
+/// their permissions.
+pub const UDEV_RULES: &str = r#"# Installed by booklid-rust: let the logged-in user read lid/light sensors.
+KERNEL=="hidraw*", SUBSYSTEM=="hidraw", DRIVERS=="hid-sensor-hub", TAG+="uaccess"
+KERNEL=="hidraw*", SUBSYSTEM=="hidraw", KERNELS=="*:05AC:*", TAG+="uaccess"
+SUBSYSTEM=="iio", TAG+="uaccess"
+"#;
+
+/// Write `UDEV_RULES` to `path` (for packagers and custom layouts). Returns
+/// `false` if the file already had exactly this content. Does not reload udev.
+pub fn write_udev_rules(path: impl AsRef<Path>) -> Result<bool> {
+    let path = path.as_ref();
+    if std::fs::read_to_string(path).is_ok_and(|cur| cur == UDEV_RULES) {
+        return Ok(false);
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, UDEV_RULES)?;
+    Ok(true)
+}
+
+/// Install the udev rules to `UDEV_RULES_PATH` and reload udev so they apply
+/// without a reboot. Needs root; returns `false` if already installed.
+pub fn install_udev_rules() -> Result<bool> {
+    if !is_root() {
+        return Err(Error::Other(format!(
+            "installing {UDEV_RULES_PATH} needs root; re-run with sudo (e.g. `sudo booklid doctor --fix`)"
+        )));
+    }
+    if !write_udev_rules(UDEV_RULES_PATH)? {
+        return Ok(false);
+    }
+    udevadm(&["control", "--reload-rules"])?;
+    udevadm(&[
+        "trigger",
+        "--subsystem-match=iio",
+        "--subsystem-match=hidraw",
+    ])?;
+    Ok(true)
+}
+
+fn udevadm(args: &[&str]) -> Result<()> {
+    let status = Command::new("udevadm").args(args).status()?;
+    if !status.success() {
+        return Err(Error::Other(format!(
+            "udevadm {} failed: {status}",
+            args[0]
+        )));
+    }
+    Ok(())
+}
+
+/// Effective uid 0, read from `/proc/self/status`.
+fn is_root() -> bool {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|s| {
+            s.lines()
+                .find_map(|l| l.strip_prefix("Uid:"))
+                .and_then(|ids| ids.split_whitespace().nth(1).map(|e| e == "0"))
+        })
+        .unwrap_or(false)
+}
@@ -0,0 +1,568 @@
+//! IOKit plumbing shared by the macOS sensor backends: services of an
+//! `IOHIDEventSystemClient` (Apple Silicon sensors), the
+//! `AppleLMUController` light sensor of older Macs, SMC keys, and power
+//! management notifications. Each part is built only with the features
+//! whose backends use it.
+
+use std::ffi::{c_char, c_void};
+
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw", feature = "mac_power"))]
+type CFTypeRef = *const c_void;
+
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+#[link(name = "IOKit", kind = "framework")]
+unsafe extern "C" {
+    fn IOHIDEventSystemClientCreate(allocator: CFTypeRef) -> CFTypeRef;
+    fn IOHIDEventSystemClientSetMatching(client: CFTypeRef, matching: CFTypeRef);
+    fn IOHIDEventSystemClientCopyServices(client: CFTypeRef) -> CFTypeRef;
+    fn IOHIDServiceClientCopyEvent(
+        service: CFTypeRef,
+        event_type: i64,
+        options: i32,
+        timestamp: i64,
+    ) -> CFTypeRef;
+    fn IOHIDEventGetFloatValue(event: CFTypeRef, field: u32) -> f64;
+}
+
+#[cfg(any(feature = "mac_als", feature = "mac_smc", feature = "mac_power"))]
+#[link(name = "IOKit", kind = "framework")]
+unsafe extern "C" {
+    fn IOServiceMatching(name: *const c_char) -> *mut c_void;
+    fn IOServiceGetMatchingService(main_port: u32, matching: *mut c_void) -> u32;
+}
+
+#[cfg(any(feature = "mac_als", feature = "mac_smc"))]
+#[link(name = "IOKit", kind = "framework")]
+unsafe extern "C" {
+    fn IOServiceOpen(service: u32, owning_task: u32, kind: u32, connect: *mut u32) -> i32;
+    fn IOServiceClose(connect: u32) -> i32;
+    fn IOObjectRelease(object: u32) -> i32;
+}
+
+#[cfg(feature = "mac_als")]
+#[link(name = "IOKit", kind = "framework")]
+unsafe extern "C" {
+    fn IOConnectCallMethod(
+        connect: u32,
+        selector: u32,
+        input: *const u64,
+        input_count: u32,
+        input_struct: *const c_void,
+        input_struct_size: usize,
+        output: *mut u64,
+        output_count: *mut u32,
+        output_struct: *mut c_void,
+        output_struct_size: *mut usize,
+    ) -> i32;
+}
+
+#[cfg(feature = "mac_smc")]
+#[link(name = "IOKit", kind = "framework")]
+unsafe extern "C" {
+    fn IOConnectCallStructMethod(
+        connect: u32,
+        selector: u32,
+        input: *const c_void,
+        input_size: usize,
+        output: *mut c_void,
+        output_size: *mut usize,
+    ) -> i32;
+}
+
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+#[link(name = "CoreFoundation", kind = "framework")]
+unsafe extern "C" {
+    static kCFTypeDictionaryKeyCallBacks: c_void;
+    static kCFTypeDictionaryValueCallBacks: c_void;
+    fn CFDictionaryCreate(
+        allocator: CFTypeRef,
+        keys: *const CFTypeRef,
+        values: *const CFTypeRef,
+        count: isize,
+        key_callbacks: *const c_void,
+        value_callbacks: *const c_void,
+    ) -> CFTypeRef;
+    fn CFStringCreateWithCString(
+        allocator: CFTypeRef,
+        s: *const c_char,
+        encoding: u32,
+    ) -> CFTypeRef;
+    fn CFNumberCreate(allocator: CFTypeRef, number_type: isize, value: *const c_void) -> CFTypeRef;
+    fn CFArrayGetCount(array: CFTypeRef) -> isize;
+    fn CFArrayGetValueAtIndex(array: CFTypeRef, index: isize) -> CFTypeRef;
+    fn CFRetain(cf: CFTypeRef) -> CFTypeRef;
+    fn CFRelease(cf: CFTypeRef);
+}
+
+#[cfg(any(feature = "mac_als", feature = "mac_smc"))]
+unsafe extern "C" {
+    static mach_task_self_: u32;
+}
+
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+const UTF8: u32 = 0x0800_0100;
+/// `kCFNumberSInt32Type`.
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+const SINT32: isize = 3;
+
+/// The first service of an `IOHIDEventSystemClient` with a given primary
+/// usage.
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+pub(crate) struct EventService {
+    client: CFTypeRef,
+    service: CFTypeRef,
+}
+
+// SAFETY: the client and service are CF objects, which are thread-safe to
+// retain, release and query; each `EventService` is used by one task at a time.
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+unsafe impl Send for EventService {}
+
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+impl EventService {
+    /// The first service matching `usage_page`/`usage`, if any.
+    pub(crate) fn find(usage_page: i32, usage: i32) -> Option<Self> {
+        // SAFETY: every CF object created here is released once (the
+        // matching dictionary after `SetMatching` has copied it, the service
+        // list after the chosen service is retained); the client is kept in
+        // `Self` and released on drop.
+        unsafe {
+            let client = IOHIDEventSystemClientCreate(std::ptr::null());
+            if client.is_null() {
+                return None;
+            }
+            let matching = matching_dictionary(usage_page, usage);
+            if !matching.is_null() {
+                IOHIDEventSystemClientSetMatching(client, matching);
+                CFRelease(matching);
+            }
+            let services = IOHIDEventSystemClientCopyServices(client);
+            if services.is_null() {
+                CFRelease(client);
+                return None;
+            }
+            let service = if CFArrayGetCount(services) > 0 {
+                CFRetain(CFArrayGetValueAtIndex(services, 0))
+            } else {
+                std::ptr::null()
+            };
+            CFRelease(services);
+            if service.is_null() {
+                CFRelease(client);
+                return None;
+            }
+            Some(Self { client, service })
+        }
+    }
+
+    /// `field` of the service's current `event_type` event; `None` when it
+    /// has no event or the value is not finite.
+    pub(crate) fn read(&self, event_type: i64, field: u32) -> Option<f64> {
+        // SAFETY: `service` is retained by `self`; the copied event is
+        // released after its field is read.
+        unsafe {
+            let event = IOHIDServiceClientCopyEvent(self.service, event_type, 0, 0);
+            if event.is_null() {
+                return None;
+            }
+            let value = IOHIDEventGetFloatValue(event, field);
+            CFRelease(event);
+            value.is_finite().then_some(value)
+        }
+    }
+}
+
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+impl Drop for EventService {
+    fn drop(&mut self) {
+        // SAFETY: both were retained by `find` and are released only here.
+        unsafe {
+            CFRelease(self.service);
+            CFRelease(self.client);
+        }
+    }
+}
+
+/// `{PrimaryUsagePage, PrimaryUsage}`, or null if it can't be built.
+///
+/// # Safety
+/// The caller owns the returned dictionary and must release it.
+#[cfg(any(feature = "mac_als", feature = "mac_iokit_raw"))]
+unsafe fn matching_dictionary(usage_page: i32, usage: i32) -> CFTypeRef {
+    // SAFETY: the keys and numbers are released once the dictionary (which
+    // retains them) is built.
+    unsafe {
+        let keys = [
+            CFStringCreateWithCString(std::ptr::null(), c"PrimaryUsagePage".as_ptr(), UTF8),
+            CFStringCreateWithCString(std::ptr::null(), c"PrimaryUsage".as_ptr(), UTF8),
+        ];
+        let values = [
+            CFNumberCreate(
+                std::ptr::null(),
+                SINT32,
+                &usage_page as *const i32 as *const c_void,
+            ),
+            CFNumberCreate(
+                std::ptr::null(),
+                SINT32,
+                &usage as *const i32 as *const c_void,
+            ),
+        ];
+        let dict = if keys.iter().chain(&values).any(|p| p.is_null()) {
+            std::ptr::null()
+        } else {
+            CFDictionaryCreate(
+                std::ptr::null(),
+                keys.as_ptr(),
+                values.as_ptr(),
+                2,
+                &kCFTypeDictionaryKeyCallBacks,
+                &kCFTypeDictionaryValueCallBacks,
+            )
+        };
+        for p in keys.into_iter().chain(values) {
+            if !p.is_null() {
+                CFRelease(p);
+            }
+        }
+        dict
+    }
+}
+
+/// A connection to `AppleLMUController`, the ambient light sensor of Intel
+/// MacBooks.
+#[cfg(feature = "mac_als")]
+pub(crate) struct Lmu {
+    connect: u32,
+}
+
+#[cfg(feature = "mac_als")]
+impl Lmu {
+    pub(crate) fn open() -> Option<Self> {
+        connect_to(c"AppleLMUController").map(|connect| Self { connect })
+    }
+
+    /// The two sensors' readings, in the controller's own (uncalibrated)
+    /// units; `None` if the call fails.
+    pub(crate) fn read(&self) -> Option<[u64; 2]> {
+        let mut out = [0u64; 2];
+        let mut count = out.len() as u32;
+        // SAFETY: selector 0 writes at most `count` scalars into `out`; no
+        // struct input or output.
+        let kr = unsafe {
+            IOConnectCallMethod(
+                self.connect,
+                0,
+                std::ptr::null(),
+                0,
+                std::ptr::null(),
+                0,
+                out.as_mut_ptr(),
+                &mut count,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        (kr == 0 && count == 2).then_some(out)
+    }
+}
+
+#[cfg(feature = "mac_als")]
+impl Drop for Lmu {
+    fn drop(&mut self) {
+        // SAFETY: opened by `open` and closed only here.
+        unsafe {
+            IOServiceClose(self.connect);
+        }
+    }
+}
+
+/// `AppleSMC`'s `kSMCHandleYPCEvent` method and its read commands.
+#[cfg(feature = "mac_smc")]
+const SMC_HANDLE_YPC_EVENT: u32 = 2;
+#[cfg(feature = "mac_smc")]
+const SMC_READ_KEY: u8 = 5;
+#[cfg(feature = "mac_smc")]
+const SMC_GET_KEY_INFO: u8 = 9;
+
+/// `SMCKeyData_t`, the struct every SMC call takes and returns.
+#[cfg(feature = "mac_smc")]
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct SmcKeyData {
+    key: u32,
+    vers: [u8; 6],
+    p_limit_data: [u32; 4],
+    data_size: u32,
+    data_type: u32,
+    data_attributes: u8,
+    /// End of the nested `SMCKeyData_keyInfo_t`.
+    _key_info_pad: [u8; 3],
+    result: u8,
+    status: u8,
+    data8: u8,
+    data32: u32,
+    bytes: [u8; 32],
+}
+
+#[cfg(feature = "mac_smc")]
+const _: () = assert!(std::mem::size_of::<SmcKeyData>() == 80);
+
+/// A connection to the System Management Controller.
+#[cfg(feature = "mac_smc")]
+pub(crate) struct Smc {
+    connect: u32,
+}
+
+#[cfg(feature = "mac_smc")]
+impl Smc {
+    pub(crate) fn open() -> Option<Self> {
+        connect_to(c"AppleSMC").map(|connect| Self { connect })
+    }
+
+    /// `key`'s value as a number, from the SMC types sensors use (`flt `,
+    /// `sp78`, `ui8 `, `ui16`, `ui32`); `None` if the key is missing or of
+    /// another type.
+    pub(crate) fn read_f32(&self, key: [u8; 4]) -> Option<f32> {
+        let key = u32::from_be_bytes(key);
+        let info = self.call(SmcKeyData {
+            key,
+            data8: SMC_GET_KEY_INFO,
+            ..SmcKeyData::default()
+        })?;
+        let size = info.data_size as usize;
+        let value = self.call(SmcKeyData {
+            key,
+            data_size: info.data_size,
+            data8: SMC_READ_KEY,
+            ..SmcKeyData::default()
+        })?;
+        let bytes = value.bytes.get(..size)?;
+        let v = match &info.data_type.to_be_bytes() {
+            b"flt " => f32::from_le_bytes(bytes.try_into().ok()?),
+            b"sp78" => i16::from_be_bytes(bytes.try_into().ok()?) as f32 / 256.0,
+            b"ui8 " => *bytes.first()? as f32,
+            b"ui16" => u16::from_be_bytes(bytes.try_into().ok()?) as f32,
+            b"ui32" => u32::from_be_bytes(bytes.try_into().ok()?) as f32,
+            _ => return None,
+        };
+        v.is_finite().then_some(v)
+    }
+
+    fn call(&self, input: SmcKeyData) -> Option<SmcKeyData> {
+        let mut output = SmcKeyData::default();
+        let mut size = std::mem::size_of::<SmcKeyData>();
+        // SAFETY: both structs are `SMCKeyData_t`-sized and laid out as the
+        // driver expects; `size` is the output buffer's length.
+        let kr = unsafe {
+            IOConnectCallStructMethod(
+                self.connect,
+                SMC_HANDLE_YPC_EVENT,
+                &input as *const SmcKeyData as *const c_void,
+                std::mem::size_of::<SmcKeyData>(),
+                &mut output as *mut SmcKeyData as *mut c_void,
+                &mut size,
+            )
+        };
+        (kr == 0 && output.result == 0).then_some(output)
+    }
+}
+
+#[cfg(feature = "mac_smc")]
+impl Drop for Smc {
+    fn drop(&mut self) {
+        // SAFETY: opened by `open` and closed only here.
+        unsafe {
+            IOServiceClose(self.connect);
+        }
+    }
+}
+
+/// A user client connection to the first service of class `name`.
+#[cfg(any(feature = "mac_als", feature = "mac_smc"))]
+fn connect_to(name: &std::ffi::CStr) -> Option<u32> {
+    // SAFETY: the matching dictionary is consumed by
+    // IOServiceGetMatchingService; the service is released once the
+    // connection (which holds its own reference) is open.
+    unsafe {
+        let matching = IOServiceMatching(name.as_ptr());
+        if matching.is_null() {
+            return None;
+        }
+        let service = IOServiceGetMatchingService(0, matching);
+        if service == 0 {
+            return None;
+        }
+        let mut connect = 0u32;
+        let kr = IOServiceOpen(service, mach_task_self_, 0, &mut connect);
+        IOObjectRelease(service);
+        (kr == 0).then_some(connect)
+    }
+}
+
+#[cfg(feature = "mac_power")]
+pub(crate) use power::watch_power;
+
+/// Sleep/wake and clamshell notifications (`sleep`).
+#[cfg(feature = "mac_power")]
+mod power {
+    use super::*;
+
+    /// `kIOMessageCanSystemSleep`, `kIOMessageSystemWillSleep`,
+    /// `kIOMessageSystemHasPoweredOn`, and `kIOPMMessageClamshellStateChange`
+    /// (whose argument has `kClamshellStateBit` set while closed).
+    const MSG_CAN_SYSTEM_SLEEP: u32 = 0xE000_0270;
+    const MSG_SYSTEM_WILL_SLEEP: u32 = 0xE000_0280;
+    const MSG_SYSTEM_HAS_POWERED_ON: u32 = 0xE000_0300;
+    const MSG_CLAMSHELL_STATE_CHANGE: u32 = 0xE003_4100;
+    const CLAMSHELL_STATE_BIT: usize = 1;
+
+    type InterestCallback = extern "C" fn(*mut c_void, u32, u32, *mut c_void);
+
+    #[link(name = "IOKit", kind = "framework")]
+    unsafe extern "C" {
+        fn IORegisterForSystemPower(
+            refcon: *mut c_void,
+            port: *mut *mut c_void,
+            callback: InterestCallback,
+            notifier: *mut u32,
+        ) -> u32;
+        fn IOAllowPowerChange(kernel_port: u32, notification_id: isize) -> i32;
+        fn IONotificationPortCreate(main_port: u32) -> *mut c_void;
+        fn IONotificationPortGetRunLoopSource(port: *mut c_void) -> CFTypeRef;
+        fn IOServiceAddInterestNotification(
+            port: *mut c_void,
+            service: u32,
+            interest_type: *const c_char,
+            callback: InterestCallback,
+            refcon: *mut c_void,
+            notification: *mut u32,
+        ) -> i32;
+    }
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        static kCFRunLoopDefaultMode: CFTypeRef;
+        fn CFRunLoopGetCurrent() -> CFTypeRef;
+        fn CFRunLoopAddSource(run_loop: CFTypeRef, source: CFTypeRef, mode: CFTypeRef);
+        fn CFRunLoopRun();
+    }
+
+    /// What the callbacks see: where to send events, and the root power domain
+    /// connection that sleep messages must be acknowledged on.
+    struct PowerWatch {
+        tx: tokio::sync::broadcast::Sender<crate::sleep::PowerEvent>,
+        root: std::sync::atomic::AtomicU32,
+    }
+
+    extern "C" fn on_system_power(refcon: *mut c_void, _service: u32, msg: u32, arg: *mut c_void) {
+        use crate::sleep::PowerEvent;
+        use std::sync::atomic::Ordering;
+        // SAFETY: `refcon` is the leaked `PowerWatch` from `watch_power`.
+        let watch = unsafe { &*(refcon as *const PowerWatch) };
+        let root = watch.root.load(Ordering::Acquire);
+        match msg {
+            // SAFETY: acknowledging with the id the message carried.
+            MSG_CAN_SYSTEM_SLEEP => unsafe {
+                IOAllowPowerChange(root, arg as isize);
+            },
+            MSG_SYSTEM_WILL_SLEEP => {
+                let _ = watch.tx.send(PowerEvent::WillSleep);
+                // SAFETY: as above.
+                unsafe {
+                    IOAllowPowerChange(root, arg as isize);
+                }
+            }
+            MSG_SYSTEM_HAS_POWERED_ON => {
+                let _ = watch.tx.send(PowerEvent::DidWake);
+            }
+            _ => {}
+        }
+    }
+
+    extern "C" fn on_root_domain(refcon: *mut c_void, _service: u32, msg: u32, arg: *mut c_void) {
+        use crate::sleep::PowerEvent;
+        if msg != MSG_CLAMSHELL_STATE_CHANGE {
+            return;
+        }
+        // SAFETY: `refcon` is the leaked `PowerWatch` from `watch_power`.
+        let watch = unsafe { &*(refcon as *const PowerWatch) };
+        let ev = if arg as usize & CLAMSHELL_STATE_BIT != 0 {
+            PowerEvent::ClamshellClosed
+        } else {
+            PowerEvent::ClamshellOpened
+        };
+        let _ = watch.tx.send(ev);
+    }
+
+    /// Forward sleep/wake and clamshell messages to `tx` from a new run-loop
+    /// thread that lives as long as the process. False if registering for
+    /// system power messages failed.
+    pub(crate) fn watch_power(
+        tx: tokio::sync::broadcast::Sender<crate::sleep::PowerEvent>,
+    ) -> bool {
+        let (ready_tx, ready_rx) = std::sync::mpsc::sync_channel(1);
+        let spawned = std::thread::Builder::new()
+            .name("booklid-power".into())
+            .spawn(move || {
+                use std::sync::atomic::{AtomicU32, Ordering};
+                let watch: &'static PowerWatch = Box::leak(Box::new(PowerWatch {
+                    tx,
+                    root: AtomicU32::new(0),
+                }));
+                let refcon = watch as *const PowerWatch as *mut c_void;
+                // SAFETY: `refcon` outlives the run loop (it is leaked); the
+                // notification ports and their run-loop sources stay registered
+                // for the life of the thread, which never returns.
+                unsafe {
+                    let mut port = std::ptr::null_mut();
+                    let mut notifier = 0u32;
+                    let root =
+                        IORegisterForSystemPower(refcon, &mut port, on_system_power, &mut notifier);
+                    if root == 0 || port.is_null() {
+                        let _ = ready_tx.send(false);
+                        return;
+                    }
+                    watch.root.store(root, Ordering::Release);
+                    let run_loop = CFRunLoopGetCurrent();
+                    CFRunLoopAddSource(
+                        run_loop,
+                        IONotificationPortGetRunLoopSource(port),
+                        kCFRunLoopDefaultMode,
+                    );
+
+                    // Clamshell messages are best effort: sleep/wake still works
+                    // without them.
+                    let matching = IOServiceMatching(c"IOPMrootDomain".as_ptr());
+                    let domain = if matching.is_null() {
+                        0
+                    } else {
+                        IOServiceGetMatchingService(0, matching)
+                    };
+                    let interest_port = IONotificationPortCreate(0);
+                    if domain != 0 && !interest_port.is_null() {
+                        let mut note = 0u32;
+                        let kr = IOServiceAddInterestNotification(
+                            interest_port,
+                            domain,
+                            c"IOGeneralInterest".as_ptr(),
+                            on_root_domain,
+                            refcon,
+                            &mut note,
+                        );
+                        if kr == 0 {
+                            CFRunLoopAddSource(
+                                run_loop,
+                                IONotificationPortGetRunLoopSource(interest_port),
+                                kCFRunLoopDefaultMode,
+                            );
+                        }
+                    }
+
+                    let _ = ready_tx.send(true);
+                    CFRunLoopRun();
+                }
+            });
+        spawned.is_ok() && ready_rx.recv().unwrap_or(false)
+    }
+}
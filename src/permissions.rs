@@ -0,0 +1,393 @@
+//! Per-source permission pre-flight, so apps can explain what to fix before
+//! `open()` silently skips a backend.
+
+#[cfg(feature = "mac_hid_feature")]
+use crate::Error;
+use crate::Source;
+
+/// Result of `check_permissions()`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PermissionStatus {
+    /// Access looks fine (the sensor may still fail to open for other reasons).
+    Granted,
+    /// The sensor exists but the OS denies access; `hint` says how to fix it.
+    Denied { hint: String },
+    /// The source can't work here (wrong platform, no sensor, feature off).
+    Unavailable { reason: String },
+    /// Access can't be determined without opening the sensor.
+    Unknown,
+}
+
+impl PermissionStatus {
+    /// Remediation or explanation text, if any.
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            PermissionStatus::Denied { hint } => Some(hint),
+            PermissionStatus::Unavailable { reason } => Some(reason),
+            _ => None,
+        }
+    }
+
+    pub fn is_granted(&self) -> bool {
+        matches!(self, PermissionStatus::Granted)
+    }
+}
+
+fn unavailable(reason: &str) -> PermissionStatus {
+    PermissionStatus::Unavailable {
+        reason: reason.into(),
+    }
+}
+
+/// Check whether this process may read `source`, without opening it.
+///
+/// Detects missing udev/file access to `/dev/hidraw*` and IIO sysfs nodes,
+/// a macOS App Sandbox without the USB entitlement or Input Monitoring (TCC)
+/// denial for the HID hinge, and Windows sensor privacy denial.
+pub fn check_permissions(source: Source) -> PermissionStatus {
+    match source {
+        Source::Mock => PermissionStatus::Granted,
+        Source::HingeFeature | Source::HingeHid | Source::HingeIOKit => hid_access(),
+        Source::ALS => {
+            if cfg!(target_os = "macos") {
+                PermissionStatus::Granted
+            } else {
+                unavailable("macOS ALS fallback is macOS-only")
+            }
+        }
+        Source::HingeSMC => {
+            if cfg!(target_os = "macos") {
+                PermissionStatus::Granted
+            } else {
+                unavailable("the SMC is macOS-only")
+            }
+        }
+        Source::MacClamshell => {
+            if cfg!(target_os = "macos") {
+                PermissionStatus::Granted
+            } else {
+                unavailable("the clamshell state is macOS-only")
+            }
+        }
+        Source::WinPosture if cfg!(target_os = "windows") && !cfg!(feature = "win_posture") => {
+            unavailable("enable the `win_posture` feature")
+        }
+        Source::WinHinge | Source::WinPosture | Source::WinTilt | Source::WinALS => {
+            windows_access(source)
+        }
+        Source::WinLidSwitch => {
+            if cfg!(target_os = "windows") {
+                PermissionStatus::Granted
+            } else {
+                unavailable("the Windows lid switch is Windows-only")
+            }
+        }
+        Source::LinuxLidSwitch => {
+            if !cfg!(target_os = "linux") {
+                unavailable("libinput's lid switch is Linux-only")
+            } else if !cfg!(feature = "linux_libinput") {
+                unavailable("enable the `linux_libinput` feature")
+            } else {
+                input_access()
+            }
+        }
+        Source::LinuxUPowerLid => {
+            if !cfg!(target_os = "linux") {
+                unavailable("UPower's lid state is Linux-only")
+            } else if !cfg!(feature = "linux_upower") {
+                unavailable("enable the `linux_upower` feature")
+            } else {
+                PermissionStatus::Granted
+            }
+        }
+        Source::LinuxAcpiLid => {
+            if !cfg!(target_os = "linux") {
+                unavailable("the ACPI lid state file is Linux-only")
+            } else if !cfg!(feature = "linux_acpi_lid") {
+                unavailable("enable the `linux_acpi_lid` feature")
+            } else {
+                PermissionStatus::Granted
+            }
+        }
+        Source::LinuxHinge | Source::LinuxTilt => {
+            iio_access(&["in_accel_x_raw", "in_accel_x_input"])
+        }
+        Source::LinuxALS => iio_access(&[
+            "in_illuminance_raw",
+            "in_illuminance_input",
+            "in_illuminance0_raw",
+            "in_illuminance0_input",
+            "in_intensity_both_raw",
+            "in_intensity_input",
+        ]),
+        _ => PermissionStatus::Unknown,
+    }
+}
+
+/// `err` from opening a HID device, as `Error::PermissionDenied` (with the
+/// fix from `check_permissions`) when the OS refused access rather than the
+/// device being absent or busy.
+#[cfg(feature = "mac_hid_feature")]
+pub(crate) fn hid_open_error(err: hidapi::HidError) -> Error {
+    if !hid_refused(&err) {
+        return err.into();
+    }
+    let hint = match hid_access() {
+        PermissionStatus::Denied { hint } => hint,
+        _ => format!("the OS refused to open the HID device ({err})"),
+    };
+    Error::PermissionDenied {
+        sensor: Source::HingeFeature,
+        hint,
+    }
+}
+
+/// IOReturn (macOS) and Win32 (Windows) codes for a refused open, which
+/// hidapi puts in its message as `(0x%08X)`.
+#[cfg(feature = "mac_hid_feature")]
+const REFUSED_CODES: [u32; 3] = [
+    0xE000_02E2, // kIOReturnNotPermitted
+    0xE000_02C1, // kIOReturnNotPrivileged
+    5,           // ERROR_ACCESS_DENIED
+];
+
+/// errnos for a refused open on Linux: EPERM, EACCES.
+#[cfg(feature = "mac_hid_feature")]
+const REFUSED_ERRNOS: [i32; 2] = [1, 13];
+
+/// Whether hidapi failed to open a device because the OS refused access.
+#[cfg(feature = "mac_hid_feature")]
+fn hid_refused(err: &hidapi::HidError) -> bool {
+    match err {
+        hidapi::HidError::IoError { error } => error.kind() == std::io::ErrorKind::PermissionDenied,
+        hidapi::HidError::HidApiError { message } => {
+            hid_error_code(message).is_some_and(|c| REFUSED_CODES.contains(&c))
+                || open_errno(message).is_some_and(|e| REFUSED_ERRNOS.contains(&e))
+        }
+        _ => false,
+    }
+}
+
+/// The `(0x%08X)` code in a macOS or Windows hidapi message.
+#[cfg(feature = "mac_hid_feature")]
+fn hid_error_code(message: &str) -> Option<u32> {
+    let (_, rest) = message.split_once("(0x")?;
+    let (hex, _) = rest.split_once(')')?;
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// The errno of a failed Linux open: the native backend's message ends in
+/// the `io::Error` ("... (os error 13)"), the hidraw one in `strerror`'s
+/// text for it.
+#[cfg(feature = "mac_hid_feature")]
+fn open_errno(message: &str) -> Option<i32> {
+    if let Some((_, tail)) = message.rsplit_once("(os error ")
+        && let Some(n) = tail.strip_suffix(')')
+    {
+        return n.parse().ok();
+    }
+    REFUSED_ERRNOS.into_iter().find(|&e| {
+        let suffix = format!(" (os error {e})");
+        let io = std::io::Error::from_raw_os_error(e).to_string();
+        message.ends_with(io.strip_suffix(suffix.as_str()).unwrap_or(&io))
+    })
+}
+
+#[cfg(target_os = "macos")]
+fn hid_access() -> PermissionStatus {
+    if sandboxed_without_usb() {
+        return PermissionStatus::Denied {
+            hint: "the app runs in the App Sandbox without USB device access; add \
+                   the com.apple.security.device.usb entitlement and re-sign it"
+                .into(),
+        };
+    }
+    // IOHIDCheckAccess(kIOHIDRequestTypeListenEvent): 0 granted, 1 denied, 2 unknown.
+    #[link(name = "IOKit", kind = "framework")]
+    unsafe extern "C" {
+        fn IOHIDCheckAccess(request_type: u32) -> u32;
+    }
+    // SAFETY: plain query with no pointers; available since macOS 10.15.
+    match unsafe { IOHIDCheckAccess(1) } {
+        0 => PermissionStatus::Granted,
+        1 => PermissionStatus::Denied {
+            hint: "allow this app under System Settings → Privacy & Security → \
+                   Input Monitoring, then restart it"
+                .into(),
+        },
+        _ => PermissionStatus::Unknown,
+    }
+}
+
+/// Whether this process has `com.apple.security.app-sandbox` but not
+/// `com.apple.security.device.usb`, without which hidapi cannot open the
+/// hinge sensor.
+#[cfg(target_os = "macos")]
+fn sandboxed_without_usb() -> bool {
+    use std::ffi::{CStr, c_void};
+    type CFTypeRef = *const c_void;
+
+    #[link(name = "Security", kind = "framework")]
+    unsafe extern "C" {
+        fn SecTaskCreateFromSelf(allocator: CFTypeRef) -> CFTypeRef;
+        fn SecTaskCopyValueForEntitlement(
+            task: CFTypeRef,
+            entitlement: CFTypeRef,
+            error: *mut CFTypeRef,
+        ) -> CFTypeRef;
+    }
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFStringCreateWithCString(
+            allocator: CFTypeRef,
+            s: *const std::ffi::c_char,
+            encoding: u32,
+        ) -> CFTypeRef;
+        fn CFGetTypeID(cf: CFTypeRef) -> usize;
+        fn CFBooleanGetTypeID() -> usize;
+        fn CFBooleanGetValue(b: CFTypeRef) -> u8;
+        fn CFRelease(cf: CFTypeRef);
+    }
+    const UTF8: u32 = 0x0800_0100;
+
+    // SAFETY: every object created here is released once, and an
+    // entitlement value is only read as a CFBoolean after its type is
+    // checked.
+    unsafe {
+        let task = SecTaskCreateFromSelf(std::ptr::null());
+        if task.is_null() {
+            return false;
+        }
+        let entitled = |name: &CStr| {
+            let key = CFStringCreateWithCString(std::ptr::null(), name.as_ptr(), UTF8);
+            if key.is_null() {
+                return false;
+            }
+            let value = SecTaskCopyValueForEntitlement(task, key, std::ptr::null_mut());
+            CFRelease(key);
+            if value.is_null() {
+                return false;
+            }
+            let on = CFGetTypeID(value) == CFBooleanGetTypeID() && CFBooleanGetValue(value) != 0;
+            CFRelease(value);
+            on
+        };
+        let blocked = entitled(c"com.apple.security.app-sandbox")
+            && !entitled(c"com.apple.security.device.usb");
+        CFRelease(task);
+        blocked
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn hid_access() -> PermissionStatus {
+    let nodes: Vec<_> = glob::glob("/dev/hidraw*")
+        .map(|g| g.flatten().collect())
+        .unwrap_or_default();
+    if nodes.is_empty() {
+        return unavailable("no /dev/hidraw* devices");
+    }
+    if nodes.iter().any(|p| std::fs::File::open(p).is_ok()) {
+        return PermissionStatus::Granted;
+    }
+    PermissionStatus::Denied {
+        hint: "no readable /dev/hidraw*; add a udev rule such as \
+               KERNEL==\"hidraw*\", TAG+=\"uaccess\" and replug or reboot"
+            .into(),
+    }
+}
+
+#[cfg(not(unix))]
+fn hid_access() -> PermissionStatus {
+    PermissionStatus::Unknown
+}
+
+#[cfg(all(target_os = "windows", feature = "win_sensors"))]
+fn windows_access(source: Source) -> PermissionStatus {
+    use windows::Devices::Sensors::{HingeAngleSensor, Inclinometer, LightSensor};
+
+    // E_ACCESSDENIED: blocked by sensor privacy settings or policy.
+    const E_ACCESSDENIED: windows::core::HRESULT = windows::core::HRESULT(0x8007_0005_u32 as i32);
+
+    let found = match source {
+        Source::WinHinge => HingeAngleSensor::GetDefaultAsync()
+            .and_then(|op| op.get())
+            .map(|_| ()),
+        #[cfg(feature = "win_posture")]
+        Source::WinPosture => crate::backend_win::posture::find(),
+        Source::WinTilt => Inclinometer::GetDefault().map(|_| ()),
+        _ => LightSensor::GetDefault().map(|_| ()),
+    };
+    match found {
+        Ok(()) => PermissionStatus::Granted,
+        Err(e) if e.code() == E_ACCESSDENIED => PermissionStatus::Denied {
+            hint: "sensor access is blocked; check Settings → Privacy & security \
+                   (location/sensors) and the \"Turn off sensors\" group policy"
+                .into(),
+        },
+        Err(_) => unavailable("no such sensor on this device"),
+    }
+}
+
+#[cfg(not(all(target_os = "windows", feature = "win_sensors")))]
+fn windows_access(_source: Source) -> PermissionStatus {
+    if cfg!(target_os = "windows") {
+        unavailable("enable the `win_sensors` feature")
+    } else {
+        unavailable("Windows sensors are Windows-only")
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn input_access() -> PermissionStatus {
+    let nodes = crate::linux::glob_under(&crate::linux::dev_root(), "input/event*");
+    if nodes.is_empty() {
+        return unavailable("no /dev/input/event* devices");
+    }
+    if nodes.iter().any(|p| std::fs::File::open(p).is_ok()) {
+        return PermissionStatus::Granted;
+    }
+    PermissionStatus::Denied {
+        hint: "no readable /dev/input/event*; join the `input` group or add a \
+               udev rule such as SUBSYSTEM==\"input\", TAG+=\"uaccess\""
+            .into(),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn input_access() -> PermissionStatus {
+    unavailable("libinput's lid switch is Linux-only")
+}
+
+#[cfg(target_os = "linux")]
+fn iio_access(channels: &[&str]) -> PermissionStatus {
+    let mut found = false;
+    let root = crate::linux::sysfs_root();
+    for dev in crate::linux::glob_under(&root, "bus/iio/devices/iio:device*") {
+        for ch in channels {
+            let p = dev.join(ch);
+            if !p.exists() {
+                continue;
+            }
+            found = true;
+            if std::fs::File::open(&p).is_ok() {
+                return PermissionStatus::Granted;
+            }
+        }
+    }
+    if found {
+        PermissionStatus::Denied {
+            hint: "IIO channels exist but are not readable; add a udev rule such \
+                   as SUBSYSTEM==\"iio\", MODE=\"0644\" or run iio-sensor-proxy"
+                .into(),
+        }
+    } else {
+        unavailable("no matching IIO sensor in /sys/bus/iio")
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn iio_access(_channels: &[&str]) -> PermissionStatus {
+    unavailable("Linux IIO sensors are Linux-only")
+}
@@ -1,4 +1,6 @@
-use crate::{Error, Result, Source};
+use crate::calibrate::AngleCalibration;
+use crate::hid::DiscoveredHinge;
+use crate::{Histogram, Result, Source};
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::{fs, path::PathBuf};
@@ -6,6 +8,63 @@ use std::{fs, path::PathBuf};
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct PersistedState {
     pub last_source: Option<Source>,
+    #[serde(default)]
+    pub histogram: Option<Histogram>,
+    /// Open outcomes per source, used to rank sources at selection time.
+    #[serde(default)]
+    pub scores: Vec<SourceScore>,
+    /// Saved with `calibrate::save`.
+    #[serde(default)]
+    pub calibrations: Vec<AngleCalibration>,
+    /// HID device and report id found by `hid::discover`.
+    #[serde(default)]
+    pub hid_discovery: Option<DiscoveredHinge>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SourceScore {
+    pub source: Source,
+    pub successes: u32,
+    pub failures: u32,
+}
+
+/// Counts are halved once they exceed this, so old outcomes fade.
+const SCORE_WINDOW: u32 = 20;
+
+impl PersistedState {
+    /// Smoothed open success rate in 0..1; 0.5 with no history.
+    pub fn success_rate(&self, source: Source) -> f32 {
+        let (ok, failed) = self
+            .scores
+            .iter()
+            .find(|s| s.source == source)
+            .map_or((0, 0), |s| (s.successes, s.failures));
+        (ok as f32 + 1.0) / ((ok + failed) as f32 + 2.0)
+    }
+
+    pub fn record_open(&mut self, source: Source, ok: bool) {
+        let i = match self.scores.iter().position(|s| s.source == source) {
+            Some(i) => i,
+            None => {
+                self.scores.push(SourceScore {
+                    source,
+                    successes: 0,
+                    failures: 0,
+                });
+                self.scores.len() - 1
+            }
+        };
+        let s = &mut self.scores[i];
+        if ok {
+            s.successes += 1;
+        } else {
+            s.failures += 1;
+        }
+        if s.successes + s.failures > SCORE_WINDOW {
+            s.successes /= 2;
+            s.failures /= 2;
+        }
+    }
 }
 
 fn state_path() -> Option<PathBuf> {
@@ -32,7 +91,7 @@ pub fn store(st: &PersistedState) -> Result<()> {
     if let Some(parent) = p.parent() {
         fs::create_dir_all(parent)?;
     }
-    let s = serde_json::to_string_pretty(st).map_err(|e| Error::Other(e.to_string()))?;
+    let s = serde_json::to_string_pretty(st)?;
     fs::write(p, s)?;
     Ok(())
 }
@@ -0,0 +1,381 @@
+//! Backend plugins loaded from shared libraries at runtime (feature
+//! `plugins`), for vendor or closed-source sensors that can't be linked in.
+//!
+//! A plugin exports a C function named `booklid_plugin_v1` returning a
+//! pointer to a static `PluginV1`. The table is `#[repr(C)]` and versioned,
+//! so plugins need not be built with the same compiler as the host:
+//!
+//! ```ignore
+//! #[unsafe(no_mangle)]
+//! pub extern "C" fn booklid_plugin_v1() -> *const booklid_rust::plugin::PluginV1 {
+//!     &PLUGIN
+//! }
+//! ```
+//!
+//! Loaded plugins are registered with `register_backend` under
+//! `Source::Other(id)` and stay loaded for the life of the process.
+
+use crate::filters::{Filter, OutlierRejection, Smoother, Stages};
+use crate::types::Velocity;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, BackendContext, BackendFactory, Backoff, DeviceInfo,
+    Error, Result, Source, TaskGuard, register_backend,
+};
+use futures_util::{StreamExt, future::BoxFuture, stream::BoxStream};
+use std::{
+    borrow::Cow,
+    ffi::{CStr, c_char, c_void},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+use tokio::{
+    sync::broadcast,
+    time::{self, Duration},
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+/// `PluginV1::abi_version` this host understands.
+pub const ABI_VERSION: u32 = 1;
+
+/// Name of the entry point every plugin exports.
+pub const ENTRY_SYMBOL: &str = "booklid_plugin_v1";
+
+/// Overrides the plugin directory, see `plugin_dir`.
+pub const PLUGIN_DIR_ENV: &str = "BOOKLID_PLUGIN_DIR";
+
+/// One reading filled in by `PluginV1::read`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PluginReading {
+    /// Lid angle in the `convention` frame.
+    pub angle_deg: f32,
+    /// Confidence in 0..1.
+    pub confidence: f32,
+}
+
+/// The plugin's function table. The host calls `read` from one task at a
+/// time, but not always on the same thread; `open` and `read` run on Tokio's
+/// blocking pool, so they may block.
+#[repr(C)]
+pub struct PluginV1 {
+    /// Must equal `ABI_VERSION`.
+    pub abi_version: u32,
+    /// NUL-terminated source id, valid for the life of the library.
+    pub id: *const c_char,
+    /// Open the sensor for sampling at `hz`; null on failure.
+    pub open: extern "C" fn(hz: f32) -> *mut c_void,
+    /// 1 = `out` holds a new reading, 0 = no reading yet, negative = the
+    /// read failed (the host backs off per `OpenConfig::reconnect`).
+    pub read: extern "C" fn(handle: *mut c_void, out: *mut PluginReading) -> i32,
+    /// Release a handle returned by `open`.
+    pub close: extern "C" fn(handle: *mut c_void),
+}
+
+// The table is immutable and `id` points at static data.
+unsafe impl Sync for PluginV1 {}
+
+/// Register the plugin described by `table`.
+///
+/// # Safety
+/// `table.id` must point to a NUL-terminated string, and the functions must
+/// honor the `PluginV1` contract, for as long as the process runs.
+pub unsafe fn register_plugin(table: &'static PluginV1) -> Result<Source> {
+    if table.abi_version != ABI_VERSION {
+        return Err(Error::Backend(format!(
+            "plugin ABI version {} (host supports {ABI_VERSION})",
+            table.abi_version
+        )));
+    }
+    if table.id.is_null() {
+        return Err(Error::Backend("plugin has no id".into()));
+    }
+    // SAFETY: non-null, NUL-terminated, and live for the process per the
+    // caller's contract.
+    let id: Cow<'static, str> = unsafe { CStr::from_ptr(table.id) }.to_string_lossy();
+    // `Source` is `Copy`: a non-UTF-8 id is interned once.
+    let id = match id {
+        Cow::Borrowed(id) => id,
+        Cow::Owned(id) => crate::types::intern(id)
+            .ok_or_else(|| Error::Backend("too many distinct plugin ids".into()))?,
+    };
+    let source = Source::Other(id);
+    register_backend(PluginFactory { table, source });
+    Ok(source)
+}
+
+/// Load the shared library at `path` and register its plugin.
+///
+/// # Safety
+/// Loading runs the library's initializers, and its entry point and table
+/// are trusted to follow the `PluginV1` contract. Only load plugins you
+/// trust.
+pub unsafe fn load_plugin(path: impl AsRef<Path>) -> Result<Source> {
+    let path = path.as_ref();
+    let fail = |e: libloading::Error| Error::Backend(format!("plugin {}: {e}", path.display()));
+    // SAFETY: the caller vouches for the library.
+    let lib = unsafe { libloading::Library::new(path) }.map_err(fail)?;
+    // SAFETY: the entry point has this signature per the plugin contract.
+    let table = unsafe {
+        let entry = lib
+            .get::<extern "C" fn() -> *const PluginV1>(ENTRY_SYMBOL.as_bytes())
+            .map_err(fail)?;
+        entry()
+    };
+    if table.is_null() {
+        return Err(Error::Backend(format!(
+            "plugin {}: {ENTRY_SYMBOL} returned null",
+            path.display()
+        )));
+    }
+    // Factories and devices call into the library from now on; keep it
+    // loaded for good.
+    std::mem::forget(lib);
+    // SAFETY: the table is static in a library that is never unloaded.
+    unsafe { register_plugin(&*table) }
+}
+
+/// Load every shared library (`.so` / `.dylib` / `.dll`) directly inside
+/// `dir`, in file-name order, returning each path with its outcome. A
+/// missing directory yields no entries.
+///
+/// # Safety
+/// As for `load_plugin`, for every library in `dir`.
+pub unsafe fn load_plugins(dir: impl AsRef<Path>) -> Vec<(PathBuf, Result<Source>)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| {
+            p.is_file()
+                && p.extension()
+                    .is_some_and(|x| x == std::env::consts::DLL_EXTENSION)
+        })
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|p| {
+            // SAFETY: forwarded from the caller.
+            let r = unsafe { load_plugin(&p) };
+            (p, r)
+        })
+        .collect()
+}
+
+/// Where the daemon and CLI look for plugins: `$BOOKLID_PLUGIN_DIR`, else
+/// `plugins/` under the user's booklid data directory.
+pub fn plugin_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(PLUGIN_DIR_ENV) {
+        return Some(dir.into());
+    }
+    let proj = directories::ProjectDirs::from("com", "booklid", "booklid-rust")?;
+    Some(proj.data_dir().join("plugins"))
+}
+
+struct PluginFactory {
+    table: &'static PluginV1,
+    source: Source,
+}
+
+impl BackendFactory for PluginFactory {
+    fn source(&self) -> Source {
+        self.source
+    }
+
+    fn open(&self, ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        let (table, source) = (self.table, self.source);
+        Box::pin(async move {
+            // A vendor driver may block; keep it off the runtime's workers.
+            let handle = tokio::task::spawn_blocking(move || {
+                let ptr = (table.open)(ctx.hz);
+                (!ptr.is_null()).then_some(Handle { table, ptr })
+            })
+            .await
+            .ok()
+            .flatten()
+            .ok_or_else(|| Error::Backend(format!("{source:?}: plugin failed to open")))?;
+            Ok(
+                Box::new(PluginAngle::spawn(handle, source, ctx.hz, ctx.reconnect))
+                    as Box<dyn AngleDevice>,
+            )
+        })
+    }
+}
+
+/// An open plugin handle; closed on drop.
+struct Handle {
+    table: &'static PluginV1,
+    ptr: *mut c_void,
+}
+
+// The plugin contract allows calls from any thread, one at a time (the
+// sampler task is the only caller until drop).
+unsafe impl Send for Handle {}
+unsafe impl Sync for Handle {}
+
+impl Handle {
+    fn read(&self) -> std::result::Result<Option<PluginReading>, i32> {
+        let mut out = PluginReading::default();
+        match (self.table.read)(self.ptr, &mut out) {
+            1 => Ok(Some(out)),
+            0 => Ok(None),
+            code => Err(code),
+        }
+    }
+}
+
+/// `Handle::read` on the blocking pool, as `open` is; a read that panicked
+/// counts as failed.
+async fn read_blocking(
+    handle: Arc<Mutex<Handle>>,
+) -> std::result::Result<Option<PluginReading>, i32> {
+    tokio::task::spawn_blocking(move || {
+        handle.lock().unwrap_or_else(PoisonError::into_inner).read()
+    })
+    .await
+    .unwrap_or(Err(-1))
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        (self.table.close)(self.ptr);
+    }
+}
+
+struct PluginAngle {
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
+    conf: Arc<Mutex<f32>>,
+    source: Source,
+}
+
+impl PluginAngle {
+    fn spawn(handle: Handle, source: Source, hz: f32, backoff: Backoff) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let stages = Arc::new(Mutex::new(Stages::default()));
+        let conf = Arc::new(Mutex::new(0.0f32));
+        let handle = Arc::new(Mutex::new(handle));
+
+        let latest_c = latest.clone();
+        let tx_c = tx.clone();
+        let stages_c = stages.clone();
+        let conf_c = conf.clone();
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let stages_c = stages_c.clone();
+            let conf_c = conf_c.clone();
+            let handle = handle.clone();
+            async move {
+                let mut interval = time::interval(Duration::from_secs_f32(1.0 / hz));
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut attempt = 0u32;
+
+                loop {
+                    interval.tick().await;
+                    let reading = read_blocking(handle.clone()).await;
+                    let r = match reading {
+                        Ok(Some(r)) => {
+                            attempt = 0;
+                            r
+                        }
+                        Ok(None) => continue,
+                        Err(_code) => {
+                            #[cfg(feature = "diagnostics")]
+                            eprintln!("[booklid] {source:?}: plugin read failed ({_code})");
+                            let Some(d) = backoff.delay(attempt) else {
+                                return;
+                            };
+                            attempt += 1;
+                            time::sleep(d).await;
+                            continue;
+                        }
+                    };
+
+                    let now = Instant::now();
+                    let Some(s) = smoother.apply(
+                        *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                        r.angle_deg,
+                        now,
+                    ) else {
+                        continue;
+                    };
+                    let confidence = r.confidence.clamp(0.0, 1.0);
+                    *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = confidence;
+
+                    let sample = AngleSample {
+                        angle_deg: s,
+                        raw: r.angle_deg,
+                        timestamp: now,
+                        source,
+                        confidence,
+                        velocity_deg_s: velocity.update(r.angle_deg, now),
+                    };
+                    *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                    let _ = tx_c.send(sample);
+                }
+            }
+        });
+
+        Self {
+            latest,
+            tx,
+            task,
+            stages,
+            conf,
+            source,
+        }
+    }
+}
+
+impl AngleDevice for PluginAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+
+    fn set_smoothing(&self, alpha: f32) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = Filter::Ema { alpha };
+    }
+
+    fn set_filter(&self, filter: Filter) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = filter;
+    }
+
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .outliers = outliers;
+    }
+
+    fn confidence(&self) -> f32 {
+        *self.conf.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(self.source, "plugin")
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
+    }
+}
@@ -0,0 +1,105 @@
+//! Device posture (closed / laptop / flat / tent / tablet) from the lid
+//! angle, for convertible-laptop apps that care about how the device is being
+//! held rather than about degrees.
+
+use crate::AngleSample;
+
+/// How the device is being used, by lid angle in the `convention` frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Posture {
+    /// Lid shut.
+    Closed,
+    /// Ordinary clamshell use.
+    Laptop,
+    /// Opened out to about 180°.
+    Flat,
+    /// Folded back past flat: tent or stand.
+    Tent,
+    /// Folded all the way back.
+    Tablet,
+    /// No sample yet, a normalized (ALS) source, or confidence too low.
+    Unknown,
+}
+
+/// Angle bounds for `Posture`; each posture runs up to the next bound.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct PostureConfig {
+    /// At or below: `Closed`.
+    pub closed_below: f32,
+    /// From here: `Flat`.
+    pub flat_from: f32,
+    /// From here: `Tent`.
+    pub tent_from: f32,
+    /// From here: `Tablet`.
+    pub tablet_from: f32,
+    /// Degrees a stream must move past a bound before `postures` reports
+    /// the new posture.
+    pub hysteresis: f32,
+    /// Below this sample confidence the posture is `Unknown`.
+    pub min_confidence: f32,
+}
+
+impl Default for PostureConfig {
+    /// Closed ≤ 10°, flat from 160°, tent from 200°, tablet from 320°, 5°
+    /// hysteresis, confidence 0.5.
+    fn default() -> Self {
+        Self {
+            closed_below: 10.0,
+            flat_from: 160.0,
+            tent_from: 200.0,
+            tablet_from: 320.0,
+            hysteresis: 5.0,
+            min_confidence: 0.5,
+        }
+    }
+}
+
+impl PostureConfig {
+    /// Posture of a single sample, without hysteresis.
+    pub fn classify(&self, s: &AngleSample) -> Posture {
+        if !s.is_degrees() || !s.angle_deg.is_finite() || s.confidence < self.min_confidence {
+            return Posture::Unknown;
+        }
+        self.by_angle(s.angle_deg)
+    }
+
+    fn by_angle(&self, a: f32) -> Posture {
+        if a <= self.closed_below {
+            Posture::Closed
+        } else if a < self.flat_from {
+            Posture::Laptop
+        } else if a < self.tent_from {
+            Posture::Flat
+        } else if a < self.tablet_from {
+            Posture::Tent
+        } else {
+            Posture::Tablet
+        }
+    }
+
+    /// `classify`, holding on to `prev` until the angle is `hysteresis`
+    /// clear of `prev`'s bound. Other bounds crossed on the way don't
+    /// delay the change.
+    pub(crate) fn next(&self, prev: Option<Posture>, s: &AngleSample) -> Posture {
+        let p = self.classify(s);
+        let h = self.hysteresis.abs();
+        match prev {
+            Some(prev)
+                if prev != Posture::Unknown
+                    && p != Posture::Unknown
+                    && p != prev
+                    && (self.by_angle(s.angle_deg - h) == prev
+                        || self.by_angle(s.angle_deg + h) == prev) =>
+            {
+                prev
+            }
+            _ => p,
+        }
+    }
+}
@@ -0,0 +1,685 @@
+//! Per-machine sensor quirks: laptops whose hinge angle is reported mirrored
+//! or offset because of how the sensor is mounted. `open()` applies the
+//! matching `AxisTransform` unless `OpenConfig::axis` overrides it.
+//!
+//! HID hinges whose feature report is not the default (report 1, a
+//! little-endian u16 of degrees after the id) are described by `HidQuirk`.
+//! These are consulted before HID discovery: a matching device is opened
+//! first and read with the entry's `ReportFormat`, without probing report
+//! ids. Hardware missing from `HID_QUIRKS` can be added at runtime with
+//! `register_hid_quirk` instead of patching the table.
+//!
+//! Windows machines whose hinge is only a custom Sensor API sensor, not a
+//! `HingeAngleSensor`, are described by `WinCustomHinge` the same way
+//! (`WIN_CUSTOM_HINGES`, `register_win_custom_hinge`).
+//!
+//! Users can fix odd hardware without recompiling in a TOML file,
+//! `quirks.toml` in the config directory (or `$BOOKLID_QUIRKS`), read once
+//! per process, or the one an open names in `OpenConfig::quirks_file`; its
+//! entries take precedence over the built-in ones:
+//!
+//! ```toml
+//! [sources]             # used when OpenConfig leaves prefer/disable empty
+//! prefer = ["LinuxTilt"]
+//! disable = ["LinuxALS"]
+//!
+//! [[hid]]
+//! vendor_id = 0x05ac
+//! product_id = 0x8104
+//! model = "MacBookPro18"  # prefix of machine_model(); omit for any machine
+//! report_id = 2
+//! scale = 0.015625
+//!
+//! [[axis]]
+//! model = "LENOVO 82"
+//! source = "LinuxTilt"
+//! invert = true
+//! offset_deg = 180.0
+//!
+//! [[win_custom_hinge]]
+//! model = "Microsoft Corporation Surface Book"
+//! interface_id = "{00000000-0000-0000-0000-000000000000}"
+//! angle_key = "{00000000-0000-0000-0000-000000000000} 2"
+//! ```
+
+use crate::hid::HidDeviceInfo;
+use crate::{AxisTransform, Error, Result, Source};
+use directories::ProjectDirs;
+use once_cell::sync::Lazy;
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+/// One quirks entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quirk {
+    /// Prefix of `machine_model()`, e.g. `"LENOVO 82"`.
+    pub model: Cow<'static, str>,
+    pub source: Source,
+    pub axis: AxisTransform,
+}
+
+/// Built-in entries; add a machine here once its report has been confirmed.
+pub const QUIRKS: &[Quirk] = &[];
+
+/// First entry of `table` whose model prefixes `model` and whose source matches.
+pub fn lookup_in(table: &[Quirk], model: &str, source: Source) -> Option<AxisTransform> {
+    table
+        .iter()
+        .find(|q| q.source == source && model.starts_with(&*q.model))
+        .map(|q| q.axis)
+}
+
+/// The user file's, else the built-in quirk for this machine and `source`,
+/// if any.
+pub fn lookup(source: Source) -> Option<AxisTransform> {
+    lookup_for(user(), machine_model().as_deref(), source)
+}
+
+/// `lookup` with `user`'s entries on a machine of `model`.
+pub(crate) fn lookup_for(
+    user: &UserQuirks,
+    model: Option<&str>,
+    source: Source,
+) -> Option<AxisTransform> {
+    lookup_in(&user.axis, model.unwrap_or(""), source).or_else(|| lookup_in(QUIRKS, model?, source))
+}
+
+/// Longest feature report a `ReportFormat` may describe.
+pub const MAX_REPORT_LEN: usize = 64;
+
+/// How the angle is stored in a feature report.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    U8,
+    U16Le,
+    U16Be,
+    I16Le,
+    I16Be,
+    /// `size` bits (1..=32, little-endian as in HID reports) starting
+    /// `shift` bits into the first byte, see `hid_descriptor`.
+    Bits {
+        shift: u8,
+        size: u8,
+        signed: bool,
+    },
+}
+
+impl Layout {
+    /// Bytes the value takes.
+    pub fn size(self) -> usize {
+        match self {
+            Layout::U8 => 1,
+            Layout::Bits { shift, size, .. } => {
+                (usize::from(shift) + usize::from(size)).div_ceil(8)
+            }
+            _ => 2,
+        }
+    }
+
+    fn is_valid(self) -> bool {
+        match self {
+            Layout::Bits { shift, size, .. } => shift < 8 && (1..=32).contains(&size),
+            _ => true,
+        }
+    }
+
+    fn read(self, b: &[u8]) -> Option<f32> {
+        if !self.is_valid() {
+            return None;
+        }
+        let pair = || Some([*b.first()?, *b.get(1)?]);
+        Some(match self {
+            Layout::U8 => *b.first()? as f32,
+            Layout::U16Le => u16::from_le_bytes(pair()?) as f32,
+            Layout::U16Be => u16::from_be_bytes(pair()?) as f32,
+            Layout::I16Le => i16::from_le_bytes(pair()?) as f32,
+            Layout::I16Be => i16::from_be_bytes(pair()?) as f32,
+            Layout::Bits {
+                shift,
+                size,
+                signed,
+            } => {
+                let bytes = b.get(..self.size())?;
+                let all = bytes
+                    .iter()
+                    .rev()
+                    .fold(0u64, |acc, b| (acc << 8) | u64::from(*b));
+                let v = (all >> shift) & ((1u64 << size) - 1);
+                if signed && size > 0 && v >> (size - 1) & 1 == 1 {
+                    (v as i64 - (1i64 << size)) as f32
+                } else {
+                    v as f32
+                }
+            }
+        })
+    }
+}
+
+/// Where a HID hinge's feature report keeps the angle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReportFormat {
+    pub report_id: u8,
+    /// Byte offset of the value; byte 0 is the report id.
+    pub offset: usize,
+    pub layout: Layout,
+    /// Degrees per count.
+    pub scale: f32,
+    /// Plausible angles; readings outside are dropped as glitches.
+    pub min_deg: f32,
+    pub max_deg: f32,
+}
+
+impl Default for ReportFormat {
+    fn default() -> Self {
+        Self::with_report_id(1)
+    }
+}
+
+impl ReportFormat {
+    /// The default layout (a little-endian u16 of degrees after the id, any
+    /// value) in report `report_id`.
+    pub const fn with_report_id(report_id: u8) -> Self {
+        Self {
+            report_id,
+            offset: 1,
+            layout: Layout::U16Le,
+            scale: 1.0,
+            min_deg: f32::NEG_INFINITY,
+            max_deg: f32::INFINITY,
+        }
+    }
+
+    /// Bytes to request: the id through the end of the value.
+    pub fn report_len(&self) -> usize {
+        self.offset.saturating_add(self.layout.size())
+    }
+
+    /// Degrees from the bytes a feature-report read returned: `None` when
+    /// the report is too short or carries a different id. The range is not
+    /// checked, see `in_range`.
+    pub fn parse(&self, report: &[u8]) -> Option<f32> {
+        if *report.first()? != self.report_id {
+            return None;
+        }
+        let value = report.get(self.offset..self.report_len())?;
+        Some(self.layout.read(value)? * self.scale)
+    }
+
+    pub fn in_range(&self, angle_deg: f32) -> bool {
+        (self.min_deg..=self.max_deg).contains(&angle_deg)
+    }
+
+    /// The value lies after the id and within `MAX_REPORT_LEN`, the scale
+    /// is finite and non-zero, and the range is not empty.
+    pub fn is_valid(&self) -> bool {
+        self.offset >= 1
+            && self.layout.is_valid()
+            && self.report_len() <= MAX_REPORT_LEN
+            && self.scale.is_finite()
+            && self.scale != 0.0
+            && self.min_deg <= self.max_deg
+    }
+}
+
+/// One HID quirks entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HidQuirk {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// Prefix of `machine_model()`, for sensors whose ids are shared
+    /// across models; `""` matches any machine.
+    pub model: Cow<'static, str>,
+    pub format: ReportFormat,
+}
+
+/// Built-in HID entries; add a sensor here once its report has been
+/// confirmed.
+pub const HID_QUIRKS: &[HidQuirk] = &[];
+
+static REGISTERED_HID: Lazy<RwLock<Vec<HidQuirk>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Add `quirk` for every later HID open, ahead of `HID_QUIRKS`. Registering
+/// the same ids and model again replaces the entry. Fails when the format
+/// is not `ReportFormat::is_valid`.
+pub fn register_hid_quirk(quirk: HidQuirk) -> Result<()> {
+    if !quirk.format.is_valid() {
+        return Err(Error::Other(format!(
+            "quirks: invalid report format {:?}",
+            quirk.format
+        )));
+    }
+    let key = |q: &HidQuirk| (q.vendor_id, q.product_id, q.model.clone());
+    let mut reg = REGISTERED_HID.write().unwrap();
+    reg.retain(|q| key(q) != key(&quirk));
+    reg.push(quirk);
+    Ok(())
+}
+
+/// Format of the first entry of `table` for `info`'s ids whose model
+/// prefixes `model`, preferring entries that name a model.
+pub fn hid_lookup_in(
+    table: &[HidQuirk],
+    info: &HidDeviceInfo,
+    model: Option<&str>,
+) -> Option<ReportFormat> {
+    let mut found = table.iter().filter(|q| {
+        q.vendor_id == info.vendor_id
+            && q.product_id == info.product_id
+            && (q.model.is_empty() || model.is_some_and(|m| m.starts_with(&*q.model)))
+    });
+    let first = found.clone().find(|q| !q.model.is_empty());
+    first.or_else(|| found.next()).map(|q| q.format)
+}
+
+/// The user file's, registered, then built-in HID quirk for `info` on this
+/// machine, if any.
+pub fn hid_lookup(info: &HidDeviceInfo) -> Option<ReportFormat> {
+    hid_lookup_for(user(), info)
+}
+
+/// `hid_lookup` with `user`'s entries.
+pub(crate) fn hid_lookup_for(user: &UserQuirks, info: &HidDeviceInfo) -> Option<ReportFormat> {
+    // Looked up for every enumerated device, so only read once.
+    static MODEL: Lazy<Option<String>> = Lazy::new(machine_model);
+    let registered = REGISTERED_HID.read().unwrap();
+    hid_lookup_in(&user.hid, info, MODEL.as_deref())
+        .or_else(|| hid_lookup_in(&registered, info, MODEL.as_deref()))
+        .or_else(|| hid_lookup_in(HID_QUIRKS, info, MODEL.as_deref()))
+}
+
+/// A custom Sensor API sensor that carries the hinge angle, for Windows
+/// machines (several Surface Book / Laptop Studio generations) that expose
+/// no `HingeAngleSensor`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinCustomHinge {
+    /// Prefix of `machine_model()`; `""` matches any machine.
+    pub model: Cow<'static, str>,
+    /// The sensor's interface GUID, as `CustomSensor::GetDeviceSelector`
+    /// takes it (`0x12345678_9abc_def0_1234_56789abcdef0` for
+    /// `{12345678-9abc-def0-1234-56789abcdef0}`, see `parse_guid`).
+    pub interface_id: u128,
+    /// Reading property holding the angle in degrees: a PROPERTYKEY string,
+    /// `"{GUID} pid"`.
+    pub angle_key: Cow<'static, str>,
+}
+
+/// The Sensor API's hinge angle sensor type (`GUID_SensorType_HingeAngle`).
+const SENSOR_TYPE_HINGE_ANGLE: u128 = 0x82358065_f4c4_4da1_b272_13c23332a207;
+
+/// Its angle in degrees, `PKEY_SensorData_HingeAngle` (`VT_R4`).
+const HINGE_ANGLE_KEY: &str = "{82358065-F4C4-4DA1-B272-13C23332A207} 2";
+
+/// Built-in custom hinge entries: Surfaces whose hinge driver registers
+/// the Sensor API hinge type as a custom sensor, which Windows does not
+/// surface as a `HingeAngleSensor`. Add a machine here once its sensor has
+/// been confirmed.
+pub const WIN_CUSTOM_HINGES: &[WinCustomHinge] = &[
+    WinCustomHinge {
+        model: Cow::Borrowed("Microsoft Corporation Surface Book"),
+        interface_id: SENSOR_TYPE_HINGE_ANGLE,
+        angle_key: Cow::Borrowed(HINGE_ANGLE_KEY),
+    },
+    WinCustomHinge {
+        model: Cow::Borrowed("Microsoft Corporation Surface Laptop Studio"),
+        interface_id: SENSOR_TYPE_HINGE_ANGLE,
+        angle_key: Cow::Borrowed(HINGE_ANGLE_KEY),
+    },
+];
+
+static REGISTERED_WIN_CUSTOM: Lazy<RwLock<Vec<WinCustomHinge>>> =
+    Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Add `hinge` for every later Windows hinge open, ahead of
+/// `WIN_CUSTOM_HINGES`. Registering the same model and interface again
+/// replaces the entry. Fails when `angle_key` is empty.
+pub fn register_win_custom_hinge(hinge: WinCustomHinge) -> Result<()> {
+    if hinge.angle_key.trim().is_empty() {
+        return Err(Error::Other(
+            "quirks: custom hinge needs an angle_key".into(),
+        ));
+    }
+    let key = |h: &WinCustomHinge| (h.model.clone(), h.interface_id);
+    let mut reg = REGISTERED_WIN_CUSTOM.write().unwrap();
+    reg.retain(|h| key(h) != key(&hinge));
+    reg.push(hinge);
+    Ok(())
+}
+
+/// Entries of `table` whose model prefixes `model`, those naming a model
+/// first.
+pub fn win_custom_hinges_in(table: &[WinCustomHinge], model: Option<&str>) -> Vec<WinCustomHinge> {
+    let fits =
+        |h: &&WinCustomHinge| h.model.is_empty() || model.is_some_and(|m| m.starts_with(&*h.model));
+    let (named, any): (Vec<WinCustomHinge>, _) = table
+        .iter()
+        .filter(fits)
+        .cloned()
+        .partition(|h| !h.model.is_empty());
+    named.into_iter().chain(any).collect()
+}
+
+/// The user file's, registered, then built-in custom hinges for this
+/// machine, in the order to try them.
+pub fn win_custom_hinges() -> Vec<WinCustomHinge> {
+    win_custom_hinges_for(user())
+}
+
+/// `win_custom_hinges` with `user`'s entries.
+pub(crate) fn win_custom_hinges_for(user: &UserQuirks) -> Vec<WinCustomHinge> {
+    let model = machine_model();
+    let registered = REGISTERED_WIN_CUSTOM.read().unwrap();
+    [&user.win_custom_hinge[..], &registered, WIN_CUSTOM_HINGES]
+        .into_iter()
+        .flat_map(|t| win_custom_hinges_in(t, model.as_deref()))
+        .collect()
+}
+
+/// `{12345678-9abc-def0-1234-56789abcdef0}` (braces optional) as the
+/// `u128` `WinCustomHinge::interface_id` takes.
+pub fn parse_guid(s: &str) -> Option<u128> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .unwrap_or(s);
+    let groups: Vec<&str> = s.split('-').collect();
+    let lens: Vec<usize> = groups.iter().map(|g| g.len()).collect();
+    if lens != [8, 4, 4, 4, 12]
+        || !groups
+            .iter()
+            .all(|g| g.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        return None;
+    }
+    u128::from_str_radix(&groups.concat(), 16).ok()
+}
+
+/// A user quirks file, see the module docs.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UserQuirks {
+    pub hid: Vec<HidQuirk>,
+    pub axis: Vec<Quirk>,
+    /// Probe order for opens that do not set `OpenConfig::prefer`.
+    pub prefer: Vec<Source>,
+    /// Sources never opened unless `OpenConfig::prefer` names them.
+    pub disable: Vec<Source>,
+    pub win_custom_hinge: Vec<WinCustomHinge>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileQuirks {
+    #[serde(default)]
+    sources: FileSources,
+    #[serde(default)]
+    hid: Vec<FileHid>,
+    #[serde(default)]
+    axis: Vec<FileAxis>,
+    #[serde(default)]
+    win_custom_hinge: Vec<FileWinCustomHinge>,
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct FileSources {
+    prefer: Vec<Source>,
+    disable: Vec<Source>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileHid {
+    vendor_id: u16,
+    product_id: u16,
+    #[serde(default)]
+    model: String,
+    report_id: Option<u8>,
+    offset: Option<usize>,
+    layout: Option<Layout>,
+    scale: Option<f32>,
+    min_deg: Option<f32>,
+    max_deg: Option<f32>,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileAxis {
+    #[serde(default)]
+    model: String,
+    source: Source,
+    #[serde(default)]
+    invert: bool,
+    #[serde(default)]
+    offset_deg: f32,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileWinCustomHinge {
+    #[serde(default)]
+    model: String,
+    interface_id: String,
+    angle_key: String,
+}
+
+impl UserQuirks {
+    /// Parse a quirks file's contents. Fails on unknown keys and invalid
+    /// HID formats, so typos don't go unnoticed.
+    pub fn parse(s: &str) -> Result<Self> {
+        let file: FileQuirks =
+            toml::from_str(s).map_err(|e| Error::Other(format!("quirks file: {e}")))?;
+        let hid = file
+            .hid
+            .into_iter()
+            .map(|h| {
+                let d = ReportFormat::with_report_id(h.report_id.unwrap_or(1));
+                let format = ReportFormat {
+                    offset: h.offset.unwrap_or(d.offset),
+                    layout: h.layout.unwrap_or(d.layout),
+                    scale: h.scale.unwrap_or(d.scale),
+                    min_deg: h.min_deg.unwrap_or(d.min_deg),
+                    max_deg: h.max_deg.unwrap_or(d.max_deg),
+                    ..d
+                };
+                if !format.is_valid() {
+                    return Err(Error::Other(format!(
+                        "quirks file: invalid report format for {:04x}:{:04x}",
+                        h.vendor_id, h.product_id
+                    )));
+                }
+                Ok(HidQuirk {
+                    vendor_id: h.vendor_id,
+                    product_id: h.product_id,
+                    model: h.model.into(),
+                    format,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let axis = file
+            .axis
+            .into_iter()
+            .map(|a| Quirk {
+                model: a.model.into(),
+                source: a.source,
+                axis: AxisTransform {
+                    invert: a.invert,
+                    offset_deg: a.offset_deg,
+                },
+            })
+            .collect();
+        let win_custom_hinge = file
+            .win_custom_hinge
+            .into_iter()
+            .map(|h| {
+                let interface_id = parse_guid(&h.interface_id).ok_or_else(|| {
+                    Error::Other(format!(
+                        "quirks file: bad interface_id {:?}",
+                        h.interface_id
+                    ))
+                })?;
+                if h.angle_key.trim().is_empty() {
+                    return Err(Error::Other(
+                        "quirks file: custom hinge needs an angle_key".into(),
+                    ));
+                }
+                Ok(WinCustomHinge {
+                    model: h.model.into(),
+                    interface_id,
+                    angle_key: h.angle_key.into(),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            hid,
+            axis,
+            prefer: file.sources.prefer,
+            disable: file.sources.disable,
+            win_custom_hinge,
+        })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    /// Apply `[sources]` to an open's lists: `prefer` when `prefer` is
+    /// empty, and `disable` for sources it does not prefer.
+    pub(crate) fn apply_sources(&self, prefer: &mut Vec<Source>, disable: &mut Vec<Source>) {
+        if prefer.is_empty() {
+            prefer.clone_from(&self.prefer);
+        }
+        for s in &self.disable {
+            if !prefer.contains(s) && !disable.contains(s) {
+                disable.push(*s);
+            }
+        }
+    }
+}
+
+/// Where the user quirks file is read from: `$BOOKLID_QUIRKS`, else
+/// `quirks.toml` in the config directory.
+pub fn user_file_path() -> Option<PathBuf> {
+    if let Some(p) = std::env::var_os("BOOKLID_QUIRKS") {
+        return Some(p.into());
+    }
+    let proj = ProjectDirs::from("com", "booklid", "booklid-rust")?;
+    Some(proj.config_dir().join("quirks.toml"))
+}
+
+/// The user quirks file at `user_file_path()`, read on first use, and why
+/// it was ignored if it failed to load.
+static USER: Lazy<(Arc<UserQuirks>, Option<String>)> = Lazy::new(|| {
+    let Some(path) = user_file_path().filter(|p| p.exists()) else {
+        return Default::default();
+    };
+    match UserQuirks::load(&path) {
+        Ok(q) => (Arc::new(q), None),
+        Err(e) => (
+            Arc::default(),
+            Some(format!("ignoring quirks file {}: {e}", path.display())),
+        ),
+    }
+});
+
+/// The user quirks file, read on first use. Empty when it is missing or
+/// fails to parse, see `user_file_error`.
+pub fn user() -> &'static UserQuirks {
+    &USER.0
+}
+
+/// Why the user quirks file was ignored, if it exists but failed to load.
+/// Opens with `diagnostics` print it.
+pub fn user_file_error() -> Option<&'static str> {
+    USER.1.as_deref()
+}
+
+/// The quirks file an open uses: `path` (failing if it doesn't load),
+/// else `user()`.
+pub(crate) fn user_for(path: Option<&Path>) -> Result<Arc<UserQuirks>> {
+    match path {
+        Some(p) => UserQuirks::load(p).map(Arc::new),
+        None => Ok(Arc::clone(&USER.0)),
+    }
+}
+
+/// Machine identity used for matching: `"<vendor> <product>"` from DMI on
+/// Linux and from the BIOS registry key on Windows (feature `win_sensors`),
+/// `hw.model` on macOS. `None` where it can't be determined.
+pub fn machine_model() -> Option<String> {
+    machine_model_under(None)
+}
+
+/// `machine_model`, reading DMI under `sysfs_root` instead of
+/// `linux::sysfs_root()` on Linux.
+pub(crate) fn machine_model_under(sysfs_root: Option<&Path>) -> Option<String> {
+    model_impl(sysfs_root).filter(|m| !m.is_empty())
+}
+
+#[cfg(target_os = "linux")]
+fn model_impl(sysfs_root: Option<&Path>) -> Option<String> {
+    let root = sysfs_root.map_or_else(crate::linux::sysfs_root, Path::to_path_buf);
+    let dmi = root.join("class/dmi/id");
+    let read = |f: &str| {
+        std::fs::read_to_string(dmi.join(f))
+            .ok()
+            .map(|s| s.trim().to_string())
+    };
+    Some(format!("{} {}", read("sys_vendor")?, read("product_name")?))
+}
+
+#[cfg(target_os = "macos")]
+fn model_impl(_sysfs_root: Option<&Path>) -> Option<String> {
+    let out = std::process::Command::new("sysctl")
+        .args(["-n", "hw.model"])
+        .output()
+        .ok()?;
+    out.status
+        .success()
+        .then(|| String::from_utf8_lossy(&out.stdout).trim().to_string())
+}
+
+#[cfg(all(target_os = "windows", feature = "win_sensors"))]
+fn model_impl(_sysfs_root: Option<&Path>) -> Option<String> {
+    use windows::Win32::Foundation::ERROR_SUCCESS;
+    use windows::Win32::System::Registry::{HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ, RegGetValueW};
+    use windows::core::HSTRING;
+
+    let key = HSTRING::from(r"HARDWARE\DESCRIPTION\System\BIOS");
+    let read = |name: &str| {
+        let mut buf = [0u16; 256];
+        let mut len = std::mem::size_of_val(&buf) as u32;
+        // SAFETY: `buf` outlives the call and `len` is its size in bytes.
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_LOCAL_MACHINE,
+                &key,
+                &HSTRING::from(name),
+                RRF_RT_REG_SZ,
+                None,
+                Some(buf.as_mut_ptr().cast()),
+                Some(&mut len),
+            )
+        };
+        // `len` counts the terminating NUL.
+        let chars = (len as usize / 2).saturating_sub(1);
+        (status == ERROR_SUCCESS)
+            .then(|| String::from_utf16_lossy(&buf[..chars]).trim().to_string())
+    };
+    Some(format!(
+        "{} {}",
+        read("SystemManufacturer")?,
+        read("SystemProductName")?
+    ))
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    all(target_os = "windows", feature = "win_sensors")
+)))]
+fn model_impl(_sysfs_root: Option<&Path>) -> Option<String> {
+    None
+}
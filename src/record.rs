@@ -0,0 +1,235 @@
+//! Recording a client's samples to disk and reading them back.
+//!
+//! The format is newline-delimited JSON: a `Header` line, then one line per
+//! sample. Each sample stores a monotonic offset from the start of the
+//! recording; the header anchors offset zero to wall-clock (UTC) time, so
+//! traces from different machines can be lined up on a common timeline.
+
+use crate::types::Velocity;
+use crate::{AngleClient, AngleSample, Error, Result, Source};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::task::JoinHandle;
+
+pub use crate::backend_replay::{ReplayControl, SPEED_RANGE};
+#[cfg(feature = "arrow")]
+pub use arrow_array::RecordBatch;
+
+/// Current recording format version. Version 2 added annotation lines.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// First line of a recording.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Header {
+    pub version: u32,
+    pub source: Source,
+    /// Wall-clock time of offset zero, in milliseconds since the Unix epoch (UTC).
+    pub started_utc_ms: u64,
+}
+
+impl Header {
+    /// Wall-clock time of offset zero.
+    pub fn started_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.started_utc_ms)
+    }
+}
+
+/// One recorded sample.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedSample {
+    /// Monotonic time since the start of the recording.
+    #[serde(rename = "t_us", with = "duration_us")]
+    pub offset: Duration,
+    pub angle_deg: f32,
+    /// Unfiltered reading (see `AngleSample::raw`); absent in recordings
+    /// made before it was added.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<f32>,
+    pub source: Source,
+    pub confidence: f32,
+    /// See `AngleSample::velocity_deg_s`; absent in recordings made before
+    /// it was added, and then re-estimated on replay.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocity_deg_s: Option<f32>,
+}
+
+mod duration_us {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> std::result::Result<S::Ok, S::Error> {
+        s.serialize_u64(d.as_micros() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Duration, D::Error> {
+        u64::deserialize(d).map(Duration::from_micros)
+    }
+}
+
+/// A marker recorded from `AngleClient::annotate`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedAnnotation {
+    /// Monotonic time since the start of the recording, on the same
+    /// timeline as the samples.
+    #[serde(rename = "t_us", with = "duration_us")]
+    pub offset: Duration,
+    pub label: String,
+}
+
+/// One line after the header; annotations are told apart by their `label`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Line {
+    Sample(RecordedSample),
+    Annotation(RecordedAnnotation),
+}
+
+/// A recording loaded into memory.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recording {
+    pub header: Header,
+    pub samples: Vec<RecordedSample>,
+    /// Markers, in the order they were recorded.
+    pub annotations: Vec<RecordedAnnotation>,
+}
+
+impl Recording {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let mut lines = BufReader::new(File::open(path)?).lines();
+        let header = lines
+            .next()
+            .ok_or_else(|| Error::Other("recording: empty file".into()))??;
+        let header: Header = serde_json::from_str(&header)?;
+        if header.version > FORMAT_VERSION {
+            return Err(Error::Other(format!(
+                "recording: unsupported version {}",
+                header.version
+            )));
+        }
+        let mut samples = Vec::new();
+        let mut annotations = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line)? {
+                Line::Sample(s) => samples.push(s),
+                Line::Annotation(a) => annotations.push(a),
+            }
+        }
+        Ok(Self {
+            header,
+            samples,
+            annotations,
+        })
+    }
+
+    /// Wall-clock time of a sample.
+    pub fn wall_time(&self, s: &RecordedSample) -> SystemTime {
+        self.header.started_at() + s.offset
+    }
+
+    /// Offset of a wall-clock time on this recording's timeline, or `None`
+    /// if it falls before the recording started. Use it to align another
+    /// trace: `a.offset_of(b.wall_time(s))`.
+    pub fn offset_of(&self, t: SystemTime) -> Option<Duration> {
+        t.duration_since(self.header.started_at()).ok()
+    }
+
+    /// Samples with `Instant`s rebuilt from `base` (offset zero), so deltas
+    /// between them match the recording exactly.
+    pub fn samples_at(&self, base: Instant) -> impl Iterator<Item = AngleSample> + '_ {
+        let mut velocity = Velocity::default();
+        self.samples.iter().map(move |s| {
+            let (raw, timestamp) = (s.raw.unwrap_or(s.angle_deg), base + s.offset);
+            let estimated = velocity.update(raw, timestamp);
+            AngleSample {
+                angle_deg: s.angle_deg,
+                raw,
+                timestamp,
+                source: s.source,
+                confidence: s.confidence,
+                velocity_deg_s: s.velocity_deg_s.unwrap_or(estimated),
+            }
+        })
+    }
+}
+
+/// Writes a client's samples, and markers from `AngleClient::annotate`, to a
+/// file until stopped or the client closes.
+pub struct Recorder {
+    task: JoinHandle<Result<()>>,
+    stop: tokio::sync::oneshot::Sender<()>,
+}
+
+impl Recorder {
+    /// Start recording `client` to `path` (truncated). Must be called from
+    /// within a Tokio runtime.
+    pub fn start(client: &AngleClient, path: impl AsRef<Path>) -> Result<Self> {
+        let mut out = BufWriter::new(File::create(path)?);
+        // Take both clocks together: this pair is the recording's anchor.
+        let base = Instant::now();
+        let header = Header {
+            version: FORMAT_VERSION,
+            source: client.info().source,
+            started_utc_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        serde_json::to_writer(&mut out, &header)?;
+        out.write_all(b"\n")?;
+
+        let mut stream = client.subscribe();
+        let mut notes = client.annotations();
+        let (stop, mut stopped) = tokio::sync::oneshot::channel();
+        let task = tokio::spawn(async move {
+            loop {
+                let s = tokio::select! {
+                    _ = &mut stopped => break,
+                    Some(a) = notes.next() => {
+                        let rec = RecordedAnnotation {
+                            offset: a.timestamp.saturating_duration_since(base),
+                            label: a.label,
+                        };
+                        serde_json::to_writer(&mut out, &rec)?;
+                        out.write_all(b"\n")?;
+                        continue;
+                    }
+                    s = stream.next() => match s {
+                        Some(s) => s,
+                        None => break,
+                    },
+                };
+                let rec = RecordedSample {
+                    offset: s.timestamp.saturating_duration_since(base),
+                    angle_deg: s.angle_deg,
+                    raw: Some(s.raw),
+                    source: s.source,
+                    confidence: s.confidence,
+                    velocity_deg_s: Some(s.velocity_deg_s),
+                };
+                serde_json::to_writer(&mut out, &rec)?;
+                out.write_all(b"\n")?;
+            }
+            out.flush()?;
+            Ok(())
+        });
+        Ok(Self { task, stop })
+    }
+
+    /// Stop recording and flush the file.
+    pub async fn stop(self) -> Result<()> {
+        let _ = self.stop.send(());
+        self.task
+            .await
+            .map_err(|e| Error::Other(format!("recorder task failed: {e}")))?
+    }
+}
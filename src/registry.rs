@@ -0,0 +1,66 @@
+//! Backends contributed from outside the crate. A registered factory takes
+//! part in `open*` like a built-in source: it is ranked with
+//! `prefer`/`disable`/`weight` and persisted open outcomes, probed with the
+//! others, and its device is wrapped in the usual `AngleClient`.
+
+use crate::{AngleDevice, Backoff, PowerCost, Result, Source};
+use futures_util::future::BoxFuture;
+use once_cell::sync::Lazy;
+use std::sync::{Arc, RwLock};
+
+/// What `open*` passes to a backend factory.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct BackendContext {
+    /// Requested sample rate.
+    pub hz: f32,
+    /// Retry policy for a backend that loses its sensor (`OpenConfig::reconnect`).
+    pub reconnect: Backoff,
+}
+
+/// Opens a third-party backend, see `register_backend`.
+pub trait BackendFactory: Send + Sync + 'static {
+    /// The source this factory provides, used for ranking, persistence, and
+    /// sample tagging. Use `Source::Other("your_id")`; a built-in source
+    /// only reaches the factory where the built-in backend is unavailable.
+    fn source(&self) -> Source;
+
+    /// Open the device, or fail so the next source is tried.
+    fn open(&self, ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>>;
+
+    /// Power cost of keeping the device open, weighed by
+    /// `OpenConfig::prefer_low_power`.
+    fn power_cost(&self) -> PowerCost {
+        self.source().power_cost()
+    }
+}
+
+static REGISTRY: Lazy<RwLock<Vec<Arc<dyn BackendFactory>>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Make `factory` available to every later `open*`. Registered sources rank
+/// after the built-in sensors and before `Mock` until weights or history
+/// say otherwise. Registering the same source again replaces its factory.
+pub fn register_backend(factory: impl BackendFactory) {
+    let mut reg = REGISTRY.write().unwrap();
+    reg.retain(|f| f.source() != factory.source());
+    reg.push(Arc::new(factory));
+}
+
+/// Registered sources, in registration order.
+pub(crate) fn sources() -> Vec<Source> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .map(|f| f.source())
+        .collect()
+}
+
+pub(crate) fn find(source: Source) -> Option<Arc<dyn BackendFactory>> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .find(|f| f.source() == source)
+        .cloned()
+}
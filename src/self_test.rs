@@ -0,0 +1,130 @@
+//! Quick sensor sanity check (`AngleDevice::self_test`), e.g. for kiosk
+//! provisioning scripts that should refuse to deploy on a broken sensor.
+
+use crate::{AngleStream, Source};
+use futures_util::StreamExt;
+use std::time::{Duration, Instant};
+use tokio::time;
+
+/// Below this observed rate the sensor counts as too slow.
+const MIN_RATE_HZ: f32 = 5.0;
+/// Largest RMS step between consecutive raw readings, in degrees.
+const MAX_JITTER_DEG: f32 = 5.0;
+/// The same for normalized (ALS) sources.
+const MAX_JITTER_NORMALIZED: f32 = 0.2;
+
+/// Outcome of a self-test; it passes when `failures` is empty.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SelfTestReport {
+    pub source: Source,
+    /// Samples collected (at most the number requested).
+    pub samples: usize,
+    /// Observed rate, once two samples have arrived.
+    pub rate_hz: Option<f32>,
+    /// Mean and standard deviation of the raw readings.
+    pub mean: f32,
+    pub stddev: f32,
+    /// RMS difference between consecutive raw readings; large values mean a
+    /// noisy sensor rather than a moving lid.
+    pub jitter: f32,
+    pub failures: Vec<SelfTestFailure>,
+}
+
+impl SelfTestReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// One failed check in a `SelfTestReport`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
+pub enum SelfTestFailure {
+    /// The sensor stopped or timed out before delivering every sample.
+    TooFewSamples { got: usize, wanted: usize },
+    /// A reading was non-finite or outside the source's range.
+    Implausible { value: f32 },
+    /// Consecutive readings jumped around more than the jitter limit (5° or,
+    /// for normalized sources, 0.2).
+    Noisy { jitter: f32 },
+    /// Samples arrived slower than 5 Hz.
+    LowRate { rate_hz: f32 },
+}
+
+/// Collect up to `wanted` samples from `stream` and check them. Gives up
+/// once the samples could no longer arrive at `MIN_RATE_HZ` (plus a second
+/// of slack for the first reading).
+pub(crate) async fn run(mut stream: AngleStream, source: Source, wanted: usize) -> SelfTestReport {
+    let budget = Duration::from_secs_f32(1.0 + wanted as f32 / MIN_RATE_HZ);
+    let deadline = time::Instant::now() + budget;
+    let mut raw = Vec::with_capacity(wanted);
+    let mut first: Option<Instant> = None;
+    let mut last: Option<Instant> = None;
+    while raw.len() < wanted {
+        let Ok(Some(s)) = time::timeout_at(deadline, stream.next()).await else {
+            break;
+        };
+        first.get_or_insert(s.timestamp);
+        last = Some(s.timestamp);
+        raw.push(s.raw);
+    }
+
+    let n = raw.len();
+    let mut failures = Vec::new();
+    if n < wanted {
+        failures.push(SelfTestFailure::TooFewSamples { got: n, wanted });
+    }
+
+    let range = if source.is_degrees() {
+        -5.0..=365.0
+    } else {
+        0.0..=1.0
+    };
+    if let Some(&value) = raw.iter().find(|v| !v.is_finite() || !range.contains(*v)) {
+        failures.push(SelfTestFailure::Implausible { value });
+    }
+
+    let (mean, stddev) = if n == 0 {
+        (0.0, 0.0)
+    } else {
+        let mean = raw.iter().sum::<f32>() / n as f32;
+        let var = raw.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / n as f32;
+        (mean, var.sqrt())
+    };
+    let jitter = if n < 2 {
+        0.0
+    } else {
+        let sq = raw.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum::<f32>();
+        (sq / (n - 1) as f32).sqrt()
+    };
+    let max_jitter = if source.is_degrees() {
+        MAX_JITTER_DEG
+    } else {
+        MAX_JITTER_NORMALIZED
+    };
+    if jitter > max_jitter {
+        failures.push(SelfTestFailure::Noisy { jitter });
+    }
+
+    let rate_hz = match (first, last) {
+        (Some(a), Some(b)) if n >= 2 && b > a => {
+            Some((n - 1) as f32 / b.duration_since(a).as_secs_f32())
+        }
+        _ => None,
+    };
+    if let Some(rate_hz) = rate_hz.filter(|r| *r < MIN_RATE_HZ) {
+        failures.push(SelfTestFailure::LowRate { rate_hz });
+    }
+
+    SelfTestReport {
+        source,
+        samples: n,
+        rate_hz,
+        mean,
+        stddev,
+        jitter,
+        failures,
+    }
+}
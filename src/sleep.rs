@@ -0,0 +1,78 @@
+//! System sleep/wake and clamshell notifications. A client following them
+//! (`AngleClient::follow_power`) pauses sampling while the machine sleeps,
+//! so no stale reading is streamed across a suspend, and reports the
+//! clamshell closing as an authoritative lid event.
+//!
+//! `OpenConfig::power_notifications` (on by default) follows `platform()`:
+//! `IOPMrootDomain` on macOS with feature `mac_power`. Implement
+//! `PowerNotifications` for other platforms.
+
+use futures_util::stream::BoxStream;
+
+/// A power-management notification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PowerEvent {
+    /// The system is about to sleep.
+    WillSleep,
+    /// The system has woken up.
+    DidWake,
+    /// The clamshell (lid) closed.
+    ClamshellClosed,
+    /// The clamshell (lid) opened.
+    ClamshellOpened,
+}
+
+/// A source of power notifications a client can follow.
+pub trait PowerNotifications: Send + Sync + 'static {
+    /// Every notification from now on.
+    fn subscribe(&self) -> BoxStream<'static, PowerEvent>;
+}
+
+impl PowerNotifications for Box<dyn PowerNotifications> {
+    fn subscribe(&self) -> BoxStream<'static, PowerEvent> {
+        (**self).subscribe()
+    }
+}
+
+/// This machine's power notifications, if supported.
+pub fn platform() -> Option<Box<dyn PowerNotifications>> {
+    #[cfg(all(target_os = "macos", feature = "mac_power"))]
+    if let Some(n) = IoPmNotifications::get() {
+        return Some(Box::new(n));
+    }
+    None
+}
+
+/// `IOPMrootDomain`'s sleep/wake and clamshell messages, from one run-loop
+/// thread shared by the whole process.
+#[cfg(all(target_os = "macos", feature = "mac_power"))]
+#[derive(Clone)]
+pub struct IoPmNotifications {
+    tx: tokio::sync::broadcast::Sender<PowerEvent>,
+}
+
+#[cfg(all(target_os = "macos", feature = "mac_power"))]
+static IOPM: once_cell::sync::Lazy<Option<tokio::sync::broadcast::Sender<PowerEvent>>> =
+    once_cell::sync::Lazy::new(|| {
+        let (tx, _rx) = tokio::sync::broadcast::channel(16);
+        crate::mac::watch_power(tx.clone()).then_some(tx)
+    });
+
+#[cfg(all(target_os = "macos", feature = "mac_power"))]
+impl IoPmNotifications {
+    /// Registers for the notifications on first use; `None` if that failed.
+    pub fn get() -> Option<Self> {
+        IOPM.clone().map(|tx| Self { tx })
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "mac_power"))]
+impl PowerNotifications for IoPmNotifications {
+    fn subscribe(&self) -> BoxStream<'static, PowerEvent> {
+        use futures_util::StreamExt;
+        tokio_stream::wrappers::BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+}
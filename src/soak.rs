@@ -0,0 +1,163 @@
+//! Soak testing: run a client for a long time and track whether memory, task
+//! count, sample rate, and reconnects stay flat (see `soak`).
+
+use crate::{AngleClient, Degraded, Event, Health};
+use futures_util::StreamExt;
+use std::time::Duration;
+use tokio::time::{self, Instant};
+
+/// How often reconnect state is polled between snapshots.
+const POLL: Duration = Duration::from_millis(250);
+
+/// Process and client state at one point of a soak run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SoakSnapshot {
+    /// Time since the run started.
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub elapsed: Duration,
+    /// Resident set size of this process (Linux only).
+    pub rss_bytes: Option<u64>,
+    /// Live tasks on the runtime driving the soak.
+    pub tasks: usize,
+    /// Samples the client has seen since open.
+    pub samples: u64,
+    /// Observed sample rate, and its relative deviation from the requested
+    /// rate (`0.1` = 10% fast).
+    pub rate_hz: Option<f32>,
+    pub drift: Option<f32>,
+    pub sampler_restarts: u32,
+    /// Reconnects seen so far in this run.
+    pub reconnects: u32,
+    pub health: Health,
+}
+
+/// Summary of a soak run, see `soak`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SoakReport {
+    /// One snapshot at the start and one per interval, the last at the end.
+    pub snapshots: Vec<SoakSnapshot>,
+    /// RSS change between the first and last snapshot.
+    pub rss_growth_bytes: Option<i64>,
+    /// Task count change between the first and last snapshot.
+    pub task_growth: i64,
+    /// Largest absolute rate drift seen.
+    pub max_drift: Option<f32>,
+    /// Times the backend started reconnecting.
+    pub reconnects: u32,
+    /// Sampler restarts after panics during the run.
+    pub sampler_restarts: u32,
+    /// `Event::SourceSwitched` events during the run.
+    pub source_switches: u32,
+}
+
+/// Watch `client` for `duration`, taking a snapshot every `every` (passed
+/// to `on_snapshot` as it is taken) and returning the whole series with a
+/// summary. Must be called from within a Tokio runtime; the task count is
+/// that runtime's.
+pub async fn soak(
+    client: &AngleClient,
+    duration: Duration,
+    every: Duration,
+    mut on_snapshot: impl FnMut(&SoakSnapshot),
+) -> SoakReport {
+    let start = Instant::now();
+    let end = start + duration;
+    let restarts_at_start = client.stats().sampler_restarts;
+    let mut events = client.events();
+    let mut reconnecting = false;
+    let mut reconnects = 0u32;
+    let mut source_switches = 0u32;
+
+    let mut snapshots = Vec::new();
+    let mut take = |reconnects: u32| {
+        let s = snapshot(client, start.elapsed(), reconnects);
+        on_snapshot(&s);
+        snapshots.push(s);
+    };
+    take(0);
+
+    let mut poll = time::interval(POLL);
+    let mut next = start + every;
+    loop {
+        tokio::select! {
+            _ = poll.tick() => {
+                let now = client.health() == Health::Degraded(Degraded::Reconnecting);
+                if now && !reconnecting {
+                    reconnects += 1;
+                }
+                reconnecting = now;
+                let t = Instant::now();
+                if t >= end {
+                    break;
+                }
+                if t >= next {
+                    take(reconnects);
+                    next += every;
+                }
+            }
+            Some(ev) = events.next() => {
+                if matches!(ev, Event::SourceSwitched { .. }) {
+                    source_switches += 1;
+                }
+            }
+        }
+    }
+    take(reconnects);
+
+    let (first, last) = (snapshots[0], snapshots[snapshots.len() - 1]);
+    SoakReport {
+        rss_growth_bytes: first
+            .rss_bytes
+            .zip(last.rss_bytes)
+            .map(|(a, b)| b as i64 - a as i64),
+        task_growth: last.tasks as i64 - first.tasks as i64,
+        max_drift: snapshots
+            .iter()
+            .filter_map(|s| s.drift)
+            .map(f32::abs)
+            .reduce(f32::max),
+        reconnects,
+        sampler_restarts: last.sampler_restarts - restarts_at_start,
+        source_switches,
+        snapshots,
+    }
+}
+
+fn snapshot(client: &AngleClient, elapsed: Duration, reconnects: u32) -> SoakSnapshot {
+    let stats = client.stats();
+    let requested = client.rate().as_hz();
+    SoakSnapshot {
+        elapsed,
+        rss_bytes: rss_bytes(),
+        tasks: tokio::runtime::Handle::current()
+            .metrics()
+            .num_alive_tasks(),
+        samples: stats.samples,
+        rate_hz: stats.rate_hz,
+        drift: stats.rate_hz.map(|r| r / requested - 1.0),
+        sampler_restarts: stats.sampler_restarts,
+        reconnects,
+        health: client.health(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn rss_bytes() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let kb = status
+        .lines()
+        .find_map(|l| l.strip_prefix("VmRSS:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn rss_bytes() -> Option<u64> {
+    None
+}
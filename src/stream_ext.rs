@@ -0,0 +1,199 @@
+//! Per-consumer stream combinators. These run on the subscriber side, so they
+//! never touch the shared device (smoothing set via `set_smoothing` is global).
+
+use crate::calibrate::{AlsEstimate, AlsModel};
+use crate::events::{LidEvent, LidEventConfig, LidEvents};
+use crate::gesture::{Gesture, GestureConfig, GestureDetector};
+use crate::{Aggregate, AngleSample, AngleStream, Posture, PostureConfig};
+use futures_core::Stream;
+use futures_util::StreamExt;
+use futures_util::stream::BoxStream;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
+
+pub trait AngleStreamExt: Stream<Item = AngleSample> + Send + Sized + 'static {
+    /// EMA on top of whatever smoothing the device already applies.
+    fn smoothed(self, alpha: f32) -> AngleStream {
+        let a = alpha.clamp(0.0, 1.0);
+        self.scan(None::<f32>, move |prev, mut s| {
+            let v = match *prev {
+                None => s.angle_deg,
+                Some(p) => p + a * (s.angle_deg - p),
+            };
+            *prev = Some(v);
+            s.angle_deg = v;
+            futures_util::future::ready(Some(s))
+        })
+        .boxed()
+    }
+
+    /// Drop samples that moved less than `d` from the last emitted one.
+    fn deadband(self, d: f32) -> AngleStream {
+        let d = d.abs();
+        let mut last: Option<f32> = None;
+        self.filter(move |s| {
+            let pass = match last {
+                Some(l) => (s.angle_deg - l).abs() >= d,
+                None => true,
+            };
+            if pass {
+                last = Some(s.angle_deg);
+            }
+            futures_util::future::ready(pass)
+        })
+        .boxed()
+    }
+
+    /// Emit `label` whenever the angle enters a different zone.
+    /// Samples outside every zone are ignored; the first matching zone wins.
+    fn zones<L>(self, zones: Vec<(RangeInclusive<f32>, L)>) -> BoxStream<'static, L>
+    where
+        L: Clone + PartialEq + Send + 'static,
+    {
+        let mut current: Option<usize> = None;
+        self.filter_map(move |s| {
+            let hit = zones.iter().position(|(r, _)| r.contains(&s.angle_deg));
+            let out = match hit {
+                Some(i) if current != Some(i) => {
+                    current = Some(i);
+                    Some(zones[i].1.clone())
+                }
+                _ => None,
+            };
+            futures_util::future::ready(out)
+        })
+        .boxed()
+    }
+
+    /// One `Aggregate` per `window`, bucketed by sample timestamp. A window is
+    /// emitted once a later sample arrives (or the stream ends); windows
+    /// without samples are skipped.
+    fn aggregated(self, window: Duration) -> BoxStream<'static, Aggregate> {
+        let window = window.max(Duration::from_millis(1));
+        let state = (self.boxed(), None::<Bucket>, false);
+        futures_util::stream::unfold(state, move |(mut input, mut bucket, done)| async move {
+            if done {
+                return None;
+            }
+            while let Some(s) = input.next().await {
+                match &mut bucket {
+                    Some(b) if s.timestamp < b.start + window => b.push(&s),
+                    Some(b) => {
+                        // Keep window boundaries on the original grid.
+                        let skipped =
+                            s.timestamp.duration_since(b.start).as_nanos() / window.as_nanos();
+                        let start = b.start + window * skipped as u32;
+                        let out = b.finish(window);
+                        return Some((out, (input, Some(Bucket::new(start, &s)), false)));
+                    }
+                    None => bucket = Some(Bucket::new(s.timestamp, &s)),
+                }
+            }
+            let out = bucket.take()?.finish(window);
+            Some((out, (input, None, true)))
+        })
+        .boxed()
+    }
+
+    /// Semantic lid events (opened / closed, threshold crossings, source
+    /// changes), see `events::LidEvents`.
+    fn lid_events(self, cfg: LidEventConfig) -> BoxStream<'static, LidEvent> {
+        let mut detector = LidEvents::new(cfg);
+        self.flat_map(move |s| futures_util::stream::iter(detector.push(&s)))
+            .boxed()
+    }
+
+    /// Rapid closes (`ClosingFast`, then `SlamDetected`), see
+    /// `gesture::GestureDetector`.
+    fn gestures(self, cfg: GestureConfig) -> BoxStream<'static, Gesture> {
+        let mut detector = GestureDetector::new(cfg);
+        self.filter_map(move |s| futures_util::future::ready(detector.push(&s)))
+            .boxed()
+    }
+
+    /// The posture of the first sample, then each change, with
+    /// `PostureConfig::hysteresis` applied at the bounds.
+    fn postures(self, cfg: PostureConfig) -> BoxStream<'static, Posture> {
+        let mut prev: Option<Posture> = None;
+        self.filter_map(move |s| {
+            let p = cfg.next(prev, &s);
+            let out = (prev != Some(p)).then_some(p);
+            prev = Some(p);
+            futures_util::future::ready(out)
+        })
+        .boxed()
+    }
+
+    /// Each sample from `model`'s source with the angle estimated from its
+    /// unsmoothed `raw` reading, which is what `calibrate::fit_als` fits,
+    /// see `calibrate::AlsModel::estimate`; other sources' samples are
+    /// dropped.
+    fn als_angles(self, model: AlsModel) -> BoxStream<'static, (AngleSample, AlsEstimate)> {
+        self.filter_map(move |s| {
+            let est = (s.source == model.source).then(|| (s, model.estimate(s.raw)));
+            futures_util::future::ready(est)
+        })
+        .boxed()
+    }
+
+    /// Drop samples whose confidence is below `c`.
+    fn min_confidence(self, c: f32) -> AngleStream {
+        self.filter(move |s| futures_util::future::ready(s.confidence >= c))
+            .boxed()
+    }
+}
+
+/// Running sums for one aggregation window.
+struct Bucket {
+    start: Instant,
+    count: u32,
+    min: f32,
+    max: f32,
+    sum: f64,
+    sum_sq: f64,
+    source: crate::Source,
+}
+
+impl Bucket {
+    fn new(start: Instant, s: &AngleSample) -> Self {
+        let mut b = Self {
+            start,
+            count: 0,
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+            sum: 0.0,
+            sum_sq: 0.0,
+            source: s.source,
+        };
+        b.push(s);
+        b
+    }
+
+    fn push(&mut self, s: &AngleSample) {
+        let v = s.angle_deg as f64;
+        self.count += 1;
+        self.min = self.min.min(s.angle_deg);
+        self.max = self.max.max(s.angle_deg);
+        self.sum += v;
+        self.sum_sq += v * v;
+        self.source = s.source;
+    }
+
+    fn finish(&self, window: Duration) -> Aggregate {
+        let n = self.count as f64;
+        let mean = self.sum / n;
+        let var = (self.sum_sq / n - mean * mean).max(0.0);
+        Aggregate {
+            start: self.start,
+            window,
+            count: self.count,
+            min: self.min,
+            max: self.max,
+            mean: mean as f32,
+            stddev: var.sqrt() as f32,
+            source: self.source,
+        }
+    }
+}
+
+impl<S> AngleStreamExt for S where S: Stream<Item = AngleSample> + Send + Sized + 'static {}
@@ -1,8 +1,13 @@
-use std::time::Instant;
+use crate::LidEvent;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Each variant has a stable `code()` / `code_name()`; codes are never reused.
+/// Non-exhaustive: new variants may be added in minor releases.
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum Error {
     #[error("backend error: {0}")]
     Backend(String),
@@ -20,32 +25,779 @@ pub enum Error {
     /// Stable, pattern-matchable "no backend found" error.
     #[error("no suitable backend available; tried: {tried:?}")]
     NoBackend { tried: Vec<Source> },
+
+    #[cfg(feature = "linux_iio_proxy")]
+    #[error("dbus error: {0}")]
+    DBus(#[from] zbus::Error),
+
+    #[cfg(all(target_os = "windows", feature = "win_sensors"))]
+    #[error("windows sensor error ({context}): {source}")]
+    Windows {
+        context: &'static str,
+        #[source]
+        source: windows::core::Error,
+    },
+
+    #[error("serialization error: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    #[error("cancelled")]
+    Cancelled,
+
+    /// The OS refused access to `sensor` (e.g. a macOS sandbox without the
+    /// USB entitlement); retrying will not help until `hint` is acted on.
+    #[error("permission denied for {sensor:?}: {hint}")]
+    PermissionDenied { sensor: Source, hint: String },
+
+    #[cfg(feature = "arrow")]
+    #[error("arrow error: {0}")]
+    Arrow(#[from] arrow_schema::ArrowError),
+
+    #[cfg(feature = "arrow")]
+    #[error("parquet error: {0}")]
+    Parquet(#[from] parquet::errors::ParquetError),
 }
 
+impl Error {
+    /// Stable numeric code.
+    pub fn code(&self) -> u32 {
+        match self {
+            Error::Backend(_) => 1,
+            Error::Io(_) => 2,
+            #[cfg(feature = "mac_hid_feature")]
+            Error::Hid(_) => 3,
+            Error::Other(_) => 4,
+            Error::NoBackend { .. } => 5,
+            #[cfg(feature = "linux_iio_proxy")]
+            Error::DBus(_) => 6,
+            #[cfg(all(target_os = "windows", feature = "win_sensors"))]
+            Error::Windows { .. } => 7,
+            Error::Serde(_) => 8,
+            Error::Cancelled => 9,
+            #[cfg(feature = "arrow")]
+            Error::Arrow(_) => 10,
+            #[cfg(feature = "arrow")]
+            Error::Parquet(_) => 11,
+            Error::PermissionDenied { .. } => 12,
+        }
+    }
+
+    /// Stable string code, e.g. for logs and metrics labels.
+    pub fn code_name(&self) -> &'static str {
+        match self {
+            Error::Backend(_) => "backend",
+            Error::Io(_) => "io",
+            #[cfg(feature = "mac_hid_feature")]
+            Error::Hid(_) => "hid",
+            Error::Other(_) => "other",
+            Error::NoBackend { .. } => "no_backend",
+            #[cfg(feature = "linux_iio_proxy")]
+            Error::DBus(_) => "dbus",
+            #[cfg(all(target_os = "windows", feature = "win_sensors"))]
+            Error::Windows { .. } => "windows",
+            Error::Serde(_) => "serde",
+            Error::Cancelled => "cancelled",
+            #[cfg(feature = "arrow")]
+            Error::Arrow(_) => "arrow",
+            #[cfg(feature = "arrow")]
+            Error::Parquet(_) => "parquet",
+            Error::PermissionDenied { .. } => "permission_denied",
+        }
+    }
+}
+
+/// With the `serde` feature this serializes (for structured logging) but the
+/// monotonic `timestamp` is skipped. Outside this crate, build one with
+/// `AngleSample::new`; fields may be added.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[non_exhaustive]
 pub struct AngleSample {
     pub angle_deg: f32,
+    /// The reading behind `angle_deg` before smoothing and axis/output
+    /// correction, in the same units.
+    pub raw: f32,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub timestamp: Instant,
+    pub source: Source,
+    /// Backend confidence (0..1) at the time the sample was taken.
+    pub confidence: f32,
+    /// Rate of change of `angle_deg` per second (positive = opening), from
+    /// the unsmoothed readings with a short low-pass so it tracks fast moves
+    /// regardless of `smoothing`. 0 on the first sample; in 0..1 units per
+    /// second for normalized sources.
+    pub velocity_deg_s: f32,
+}
+
+/// Time constant of the low-pass behind `AngleSample::velocity_deg_s`.
+const VELOCITY_TAU_S: f32 = 0.05;
+
+/// Estimates `AngleSample::velocity_deg_s` in a backend's sampling loop:
+/// finite differences of the readings, low-passed by elapsed time so the
+/// result doesn't depend on the sample rate.
+#[derive(Default)]
+pub(crate) struct Velocity {
+    last: Option<(f32, Instant)>,
+    v: f32,
+}
+
+impl Velocity {
+    /// Feed a reading taken at `at`; returns the current estimate.
+    pub(crate) fn update(&mut self, value: f32, at: Instant) -> f32 {
+        if !value.is_finite() {
+            return self.v;
+        }
+        if let Some((prev, t)) = self.last {
+            let dt = at.saturating_duration_since(t).as_secs_f32();
+            if dt <= 0.0 {
+                return self.v;
+            }
+            let k = dt / (VELOCITY_TAU_S + dt);
+            self.v += k * ((value - prev) / dt - self.v);
+        }
+        self.last = Some((value, at));
+        self.v
+    }
+}
+
+/// An ambient-light reading from an ALS source, see `AngleClient::subscribe_lux`.
+/// Like `AngleSample`, `timestamp` is skipped when serialized.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LightSample {
+    /// Illuminance as reported by the sensor, unsmoothed.
+    pub lux: f32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub timestamp: Instant,
     pub source: Source,
 }
 
+/// A named marker attached with `AngleClient::annotate`, e.g. "user clicked
+/// suspend", so traces can be lined up with application behavior. `Recorder`
+/// stores them alongside the samples.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Annotation {
+    pub label: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub timestamp: Instant,
+}
+
+impl AngleSample {
+    /// A sample of `angle_deg` (also its `raw` reading) from `source`, with
+    /// confidence 1.0 and no velocity.
+    pub fn new(angle_deg: f32, source: Source, timestamp: Instant) -> Self {
+        Self {
+            angle_deg,
+            raw: angle_deg,
+            timestamp,
+            source,
+            confidence: 1.0,
+            velocity_deg_s: 0.0,
+        }
+    }
+    pub fn raw(mut self, raw: f32) -> Self {
+        self.raw = raw;
+        self
+    }
+    pub fn confidence(mut self, c: f32) -> Self {
+        self.confidence = c;
+        self
+    }
+    pub fn velocity_deg_s(mut self, v: f32) -> Self {
+        self.velocity_deg_s = v;
+        self
+    }
+
+    /// True when `angle_deg` carries physical degrees (false for ALS-style 0..1 values).
+    pub fn is_degrees(&self) -> bool {
+        self.source.is_degrees()
+    }
+
+    /// Angle in radians; `None` when the sample is not in degrees.
+    pub fn as_radians(&self) -> Option<f32> {
+        self.is_degrees().then(|| self.angle_deg.to_radians())
+    }
+
+    /// Value mapped into 0..1 over `range` (degrees), clamped.
+    /// Non-degree samples are already normalized and are returned as-is.
+    pub fn normalized(&self, range: RangeInclusive<f32>) -> f32 {
+        if !self.is_degrees() {
+            return self.angle_deg.clamp(0.0, 1.0);
+        }
+        let (lo, hi) = (*range.start(), *range.end());
+        let span = hi - lo;
+        if span.abs() <= f32::EPSILON {
+            return 0.0;
+        }
+        ((self.angle_deg - lo) / span).clamp(0.0, 1.0)
+    }
+
+    /// The state a lid switch sample (`Source::is_lid_switch`) reports.
+    pub(crate) fn lid_switch_state(&self) -> Option<crate::LidSwitchState> {
+        use crate::LidSwitchState;
+        let state = if self.angle_deg >= 0.5 {
+            LidSwitchState::Open
+        } else {
+            LidSwitchState::Closed
+        };
+        (self.source.is_lid_switch() && self.angle_deg.is_finite()).then_some(state)
+    }
+}
+
+/// Corrects a sensor's mounting: `v' = ±v + offset_deg`. A mirrored 0..360
+/// hinge is `AxisTransform { invert: true, offset_deg: 360.0 }`.
+/// See `OpenConfig::axis` and `quirks`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AxisTransform {
+    pub invert: bool,
+    pub offset_deg: f32,
+}
+
+impl AxisTransform {
+    pub fn apply(&self, v: f32) -> f32 {
+        let v = if self.invert { -v } else { v };
+        v + self.offset_deg
+    }
+
+    /// Correct a degree sample; normalized (non-degree) samples pass through.
+    pub fn map_sample(&self, mut s: AngleSample) -> AngleSample {
+        if s.is_degrees() {
+            s.angle_deg = self.apply(s.angle_deg);
+            if self.invert {
+                s.velocity_deg_s = -s.velocity_deg_s;
+            }
+        }
+        s
+    }
+}
+
+/// Linear map from `input` onto `output`, see `OpenConfig::output_map`.
+/// A reversed `output` range inverts the value.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutputMap {
+    pub input: RangeInclusive<f32>,
+    pub output: RangeInclusive<f32>,
+    /// Clamp values outside `input` to the ends of `output`.
+    pub clamp: bool,
+}
+
+impl OutputMap {
+    /// Clamped map, e.g. `OutputMap::new(0.0..=360.0, 0.0..=180.0)`.
+    pub fn new(input: RangeInclusive<f32>, output: RangeInclusive<f32>) -> Self {
+        Self {
+            input,
+            output,
+            clamp: true,
+        }
+    }
+
+    /// Clamp into `range` without scaling.
+    pub fn clamp_to(range: RangeInclusive<f32>) -> Self {
+        Self::new(range.clone(), range)
+    }
+
+    /// Extrapolate past the ends of `input` instead of clamping.
+    pub fn unclamped(mut self) -> Self {
+        self.clamp = false;
+        self
+    }
+
+    pub fn apply(&self, v: f32) -> f32 {
+        let (a, b) = (*self.input.start(), *self.input.end());
+        let (c, d) = (*self.output.start(), *self.output.end());
+        let span = b - a;
+        let t = if span.abs() <= f32::EPSILON {
+            0.0
+        } else {
+            (v - a) / span
+        };
+        let t = if self.clamp { t.clamp(0.0, 1.0) } else { t };
+        c + t * (d - c)
+    }
+
+    /// Map a degree sample; normalized (non-degree) samples pass through.
+    /// `velocity_deg_s` is scaled by the slope, and is 0 while clamped.
+    pub fn map_sample(&self, mut s: AngleSample) -> AngleSample {
+        if s.is_degrees() {
+            let (a, b) = (*self.input.start(), *self.input.end());
+            let (c, d) = (*self.output.start(), *self.output.end());
+            let clamped = self.clamp && !(a.min(b)..=a.max(b)).contains(&s.angle_deg);
+            s.velocity_deg_s = if clamped || (b - a).abs() <= f32::EPSILON {
+                0.0
+            } else {
+                s.velocity_deg_s * (d - c) / (b - a)
+            };
+            s.angle_deg = self.apply(s.angle_deg);
+        }
+        s
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        [
+            *self.input.start(),
+            *self.input.end(),
+            *self.output.start(),
+            *self.output.end(),
+        ]
+        .iter()
+        .all(|v| v.is_finite())
+    }
+}
+
+/// A named angle range for `AngleClient::subscribe_zones`, see
+/// `OpenConfig::zone`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Zone {
+    pub name: String,
+    pub range: RangeInclusive<f32>,
+}
+
+impl Zone {
+    pub fn new(name: impl Into<String>, range: RangeInclusive<f32>) -> Self {
+        Self {
+            name: name.into(),
+            range,
+        }
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        self.range.start().is_finite() && self.range.end().is_finite()
+    }
+}
+
+/// Summary of the samples in one window, see `AngleStreamExt::aggregated`.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Aggregate {
+    /// Start of the window (the first window starts at the first sample).
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub start: Instant,
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub window: Duration,
+    pub count: u32,
+    pub min: f32,
+    pub max: f32,
+    pub mean: f32,
+    /// Population standard deviation.
+    pub stddev: f32,
+    /// Source of the last sample in the window.
+    pub source: Source,
+}
+
+/// Non-exhaustive: new backends may add variants in minor releases.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[non_exhaustive]
 pub enum Source {
     // macOS
     HingeFeature,
     HingeHid,
     HingeIOKit,
+    /// Lid angle from an SMC key.
+    HingeSMC,
     ALS,
+    /// `AppleClamshellState`: 0.0 closed, 1.0 open (see `is_lid_switch`).
+    MacClamshell,
 
     // Windows
     WinHinge,
     WinTilt,
     WinALS,
+    /// A foldable's coarse hinge state (`win::HingeState`), in degrees.
+    WinPosture,
+    /// `GUID_LIDSWITCH_STATE_CHANGE`: 0.0 closed, 1.0 open (see
+    /// `is_lid_switch`).
+    WinLidSwitch,
 
     // Linux
+    /// Hinge angle from the `accel-display` and `accel-base` IIO
+    /// accelerometers' relative orientation.
+    LinuxHinge,
     LinuxTilt,
     LinuxALS,
+    /// libinput's lid switch on the session's seat: 0.0 closed, 1.0 open
+    /// (see `is_lid_switch`).
+    LinuxLidSwitch,
+    /// UPower's `LidIsClosed` over D-Bus: 0.0 closed, 1.0 open (see
+    /// `is_lid_switch`).
+    LinuxUPowerLid,
+    /// The legacy `/proc/acpi/button/lid/*/state` file: 0.0 closed, 1.0
+    /// open (see `is_lid_switch`), at `lid_switch::ACPI_LID_CONFIDENCE`.
+    LinuxAcpiLid,
 
     // Testing
     Mock,
+
+    /// Sources this version does not know about (e.g. externally registered
+    /// backends), identified by a string id.
+    Other(#[serde(deserialize_with = "intern_source_id")] SourceId),
+}
+
+/// String id carried by `Source::Other`.
+pub type SourceId = &'static str;
+
+/// `Source` stays `Copy`, so deserialized ids are interned, see `intern`.
+fn intern_source_id<'de, D>(d: D) -> std::result::Result<SourceId, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::Deserialize;
+    intern(String::deserialize(d)?).ok_or_else(|| {
+        serde::de::Error::custom(format!(
+            "more than {MAX_OTHER_IDS} distinct Source::Other ids"
+        ))
+    })
+}
+
+/// Most `Source::Other` ids `intern` leaks; files and daemon peers are not
+/// trusted to stay under it.
+pub(crate) const MAX_OTHER_IDS: usize = 256;
+
+/// `s` as a `&'static str`: a registered backend's own id, else leaked once
+/// per distinct string, `None` past `MAX_OTHER_IDS` of those. Only for
+/// `Source::Other` ids, which must be `'static` for `Source` to stay `Copy`.
+pub(crate) fn intern(s: String) -> Option<&'static str> {
+    use once_cell::sync::Lazy;
+    use std::collections::HashSet;
+    use std::sync::{Mutex, PoisonError};
+
+    static STRS: Lazy<Mutex<HashSet<&'static str>>> = Lazy::new(Default::default);
+
+    let registered = crate::registry::sources()
+        .into_iter()
+        .find_map(|src| match src {
+            Source::Other(id) if id == s => Some(id),
+            _ => None,
+        });
+    if registered.is_some() {
+        return registered;
+    }
+    let mut strs = STRS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(&known) = strs.get(s.as_str()) {
+        return Some(known);
+    }
+    if strs.len() >= MAX_OTHER_IDS {
+        return None;
+    }
+    let leaked: &'static str = Box::leak(s.into_boxed_str());
+    strs.insert(leaked);
+    Some(leaked)
+}
+
+/// Client events, see `AngleClient::events()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Event {
+    /// Confidence passed the gate; `latest()` now returns samples.
+    Live { confidence: f32 },
+    /// Confidence dropped below the hysteresis threshold.
+    Waiting { confidence: f32 },
+    /// The backend's sampler panicked and was restarted; `restarts` is the
+    /// running count.
+    SamplerRestarted { restarts: u32 },
+    /// The device switched between redundant sensors (e.g. WinHinge ->
+    /// WinTilt) because the active one looked unhealthy, or switched back.
+    SourceSwitched {
+        from: Source,
+        to: Source,
+        reason: SwitchReason,
+    },
+    /// A lazily opened client (`open_lazy`) attached its backend.
+    Attached { source: Source },
+    /// `AngleClient::watch_config` applied a changed config file.
+    ConfigReloaded,
+    /// `is_open` / `is_closed` changed: `LidEvent::LidOpened` or
+    /// `LidEvent::LidClosed`.
+    Lid(LidEvent),
+}
+
+/// Progress while opening a client, see `open_with_progress`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InitEvent {
+    /// Started opening this source.
+    ProbingSource(Source),
+    /// The source could not be opened (or ran out of `fail_after` budget).
+    SourceFailed { source: Source, reason: String },
+    /// The client will use this source.
+    SourceSelected(Source),
+}
+
+/// Why a device switched sources, see `Event::SourceSwitched`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SwitchReason {
+    /// The primary sensor stopped delivering samples.
+    Stalled,
+    /// The primary sensor kept reporting the same value while the
+    /// cross-check sensor saw movement.
+    Constant,
+    /// The primary sensor is healthy again.
+    Recovered,
+}
+
+impl Source {
+    /// ALS sources publish a normalized 0..1 control, not degrees, and lid
+    /// switch sources 0 or 1.
+    pub fn is_degrees(&self) -> bool {
+        !matches!(
+            self,
+            Source::ALS
+                | Source::WinALS
+                | Source::LinuxALS
+                | Source::MacClamshell
+                | Source::WinLidSwitch
+                | Source::LinuxLidSwitch
+                | Source::LinuxUPowerLid
+                | Source::LinuxAcpiLid
+        )
+    }
+
+    /// Ambient light sensors: `angle_deg` is the light level normalized to
+    /// 0..1, which clients keep off `AngleClient::subscribe`.
+    pub fn is_als(&self) -> bool {
+        matches!(self, Source::ALS | Source::WinALS | Source::LinuxALS)
+    }
+
+    /// Open/closed switches rather than angle sensors: `angle_deg` is 0.0
+    /// while the lid is shut and 1.0 while it is open.
+    pub fn is_lid_switch(&self) -> bool {
+        matches!(
+            self,
+            Source::MacClamshell
+                | Source::WinLidSwitch
+                | Source::LinuxLidSwitch
+                | Source::LinuxUPowerLid
+                | Source::LinuxAcpiLid
+        )
+    }
+
+    /// Rough power draw of keeping this source open at typical rates, see
+    /// `PowerCost`. Registered backends can declare their own through
+    /// `BackendFactory::power_cost`; unknown sources count as `Moderate`.
+    pub fn power_cost(&self) -> PowerCost {
+        match self {
+            // Pushed by the OS on change.
+            Source::WinHinge
+            | Source::WinPosture
+            | Source::WinLidSwitch
+            | Source::LinuxLidSwitch
+            | Source::LinuxUPowerLid => PowerCost::Low,
+            // Feature reports polled over USB/SPI at the sample rate.
+            Source::HingeFeature | Source::HingeHid | Source::HingeIOKit => PowerCost::High,
+            // Polled sensor framework or sysfs reads.
+            Source::HingeSMC
+            | Source::ALS
+            | Source::MacClamshell
+            | Source::WinTilt
+            | Source::WinALS
+            | Source::LinuxHinge
+            | Source::LinuxTilt
+            | Source::LinuxALS
+            | Source::LinuxAcpiLid
+            | Source::Mock
+            | Source::Other(_) => PowerCost::Moderate,
+        }
+    }
+}
+
+/// Relative power cost of a source, used by `OpenConfig::prefer_low_power`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PowerCost {
+    /// Event-driven: the sensor wakes the host only when the angle changes.
+    Low,
+    /// Polled through an OS sensor stack or sysfs.
+    Moderate,
+    /// Polled from the device at the full sample rate (e.g. 60 Hz HID
+    /// feature reports).
+    High,
+}
+
+/// Sampling rate in Hz; always finite and > 0.
+///
+/// Construct with [`SampleRate::hz`] or [`SampleRate::per_second`]; invalid
+/// rates are rejected here instead of at `open()` time.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "f32", into = "f32")
+)]
+pub struct SampleRate(f32);
+
+impl SampleRate {
+    /// Panics if `hz` is not finite or not > 0 (a compile error in const context).
+    pub const fn hz(hz: f32) -> Self {
+        match Self::try_hz(hz) {
+            Some(r) => r,
+            None => panic!("sample rate must be finite and > 0"),
+        }
+    }
+
+    /// Non-panicking variant of [`SampleRate::hz`].
+    pub const fn try_hz(hz: f32) -> Option<Self> {
+        if hz.is_finite() && hz > 0.0 {
+            Some(Self(hz))
+        } else {
+            None
+        }
+    }
+
+    /// Panics if `n == 0`.
+    pub const fn per_second(n: u32) -> Self {
+        assert!(n > 0, "sample rate must be > 0");
+        Self(n as f32)
+    }
+
+    pub const fn as_hz(self) -> f32 {
+        self.0
+    }
+
+    /// Time between samples.
+    pub fn period(self) -> Duration {
+        Duration::from_secs_f32(1.0 / self.0)
+    }
+}
+
+impl TryFrom<f32> for SampleRate {
+    type Error = Error;
+
+    fn try_from(hz: f32) -> Result<Self> {
+        Self::try_hz(hz).ok_or_else(|| Error::Other("sample rate must be finite and > 0".into()))
+    }
+}
+
+impl From<SampleRate> for f32 {
+    fn from(r: SampleRate) -> f32 {
+        r.as_hz()
+    }
+}
+
+/// Reconnect backoff used by backends when a sensor read or re-open fails.
+/// Delay for attempt `n` (0-based) is `initial * multiplier^n`, capped at `max`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Backoff {
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub initial: Duration,
+    pub multiplier: f32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub max: Duration,
+    /// `None` retries forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(300),
+            multiplier: 2.0,
+            max: Duration::from_secs(5),
+            max_attempts: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// Fixed delay, retrying forever.
+    pub fn constant(d: Duration) -> Self {
+        Self {
+            initial: d,
+            multiplier: 1.0,
+            max: d,
+            max_attempts: None,
+        }
+    }
+
+    /// Delay before retry `attempt` (0-based), or `None` once attempts are exhausted.
+    pub fn delay(&self, attempt: u32) -> Option<Duration> {
+        if self.max_attempts.is_some_and(|m| attempt >= m) {
+            return None;
+        }
+        let factor = self.multiplier.max(1.0).powi(attempt.min(64) as i32);
+        let d =
+            Duration::try_from_secs_f32(self.initial.as_secs_f32() * factor).unwrap_or(self.max);
+        Some(d.min(self.max))
+    }
+}
+
+/// Online histogram of observed angles, see `AngleClient::histogram()`.
+/// Values outside `lo..=hi` are clamped into the first/last bin; over an
+/// empty or non-finite range nothing is counted.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Histogram {
+    pub lo: f32,
+    pub hi: f32,
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    /// `bins` equal-width bins over `range` (at least one bin).
+    pub fn new(range: RangeInclusive<f32>, bins: usize) -> Self {
+        Self {
+            lo: *range.start(),
+            hi: *range.end(),
+            counts: vec![0; bins.max(1)],
+        }
+    }
+
+    pub fn add(&mut self, v: f32) {
+        let ranged = self.lo.is_finite() && self.hi.is_finite() && self.lo < self.hi;
+        if !v.is_finite() || !ranged {
+            return;
+        }
+        let n = self.counts.len();
+        let t = ((v - self.lo) / (self.hi - self.lo)).clamp(0.0, 1.0);
+        let i = ((t * n as f32) as usize).min(n - 1);
+        self.counts[i] += 1;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Value range covered by bin `i`.
+    pub fn bin_range(&self, i: usize) -> RangeInclusive<f32> {
+        let w = (self.hi - self.lo) / self.counts.len() as f32;
+        (self.lo + w * i as f32)..=(self.lo + w * (i + 1) as f32)
+    }
+
+    /// Center of the most populated bin ("where does this user usually work?").
+    pub fn mode(&self) -> Option<f32> {
+        let (i, c) = self
+            .counts
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, c)| (**c, std::cmp::Reverse(*i)))?;
+        (*c > 0).then(|| {
+            let r = self.bin_range(i);
+            (r.start() + r.end()) / 2.0
+        })
+    }
+
+    /// Upper edge of the bin containing the `p` quantile (0..1).
+    pub fn percentile(&self, p: f32) -> Option<f32> {
+        let total = self.total();
+        if total == 0 {
+            return None;
+        }
+        let target = ((p.clamp(0.0, 1.0) as f64) * total as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, c) in self.counts.iter().enumerate() {
+            seen += c;
+            if seen >= target {
+                return Some(*self.bin_range(i).end());
+            }
+        }
+        Some(self.hi)
+    }
+
+    /// True when `other` has the same range and bin count.
+    pub fn same_layout(&self, other: &Histogram) -> bool {
+        self.lo == other.lo && self.hi == other.hi && self.counts.len() == other.counts.len()
+    }
 }
@@ -0,0 +1,444 @@
+//! Sampling logic for the Windows sensor backends, written against the
+//! `WinSensor` trait rather than WinRT directly. On Windows (feature
+//! `win_sensors`) `open()` plugs in the real hinge, inclinometer, and light
+//! sensors; tests and custom integrations can plug in their own on any
+//! platform.
+
+use crate::filters::{self, Baseline, Filter, OutlierRejection, RollingVariance, Smoother, Stages};
+use crate::types::Velocity;
+use crate::{
+    AngleDevice, AngleSample, AngleStream, Backoff, DeviceInfo, LightSample, Result, Source,
+    TaskGuard,
+    convention::{self, Seam},
+};
+use futures_util::{StreamExt, stream::BoxStream};
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::Instant,
+};
+use tokio::{
+    sync::{Notify, broadcast},
+    time::{self, Duration},
+};
+use tokio_stream::wrappers::BroadcastStream;
+
+/// The sensor calls `WinAngle` makes.
+pub trait WinSensor: Send + Sync + 'static {
+    /// Current raw value: degrees for hinge/tilt, lux for light. `Ok(None)`
+    /// means no reading is available yet; `Err` means the read failed and
+    /// the sampler waits per its `Backoff`.
+    fn read(&self) -> Result<Option<f32>>;
+
+    /// The OS's identifier for the sensor, if any; becomes
+    /// `win:<id>` as the device's `AngleDevice::hardware_id`.
+    fn device_id(&self) -> Option<String> {
+        None
+    }
+
+    /// For sensors that push readings (WinRT `ReadingChanged`): ask for one
+    /// about every `interval`, call `notify.notify_one()` on each, and
+    /// return `Ok(true)`. The sampler then reads once per notification
+    /// instead of polling. The default returns `Ok(false)`.
+    fn watch(&self, _interval: Duration, _notify: Arc<Notify>) -> Result<bool> {
+        Ok(false)
+    }
+
+    /// How long a watched sensor may stay quiet (a still lid) before it is
+    /// read anyway; zero reads it only when it reports, after one read at
+    /// start. The default is `WinReporting::default().quiet_read`.
+    fn quiet_read(&self) -> Duration {
+        QUIET_READ
+    }
+}
+
+/// Default `WinReporting::quiet_read`: often enough that the client does
+/// not report a still lid stale.
+const QUIET_READ: Duration = Duration::from_millis(500);
+
+/// How much a WinRT sensor's reading must change, and how often at most it
+/// reports, see `OpenConfig::win_reporting`. The default reports every
+/// change at the sample rate, and re-reads a still lid twice a second.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(default)
+)]
+pub struct WinReporting {
+    /// Degrees the hinge angle or inclinometer pitch must move before the
+    /// sensor reports; drivers raise it to their own minimum.
+    pub threshold_deg: f32,
+    /// Illuminance change, in percent, before the light sensor reports.
+    pub threshold_lux_percent: f32,
+    /// Shortest time between reports, whatever the sample rate; zero
+    /// follows the sample rate.
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub min_interval: Duration,
+    /// How long a sensor may go without reporting before it is read anyway.
+    /// Zero reads only on reports: a still lid then sends no samples, so
+    /// `AngleClient::health` reads `Stale` until it moves.
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_ms"))]
+    pub quiet_read: Duration,
+}
+
+impl Default for WinReporting {
+    fn default() -> Self {
+        Self {
+            threshold_deg: 0.0,
+            threshold_lux_percent: 0.0,
+            min_interval: Duration::ZERO,
+            quiet_read: QUIET_READ,
+        }
+    }
+}
+
+impl WinReporting {
+    /// 1°, 10%, at most ten reports a second and no quiet reads: enough to
+    /// follow a lid, without waking for every jitter or for a still one.
+    pub fn low_power() -> Self {
+        Self {
+            threshold_deg: 1.0,
+            threshold_lux_percent: 10.0,
+            min_interval: Duration::from_millis(100),
+            quiet_read: Duration::ZERO,
+        }
+    }
+
+    /// The report interval to ask for when sampling every `period`.
+    pub fn interval(&self, period: Duration) -> Duration {
+        period.max(self.min_interval)
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        self.threshold_deg.is_finite()
+            && self.threshold_deg >= 0.0
+            && self.threshold_lux_percent.is_finite()
+            && self.threshold_lux_percent >= 0.0
+    }
+}
+
+/// Which Windows sensor a `WinSensor` stands for; picks the source tag and
+/// how raw values are conditioned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WinSensorKind {
+    Hinge,
+    Tilt,
+    Light,
+    /// A two-panel device's `HingeState`, read as `HingeState::angle_deg`
+    /// at no more than `POSTURE_CONFIDENCE`.
+    Posture,
+    /// A hinge angle computed from lid and base accelerometers
+    /// (`convention::hinge_from_gravity`); reported as `Source::WinHinge`.
+    AccelHinge,
+    /// A hinge exposed only as a custom sensor (`quirks::WinCustomHinge`);
+    /// reported as `Source::WinHinge`.
+    CustomHinge,
+}
+
+impl WinSensorKind {
+    pub fn source(self) -> Source {
+        match self {
+            WinSensorKind::Hinge | WinSensorKind::AccelHinge | WinSensorKind::CustomHinge => {
+                Source::WinHinge
+            }
+            WinSensorKind::Tilt => Source::WinTilt,
+            WinSensorKind::Light => Source::WinALS,
+            WinSensorKind::Posture => Source::WinPosture,
+        }
+    }
+
+    fn note(self) -> &'static str {
+        match self {
+            WinSensorKind::Hinge => "win_hinge",
+            WinSensorKind::Tilt => "win_tilt",
+            WinSensorKind::Light => "win_als",
+            WinSensorKind::Posture => "win_posture",
+            WinSensorKind::AccelHinge => "win_accel_hinge",
+            WinSensorKind::CustomHinge => "win_custom_hinge",
+        }
+    }
+
+    fn min_hz(self) -> f32 {
+        match self {
+            WinSensorKind::Light => 10.0,
+            _ => 20.0,
+        }
+    }
+
+    /// Variance weight in the confidence estimate.
+    fn variance_gain(self) -> f32 {
+        match self {
+            WinSensorKind::Hinge | WinSensorKind::CustomHinge | WinSensorKind::Posture => 0.02,
+            WinSensorKind::Tilt | WinSensorKind::AccelHinge => 0.05,
+            WinSensorKind::Light => 20.0,
+        }
+    }
+
+    /// Cap on the confidence estimate.
+    fn max_confidence(self) -> f32 {
+        match self {
+            WinSensorKind::Posture => POSTURE_CONFIDENCE,
+            _ => 1.0,
+        }
+    }
+}
+
+/// Confidence of `Source::WinPosture` samples: a posture is a bucket (see
+/// `HingeState::angle_deg`), so a steady one says little about the exact angle.
+pub const POSTURE_CONFIDENCE: f32 = 0.3;
+
+/// Hinge state of a two-panel (foldable) device, as Windows reports it
+/// through `TwoPanelHingedDevicePosturePreview`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HingeState {
+    Closed,
+    /// Panels facing each other, like an open book or laptop.
+    Concave,
+    Flat,
+    /// Panels facing away from each other, like a tent.
+    Convex,
+    /// Folded all the way back.
+    Full,
+}
+
+impl HingeState {
+    /// From the WinRT `HingeState` value; `None` for `Unknown` (0) and
+    /// values this version does not know.
+    pub fn from_raw(v: i32) -> Option<Self> {
+        match v {
+            1 => Some(HingeState::Closed),
+            2 => Some(HingeState::Concave),
+            3 => Some(HingeState::Flat),
+            4 => Some(HingeState::Convex),
+            5 => Some(HingeState::Full),
+            _ => None,
+        }
+    }
+
+    /// A representative lid angle for the state in the `convention` frame.
+    /// The device reports no finer angle, so the true one can be anywhere in
+    /// the bucket, e.g. 10°–170° for `Concave`.
+    pub fn angle_deg(self) -> f32 {
+        match self {
+            HingeState::Closed => 0.0,
+            HingeState::Concave => 90.0,
+            HingeState::Flat => 180.0,
+            HingeState::Convex => 270.0,
+            HingeState::Full => 360.0,
+        }
+    }
+}
+
+pub struct WinAngle {
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    /// Set for `Light` sensors only.
+    lux: Option<broadcast::Sender<LightSample>>,
+    task: TaskGuard,
+    stages: Arc<Mutex<Stages>>,
+    conf: Arc<Mutex<f32>>,
+    src: Source,
+    note: &'static str,
+    hardware_id: Option<String>,
+}
+
+impl WinAngle {
+    /// Sample `sensor` at `hz`: on each reading it pushes if it can be
+    /// watched (`WinSensor::watch`), else by polling. Must be called from
+    /// within a Tokio runtime.
+    pub fn with_sensor(
+        kind: WinSensorKind,
+        sensor: impl WinSensor,
+        hz: f32,
+        backoff: Backoff,
+    ) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _rx) = broadcast::channel::<AngleSample>(256);
+        let lux =
+            matches!(kind, WinSensorKind::Light).then(|| broadcast::channel::<LightSample>(256).0);
+        let stages = Arc::new(Mutex::new(Stages::default()));
+        let conf = Arc::new(Mutex::new(0.2f32));
+        let hardware_id = sensor.device_id().map(|id| format!("win:{id}"));
+        let period = Duration::from_secs_f32(1.0 / hz.max(kind.min_hz()));
+        let notify = Arc::new(Notify::new());
+        // A sensor that cannot be watched is polled.
+        let watched = sensor.watch(period, notify.clone()).unwrap_or(false);
+        let quiet_read = sensor.quiet_read();
+        let sensor = Arc::new(sensor);
+
+        let latest_c = latest.clone();
+        let tx_c = tx.clone();
+        let lux_c = lux.clone();
+        let stages_c = stages.clone();
+        let conf_c = conf.clone();
+
+        let task = TaskGuard::supervised(backoff, move || {
+            let latest_c = latest_c.clone();
+            let tx_c = tx_c.clone();
+            let lux_c = lux_c.clone();
+            let stages_c = stages_c.clone();
+            let conf_c = conf_c.clone();
+            let sensor = sensor.clone();
+            let notify = notify.clone();
+            async move {
+                let mut interval = time::interval(period);
+                let mut variance = RollingVariance::default();
+                let mut smoother = Smoother::default();
+                let mut velocity = Velocity::default();
+                let mut baseline = Baseline::new(10.0);
+                // Tilt goes all the way round: follow it across 0°/360°.
+                let mut seam = matches!(kind, WinSensorKind::Tilt).then(Seam::default);
+                let mut attempt = 0u32;
+                let mut started = false;
+
+                loop {
+                    if !watched {
+                        interval.tick().await;
+                    } else if quiet_read.is_zero() {
+                        // One read up front, so a still lid has a sample.
+                        if std::mem::replace(&mut started, true) {
+                            notify.notified().await;
+                        }
+                    } else {
+                        let _ = time::timeout(quiet_read, notify.notified()).await;
+                    }
+
+                    let raw = match sensor.read() {
+                        Ok(raw) => {
+                            attempt = 0;
+                            raw
+                        }
+                        Err(_) => {
+                            // Sensor read failed: wait per the backoff policy.
+                            let Some(d) = backoff.delay(attempt) else {
+                                return;
+                            };
+                            attempt += 1;
+                            time::sleep(d).await;
+                            continue;
+                        }
+                    };
+                    let Some(raw) = raw else { continue };
+
+                    let value = match kind {
+                        // sanity check (0..180 typical, but don’t crash if exotic)
+                        WinSensorKind::Hinge | WinSensorKind::CustomHinge
+                            if !(-5.0..=365.0).contains(&raw) =>
+                        {
+                            continue;
+                        }
+                        WinSensorKind::Hinge
+                        | WinSensorKind::CustomHinge
+                        | WinSensorKind::Posture
+                        | WinSensorKind::AccelHinge => raw,
+                        WinSensorKind::Tilt => convention::normalize(kind.source(), raw),
+                        // high-pass-ish, normalize to 0..1 “bellows”
+                        WinSensorKind::Light => {
+                            if let Some(lux) = &lux_c {
+                                let _ = lux.send(LightSample {
+                                    lux: raw,
+                                    timestamp: Instant::now(),
+                                    source: kind.source(),
+                                });
+                            }
+                            filters::bellows(baseline.update(raw), 0.02)
+                        }
+                    };
+
+                    let now = Instant::now();
+                    let unwrapped = seam.as_mut().map_or(value, |w| w.unwrap(value));
+                    let Some(s) = smoother.apply(
+                        *stages_c.lock().unwrap_or_else(PoisonError::into_inner),
+                        unwrapped,
+                        now,
+                    ) else {
+                        continue;
+                    };
+
+                    // confidence from variance
+                    let var = variance.push(s);
+                    let stability =
+                        filters::stability(var, kind.variance_gain()).min(kind.max_confidence());
+                    *conf_c.lock().unwrap_or_else(PoisonError::into_inner) = stability;
+
+                    let sample = AngleSample {
+                        angle_deg: if seam.is_some() {
+                            convention::wrap(s)
+                        } else {
+                            s
+                        },
+                        raw: value,
+                        timestamp: now,
+                        source: kind.source(),
+                        confidence: stability,
+                        velocity_deg_s: velocity.update(unwrapped, now),
+                    };
+                    *latest_c.lock().unwrap_or_else(PoisonError::into_inner) = Some(sample);
+                    let _ = tx_c.send(sample);
+                }
+            }
+        });
+
+        Self {
+            latest,
+            tx,
+            lux,
+            task,
+            stages,
+            conf,
+            src: kind.source(),
+            note: kind.note(),
+            hardware_id,
+        }
+    }
+}
+
+impl AngleDevice for WinAngle {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+    fn subscribe_lux(&self) -> BoxStream<'static, LightSample> {
+        match &self.lux {
+            Some(lux) => BroadcastStream::new(lux.subscribe())
+                .filter_map(|it| async move { it.ok() })
+                .boxed(),
+            None => futures_util::stream::empty().boxed(),
+        }
+    }
+    fn set_smoothing(&self, alpha: f32) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = Filter::Ema { alpha };
+    }
+    fn set_filter(&self, filter: Filter) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .filter = filter;
+    }
+    fn set_outlier_rejection(&self, outliers: Option<OutlierRejection>) {
+        self.stages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .outliers = outliers;
+    }
+    fn confidence(&self) -> f32 {
+        *self.conf.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(self.src, self.note)
+    }
+    fn hardware_id(&self) -> Option<String> {
+        self.hardware_id.clone()
+    }
+
+    fn restarts(&self) -> BoxStream<'static, u32> {
+        self.task.restarts()
+    }
+}
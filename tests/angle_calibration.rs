@@ -0,0 +1,282 @@
+//! Multi-point reading-to-degrees calibration.
+
+use booklid_rust::calibrate::{AlsModel, AngleCalibration, CalibrationPoint};
+use booklid_rust::{OpenConfig, SampleRate, Source};
+
+fn point(raw: f32, angle_deg: f32) -> CalibrationPoint {
+    CalibrationPoint { raw, angle_deg }
+}
+
+#[test]
+fn fit_interpolates_and_extends_the_end_segments() {
+    // Out of order on purpose: `fit` sorts by reading.
+    let cal = AngleCalibration::fit(
+        Source::HingeFeature,
+        &[point(200.0, 180.0), point(10.0, 0.0), point(100.0, 90.0)],
+    )
+    .expect("fit");
+    assert_eq!(cal.points[0].raw, 10.0);
+    assert_eq!(cal.apply(10.0), 0.0);
+    assert_eq!(cal.apply(55.0), 45.0);
+    assert_eq!(cal.apply(150.0), 135.0);
+    assert_eq!(cal.apply(0.0), -10.0);
+    assert_eq!(cal.apply(210.0), 189.0);
+    assert_eq!(cal.slope_at(150.0), 0.9);
+
+    // A sensor that counts down as the lid opens.
+    let reversed = AngleCalibration::fit(
+        Source::HingeFeature,
+        &[point(0.0, 180.0), point(180.0, 0.0)],
+    )
+    .expect("fit");
+    assert_eq!(reversed.apply(45.0), 135.0);
+}
+
+#[test]
+fn calibration_tied_to_hardware_only_matches_that_sensor() {
+    let cal = AngleCalibration::fit(
+        Source::HingeFeature,
+        &[point(0.0, 0.0), point(180.0, 180.0)],
+    )
+    .expect("fit");
+    // Not tied to a sensor: only sensors that cannot be told apart.
+    assert!(cal.matches(Source::HingeFeature, None));
+    assert!(!cal.matches(Source::HingeFeature, Some("hid:05ac:8104")));
+    assert!(!cal.matches(Source::WinHinge, None));
+
+    let cal = cal.for_hardware(Some("hid:05ac:8104:A".into()));
+    assert!(cal.matches(Source::HingeFeature, Some("hid:05ac:8104:A")));
+    assert!(!cal.matches(Source::HingeFeature, Some("hid:05ac:8104:B")));
+    // Nor does a backend without an id (HID before its first report) take it.
+    assert!(!cal.matches(Source::HingeFeature, None));
+}
+
+#[test]
+fn fit_rejects_unusable_points() {
+    let hinge = Source::HingeFeature;
+    assert!(AngleCalibration::fit(hinge, &[point(0.0, 0.0)]).is_err());
+    assert!(AngleCalibration::fit(hinge, &[point(5.0, 0.0), point(5.0, 90.0)]).is_err());
+    assert!(
+        AngleCalibration::fit(
+            hinge,
+            &[point(0.0, 0.0), point(50.0, 120.0), point(100.0, 90.0)]
+        )
+        .is_err()
+    );
+    assert!(AngleCalibration::fit(hinge, &[point(0.0, 0.0), point(f32::NAN, 90.0)]).is_err());
+    assert!(AngleCalibration::fit(Source::ALS, &[point(0.0, 0.0), point(1.0, 180.0)]).is_err());
+
+    let broken = AngleCalibration {
+        source: hinge,
+        hardware_id: None,
+        points: vec![point(0.0, 0.0)],
+    };
+    assert!(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .calibration(broken)
+            .validate()
+            .is_err()
+    );
+}
+
+#[test]
+fn als_model_estimates_angle_and_uncertainty() {
+    // Two readings per angle; the pair at 0.5 disagrees by 20°.
+    let points = [
+        point(0.1, 0.0),
+        point(0.1, 0.0),
+        point(0.5, 80.0),
+        point(0.5, 100.0),
+        point(0.9, 180.0),
+        point(0.9, 180.0),
+    ];
+    let model = AlsModel::fit(Source::ALS, &points).expect("fit");
+    // Equal readings end up in one bin.
+    assert_eq!(model.bins.len(), 3);
+    assert_eq!(model.bins[1].samples, 2);
+    assert_eq!(model.bins[1].spread_deg, 10.0);
+    // The middle bin sits 0° off the line through its neighbours.
+    assert!(model.interpolation_error_deg.abs() < 1e-3);
+
+    let mid = model.estimate(0.5);
+    assert!((mid.angle_deg - 90.0).abs() < 1e-3, "{mid:?}");
+    assert!((mid.uncertainty_deg - 10.0).abs() < 1e-3, "{mid:?}");
+    let q = model.estimate(0.3);
+    assert!((q.angle_deg - 45.0).abs() < 1e-3, "{q:?}");
+    assert!((q.uncertainty_deg - 5.0).abs() < 1e-3, "{q:?}");
+    // Past the trained range: held at the end, less certain.
+    let past = model.estimate(1.0);
+    assert_eq!(past.angle_deg, 180.0);
+    assert!(past.uncertainty_deg > 20.0, "{past:?}");
+
+    assert!(AlsModel::fit(Source::HingeFeature, &points).is_err());
+    assert!(AlsModel::fit(Source::ALS, &[point(0.4, 0.0), point(0.4, 90.0)]).is_err());
+    assert!(AlsModel::fit(Source::ALS, &[point(f32::NAN, 0.0), point(0.4, 90.0)]).is_err());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn als_angles_estimates_only_the_models_source() {
+    use booklid_rust::{AngleSample, AngleStreamExt};
+    use futures_util::StreamExt;
+    use std::time::Instant;
+
+    let model = AlsModel::fit(Source::ALS, &[point(0.0, 0.0), point(1.0, 180.0)]).expect("fit");
+    // Estimated from the unsmoothed reading, as the model is fitted.
+    let sample = |v: f32, source: Source| AngleSample::new(0.9, source, Instant::now()).raw(v);
+    let got: Vec<_> = futures_util::stream::iter([
+        sample(0.5, Source::ALS),
+        sample(0.5, Source::LinuxALS),
+        sample(0.25, Source::ALS),
+    ])
+    .als_angles(model)
+    .map(|(_, est)| est.angle_deg)
+    .collect()
+    .await;
+    assert_eq!(got, vec![90.0, 45.0]);
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test(flavor = "current_thread")]
+async fn client_applies_calibration_to_its_source() {
+    use booklid_rust::open_with_config;
+    use futures_util::StreamExt;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock])
+            .smoothing(1.0),
+    )
+    .await
+    .expect("open mock");
+
+    // The mock sweeps 75..115°, so its median over a few samples is in there.
+    let p = timeout(Duration::from_secs(3), dev.calibration_point(90.0, 5))
+        .await
+        .expect("samples")
+        .expect("point");
+    assert_eq!(p.angle_deg, 90.0);
+    assert!((70.0..=120.0).contains(&p.raw), "{p:?}");
+
+    // Doubles every reading.
+    let cal =
+        AngleCalibration::fit(Source::Mock, &[point(0.0, 0.0), point(100.0, 200.0)]).expect("fit");
+    dev.set_calibration(cal);
+    assert_eq!(dev.calibrations().len(), 1);
+    let mut s = dev.subscribe();
+    for _ in 0..5 {
+        let got = timeout(Duration::from_secs(1), s.next())
+            .await
+            .expect("sample")
+            .unwrap();
+        assert!(
+            (got.angle_deg - 2.0 * got.raw).abs() < 1e-3,
+            "{} vs raw {}",
+            got.angle_deg,
+            got.raw
+        );
+    }
+
+    dev.clear_calibration(Source::Mock);
+    let got = timeout(Duration::from_secs(1), s.next())
+        .await
+        .expect("sample")
+        .unwrap();
+    assert_eq!(got.angle_deg, got.raw);
+
+    // The mock reports degrees: it is no ALS to train.
+    let err = dev.train_als_model(&dev, 10).await;
+    assert!(err.is_err());
+}
+
+/// An ALS client from a registered backend, next to a mock hinge.
+#[cfg(feature = "mock")]
+mod als_client {
+    use super::*;
+    use booklid_rust::{
+        AngleDevice, AngleSample, AngleStream, BackendContext, BackendFactory, DeviceInfo, Result,
+        open_with_config, register_backend,
+    };
+    use futures_util::{FutureExt, StreamExt, future::BoxFuture};
+    use std::time::{Duration, Instant};
+    use tokio::time::timeout;
+
+    /// An ALS reading a slowly brightening room (as `WinALS`, which has no
+    /// built-in backend off Windows).
+    struct Glow;
+
+    fn glow(v: f32) -> AngleSample {
+        AngleSample::new(v, Source::WinALS, Instant::now())
+    }
+
+    impl AngleDevice for Glow {
+        fn latest(&self) -> Option<AngleSample> {
+            Some(glow(0.5))
+        }
+        fn subscribe(&self) -> AngleStream {
+            futures_util::stream::unfold(0u32, |n| async move {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+                Some((glow((n % 100) as f32 / 100.0), n + 1))
+            })
+            .boxed()
+        }
+        fn set_smoothing(&self, _alpha: f32) {}
+        fn confidence(&self) -> f32 {
+            1.0
+        }
+        fn info(&self) -> DeviceInfo {
+            DeviceInfo::new(Source::WinALS, "glow")
+        }
+    }
+
+    struct GlowFactory;
+
+    impl BackendFactory for GlowFactory {
+        fn source(&self) -> Source {
+            Source::WinALS
+        }
+        fn open(&self, _ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+            async { Ok(Box::new(Glow) as Box<dyn AngleDevice>) }.boxed()
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn als_readings_stay_off_the_angle_stream() {
+        register_backend(GlowFactory);
+        let cfg = OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false);
+        let als = open_with_config(cfg.clone().prefer(vec![Source::WinALS]))
+            .await
+            .expect("open als");
+        assert_eq!(als.info().source, Source::WinALS);
+
+        let got = timeout(Duration::from_secs(1), als.subscribe_als().next())
+            .await
+            .expect("reading")
+            .unwrap();
+        assert_eq!(got.source, Source::WinALS);
+        assert!(
+            timeout(Duration::from_millis(100), als.subscribe().next())
+                .await
+                .is_err(),
+            "light is no angle"
+        );
+        assert!(als.latest().is_none());
+
+        let hinge = open_with_config(cfg.prefer(vec![Source::Mock]))
+            .await
+            .expect("open mock");
+        hinge.subscribe().next().await;
+        let model = timeout(Duration::from_secs(3), als.train_als_model(&hinge, 20))
+            .await
+            .expect("trained in time")
+            .expect("model");
+        assert_eq!(model.source, Source::WinALS);
+    }
+}
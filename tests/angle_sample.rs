@@ -0,0 +1,102 @@
+use booklid_rust::{AngleSample, Source};
+use std::time::Instant;
+
+fn sample(angle_deg: f32, source: Source) -> AngleSample {
+    AngleSample::new(angle_deg, source, Instant::now())
+}
+
+#[test]
+fn degrees_convert_and_normalize() {
+    let s = sample(90.0, Source::HingeFeature);
+    assert!(s.is_degrees());
+    let r = s.as_radians().expect("degrees");
+    assert!((r - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+    assert!((s.normalized(0.0..=180.0) - 0.5).abs() < 1e-6);
+    assert_eq!(s.normalized(100.0..=180.0), 0.0);
+}
+
+#[test]
+fn als_is_not_degrees() {
+    let s = sample(0.4, Source::ALS);
+    assert!(!s.is_degrees());
+    assert!(s.as_radians().is_none());
+    assert_eq!(s.normalized(0.0..=180.0), 0.4);
+}
+
+#[test]
+fn clamshell_is_a_switch_not_degrees() {
+    let s = sample(1.0, Source::MacClamshell);
+    assert!(!s.is_degrees());
+    assert!(s.source.is_lid_switch());
+    assert!(!Source::HingeFeature.is_lid_switch());
+    assert!(Source::WinLidSwitch.is_lid_switch() && !Source::WinLidSwitch.is_degrees());
+    assert!(Source::LinuxLidSwitch.is_lid_switch() && !Source::LinuxLidSwitch.is_degrees());
+    assert!(Source::LinuxUPowerLid.is_lid_switch() && !Source::LinuxUPowerLid.is_degrees());
+    assert!(Source::LinuxAcpiLid.is_lid_switch() && !Source::LinuxAcpiLid.is_degrees());
+}
+
+#[test]
+fn output_map_scales_clamps_and_inverts() {
+    use booklid_rust::OutputMap;
+    let half = OutputMap::new(0.0..=360.0, 0.0..=180.0);
+    assert_eq!(half.apply(270.0), 135.0);
+    assert_eq!(half.apply(400.0), 180.0);
+    assert!((half.clone().unclamped().apply(400.0) - 200.0).abs() < 1e-4);
+    assert_eq!(OutputMap::clamp_to(0.0..=180.0).apply(-5.0), 0.0);
+    assert_eq!(OutputMap::new(0.0..=180.0, 1.0..=0.0).apply(45.0), 0.75);
+    // Normalized sources are left alone.
+    assert_eq!(half.map_sample(sample(0.4, Source::ALS)).angle_deg, 0.4);
+}
+
+#[test]
+fn corrections_carry_velocity_along() {
+    use booklid_rust::{AxisTransform, OutputMap};
+    let moving = sample(90.0, Source::HingeFeature).velocity_deg_s(40.0);
+    let mirrored = AxisTransform {
+        invert: true,
+        offset_deg: 360.0,
+    };
+    assert_eq!(mirrored.map_sample(moving).velocity_deg_s, -40.0);
+    let half = OutputMap::new(0.0..=360.0, 0.0..=180.0);
+    assert_eq!(half.map_sample(moving).velocity_deg_s, 20.0);
+    // Pinned at the end of the range: not moving.
+    let clamped = OutputMap::clamp_to(0.0..=60.0);
+    assert_eq!(clamped.map_sample(moving).velocity_deg_s, 0.0);
+}
+
+#[test]
+fn axis_transform_and_quirk_lookup() {
+    use booklid_rust::AxisTransform;
+    use booklid_rust::quirks::{Quirk, lookup_in};
+    let mirrored = AxisTransform {
+        invert: true,
+        offset_deg: 360.0,
+    };
+    assert_eq!(mirrored.apply(100.0), 260.0);
+    assert_eq!(mirrored.map_sample(sample(0.4, Source::ALS)).angle_deg, 0.4);
+
+    let table = [Quirk {
+        model: "ACME Flip".into(),
+        source: Source::LinuxTilt,
+        axis: mirrored,
+    }];
+    assert_eq!(
+        lookup_in(&table, "ACME Flip 14", Source::LinuxTilt),
+        Some(mirrored)
+    );
+    assert_eq!(lookup_in(&table, "ACME Flip 14", Source::LinuxALS), None);
+    assert_eq!(lookup_in(&table, "Other 14", Source::LinuxTilt), None);
+}
+
+#[test]
+fn capabilities_follow_source() {
+    use booklid_rust::Capabilities;
+    let hinge = Capabilities::for_source(Source::HingeFeature);
+    assert!(hinge.true_degrees && !hinge.normalized);
+    let als = Capabilities::for_source(Source::LinuxALS);
+    assert!(!als.true_degrees && als.normalized);
+    let tilt = Capabilities::for_source(Source::LinuxTilt);
+    assert!(!tilt.true_degrees && !tilt.normalized);
+    assert!(hinge.velocity && als.velocity && tilt.velocity);
+    assert!(!Capabilities::for_source(Source::LinuxLidSwitch).velocity);
+}
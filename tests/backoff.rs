@@ -0,0 +1,18 @@
+//! Reconnect backoff schedules.
+
+use booklid_rust::Backoff;
+use std::time::Duration;
+
+#[test]
+fn backoff_grows_caps_and_gives_up() {
+    let b = Backoff {
+        initial: Duration::from_millis(100),
+        multiplier: 2.0,
+        max: Duration::from_millis(350),
+        max_attempts: Some(4),
+    };
+    let ms: Vec<_> = (0..5).map(|n| b.delay(n).map(|d| d.as_millis())).collect();
+    assert_eq!(ms, [Some(100), Some(200), Some(350), Some(350), None]);
+    let c = Backoff::constant(Duration::from_millis(50));
+    assert_eq!(c.delay(1000), Some(Duration::from_millis(50)));
+}
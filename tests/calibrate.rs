@@ -0,0 +1,145 @@
+//! Confidence calibration against a reference trace.
+#![cfg(feature = "record")]
+
+use booklid_rust::calibrate::{REFERENCE_CONFIDENCE, REFERENCE_ERROR_DEG, fit_als, fit_confidence};
+use booklid_rust::record::{FORMAT_VERSION, Header, RecordedSample, Recording};
+use booklid_rust::{OpenConfig, SampleRate, Source, open_replay};
+use futures_util::StreamExt;
+use std::time::Duration;
+
+fn trace(source: Source, started_utc_ms: u64, samples: Vec<RecordedSample>) -> Recording {
+    Recording {
+        header: Header {
+            version: FORMAT_VERSION,
+            source,
+            started_utc_ms,
+        },
+        samples,
+        annotations: Vec::new(),
+    }
+}
+
+fn truth(ms: u64) -> f32 {
+    100.0 + 20.0 * (ms as f32 / 1000.0).sin()
+}
+
+/// A tilt trace whose error is 3° per unit of `1/c - 1`, recorded 500 ms
+/// after a 10 ms reference hinge trace started.
+fn pair() -> (Recording, Recording) {
+    let reference = (0..600)
+        .map(|i| RecordedSample {
+            offset: Duration::from_millis(i * 10),
+            angle_deg: truth(i * 10),
+            raw: None,
+            source: Source::HingeFeature,
+            confidence: 1.0,
+            velocity_deg_s: None,
+        })
+        .collect();
+    let tilt = (0..100u64)
+        .map(|i| {
+            let ms = i * 33;
+            let c = [0.9f32, 0.7, 0.5, 0.3][i as usize % 4];
+            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+            RecordedSample {
+                offset: Duration::from_millis(ms),
+                angle_deg: truth(ms + 500) + sign * 3.0 * (1.0 / c - 1.0),
+                raw: None,
+                source: Source::LinuxTilt,
+                confidence: c,
+                velocity_deg_s: None,
+            }
+        })
+        .collect();
+    (
+        trace(Source::LinuxTilt, 1_700_000_000_500, tilt),
+        trace(Source::HingeFeature, 1_700_000_000_000, reference),
+    )
+}
+
+#[test]
+fn fitted_scale_maps_reference_error_to_reference_confidence() {
+    let (tilt, hinge) = pair();
+    let fit = fit_confidence(&tilt, &hinge).expect("fit");
+    assert_eq!(fit.source, Source::LinuxTilt);
+    assert_eq!(fit.samples, 100);
+    // A sample off by the reference error had 1/c - 1 = 5/3 before.
+    let c = 1.0 / (1.0 + REFERENCE_ERROR_DEG / 3.0);
+    assert!(
+        (fit.apply(c) - REFERENCE_CONFIDENCE).abs() < 0.01,
+        "{fit:?}"
+    );
+    assert_eq!(fit.apply(1.0), 1.0);
+    assert_eq!(fit.apply(0.0), 0.0);
+}
+
+#[test]
+fn calibration_needs_degrees_overlap_and_varying_confidence() {
+    let (tilt, hinge) = pair();
+    let mut als = tilt.clone();
+    als.header.source = Source::LinuxALS;
+    assert!(fit_confidence(&als, &hinge).is_err());
+
+    let mut late = tilt.clone();
+    late.header.started_utc_ms += 60_000;
+    assert!(fit_confidence(&late, &hinge).is_err());
+
+    let mut flat = tilt;
+    flat.samples.iter_mut().for_each(|s| s.confidence = 1.0);
+    assert!(fit_confidence(&flat, &hinge).is_err());
+}
+
+#[tokio::test]
+async fn confidence_scale_applies_to_samples() {
+    let path = std::env::temp_dir().join(format!("booklid-cal-{}.jsonl", std::process::id()));
+    let mut text = String::from("{\"version\":2,\"source\":\"Mock\",\"started_utc_ms\":0}\n");
+    for i in 0..50 {
+        text += &format!(
+            "{{\"t_us\":{},\"angle_deg\":90.0,\"source\":\"Mock\",\"confidence\":0.5}}\n",
+            i * 20_000
+        );
+    }
+    std::fs::write(&path, text).unwrap();
+
+    let cfg = OpenConfig::new(SampleRate::hz(50.0))
+        .min_confidence(0.0)
+        .confidence_scale(Source::Mock, 0.25);
+    let replay = open_replay(&path, cfg).await.expect("open replay");
+    let s = replay.subscribe().next().await.unwrap();
+    assert!((s.confidence - 0.8).abs() < 1e-6, "{}", s.confidence);
+    assert!((replay.confidence() - 0.8).abs() < 1e-6);
+    assert!(
+        OpenConfig::default()
+            .confidence_scale(Source::Mock, f32::NAN)
+            .validate()
+            .is_err()
+    );
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn als_model_fits_against_the_reference_trace() {
+    let (_, hinge) = pair();
+    // A light sensor whose reading rises linearly over the hinge's 80..120°.
+    let als = (0..100u64)
+        .map(|i| {
+            let ms = i * 33;
+            RecordedSample {
+                offset: Duration::from_millis(ms),
+                angle_deg: 0.0,
+                raw: Some((truth(ms + 500) - 80.0) / 40.0),
+                source: Source::LinuxALS,
+                confidence: 1.0,
+                velocity_deg_s: None,
+            }
+        })
+        .collect();
+    let als = trace(Source::LinuxALS, 1_700_000_000_500, als);
+    let model = fit_als(&als, &hinge).expect("fit");
+    assert_eq!(model.source, Source::LinuxALS);
+    let est = model.estimate(0.5);
+    assert!((est.angle_deg - 100.0).abs() < 1.0, "{est:?}");
+    assert!(est.uncertainty_deg < 2.0, "{est:?}");
+
+    assert!(fit_als(&hinge, &als).is_err());
+}
@@ -0,0 +1,85 @@
+#![cfg(all(feature = "serde", feature = "mock"))]
+
+//! Config files: loading and live reload into an open client.
+
+use booklid_rust::{Event, OpenConfig, OutputMap, SampleRate, Source, open_with_config};
+use futures_util::StreamExt;
+use std::fs;
+use tokio::time::{Duration, timeout};
+
+fn base() -> OpenConfig {
+    OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .persistence(false)
+        .attach_daemon(false)
+        .prefer(vec![Source::Mock])
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reload_applies_live_settings() {
+    let path = std::env::temp_dir().join(format!("booklid-cfg-{}.json", std::process::id()));
+    fs::write(&path, serde_json::to_string(&base()).unwrap()).unwrap();
+
+    let cfg = OpenConfig::from_file(&path).expect("load config");
+    assert_eq!(cfg.prefer_sources, vec![Source::Mock]);
+    let dev = open_with_config(cfg).await.expect("open mock");
+    let _watch = dev.watch_config(&path);
+    let mut events = dev.events();
+
+    // Map the mock's ~75..115° swing onto 0..1.
+    let updated = base()
+        .smoothing(1.0)
+        .output_map(OutputMap::new(0.0..=180.0, 0.0..=1.0));
+    fs::write(&path, serde_json::to_string_pretty(&updated).unwrap()).unwrap();
+
+    timeout(Duration::from_secs(5), async {
+        while let Some(e) = events.next().await {
+            if e == Event::ConfigReloaded {
+                return;
+            }
+        }
+        panic!("events ended");
+    })
+    .await
+    .expect("reloaded");
+    let s = timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample")
+        .unwrap();
+    assert!((0.0..=1.0).contains(&s.angle_deg), "{}", s.angle_deg);
+
+    // A broken file is ignored; the previous settings stay.
+    fs::write(&path, "{ not json").unwrap();
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let s = dev.subscribe().next().await.unwrap();
+    assert!((0.0..=1.0).contains(&s.angle_deg));
+    assert!(OpenConfig::from_file(&path).is_err());
+
+    fs::remove_file(&path).ok();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reload_applies_zones_to_running_streams() {
+    let path = std::env::temp_dir().join(format!("booklid-zones-{}.json", std::process::id()));
+    let cfg = base().zone("before", 0.0..=360.0);
+    fs::write(&path, serde_json::to_string(&cfg).unwrap()).unwrap();
+
+    let dev = open_with_config(OpenConfig::from_file(&path).unwrap())
+        .await
+        .expect("open mock");
+    let _watch = dev.watch_config(&path);
+    let mut zones = dev.subscribe_zones();
+    let first = timeout(Duration::from_secs(2), zones.next())
+        .await
+        .expect("zone");
+    assert_eq!(first.as_deref(), Some("before"));
+
+    let updated = base().zone("after", 0.0..=360.0);
+    fs::write(&path, serde_json::to_string(&updated).unwrap()).unwrap();
+    let next = timeout(Duration::from_secs(5), zones.next())
+        .await
+        .expect("zone");
+    assert_eq!(next.as_deref(), Some("after"));
+
+    fs::remove_file(&path).ok();
+}
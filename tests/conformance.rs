@@ -0,0 +1,133 @@
+//! The `conformance` suite against a built-in backend, a well-behaved
+//! third-party device, and a broken one.
+#![cfg(all(feature = "conformance", feature = "mock"))]
+
+use booklid_rust::conformance::{Violation, assert_conforms, builtin, check};
+use booklid_rust::{
+    AngleDevice, AngleSample, AngleStream, BackendContext, BackendFactory, DeviceInfo, Result,
+    Source,
+};
+use futures_util::{FutureExt, StreamExt, future::BoxFuture};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::BroadcastStream;
+
+#[tokio::test]
+async fn mock_backend_conforms() {
+    assert_conforms(&builtin(Source::Mock), 30.0).await;
+}
+
+#[tokio::test]
+async fn missing_builtin_fails_to_open() {
+    let report = check(&builtin(Source::Other("nope")), 30.0).await;
+    assert!(matches!(
+        report.violations[..],
+        [Violation::OpenFailed { .. }]
+    ));
+}
+
+/// A sawtooth sensor; `sloppy` skips smoothing, reports confidence 2.0, and
+/// never stops its sampler.
+struct Saw {
+    latest: Arc<Mutex<Option<AngleSample>>>,
+    tx: broadcast::Sender<AngleSample>,
+    alpha: Arc<Mutex<f32>>,
+    task: tokio::task::JoinHandle<()>,
+    sloppy: bool,
+}
+
+impl Saw {
+    fn spawn(hz: f32, sloppy: bool) -> Self {
+        let latest = Arc::new(Mutex::new(None));
+        let (tx, _) = broadcast::channel(64);
+        let alpha = Arc::new(Mutex::new(0.25f32));
+        let (latest_c, tx_c, alpha_c) = (latest.clone(), tx.clone(), alpha.clone());
+        let task = tokio::spawn(async move {
+            let mut tick = tokio::time::interval(Duration::from_secs_f32(1.0 / hz));
+            let mut smoothed: Option<f32> = None;
+            for i in 0u32.. {
+                tick.tick().await;
+                let raw = 90.0 + (i % 20) as f32;
+                let a = if sloppy {
+                    1.0
+                } else {
+                    *alpha_c.lock().unwrap()
+                };
+                let s = smoothed.map_or(raw, |p| p + a * (raw - p));
+                smoothed = Some(s);
+                let sample = AngleSample::new(s, Source::Other("saw"), Instant::now())
+                    .raw(raw)
+                    .confidence(if sloppy { 2.0 } else { 1.0 });
+                *latest_c.lock().unwrap() = Some(sample);
+                let _ = tx_c.send(sample);
+            }
+        });
+        Self {
+            latest,
+            tx,
+            alpha,
+            task,
+            sloppy,
+        }
+    }
+}
+
+impl Drop for Saw {
+    fn drop(&mut self) {
+        if !self.sloppy {
+            self.task.abort();
+        }
+    }
+}
+
+impl AngleDevice for Saw {
+    fn latest(&self) -> Option<AngleSample> {
+        *self.latest.lock().unwrap()
+    }
+    fn subscribe(&self) -> AngleStream {
+        BroadcastStream::new(self.tx.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+    fn set_smoothing(&self, alpha: f32) {
+        *self.alpha.lock().unwrap() = alpha;
+    }
+    fn confidence(&self) -> f32 {
+        self.latest().map_or(0.0, |s| s.confidence)
+    }
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(Source::Other("saw"), "saw")
+    }
+}
+
+struct SawFactory {
+    sloppy: bool,
+}
+
+impl BackendFactory for SawFactory {
+    fn source(&self) -> Source {
+        Source::Other("saw")
+    }
+    fn open(&self, ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        let sloppy = self.sloppy;
+        async move { Ok(Box::new(Saw::spawn(ctx.hz, sloppy)) as Box<dyn AngleDevice>) }.boxed()
+    }
+}
+
+#[tokio::test]
+async fn third_party_device_conforms() {
+    assert_conforms(&SawFactory { sloppy: false }, 40.0).await;
+}
+
+#[tokio::test]
+async fn violations_are_reported() {
+    let report = check(&SawFactory { sloppy: true }, 40.0).await;
+    assert!(!report.passed());
+    let v = &report.violations;
+    assert!(v.contains(&Violation::ConfidenceOutOfRange { value: 2.0 }));
+    assert!(v.contains(&Violation::SmoothingIgnored { alpha: 0.0 }));
+    assert!(v.contains(&Violation::StreamNotClosed));
+    assert!(v.iter().any(|v| matches!(v, Violation::TasksLeaked { .. })));
+    assert!(!v.iter().any(|v| matches!(v, Violation::Rate { .. })));
+}
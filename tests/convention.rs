@@ -0,0 +1,15 @@
+//! The shared hinge frame every source reports in.
+
+use booklid_rust::Source;
+use booklid_rust::convention::{from_pitch, normalize};
+
+#[test]
+fn tilt_readings_share_the_hinge_frame() {
+    assert_eq!(from_pitch(0.0), 180.0); // lid lying flat, face-up
+    assert_eq!(from_pitch(90.0), 90.0); // upright
+    assert_eq!(from_pitch(180.0), 0.0); // face-down: closed
+    assert_eq!(from_pitch(-20.0), 200.0); // tipped back past flat
+    assert_eq!(normalize(Source::LinuxTilt, 30.0), 150.0);
+    assert_eq!(normalize(Source::WinHinge, 30.0), 30.0);
+    assert_eq!(normalize(Source::ALS, 0.3), 0.3);
+}
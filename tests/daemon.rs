@@ -0,0 +1,144 @@
+#![cfg(all(unix, feature = "daemon", feature = "mock"))]
+
+// Separate test binary: attaching reads `BOOKLID_SOCKET`, which is process-global
+// (the plist test only checks that the variable is present).
+
+use booklid_rust::{
+    AngleDevice, BackendContext, BackendFactory, Backoff, LidEvent, LidEventConfig, LidSwitch,
+    LidSwitchAngle, LidSwitchState, OpenConfig, SampleRate, Source, daemon, open_with_config,
+    register_backend,
+};
+use futures_util::{FutureExt, StreamExt, future::BoxFuture};
+use std::os::unix::fs::PermissionsExt;
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, timeout};
+
+#[derive(Clone)]
+struct FakeSwitch(Arc<Mutex<LidSwitchState>>);
+
+impl LidSwitch for FakeSwitch {
+    fn read(&self) -> booklid_rust::Result<Option<LidSwitchState>> {
+        Ok(Some(*self.0.lock().unwrap()))
+    }
+}
+
+struct Clamshell(FakeSwitch);
+
+impl BackendFactory for Clamshell {
+    fn source(&self) -> Source {
+        Source::MacClamshell
+    }
+    fn open(
+        &self,
+        ctx: BackendContext,
+    ) -> BoxFuture<'static, booklid_rust::Result<Box<dyn AngleDevice>>> {
+        let switch = self.0.clone();
+        async move {
+            LidSwitchAngle::open(switch, Source::MacClamshell, ctx.hz, Backoff::default())
+                .map(|d| Box::new(d) as Box<dyn AngleDevice>)
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test]
+async fn open_attaches_to_running_daemon() {
+    let path = std::env::temp_dir().join(format!("booklid-test-{}.sock", std::process::id()));
+    // SAFETY: the only test in this binary.
+    unsafe { std::env::set_var("BOOKLID_SOCKET", &path) };
+
+    let cfg = OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true);
+    let owner = open_with_config(cfg.clone().attach_daemon(false))
+        .await
+        .expect("open mock");
+    let server = tokio::spawn(daemon::serve(owner.clone(), daemon::socket_path()));
+    while !path.exists() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // A config that refuses Mock is not handed the daemon's.
+    let refused = open_with_config(OpenConfig::new(SampleRate::hz(60.0))).await;
+    assert!(refused.map_or(true, |c| c.info().note != "daemon"));
+
+    // Nor is one attached to a socket others could have bound.
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o666)).unwrap();
+    let local = open_with_config(cfg.clone()).await.expect("open mock");
+    assert_ne!(local.info().note, "daemon");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    let remote = open_with_config(cfg.clone())
+        .await
+        .expect("attach to daemon");
+    assert_eq!(remote.info().note, "daemon");
+    assert_eq!(remote.info().source, Source::Mock);
+    let s = timeout(Duration::from_millis(750), remote.subscribe().next())
+        .await
+        .expect("sample from daemon");
+    assert!(s.is_some());
+
+    // Closing the owner stops the daemon and removes its socket.
+    owner.close();
+    timeout(Duration::from_secs(1), server)
+        .await
+        .expect("daemon stops")
+        .unwrap()
+        .unwrap();
+    assert!(!path.exists());
+
+    // A lid switch daemon forwards its states, which `subscribe()` leaves out.
+    let switch = FakeSwitch(Arc::new(Mutex::new(LidSwitchState::Open)));
+    register_backend(Clamshell(switch.clone()));
+    let owner = open_with_config(
+        cfg.clone()
+            .attach_daemon(false)
+            .persistence(false)
+            .prefer(vec![Source::MacClamshell]),
+    )
+    .await
+    .expect("open clamshell");
+    assert_eq!(owner.info().source, Source::MacClamshell);
+    let server = tokio::spawn(daemon::serve(owner.clone(), daemon::socket_path()));
+    while !path.exists() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let remote = open_with_config(cfg.clone().persistence(false))
+        .await
+        .expect("attach to daemon");
+    assert_eq!(remote.info().note, "daemon");
+    assert_eq!(remote.info().source, Source::MacClamshell);
+    let mut events = remote.subscribe_events(LidEventConfig::default());
+    timeout(Duration::from_secs(2), async {
+        while !remote.is_open() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("open state from daemon");
+    *switch.0.lock().unwrap() = LidSwitchState::Closed;
+    let closed = timeout(Duration::from_secs(2), async {
+        while let Some(e) = events.next().await {
+            if e == LidEvent::LidClosed {
+                return true;
+            }
+        }
+        false
+    })
+    .await;
+    assert_eq!(closed, Ok(true));
+    assert!(remote.is_closed());
+    owner.close();
+    timeout(Duration::from_secs(1), server)
+        .await
+        .expect("daemon stops")
+        .unwrap()
+        .unwrap();
+}
+
+#[test]
+fn launchd_plist_runs_daemon_subcommand() {
+    let plist = daemon::launchd_plist(std::path::Path::new("/usr/local/bin/booklid"));
+    assert!(plist.contains(daemon::LAUNCHD_LABEL));
+    assert!(plist.contains("<string>/usr/local/bin/booklid</string>"));
+    assert!(plist.contains("<string>daemon</string>"));
+    assert!(plist.contains("BOOKLID_SOCKET"));
+}
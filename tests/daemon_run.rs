@@ -0,0 +1,46 @@
+#![cfg(all(unix, feature = "daemon", feature = "mock"))]
+
+// Separate test binary: `run()` serves on `BOOKLID_SOCKET`, which is
+// process-global.
+
+use booklid_rust::{OpenConfig, SampleRate, Source, daemon, open_with_config};
+use futures_util::StreamExt;
+use tokio::time::{Duration, timeout};
+
+#[tokio::test]
+async fn run_serves_a_moving_angle() {
+    let path = std::env::temp_dir().join(format!("booklid-run-{}.sock", std::process::id()));
+    // SAFETY: the only test in this binary.
+    unsafe { std::env::set_var("BOOKLID_SOCKET", &path) };
+
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .prefer(vec![Source::Mock]);
+    let server = tokio::spawn(daemon::run(cfg));
+    while !path.exists() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let remote = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .smoothing(1.0),
+    )
+    .await
+    .expect("attach to daemon");
+    assert_eq!(remote.info().note, "daemon");
+    let angles: Vec<f32> = timeout(
+        Duration::from_secs(2),
+        remote.subscribe().take(30).map(|s| s.angle_deg).collect(),
+    )
+    .await
+    .expect("samples from daemon");
+    let (lo, hi) = angles
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(lo, hi), &a| (lo.min(a), hi.max(a)));
+    // The mock swings 20° around 95°; a frozen filter would serve one value.
+    assert!(hi - lo > 1.0, "served angle did not change: {angles:?}");
+
+    server.abort();
+    std::fs::remove_file(&path).ok();
+}
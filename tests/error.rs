@@ -0,0 +1,18 @@
+//! Stable error codes and names.
+
+use booklid_rust::{Error, Source};
+
+#[test]
+fn error_codes_are_stable() {
+    let e = Error::NoBackend { tried: vec![] };
+    assert_eq!((e.code(), e.code_name()), (5, "no_backend"));
+    let e = Error::PermissionDenied {
+        sensor: Source::HingeFeature,
+        hint: "add the entitlement".into(),
+    };
+    assert_eq!((e.code(), e.code_name()), (12, "permission_denied"));
+    assert!(e.to_string().contains("add the entitlement"));
+    let e: Error = serde_json::from_str::<u8>("x").unwrap_err().into();
+    assert_eq!(e.code_name(), "serde");
+    assert!(std::error::Error::source(&e).is_some());
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "arrow")]
+
+use booklid_rust::Source;
+use booklid_rust::record::{FORMAT_VERSION, Header, RecordedSample, Recording};
+use std::time::Duration;
+
+fn recording() -> Recording {
+    let sample = |ms: u64, angle_deg: f32| RecordedSample {
+        offset: Duration::from_millis(ms),
+        angle_deg,
+        raw: (ms == 0).then_some(angle_deg + 0.5),
+        source: Source::Other("acme_hinge"),
+        confidence: 0.9,
+        velocity_deg_s: None,
+    };
+    Recording {
+        header: Header {
+            version: FORMAT_VERSION,
+            source: Source::Other("acme_hinge"),
+            started_utc_ms: 1_700_000_000_000,
+        },
+        samples: vec![sample(0, 100.0), sample(16, 101.5)],
+        annotations: Vec::new(),
+    }
+}
+
+#[test]
+fn record_batch_has_wall_clock_and_offsets() {
+    use arrow_array::{Array, Float32Array, StringArray, TimestampMicrosecondArray};
+    let batch = recording().to_record_batch().expect("batch");
+    assert_eq!(batch.num_rows(), 2);
+    let utc = batch
+        .column_by_name("utc")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<TimestampMicrosecondArray>()
+        .unwrap();
+    assert_eq!(utc.value(1) - utc.value(0), 16_000);
+    assert_eq!(utc.value(0), 1_700_000_000_000_000);
+    let src = batch
+        .column_by_name("source")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<StringArray>()
+        .unwrap();
+    assert_eq!(src.value(0), "acme_hinge");
+    let raw = batch
+        .column_by_name("raw")
+        .unwrap()
+        .as_any()
+        .downcast_ref::<Float32Array>()
+        .unwrap();
+    assert_eq!(raw.value(0), 100.5);
+    assert!(raw.is_null(1));
+}
+
+#[test]
+fn parquet_round_trips() {
+    let path = std::env::temp_dir().join(format!("booklid-{}.parquet", std::process::id()));
+    recording().write_parquet(&path).expect("write parquet");
+    let file = std::fs::File::open(&path).unwrap();
+    let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap();
+    let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+    assert_eq!(rows, 2);
+    std::fs::remove_file(&path).ok();
+}
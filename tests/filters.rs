@@ -0,0 +1,60 @@
+//! The standalone signal-processing building blocks.
+
+use booklid_rust::filters::{Baseline, RollingVariance, bellows, ema, stability};
+
+#[test]
+fn ema_steps_toward_the_reading() {
+    assert_eq!(ema(10.0, 20.0, 0.25), 12.5);
+    assert_eq!(ema(10.0, 20.0, 1.0), 20.0);
+    // Out-of-range factors are clamped rather than overshooting.
+    assert_eq!(ema(10.0, 20.0, 2.0), 20.0);
+    assert_eq!(ema(10.0, 20.0, -1.0), 10.0);
+}
+
+#[test]
+fn baseline_removes_slow_drift() {
+    let mut b = Baseline::new(10.0);
+    // A step is passed through at first, then decays as the baseline follows.
+    let first = b.update(110.0);
+    assert!((first - 99.5).abs() < 1e-3, "{first}");
+    for _ in 0..2000 {
+        b.update(110.0);
+    }
+    assert!(b.update(110.0).abs() < 0.01);
+    assert!((b.value() - 110.0).abs() < 0.01);
+
+    let mut fast = Baseline::new(0.0).rate(1.0);
+    assert_eq!(fast.update(5.0), 0.0);
+}
+
+#[test]
+fn bellows_centres_and_clamps() {
+    assert_eq!(bellows(0.0, 0.02), 0.5);
+    assert_eq!(bellows(10.0, 0.02), 0.7);
+    assert_eq!(bellows(1000.0, 0.02), 1.0);
+    assert_eq!(bellows(-1000.0, 0.02), 0.0);
+}
+
+#[test]
+fn rolling_variance_forgets_old_values() {
+    let mut v = RollingVariance::new(4);
+    assert!(v.is_empty());
+    assert_eq!(v.variance(), 0.0);
+    assert_eq!(v.push(1.0), 0.0);
+    v.push(3.0);
+    assert_eq!(v.mean(), 2.0);
+    assert_eq!(v.variance(), 1.0);
+    for _ in 0..4 {
+        v.push(7.0);
+    }
+    assert_eq!(v.len(), 4);
+    assert_eq!(v.variance(), 0.0);
+    assert_eq!(RollingVariance::default().push(1.0), 0.0);
+}
+
+#[test]
+fn stability_falls_with_variance() {
+    assert_eq!(stability(0.0, 20.0), 1.0);
+    assert_eq!(stability(0.05, 20.0), 0.5);
+    assert!(stability(10.0, 20.0) < stability(1.0, 20.0));
+}
@@ -0,0 +1,91 @@
+//! Rapid-close gestures from sample velocity.
+
+use booklid_rust::gesture::GestureDetector;
+use booklid_rust::{AngleSample, AngleStreamExt, Gesture, GestureConfig, Source};
+use futures_util::StreamExt;
+use std::time::{Duration, Instant};
+
+/// Samples every 20 ms with the given (angle, velocity) pairs.
+fn samples(source: Source, moves: &[(f32, f32)]) -> Vec<AngleSample> {
+    let t0 = Instant::now();
+    moves
+        .iter()
+        .enumerate()
+        .map(|(i, &(angle_deg, velocity_deg_s))| {
+            AngleSample::new(angle_deg, source, t0 + Duration::from_millis(20 * i as u64))
+                .velocity_deg_s(velocity_deg_s)
+        })
+        .collect()
+}
+
+fn detect(moves: &[(f32, f32)]) -> Vec<Gesture> {
+    let mut d = GestureDetector::new(GestureConfig::default());
+    samples(Source::HingeFeature, moves)
+        .iter()
+        .filter_map(|s| d.push(s))
+        .collect()
+}
+
+#[test]
+fn slam_warns_then_confirms() {
+    let got = detect(&[
+        (110.0, 0.0),
+        (100.0, -80.0),
+        (80.0, -200.0),
+        (40.0, -400.0),
+        (5.0, -300.0),
+        (0.0, -50.0),
+    ]);
+    assert_eq!(
+        got,
+        vec![
+            Gesture::ClosingFast {
+                angle_deg: 80.0,
+                velocity_deg_s: -200.0
+            },
+            Gesture::SlamDetected {
+                peak_velocity_deg_s: 400.0
+            },
+        ]
+    );
+}
+
+#[test]
+fn slow_closes_and_aborted_slams_are_not_slams() {
+    // A gentle close never passes the rate.
+    assert!(detect(&[(100.0, -30.0), (50.0, -60.0), (5.0, -40.0)]).is_empty());
+
+    // Fast, then caught before shutting: a warning only.
+    let got = detect(&[(110.0, -200.0), (60.0, -40.0), (5.0, -50.0)]);
+    assert!(matches!(got.as_slice(), [Gesture::ClosingFast { .. }]));
+
+    // Opening fast is not closing.
+    assert!(detect(&[(20.0, 300.0), (120.0, 300.0)]).is_empty());
+}
+
+#[test]
+fn shut_between_samples_is_a_slam_and_needs_reopening() {
+    let got = detect(&[(100.0, 0.0), (3.0, -500.0), (0.0, -600.0), (2.0, 0.0)]);
+    assert_eq!(
+        got,
+        vec![Gesture::SlamDetected {
+            peak_velocity_deg_s: 500.0
+        }]
+    );
+}
+
+#[tokio::test]
+async fn gestures_on_a_stream_skip_normalized_sources() {
+    let fast = [(120.0, -300.0), (5.0, -300.0)];
+    let als = futures_util::stream::iter(samples(Source::ALS, &fast)).boxed();
+    assert!(
+        als.gestures(GestureConfig::default())
+            .next()
+            .await
+            .is_none()
+    );
+
+    let hinge = futures_util::stream::iter(samples(Source::HingeFeature, &fast)).boxed();
+    let got: Vec<_> = hinge.gestures(GestureConfig::default()).collect().await;
+    assert_eq!(got.len(), 2);
+}
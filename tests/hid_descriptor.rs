@@ -0,0 +1,104 @@
+//! Locating the hinge angle from a HID report descriptor.
+
+use booklid_rust::hid_descriptor::{ReportKind, find_angle_field, parse};
+use booklid_rust::quirks::Layout;
+
+/// An orientation sensor with report id 3: a report interval (ms), a tilt
+/// sensitivity in degrees, then the angle in hundredths of a degree.
+const SENSOR: &[u8] = &[
+    0x05, 0x20, // Usage Page (Sensor)
+    0x09, 0x8A, // Usage (Orientation)
+    0xA1, 0x01, // Collection (Application)
+    0x85, 0x03, //   Report ID (3)
+    0x0A, 0x0E, 0x03, //   Usage (Report Interval)
+    0x15, 0x00, //   Logical Minimum (0)
+    0x27, 0xFF, 0xFF, 0xFF, 0xFF, //   Logical Maximum (0xFFFFFFFF)
+    0x75, 0x20, //   Report Size (32)
+    0x95, 0x01, //   Report Count (1)
+    0x66, 0x01, 0x10, //   Unit (seconds)
+    0x55, 0x0D, //   Unit Exponent (-3)
+    0xB1, 0x02, //   Feature (Data, Var, Abs)
+    0x0A, 0x7F, 0x14, //   Usage (Tilt X | Change Sensitivity Abs)
+    0x26, 0xFF, 0x7F, //   Logical Maximum (32767)
+    0x75, 0x10, //   Report Size (16)
+    0x65, 0x14, //   Unit (degrees)
+    0x55, 0x0E, //   Unit Exponent (-2)
+    0xB1, 0x02, //   Feature (Data, Var, Abs)
+    0x0A, 0x7F, 0x04, //   Usage (Tilt X)
+    0x27, 0xA0, 0x8C, 0x00, 0x00, //   Logical Maximum (36000)
+    0xB1, 0x02, //   Feature (Data, Var, Abs)
+    0xC0, // End Collection
+];
+
+#[test]
+fn parses_fields_with_offsets_ranges_and_units() {
+    let fields = parse(SENSOR);
+    assert_eq!(fields.len(), 3);
+    let interval = fields[0];
+    assert_eq!(interval.kind, ReportKind::Feature);
+    assert_eq!((interval.usage_page, interval.usage), (0x20, 0x030E));
+    assert_eq!(interval.logical_max, 0xFFFF_FFFF);
+    assert_eq!(interval.unit_exponent, -3);
+    assert!(!interval.is_rotation());
+
+    let angle = fields[2];
+    assert_eq!(angle.report_id, 3);
+    assert_eq!((angle.bit_offset, angle.bit_size), (48, 16));
+    assert_eq!((angle.logical_min, angle.logical_max), (0, 36000));
+    assert!(angle.is_rotation());
+}
+
+#[test]
+fn finds_the_angle_and_reads_it_at_its_offset() {
+    // The sensitivity is in degrees too, but is a modified usage.
+    let field = find_angle_field(&parse(SENSOR)).expect("angle field");
+    assert_eq!(field.usage, 0x047F);
+
+    let format = field.format().expect("format");
+    assert_eq!(format.report_id, 3);
+    assert_eq!(format.offset, 7);
+    assert_eq!((format.min_deg, format.max_deg), (0.0, 360.0));
+    let report = [3, 0xE8, 0x03, 0, 0, 0x64, 0, 0x28, 0x23];
+    let deg = format.parse(&report).expect("parse");
+    assert!((deg - 90.0).abs() < 1e-3, "{deg}");
+}
+
+#[test]
+fn bit_fields_read_across_bytes_and_signs() {
+    use booklid_rust::quirks::ReportFormat;
+    let format = ReportFormat {
+        layout: Layout::Bits {
+            shift: 4,
+            size: 12,
+            signed: true,
+        },
+        ..ReportFormat::default()
+    };
+    assert_eq!(format.report_len(), 3);
+    assert_eq!(format.parse(&[1, 0xF0, 0xFF]), Some(-1.0));
+    assert_eq!(format.parse(&[1, 0x5F, 0x00]), Some(5.0));
+    let unsigned = ReportFormat {
+        layout: Layout::Bits {
+            shift: 4,
+            size: 12,
+            signed: false,
+        },
+        ..format
+    };
+    assert_eq!(unsigned.parse(&[1, 0xF0, 0xFF]), Some(4095.0));
+}
+
+#[test]
+fn truncated_or_foreign_descriptors_find_nothing() {
+    assert!(find_angle_field(&parse(&SENSOR[..20])).is_none());
+    assert!(parse(&[]).is_empty());
+    // A keyboard: Generic Desktop page, input report.
+    let keyboard = [
+        0x05, 0x01, 0x09, 0x06, 0xA1, 0x01, 0x05, 0x07, 0x19, 0xE0, 0x29, 0xE7, 0x15, 0x00, 0x25,
+        0x01, 0x75, 0x01, 0x95, 0x08, 0x81, 0x02, 0xC0,
+    ];
+    let fields = parse(&keyboard);
+    assert_eq!(fields.len(), 8);
+    assert_eq!(fields[7].bit_offset, 7);
+    assert!(find_angle_field(&fields).is_none());
+}
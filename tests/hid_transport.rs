@@ -0,0 +1,755 @@
+//! HID hinge logic against a scripted fake transport (runs anywhere).
+
+use booklid_rust::hid::{
+    DiscoveredHinge, Discovery, DiscoveryCache, HidAngle, HidDeviceInfo, HidOverride, HidTransport,
+    ReadMode, discover, find_hinge, parse_angle, parse_feature_report, probe_report_id,
+};
+use booklid_rust::quirks::{HidQuirk, Layout, ReportFormat, hid_lookup_in, register_hid_quirk};
+use booklid_rust::{AngleDevice, Backoff, Error, Result};
+use futures_util::StreamExt;
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+use tokio::time::timeout;
+
+type DeviceAngle = fn(&str, u8, u32) -> Option<u16>;
+
+#[derive(Clone, Default)]
+struct Fake {
+    devices: Vec<HidDeviceInfo>,
+    /// Value served for a report id: `angle(id, n)` for the n-th read.
+    angle: Option<fn(u8, u32) -> Option<u16>>,
+    /// Like `angle`, per device path; takes precedence.
+    device_angle: Option<DeviceAngle>,
+    reads: Arc<AtomicU32>,
+    /// While set, every feature report read fails.
+    unplugged: Arc<AtomicBool>,
+    opened: Arc<Mutex<Vec<String>>>,
+    descriptor: Vec<u8>,
+    /// `read` serves input report 2 from `angle` every millisecond, with
+    /// an unrelated report 9 in between.
+    input: bool,
+    /// Every open fails as the OS refusing access.
+    refused: bool,
+    /// The input reader gets a clone of its own (`input_handle`).
+    own_reader: bool,
+}
+
+impl HidTransport for Fake {
+    type Device = String;
+
+    fn enumerate(&mut self) -> Result<Vec<HidDeviceInfo>> {
+        Ok(self.devices.clone())
+    }
+
+    fn open(&mut self, info: &HidDeviceInfo) -> Result<String> {
+        self.opened.lock().unwrap().push(info.path.clone());
+        if self.refused {
+            return Err(Error::PermissionDenied {
+                sensor: booklid_rust::Source::HingeFeature,
+                hint: "sandboxed".into(),
+            });
+        }
+        Ok(info.path.clone())
+    }
+
+    fn get_feature_report(&mut self, dev: &String, buf: &mut [u8]) -> Result<usize> {
+        if self.unplugged.load(Ordering::Relaxed) {
+            return Err(Error::Other("unplugged".into()));
+        }
+        let n = self.reads.fetch_add(1, Ordering::Relaxed);
+        let v = match self.device_angle {
+            Some(f) => f(dev, buf[0], n),
+            None => self.angle.and_then(|f| f(buf[0], n)),
+        };
+        let v = v.ok_or_else(|| Error::Other("no such report".into()))?;
+        buf[1..3].copy_from_slice(&v.to_le_bytes());
+        Ok(3)
+    }
+
+    fn read(&mut self, _dev: &String, buf: &mut [u8], _timeout_ms: i32) -> Result<usize> {
+        if !self.input {
+            return Ok(0);
+        }
+        if self.unplugged.load(Ordering::Relaxed) {
+            return Err(Error::Other("unplugged".into()));
+        }
+        std::thread::sleep(Duration::from_millis(1));
+        let n = self.reads.fetch_add(1, Ordering::Relaxed);
+        if n % 2 == 1 {
+            buf[..3].copy_from_slice(&[9, 0, 0]);
+            return Ok(3);
+        }
+        let v = self.angle.and_then(|f| f(2, n)).unwrap_or(0);
+        buf[0] = 2;
+        buf[1..3].copy_from_slice(&v.to_le_bytes());
+        Ok(3)
+    }
+
+    fn report_descriptor(&mut self, _dev: &String) -> Result<Vec<u8>> {
+        Ok(self.descriptor.clone())
+    }
+
+    fn input_handle(&mut self) -> Option<Self> {
+        self.own_reader.then(|| self.clone())
+    }
+}
+
+#[derive(Default)]
+struct MemoryCache(Mutex<Option<DiscoveredHinge>>);
+
+impl DiscoveryCache for MemoryCache {
+    fn load(&self) -> Option<DiscoveredHinge> {
+        *self.0.lock().unwrap()
+    }
+
+    fn store(&self, hinge: Option<DiscoveredHinge>) {
+        *self.0.lock().unwrap() = hinge;
+    }
+}
+
+fn device(vid: u16, pid: u16, usage_page: u16, usage: u16, path: &str) -> HidDeviceInfo {
+    HidDeviceInfo {
+        vendor_id: vid,
+        product_id: pid,
+        usage_page,
+        usage,
+        path: path.into(),
+        serial_number: None,
+    }
+}
+
+#[test]
+fn parses_little_endian_report() {
+    assert_eq!(parse_angle(&[1, 0x6e, 0x00]), 110.0);
+    assert_eq!(parse_angle(&[1, 0x2c, 0x01]), 300.0);
+}
+
+#[test]
+fn rejects_short_or_mismatched_reports() {
+    assert_eq!(parse_feature_report(&[1, 0x6e, 0x00, 0xff], 1), Some(110.0));
+    assert_eq!(parse_feature_report(&[1, 0x6e], 1), None);
+    assert_eq!(parse_feature_report(&[2, 0x6e, 0x00], 1), None);
+    assert_eq!(parse_feature_report(&[], 1), None);
+}
+
+#[test]
+fn prefers_sensor_usage_then_apple_hinge_pid() {
+    let mut t = Fake {
+        devices: vec![
+            device(0x05AC, 0x1234, 0, 0, "apple-other"),
+            device(0x05AC, 0x8104, 0, 0, "apple-hinge"),
+            device(0x1111, 0x2222, 0x20, 0x008A, "sensor"),
+        ],
+        ..Fake::default()
+    };
+    assert_eq!(find_hinge(&mut t).as_deref(), Some("sensor"));
+
+    t.devices.pop();
+    assert_eq!(find_hinge(&mut t).as_deref(), Some("apple-hinge"));
+
+    // Last resort: an Apple device that answers feature report 1.
+    t.devices.pop();
+    assert_eq!(find_hinge(&mut t), None);
+    t.angle = Some(|_, _| Some(90));
+    assert_eq!(find_hinge(&mut t).as_deref(), Some("apple-other"));
+}
+
+#[test]
+fn discovery_picks_the_moving_in_range_report() {
+    let mut t = Fake {
+        angle: Some(|id, n| match id {
+            1 => Some(100),                        // constant
+            2 => Some(200 + (n % 50) as u16),      // out of range
+            3 => Some(20 + (n * 17 % 150) as u16), // moving hinge
+            4 => Some(80 + (n * 7 % 15) as u16),   // moving, less variance
+            _ => None,
+        }),
+        ..Fake::default()
+    };
+    let id = probe_report_id(&mut t, &"dev".into(), 1..=5, Duration::from_millis(60));
+    assert_eq!(id, Some(3));
+}
+
+/// Two candidates and a non-candidate: the Apple device's report 3 moves
+/// the most among the candidates.
+fn scan_fake() -> Fake {
+    Fake {
+        devices: vec![
+            device(0x1111, 0x2222, 0x20, 0x008A, "sensor"),
+            device(0x05AC, 0x1234, 0, 0, "apple"),
+            device(0x05AC, 0x1234, 0xFF00, 1, "apple"),
+            device(0x3333, 0x4444, 0, 0, "keyboard"),
+        ],
+        device_angle: Some(|dev, id, n| match (dev, id) {
+            ("sensor", 1) => Some(100),
+            ("sensor", 2) => Some(80 + (n * 7 % 15) as u16),
+            ("apple", 3) => Some(20 + (n * 17 % 150) as u16),
+            ("keyboard", _) => Some(n as u16 % 180),
+            _ => None,
+        }),
+        ..Fake::default()
+    }
+}
+
+#[test]
+fn discovery_scans_every_candidate_device() {
+    let mut t = scan_fake();
+    let (dev, info, id) = discover(&mut t, 1..=4, Duration::from_millis(40)).expect("found");
+    assert_eq!((dev.as_str(), info.product_id, id), ("apple", 0x1234, 3));
+    // Each device is probed once, however many usages it lists.
+    let opened = t.opened.lock().unwrap();
+    assert_eq!(opened.iter().filter(|p| *p == "apple").count(), 1);
+    assert!(!opened.iter().any(|p| p == "keyboard"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn discovery_winner_is_cached_and_reused() {
+    let cache = Arc::new(MemoryCache::default());
+    let discovery = Discovery {
+        probe: true,
+        window: Duration::from_millis(20),
+        cache: Some(cache.clone()),
+        device: None,
+        quirks: None,
+    };
+    let dev = HidAngle::with_transport(
+        scan_fake(),
+        200.0,
+        discovery.clone(),
+        Backoff::constant(Duration::from_millis(5)),
+    );
+    timeout(Duration::from_secs(2), dev.subscribe().next())
+        .await
+        .expect("sample");
+    // The first collection listed for the device.
+    let hinge = DiscoveredHinge {
+        vendor_id: 0x05AC,
+        product_id: 0x1234,
+        usage_page: 0,
+        usage: 0,
+        report_id: 3,
+    };
+    assert_eq!(cache.load(), Some(hinge));
+    assert_eq!(dev.hardware_id().as_deref(), Some("hid:05ac:1234"));
+    drop(dev);
+
+    // A probe this slow would miss the deadline; the cache skips it.
+    let discovery = Discovery {
+        window: Duration::from_secs(5),
+        ..discovery
+    };
+    let dev = HidAngle::with_transport(
+        scan_fake(),
+        200.0,
+        discovery.clone(),
+        Backoff::constant(Duration::from_millis(5)),
+    );
+    timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample from the cached report");
+    drop(dev);
+
+    // A cached report that no longer answers is forgotten.
+    cache.store(Some(DiscoveredHinge {
+        report_id: 7,
+        ..hinge
+    }));
+    let dev = HidAngle::with_transport(
+        scan_fake(),
+        200.0,
+        Discovery {
+            probe: false,
+            ..discovery
+        },
+        Backoff::constant(Duration::from_millis(5)),
+    );
+    timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample from the sensor");
+    assert_eq!(cache.load(), None);
+    assert_eq!(dev.hardware_id().as_deref(), Some("hid:1111:2222"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn discovery_runs_when_no_device_answers_report_1() {
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x1234, 0xFF00, 1, "apple")],
+        device_angle: Some(|_, id, n| (id == 3).then(|| 20 + (n * 17 % 150) as u16)),
+        ..Fake::default()
+    };
+    let cache = Arc::new(MemoryCache::default());
+    let discovery = Discovery {
+        probe: true,
+        window: Duration::from_millis(20),
+        cache: Some(cache.clone()),
+        device: None,
+        quirks: None,
+    };
+    let dev = HidAngle::open_transport(
+        t,
+        200.0,
+        discovery,
+        ReadMode::Poll,
+        Backoff::constant(Duration::from_millis(5)),
+    )
+    .await
+    .expect("a candidate to probe");
+    timeout(Duration::from_secs(2), dev.subscribe().next())
+        .await
+        .expect("sample from the probed report");
+    let hinge = cache.load().expect("cached");
+    assert_eq!(
+        (hinge.usage_page, hinge.usage, hinge.report_id),
+        (0xFF00, 1, 3)
+    );
+    // Another collection of the same device is not the cached one.
+    assert!(!hinge.matches(&device(0x05AC, 0x1234, 0, 0, "apple")));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn override_reads_the_named_device_without_discovery() {
+    let t = scan_fake();
+    let opened = t.opened.clone();
+    let cache = Arc::new(MemoryCache::default());
+    let discovery = Discovery {
+        probe: true,
+        window: Duration::from_secs(5),
+        cache: Some(cache.clone()),
+        device: Some(HidOverride {
+            vendor_id: 0x05AC,
+            product_id: 0x1234,
+            usage_page: 0xFF00,
+            usage: 1,
+            report_id: 3,
+        }),
+        quirks: None,
+    };
+    let dev = HidAngle::with_transport(
+        t,
+        200.0,
+        discovery,
+        Backoff::constant(Duration::from_millis(5)),
+    );
+    timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample without probing");
+    assert_eq!(dev.hardware_id().as_deref(), Some("hid:05ac:1234"));
+    assert!(opened.lock().unwrap().iter().all(|p| p == "apple"));
+    assert_eq!(cache.load(), None);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn input_mode_forwards_reports_as_they_arrive() {
+    input_mode_forwards(false).await;
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn input_mode_reads_through_its_own_handle() {
+    input_mode_forwards(true).await;
+}
+
+async fn input_mode_forwards(own_reader: bool) {
+    let t = Fake {
+        devices: vec![device(0x1209, 0xb00e, 0x20, 0x008A, "sensor")],
+        angle: Some(|id, n| (id == 2).then_some(30 + (n % 100) as u16)),
+        input: true,
+        own_reader,
+        ..Fake::default()
+    };
+    let unplugged = t.unplugged.clone();
+    let discovery = Discovery {
+        device: Some(HidOverride {
+            vendor_id: 0x1209,
+            product_id: 0xb00e,
+            usage_page: 0x20,
+            usage: 0x008A,
+            report_id: 2,
+        }),
+        ..Discovery::default()
+    };
+    // At 1 Hz polling this would take 20 seconds.
+    let dev = HidAngle::with_transport_mode(
+        t,
+        1.0,
+        discovery,
+        ReadMode::Input,
+        Backoff::constant(Duration::from_millis(5)),
+    );
+    let got: Vec<f32> = timeout(
+        Duration::from_secs(1),
+        dev.subscribe().take(20).map(|s| s.raw).collect::<Vec<_>>(),
+    )
+    .await
+    .expect("input reports");
+    assert!(got.iter().all(|v| (30.0..130.0).contains(v)), "{got:?}");
+
+    // A failed read restarts the reader on the re-opened device.
+    unplugged.store(true, Ordering::Relaxed);
+    timeout(Duration::from_secs(1), async {
+        while !dev.is_reconnecting() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("reconnecting");
+    unplugged.store(false, Ordering::Relaxed);
+    timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("samples after re-open");
+}
+
+#[cfg(feature = "hid_debug")]
+#[tokio::test(flavor = "current_thread")]
+async fn raw_reports_carry_the_bytes_read() {
+    use booklid_rust::hid_descriptor::ReportKind;
+
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x8104, 0, 0, "hinge")],
+        angle: Some(|_, _| Some(110)),
+        ..Fake::default()
+    };
+    let dev =
+        HidAngle::with_transport(t, 200.0, false, Backoff::constant(Duration::from_millis(5)));
+    let r = timeout(Duration::from_secs(1), dev.subscribe_raw_reports().next())
+        .await
+        .expect("raw report")
+        .unwrap();
+    assert_eq!((r.kind, r.bytes), (ReportKind::Feature, vec![1, 110, 0]));
+
+    // Input mode passes on reports the angle is not in, too.
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x8104, 0, 0, "hinge")],
+        angle: Some(|_, _| Some(110)),
+        input: true,
+        ..Fake::default()
+    };
+    let dev = HidAngle::with_transport_mode(
+        t,
+        200.0,
+        false,
+        ReadMode::Input,
+        Backoff::constant(Duration::from_millis(5)),
+    );
+    let got: Vec<_> = timeout(
+        Duration::from_secs(1),
+        dev.subscribe_raw_reports().take(4).collect::<Vec<_>>(),
+    )
+    .await
+    .expect("raw reports");
+    assert!(got.iter().all(|r| r.kind == ReportKind::Input));
+    assert!(got.iter().any(|r| r.bytes == [9, 0, 0]), "{got:?}");
+    assert!(got.iter().any(|r| r.bytes == [2, 110, 0]), "{got:?}");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn open_fails_fast_without_a_hinge() {
+    // Only a keyboard: no sensor usage, no Apple device.
+    let t = Fake {
+        devices: vec![device(0x3333, 0x4444, 0, 0, "keyboard")],
+        angle: Some(|_, _| Some(90)),
+        ..Fake::default()
+    };
+    let backoff = Backoff::constant(Duration::from_millis(5));
+    let opened = timeout(
+        Duration::from_secs(1),
+        HidAngle::open_transport(t, 60.0, true, ReadMode::Poll, backoff),
+    )
+    .await
+    .expect("bounded");
+    assert!(opened.is_err());
+
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x8104, 0, 0, "hinge")],
+        angle: Some(|_, _| Some(90)),
+        ..Fake::default()
+    };
+    let dev = HidAngle::open_transport(t, 200.0, false, ReadMode::Poll, backoff)
+        .await
+        .expect("hinge present");
+    timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn refused_hinge_is_reported_and_not_retried() {
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x8104, 0, 0, "hinge")],
+        angle: Some(|_, _| Some(90)),
+        refused: true,
+        ..Fake::default()
+    };
+    let backoff = Backoff::constant(Duration::from_millis(5));
+    let opened = HidAngle::open_transport(t.clone(), 60.0, false, ReadMode::Poll, backoff).await;
+    assert!(
+        matches!(opened, Err(Error::PermissionDenied { .. })),
+        "{:?}",
+        opened.err()
+    );
+
+    // Started without the upfront check, the sampler gives up at once.
+    let opened = t.opened.clone();
+    let dev = HidAngle::with_transport(t, 60.0, false, backoff);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let attempts = opened.lock().unwrap().len();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(opened.lock().unwrap().len(), attempts, "no retries");
+    assert!(dev.latest().is_none());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reports_the_opened_devices_hardware_id() {
+    let mut hinge = device(0x05AC, 0x8104, 0, 0, "hinge");
+    assert_eq!(hinge.hardware_id(), "hid:05ac:8104");
+    hinge.serial_number = Some("C02XK".into());
+    assert_eq!(hinge.hardware_id(), "hid:05ac:8104:C02XK");
+
+    let t = Fake {
+        devices: vec![hinge],
+        angle: Some(|_, _| Some(110)),
+        ..Fake::default()
+    };
+    let dev =
+        HidAngle::with_transport(t, 100.0, false, Backoff::constant(Duration::from_millis(5)));
+    timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(dev.hardware_id().as_deref(), Some("hid:05ac:8104:C02XK"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn reopens_after_read_failures() {
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x8104, 0, 0, "hinge")],
+        angle: Some(|_, _| Some(110)),
+        ..Fake::default()
+    };
+    let unplugged = t.unplugged.clone();
+    let opened = t.opened.clone();
+    let dev =
+        HidAngle::with_transport(t, 100.0, false, Backoff::constant(Duration::from_millis(5)));
+    let mut s = dev.subscribe();
+
+    let first = timeout(Duration::from_secs(1), s.next())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.angle_deg, 110.0);
+    assert!(!dev.is_reconnecting());
+
+    unplugged.store(true, Ordering::Relaxed);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(dev.is_reconnecting());
+    unplugged.store(false, Ordering::Relaxed);
+
+    timeout(Duration::from_secs(1), async {
+        while dev.is_reconnecting() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("recovered");
+    assert!(opened.lock().unwrap().len() >= 2, "device was re-opened");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn paused_device_stops_reading() {
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x8104, 0, 0, "hinge")],
+        angle: Some(|_, _| Some(110)),
+        ..Fake::default()
+    };
+    let reads = t.reads.clone();
+    let dev =
+        HidAngle::with_transport(t, 100.0, false, Backoff::constant(Duration::from_millis(5)));
+    let mut s = dev.subscribe();
+    timeout(Duration::from_secs(1), s.next())
+        .await
+        .unwrap()
+        .unwrap();
+
+    dev.set_paused(true);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let before = reads.load(Ordering::Relaxed);
+    let mut s = dev.subscribe();
+    assert!(
+        timeout(Duration::from_millis(100), s.next()).await.is_err(),
+        "no samples while paused"
+    );
+    assert_eq!(
+        reads.load(Ordering::Relaxed),
+        before,
+        "no reads while paused"
+    );
+
+    dev.set_paused(false);
+    timeout(Duration::from_secs(1), s.next())
+        .await
+        .expect("resumed")
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn outlier_rejection_drops_garbage_reports() {
+    use booklid_rust::OutlierRejection;
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x8104, 0, 0, "hinge")],
+        // A steady ~110° hinge that now and then reports 0 or 65535.
+        angle: Some(|_, n| match n % 9 {
+            4 => Some(0),
+            7 => Some(u16::MAX),
+            _ => Some(110 + (n % 3) as u16),
+        }),
+        ..Fake::default()
+    };
+    let dev =
+        HidAngle::with_transport(t, 200.0, false, Backoff::constant(Duration::from_millis(5)));
+    dev.set_smoothing(1.0);
+    dev.set_outlier_rejection(Some(OutlierRejection::default()));
+    let got: Vec<_> = timeout(Duration::from_secs(2), dev.subscribe().take(40).collect())
+        .await
+        .unwrap();
+    assert!(
+        got.iter().all(|s| (109.0..=113.0).contains(&s.angle_deg)),
+        "{:?}",
+        got.iter().map(|s| s.angle_deg).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn outlier_rejection_follows_a_real_jump() {
+    use booklid_rust::OutlierRejection;
+    let t = Fake {
+        devices: vec![device(0x05AC, 0x8104, 0, 0, "hinge")],
+        angle: Some(|_, n| Some(if n < 20 { 110 } else { 30 })),
+        ..Fake::default()
+    };
+    let dev =
+        HidAngle::with_transport(t, 200.0, false, Backoff::constant(Duration::from_millis(5)));
+    dev.set_smoothing(1.0);
+    dev.set_outlier_rejection(Some(OutlierRejection::default()));
+    let mut s = dev.subscribe();
+    timeout(Duration::from_secs(2), async {
+        while s.next().await.unwrap().angle_deg != 30.0 {}
+    })
+    .await
+    .expect("the new angle is accepted once it persists");
+}
+
+#[test]
+fn report_formats_read_layout_offset_and_scale() {
+    let be = ReportFormat {
+        report_id: 2,
+        offset: 2,
+        layout: Layout::I16Be,
+        scale: 1.0 / 64.0,
+        ..ReportFormat::default()
+    };
+    assert_eq!(be.report_len(), 4);
+    assert_eq!(be.parse(&[2, 0xff, 0x16, 0x80]), Some(90.0));
+    assert_eq!(be.parse(&[2, 0x00, 0xff, 0xc0]), Some(-1.0));
+    assert_eq!(be.parse(&[2, 0x00, 0x16]), None);
+    assert_eq!(be.parse(&[1, 0x00, 0x16, 0x80]), None);
+    assert!(be.is_valid());
+    assert!(!ReportFormat { offset: 0, ..be }.is_valid());
+    assert!(!ReportFormat { scale: 0.0, ..be }.is_valid());
+}
+
+#[test]
+fn quirk_naming_the_machine_wins() {
+    let any = HidQuirk {
+        vendor_id: 0x05AC,
+        product_id: 0x8104,
+        model: "".into(),
+        format: ReportFormat::with_report_id(3),
+    };
+    let mbp = HidQuirk {
+        model: "MacBookPro18".into(),
+        format: ReportFormat::with_report_id(2),
+        ..any
+    };
+    let table = [any, mbp];
+    let hinge = device(0x05AC, 0x8104, 0, 0, "hinge");
+    let id = |model| hid_lookup_in(&table, &hinge, model).map(|f| f.report_id);
+    assert_eq!(id(Some("MacBookPro18,3")), Some(2));
+    assert_eq!(id(Some("MacBookAir10,1")), Some(3));
+    assert_eq!(id(None), Some(3));
+    let other = device(0x05AC, 0x1234, 0, 0, "other");
+    assert_eq!(hid_lookup_in(&table, &other, None), None);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn registered_quirk_is_opened_first_and_read_with_its_format() {
+    // Ids of its own so other tests' devices are unaffected.
+    register_hid_quirk(HidQuirk {
+        vendor_id: 0x1209,
+        product_id: 0xb00c,
+        model: "".into(),
+        format: ReportFormat {
+            report_id: 2,
+            scale: 1.0 / 64.0,
+            max_deg: 180.0,
+            ..ReportFormat::default()
+        },
+    })
+    .expect("register");
+    let invalid = HidQuirk {
+        vendor_id: 0x1209,
+        product_id: 0xb00d,
+        model: "".into(),
+        format: ReportFormat {
+            scale: f32::NAN,
+            ..ReportFormat::default()
+        },
+    };
+    assert!(register_hid_quirk(invalid).is_err());
+
+    let t = Fake {
+        devices: vec![
+            device(0x1111, 0x2222, 0x20, 0x008A, "sensor"),
+            device(0x1209, 0xb00c, 0, 0, "quirky"),
+        ],
+        // 90° in 1/64ths, with a glitch every fourth read.
+        angle: Some(|id, n| (id == 2).then_some(if n % 4 == 3 { u16::MAX } else { 90 * 64 })),
+        ..Fake::default()
+    };
+    assert_eq!(find_hinge(&mut t.clone()).as_deref(), Some("quirky"));
+
+    // Discovery would probe report 1 and find nothing.
+    let dev = HidAngle::with_transport(t, 200.0, true, Backoff::constant(Duration::from_millis(5)));
+    let got: Vec<f32> = timeout(
+        Duration::from_secs(2),
+        dev.subscribe().take(8).map(|s| s.raw).collect::<Vec<_>>(),
+    )
+    .await
+    .expect("samples");
+    assert!(got.iter().all(|&v| v == 90.0), "{got:?}");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn report_descriptor_locates_the_angle_without_probing() {
+    let t = Fake {
+        devices: vec![device(0x1111, 0x2222, 0x20, 0x008A, "sensor")],
+        // Report 5 holds hundredths of a degree.
+        angle: Some(|id, _| (id == 5).then_some(9000)),
+        descriptor: vec![
+            0x05, 0x20, 0x09, 0x8A, 0xA1, 0x01, 0x85, 0x05, 0x0A, 0x7F, 0x04, 0x15, 0x00, 0x27,
+            0xA0, 0x8C, 0x00, 0x00, 0x75, 0x10, 0x95, 0x01, 0x65, 0x14, 0x55, 0x0E, 0xB1, 0x02,
+            0xC0,
+        ],
+        ..Fake::default()
+    };
+    let dev =
+        HidAngle::with_transport(t, 200.0, false, Backoff::constant(Duration::from_millis(5)));
+    let s = timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample")
+        .unwrap();
+    assert!((s.raw - 90.0).abs() < 1e-3, "{}", s.raw);
+}
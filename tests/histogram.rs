@@ -0,0 +1,60 @@
+//! The online angle histogram.
+
+use booklid_rust::Histogram;
+
+#[test]
+fn histogram_bins_mode_and_percentile() {
+    let mut h = Histogram::new(0.0..=180.0, 18);
+    for v in [95.0, 97.0, 99.0, 12.0, 250.0, f32::NAN] {
+        h.add(v);
+    }
+    assert_eq!(h.total(), 5);
+    assert_eq!(h.counts[9], 3);
+    assert_eq!(
+        h.counts[17], 1,
+        "out-of-range values clamp into the last bin"
+    );
+    assert_eq!(h.mode(), Some(95.0));
+    assert_eq!(h.percentile(0.5), Some(100.0));
+    assert!(Histogram::new(0.0..=1.0, 0).counts.len() == 1);
+}
+
+#[test]
+fn non_finite_values_are_skipped_and_out_of_range_ones_clamped() {
+    let mut h = Histogram::new(0.0..=180.0, 18);
+    for v in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        h.add(v);
+    }
+    assert_eq!(h.total(), 0);
+    assert_eq!((h.mode(), h.percentile(0.5)), (None, None));
+
+    h.add(-40.0);
+    h.add(180.0);
+    h.add(1e9);
+    assert_eq!((h.counts[0], h.counts[17], h.total()), (1, 2, 3));
+    assert_eq!(h.percentile(0.0), Some(10.0));
+    assert_eq!(h.percentile(1.0), Some(180.0));
+}
+
+#[test]
+fn empty_or_non_finite_ranges_are_rejected() {
+    use booklid_rust::{HistogramConfig, OpenConfig, SampleRate};
+    for range in [
+        10.0..=10.0,
+        90.0..=0.0,
+        0.0..=f32::NAN,
+        f32::NEG_INFINITY..=180.0,
+    ] {
+        let cfg = OpenConfig::new(SampleRate::hz(60.0)).histogram(HistogramConfig {
+            range: range.clone(),
+            ..HistogramConfig::default()
+        });
+        assert!(cfg.validate().is_err(), "{range:?}");
+
+        let mut h = Histogram::new(range, 4);
+        h.add(5.0);
+        assert_eq!((h.total(), h.mode()), (0, None));
+    }
+    let cfg = OpenConfig::new(SampleRate::hz(60.0)).histogram(HistogramConfig::default());
+    assert!(cfg.validate().is_ok());
+}
@@ -0,0 +1,196 @@
+//! Semantic lid events from sample streams.
+
+use booklid_rust::events::{Direction, LidEvents};
+use booklid_rust::{
+    AngleSample, AngleStreamExt, LidEvent, LidEventConfig, Source, Threshold, ThresholdWatcher,
+};
+use futures_util::StreamExt;
+use std::time::{Duration, Instant};
+
+/// Samples every 20 ms with the given angles.
+fn samples(source: Source, angles: &[f32]) -> Vec<AngleSample> {
+    let t0 = Instant::now();
+    angles
+        .iter()
+        .enumerate()
+        .map(|(i, &a)| AngleSample::new(a, source, t0 + Duration::from_millis(20 * i as u64)))
+        .collect()
+}
+
+fn run(cfg: LidEventConfig, s: &[AngleSample]) -> Vec<LidEvent> {
+    let mut d = LidEvents::new(cfg);
+    s.iter().flat_map(|s| d.push(s)).collect()
+}
+
+#[test]
+fn open_and_close_are_debounced() {
+    let cfg = LidEventConfig::default().debounce(Duration::from_millis(40));
+    // Starts open (not reported), a 20 ms dip is ignored, then a real close
+    // and reopen; values between 10° and 20° never flip the state.
+    let s = samples(
+        Source::Mock,
+        &[
+            90.0, 5.0, 90.0, 15.0, 5.0, 4.0, 3.0, 15.0, 18.0, 40.0, 60.0, 80.0,
+        ],
+    );
+    assert_eq!(run(cfg, &s), [LidEvent::LidClosed, LidEvent::LidOpened]);
+}
+
+#[test]
+fn thresholds_report_direction_with_hysteresis() {
+    let cfg = LidEventConfig::default()
+        .threshold(90.0)
+        .hysteresis(4.0)
+        .debounce(Duration::ZERO);
+    // Jitter inside 88..=92 does not cross.
+    let s = samples(
+        Source::Mock,
+        &[80.0, 89.0, 91.0, 89.5, 93.0, 91.0, 88.5, 87.0],
+    );
+    let crossed = |direction| LidEvent::AngleCrossed {
+        threshold: 90.0,
+        direction,
+    };
+    assert_eq!(
+        run(cfg, &s),
+        [crossed(Direction::Rising), crossed(Direction::Falling)]
+    );
+}
+
+#[tokio::test]
+async fn source_changes_and_normalized_samples() {
+    let mut s = samples(Source::WinHinge, &[100.0, 100.0]);
+    s.extend(samples(Source::WinTilt, &[100.0]));
+    // ALS values are 0..1, not degrees: no LidClosed.
+    s.extend(samples(Source::WinALS, &[0.1, 0.1, 0.1]));
+    let got: Vec<_> = futures_util::stream::iter(s)
+        .lid_events(LidEventConfig::default().debounce(Duration::ZERO))
+        .collect()
+        .await;
+    assert_eq!(
+        got,
+        [
+            LidEvent::SourceChanged {
+                from: Source::WinHinge,
+                to: Source::WinTilt
+            },
+            LidEvent::SourceChanged {
+                from: Source::WinTilt,
+                to: Source::WinALS
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn threshold_watcher_uses_per_threshold_settings() {
+    // 20° has a wide band and no debounce; 160° a narrow band and 50 ms,
+    // so the 20 ms dip to 159° restarts its debounce.
+    let w = ThresholdWatcher::new()
+        .threshold(
+            Threshold::new(20.0)
+                .hysteresis(10.0)
+                .debounce(Duration::ZERO),
+        )
+        .threshold(
+            Threshold::new(160.0)
+                .hysteresis(1.0)
+                .debounce(Duration::from_millis(50)),
+        );
+    let s = samples(
+        Source::Mock,
+        &[
+            10.0, 22.0, 26.0, 161.0, 159.0, 161.0, 161.0, 161.0, 161.0, 14.0,
+        ],
+    );
+    let got: Vec<_> = w
+        .watch(futures_util::stream::iter(s).boxed())
+        .map(|c| (c.threshold, c.direction, c.angle_deg))
+        .collect()
+        .await;
+    assert_eq!(
+        got,
+        [
+            (20.0, Direction::Rising, 26.0),
+            (160.0, Direction::Rising, 161.0),
+            (20.0, Direction::Falling, 14.0),
+        ]
+    );
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn client_subscribes_to_threshold_events() {
+    use booklid_rust::{OpenConfig, SampleRate, open_with_config};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .await
+    .expect("open mock");
+    // The mock sweeps roughly 75..115°.
+    let mut events = dev.subscribe_events(
+        LidEventConfig::default()
+            .threshold(95.0)
+            .debounce(Duration::ZERO),
+    );
+    let ev = tokio::time::timeout(Duration::from_secs(10), events.next())
+        .await
+        .expect("a crossing")
+        .unwrap();
+    assert!(matches!(
+        ev,
+        LidEvent::AngleCrossed {
+            threshold: 95.0,
+            ..
+        }
+    ));
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn is_closed_holds_between_thresholds() {
+    use booklid_rust::{Event, OpenConfig, SampleRate, open_with_config};
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .persistence(false)
+        .attach_daemon(false)
+        .prefer(vec![Source::Mock]);
+    assert!(cfg.clone().lid_thresholds(20.0, 10.0).validate().is_err());
+
+    // The mock sweeps roughly 75..115°: below 120 is closed.
+    let dev = open_with_config(cfg.lid_thresholds(120.0, 130.0))
+        .await
+        .expect("open mock");
+    let mut s = dev.subscribe();
+    s.next().await.unwrap();
+    assert!(dev.is_closed() && !dev.is_open());
+
+    // Inside the band the state holds...
+    dev.set_lid_thresholds(70.0, 130.0).unwrap();
+    for _ in 0..5 {
+        s.next().await.unwrap();
+    }
+    assert!(dev.is_closed());
+
+    // ...until a sample reaches the open threshold.
+    assert!(dev.set_lid_thresholds(60.0, 50.0).is_err());
+    assert!(dev.set_lid_thresholds(f32::NAN, 50.0).is_err());
+    let before = Instant::now();
+    dev.set_lid_thresholds(50.0, 60.0).unwrap();
+    s.next().await.unwrap();
+    assert!(dev.is_open() && !dev.is_closed());
+    // The change is logged for callers that weren't listening.
+    let logged: Vec<_> = dev
+        .events_since(before)
+        .into_iter()
+        .filter_map(|(_, e)| matches!(e, Event::Lid(_)).then_some(e))
+        .collect();
+    assert_eq!(logged, vec![Event::Lid(LidEvent::LidOpened)]);
+
+    dev.close();
+    assert!(!dev.is_open() && !dev.is_closed());
+}
@@ -0,0 +1,251 @@
+#![cfg(feature = "mock")]
+
+//! Zero calibration driven by a lid switch, and lid switches as devices.
+
+use booklid_rust::{
+    AngleDevice, AutoCalibrationConfig, BackendContext, BackendFactory, Backoff, LidEvent,
+    LidEventConfig, LidSwitch, LidSwitchAngle, LidSwitchState, OpenConfig, SampleRate, Source,
+    open_with_config, register_backend,
+};
+use futures_util::{FutureExt, StreamExt, future::BoxFuture};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Clone)]
+struct FakeSwitch(Arc<Mutex<Option<LidSwitchState>>>);
+
+impl FakeSwitch {
+    fn set(&self, state: LidSwitchState) {
+        *self.0.lock().unwrap() = Some(state);
+    }
+}
+
+impl LidSwitch for FakeSwitch {
+    fn read(&self) -> booklid_rust::Result<Option<LidSwitchState>> {
+        Ok(*self.0.lock().unwrap())
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn closing_the_lid_anchors_zero_once_per_close() {
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock])
+            .smoothing(1.0),
+    )
+    .await
+    .expect("open mock");
+    let mut s = dev.subscribe();
+    s.next().await.unwrap();
+
+    let switch = FakeSwitch(Arc::default());
+    let auto = dev.auto_calibrate(
+        switch.clone(),
+        AutoCalibrationConfig::default()
+            .poll(Duration::from_millis(10))
+            .settle(Duration::from_millis(50))
+            // The mock keeps sweeping; the gate is covered by settle here.
+            .max_velocity(f32::INFINITY),
+    );
+
+    // Open or unknown: nothing happens.
+    switch.set(LidSwitchState::Open);
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(auto.anchors(), 0);
+    let got = s.next().await.unwrap();
+    assert_eq!(got.angle_deg, got.raw);
+
+    switch.set(LidSwitchState::Closed);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(auto.anchors(), 1);
+    let got = dev.subscribe().next().await.unwrap();
+    // The mock moves a few degrees in the time since anchoring.
+    assert!(got.angle_deg.abs() < 15.0, "{}", got.angle_deg);
+
+    // Staying shut does not anchor again; reopening and closing does.
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(auto.anchors(), 1);
+    switch.set(LidSwitchState::Open);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    switch.set(LidSwitchState::Closed);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(auto.anchors(), 2);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn auto_calibration_does_not_keep_the_client_open() {
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .await
+    .expect("open mock");
+    let switch = FakeSwitch(Arc::default());
+    let _auto = dev.auto_calibrate(
+        switch.clone(),
+        AutoCalibrationConfig::default().poll(Duration::from_millis(10)),
+    );
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert_eq!(Arc::strong_count(&switch.0), 2);
+
+    // Dropping the client ends the task, which lets go of its switch.
+    drop(dev);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(Arc::strong_count(&switch.0), 1);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn switch_device_reports_open_and_closed() {
+    let switch = FakeSwitch(Arc::default());
+    switch.set(LidSwitchState::Closed);
+    let dev = LidSwitchAngle::open(
+        switch.clone(),
+        Source::MacClamshell,
+        60.0,
+        Backoff::default(),
+    )
+    .expect("open switch");
+    assert_eq!(dev.info().source, Source::MacClamshell);
+    let mut s = dev.subscribe();
+    let got = s.next().await.unwrap();
+    assert_eq!((got.angle_deg, got.source), (0.0, Source::MacClamshell));
+    assert_eq!(got.confidence, 1.0);
+
+    switch.set(LidSwitchState::Open);
+    let got = s.next().await.unwrap();
+    let got = if got.angle_deg == 0.0 {
+        s.next().await.unwrap()
+    } else {
+        got
+    };
+    assert_eq!(got.angle_deg, 1.0);
+}
+
+/// A switch the OS notifies about, see `LidSwitch::changes`.
+struct NotifyingSwitch(tokio::sync::watch::Sender<Option<LidSwitchState>>);
+
+impl LidSwitch for NotifyingSwitch {
+    fn read(&self) -> booklid_rust::Result<Option<LidSwitchState>> {
+        Ok(*self.0.borrow())
+    }
+    fn changes(&self) -> Option<tokio::sync::watch::Receiver<Option<LidSwitchState>>> {
+        Some(self.0.subscribe())
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn notified_switch_sends_changes_without_waiting_for_a_poll() {
+    let (tx, _rx) = tokio::sync::watch::channel(Some(LidSwitchState::Open));
+    let switch = NotifyingSwitch(tx.clone());
+    // Polled once a second at most.
+    let dev = LidSwitchAngle::open(switch, Source::WinLidSwitch, 1.0, Backoff::default())
+        .expect("open switch");
+    let mut s = dev.subscribe();
+    assert_eq!(s.next().await.unwrap().angle_deg, 1.0);
+
+    tx.send_replace(Some(LidSwitchState::Closed));
+    let got = tokio::time::timeout(Duration::from_millis(300), s.next())
+        .await
+        .expect("pushed before the next poll")
+        .unwrap();
+    assert_eq!(got.angle_deg, 0.0);
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test(flavor = "current_thread")]
+async fn acpi_lid_file_reports_at_low_confidence() {
+    use booklid_rust::lid_switch::{ACPI_LID_CONFIDENCE, AcpiLid};
+    let path = std::env::temp_dir().join(format!("booklid-acpi-lid-{}", std::process::id()));
+    std::fs::write(&path, "state:      closed\n").unwrap();
+    let dev = LidSwitchAngle::open_with_confidence(
+        AcpiLid::at(&path),
+        Source::LinuxAcpiLid,
+        ACPI_LID_CONFIDENCE,
+        60.0,
+        Backoff::default(),
+    )
+    .expect("open acpi lid");
+    let mut s = dev.subscribe();
+    let got = s.next().await.unwrap();
+    assert_eq!((got.angle_deg, got.source), (0.0, Source::LinuxAcpiLid));
+    assert_eq!(got.confidence, ACPI_LID_CONFIDENCE);
+    assert_eq!(dev.confidence(), ACPI_LID_CONFIDENCE);
+
+    std::fs::write(&path, "state:      open\n").unwrap();
+    let got = s.next().await.unwrap();
+    let got = if got.angle_deg == 0.0 {
+        s.next().await.unwrap()
+    } else {
+        got
+    };
+    assert_eq!(got.angle_deg, 1.0);
+    std::fs::remove_file(&path).ok();
+}
+
+struct Clamshell(FakeSwitch);
+
+impl BackendFactory for Clamshell {
+    fn source(&self) -> Source {
+        Source::MacClamshell
+    }
+    fn open(
+        &self,
+        ctx: BackendContext,
+    ) -> BoxFuture<'static, booklid_rust::Result<Box<dyn AngleDevice>>> {
+        let switch = self.0.clone();
+        async move {
+            LidSwitchAngle::open(switch, Source::MacClamshell, ctx.hz, ctx.reconnect)
+                .map(|d| Box::new(d) as Box<dyn AngleDevice>)
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn clamshell_source_drives_lid_state() {
+    let switch = FakeSwitch(Arc::default());
+    switch.set(LidSwitchState::Open);
+    register_backend(Clamshell(switch.clone()));
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::MacClamshell]),
+    )
+    .await
+    .expect("open clamshell");
+    let mut events = dev.subscribe_events(LidEventConfig::default());
+    tokio::time::timeout(Duration::from_secs(2), async {
+        while !dev.is_open() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("open");
+    // Switch states drive lid events, not the angle stream.
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), dev.subscribe().next())
+            .await
+            .is_err()
+    );
+
+    switch.set(LidSwitchState::Closed);
+    let closed = tokio::time::timeout(Duration::from_secs(2), async {
+        while let Some(e) = events.next().await {
+            if e == LidEvent::LidClosed {
+                return true;
+            }
+        }
+        false
+    })
+    .await;
+    assert_eq!(closed, Ok(true));
+    assert!(dev.is_closed());
+    assert_eq!(dev.latest().unwrap().angle_deg, 0.0);
+}
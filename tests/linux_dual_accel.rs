@@ -0,0 +1,72 @@
+#![cfg(all(target_os = "linux", feature = "linux_iio_sys"))]
+
+//! The hinge angle from a 2-in-1's lid and base accelerometers, against a
+//! fixture sysfs tree.
+
+use booklid_rust::{OpenConfig, SampleRate, Source, open_with_config};
+use futures_util::StreamExt;
+use std::{fs, path::Path};
+use tokio::time::{Duration, timeout};
+
+fn write(root: &Path, rel: &str, contents: &str) {
+    let p = root.join(rel);
+    fs::create_dir_all(p.parent().unwrap()).unwrap();
+    fs::write(p, contents).unwrap();
+}
+
+fn accel(root: &Path, dev: &str, label: &str, [x, y, z]: [i32; 3]) {
+    let dir = format!("bus/iio/devices/{dev}");
+    write(root, &format!("{dir}/in_accel_x_raw"), &format!("{x}\n"));
+    write(root, &format!("{dir}/in_accel_y_raw"), &format!("{y}\n"));
+    write(root, &format!("{dir}/in_accel_z_raw"), &format!("{z}\n"));
+    write(root, &format!("{dir}/in_accel_scale"), "0.01\n");
+    write(root, &format!("{dir}/label"), &format!("{label}\n"));
+    write(root, &format!("{dir}/name"), &format!("{label}_3d\n"));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn lid_and_base_accelerometers_give_the_hinge_angle() {
+    let root = std::env::temp_dir().join(format!("booklid-dual-accel-{}", std::process::id()));
+    // Base flat on a desk; lid opened to 120°. The lid's sensor is mounted
+    // with y and z swapped, which its mount matrix undoes.
+    accel(&root, "iio:device0", "accel-base", [0, 0, -1000]);
+    accel(&root, "iio:device1", "accel-display", [0, -500, -866]);
+    write(
+        &root,
+        "bus/iio/devices/iio:device1/in_accel_mount_matrix",
+        "1, 0, 0; 0, 0, 1; 0, 1, 0\n",
+    );
+    let base = OpenConfig::new(SampleRate::hz(60.0))
+        .persistence(false)
+        .attach_daemon(false)
+        .sysfs_root(&root);
+
+    let hinge = open_with_config(base.clone().prefer(vec![Source::LinuxHinge]))
+        .await
+        .expect("open hinge");
+    assert_eq!(hinge.info().source, Source::LinuxHinge);
+    assert_eq!(hinge.info().note, "linux_sys_hinge");
+    assert_eq!(hinge.hardware_id().as_deref(), Some("iio:accel-display_3d"));
+    let s = timeout(Duration::from_secs(2), hinge.subscribe().next())
+        .await
+        .expect("hinge sample")
+        .unwrap();
+    assert!((s.angle_deg - 120.0).abs() < 0.1, "angle {}", s.angle_deg);
+
+    // The tilt source reads the lid, not the base listed before it.
+    let tilt = open_with_config(base.clone().prefer(vec![Source::LinuxTilt]))
+        .await
+        .expect("open tilt");
+    assert_eq!(tilt.hardware_id().as_deref(), Some("iio:accel-display_3d"));
+
+    // Without a base there is no hinge angle.
+    fs::remove_dir_all(root.join("bus/iio/devices/iio:device0")).unwrap();
+    let none = open_with_config(
+        base.prefer(vec![Source::LinuxHinge])
+            .disable(vec![Source::LinuxTilt, Source::LinuxALS]),
+    )
+    .await;
+    assert!(none.is_err());
+
+    fs::remove_dir_all(&root).ok();
+}
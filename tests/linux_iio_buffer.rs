@@ -0,0 +1,180 @@
+#![cfg(all(target_os = "linux", feature = "linux_iio_sys"))]
+
+//! Buffered accelerometer reads through the IIO character device, against a
+//! fixture sysfs tree and a regular file standing in for `/dev/iio:device0`.
+//! Covers the device's own trigger and an hrtimer one made through configfs.
+
+use booklid_rust::{OpenConfig, SampleRate, Source, open_with_config};
+use futures_util::StreamExt;
+use std::{fs, path::Path};
+use tokio::time::{Duration, sleep, timeout};
+
+fn write(root: &Path, rel: &str, contents: &str) {
+    let p = root.join(rel);
+    fs::create_dir_all(p.parent().unwrap()).unwrap();
+    fs::write(p, contents).unwrap();
+}
+
+fn read(root: &Path, rel: &str) -> String {
+    fs::read_to_string(root.join(rel))
+        .unwrap()
+        .trim()
+        .to_owned()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn scans_come_from_the_buffer_and_setup_is_undone() {
+    let root = std::env::temp_dir().join(format!("booklid-iio-buffer-{}", std::process::id()));
+    let (sys, dev) = (root.join("sys"), root.join("dev"));
+    let d = "bus/iio/devices/iio:device0";
+    // The attributes say face-up; the buffer says pitched 30° from it.
+    write(&sys, &format!("{d}/in_accel_x_raw"), "0\n");
+    write(&sys, &format!("{d}/in_accel_y_raw"), "0\n");
+    write(&sys, &format!("{d}/in_accel_z_raw"), "1000\n");
+    write(&sys, &format!("{d}/in_accel_scale"), "0.01\n");
+    write(&sys, &format!("{d}/name"), "accel_3d\n");
+    write(&sys, &format!("{d}/in_accel_sampling_frequency"), "25\n");
+    write(
+        &sys,
+        &format!("{d}/in_accel_sampling_frequency_available"),
+        "12.5 25 50 100\n",
+    );
+    write(&sys, &format!("{d}/buffer/enable"), "0\n");
+    write(&sys, &format!("{d}/trigger/current_trigger"), "\n");
+    write(&sys, "bus/iio/devices/trigger0/name", "accel_3d-dev0\n");
+    for (i, ch) in ["in_accel_x", "in_accel_y", "in_accel_z"]
+        .iter()
+        .enumerate()
+    {
+        write(&sys, &format!("{d}/scan_elements/{ch}_en"), "0\n");
+        write(
+            &sys,
+            &format!("{d}/scan_elements/{ch}_index"),
+            &format!("{i}\n"),
+        );
+        write(
+            &sys,
+            &format!("{d}/scan_elements/{ch}_type"),
+            "le:s16/16>>0\n",
+        );
+    }
+    write(&sys, &format!("{d}/scan_elements/in_timestamp_en"), "0\n");
+    write(
+        &sys,
+        &format!("{d}/scan_elements/in_timestamp_index"),
+        "3\n",
+    );
+    write(
+        &sys,
+        &format!("{d}/scan_elements/in_timestamp_type"),
+        "le:s64/64>>0\n",
+    );
+
+    // x, y, z, padding to 8 bytes, then a timestamp.
+    let mut scans = Vec::new();
+    for _ in 0..4 {
+        for v in [-500i16, 0, 866, 0] {
+            scans.extend_from_slice(&v.to_le_bytes());
+        }
+        scans.extend_from_slice(&0i64.to_le_bytes());
+    }
+    fs::create_dir_all(&dev).unwrap();
+    fs::write(dev.join("iio:device0"), &scans).unwrap();
+
+    // Buffered reads are opt-in: by default the attributes are polled and
+    // the buffer left alone.
+    let tilt = open_tilt(&root, false).await;
+    let s = timeout(Duration::from_secs(2), tilt.subscribe().next())
+        .await
+        .expect("polled sample")
+        .unwrap();
+    assert!((s.angle_deg - 180.0).abs() < 0.1, "angle {}", s.angle_deg);
+    assert_eq!(read(&sys, &format!("{d}/buffer/enable")), "0");
+    assert!(!tilt.info().capabilities().hardware_timestamps);
+    drop(tilt);
+
+    let tilt = open_tilt(&root, true).await;
+    assert_eq!(tilt.info().source, Source::LinuxTilt);
+    // 60 Hz asked for; the nearest rate the device has is 100 Hz.
+    assert_eq!(tilt.info().sample_hz, Some(100.0));
+    assert_eq!(
+        read(&sys, &format!("{d}/in_accel_sampling_frequency")),
+        "100"
+    );
+    let s = timeout(Duration::from_secs(2), tilt.subscribe().next())
+        .await
+        .expect("tilt sample")
+        .unwrap();
+    // 150° in the lid-angle frame, where the attributes would give 180°.
+    assert!((s.angle_deg - 150.0).abs() < 0.1, "angle {}", s.angle_deg);
+    assert!(tilt.info().capabilities().hardware_timestamps);
+
+    // While streaming: the channels, trigger and buffer are on.
+    assert_eq!(read(&sys, &format!("{d}/buffer/enable")), "1");
+    assert_eq!(
+        read(&sys, &format!("{d}/trigger/current_trigger")),
+        "accel_3d-dev0"
+    );
+    assert_eq!(
+        read(&sys, &format!("{d}/scan_elements/in_timestamp_en")),
+        "1"
+    );
+
+    // Dropping the device puts every attribute back.
+    drop(tilt);
+    sleep(Duration::from_millis(600)).await;
+    assert_eq!(read(&sys, &format!("{d}/buffer/enable")), "0");
+    assert_eq!(read(&sys, &format!("{d}/trigger/current_trigger")), "");
+    assert_eq!(read(&sys, &format!("{d}/scan_elements/in_accel_x_en")), "0");
+    assert_eq!(
+        read(&sys, &format!("{d}/in_accel_sampling_frequency")),
+        "25"
+    );
+
+    // Without a trigger of its own the device gets an hrtimer one, created
+    // through configfs (where the kernel would register `trigger1`).
+    fs::remove_dir_all(sys.join("bus/iio/devices/trigger0")).unwrap();
+    let hrtimer = sys.join("kernel/config/iio/triggers/hrtimer");
+    fs::create_dir_all(&hrtimer).unwrap();
+    write(
+        &sys,
+        "bus/iio/devices/trigger1/name",
+        "booklid-iio:device0\n",
+    );
+    write(&sys, "bus/iio/devices/trigger1/sampling_frequency", "0\n");
+
+    let tilt = open_tilt(&root, true).await;
+    timeout(Duration::from_secs(2), tilt.subscribe().next())
+        .await
+        .expect("hrtimer-triggered sample");
+    assert!(hrtimer.join("booklid-iio:device0").is_dir());
+    assert_eq!(
+        read(&sys, &format!("{d}/trigger/current_trigger")),
+        "booklid-iio:device0"
+    );
+    assert_eq!(
+        read(&sys, "bus/iio/devices/trigger1/sampling_frequency"),
+        "100"
+    );
+
+    drop(tilt);
+    sleep(Duration::from_millis(600)).await;
+    assert_eq!(read(&sys, &format!("{d}/trigger/current_trigger")), "");
+    assert!(!hrtimer.join("booklid-iio:device0").exists());
+
+    fs::remove_dir_all(&root).ok();
+}
+
+async fn open_tilt(root: &Path, buffer: bool) -> booklid_rust::AngleClient {
+    open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .persistence(false)
+            .attach_daemon(false)
+            .sysfs_root(root.join("sys"))
+            .dev_root(root.join("dev"))
+            .linux_iio_buffer(buffer)
+            .prefer(vec![Source::LinuxTilt]),
+    )
+    .await
+    .expect("open tilt")
+}
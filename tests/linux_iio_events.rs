@@ -0,0 +1,85 @@
+#![cfg(all(target_os = "linux", feature = "linux_iio_sys"))]
+
+//! A settled lid on an accelerometer with threshold events, against a fixture
+//! sysfs tree. The fixture's `/dev/iio:device0` is a regular file with no
+//! event fd, so the reader must keep sampling and leave the thresholds as it
+//! found them.
+
+use booklid_rust::{OpenConfig, SampleRate, Source, open_with_config};
+use futures_util::StreamExt;
+use std::{fs, path::Path};
+use tokio::time::{Duration, Instant, sleep, timeout};
+
+fn write(root: &Path, rel: &str, contents: &str) {
+    let p = root.join(rel);
+    fs::create_dir_all(p.parent().unwrap()).unwrap();
+    fs::write(p, contents).unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn settled_lid_keeps_sampling_without_an_event_fd() {
+    let root = std::env::temp_dir().join(format!("booklid-iio-events-{}", std::process::id()));
+    let (sys, dev) = (root.join("sys"), root.join("dev"));
+    let d = "bus/iio/devices/iio:device0";
+    write(&sys, &format!("{d}/in_accel_x_raw"), "-500\n");
+    write(&sys, &format!("{d}/in_accel_y_raw"), "0\n");
+    write(&sys, &format!("{d}/in_accel_z_raw"), "866\n");
+    write(&sys, &format!("{d}/in_accel_scale"), "0.01\n");
+    write(&sys, &format!("{d}/name"), "accel_3d\n");
+    let events = ["x", "y", "z"]
+        .iter()
+        .flat_map(|a| {
+            ["rising", "falling"].map(|dir| format!("{d}/events/in_accel_{a}_thresh_{dir}"))
+        })
+        .collect::<Vec<_>>();
+    for e in &events {
+        write(&sys, &format!("{e}_en"), "0\n");
+        write(&sys, &format!("{e}_value"), "7\n");
+    }
+    write(&dev, "iio:device0", "");
+
+    let tilt = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .persistence(false)
+            .attach_daemon(false)
+            .sysfs_root(&sys)
+            .dev_root(&dev)
+            .prefer(vec![Source::LinuxTilt]),
+    )
+    .await
+    .expect("open tilt");
+    let mut samples = tilt.subscribe();
+
+    // Well past the point where the still lid would park.
+    let end = Instant::now() + Duration::from_secs(5);
+    let mut count = 0;
+    while Instant::now() < end {
+        let s = timeout(Duration::from_millis(700), samples.next())
+            .await
+            .expect("samples keep coming")
+            .unwrap();
+        assert!((s.angle_deg - 150.0).abs() < 0.1, "angle {}", s.angle_deg);
+        count += 1;
+    }
+    assert!(count > 60, "{count} samples in 5s");
+
+    drop(samples);
+    drop(tilt);
+    sleep(Duration::from_millis(300)).await;
+    for e in &events {
+        assert_eq!(
+            fs::read_to_string(sys.join(format!("{e}_en")))
+                .unwrap()
+                .trim(),
+            "0"
+        );
+        assert_eq!(
+            fs::read_to_string(sys.join(format!("{e}_value")))
+                .unwrap()
+                .trim(),
+            "7"
+        );
+    }
+
+    fs::remove_dir_all(&root).ok();
+}
@@ -0,0 +1,134 @@
+#![cfg(all(target_os = "linux", feature = "linux_iio_sys"))]
+
+//! Linux backend discovery against a fixture sysfs tree. The root is set via
+//! an env var, so everything lives in one test.
+
+use booklid_rust::{
+    Event, OpenConfig, PermissionStatus, SampleRate, Source, SwitchReason, check_permissions,
+    linux, open_with_config, quirks,
+};
+use futures_util::StreamExt;
+use std::{fs, path::Path};
+use tokio::time::{Duration, timeout};
+
+fn write(root: &Path, rel: &str, contents: &str) {
+    let p = root.join(rel);
+    fs::create_dir_all(p.parent().unwrap()).unwrap();
+    fs::write(p, contents).unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn sys_backends_read_fixture_tree() {
+    let root = std::env::temp_dir().join(format!("booklid-sysfs-{}", std::process::id()));
+    // Accelerometer tilted 30° (x = -g·sin 30°), with a shared scale.
+    write(
+        &root,
+        "bus/iio/devices/iio:device0/in_accel_x_raw",
+        "-500\n",
+    );
+    write(&root, "bus/iio/devices/iio:device0/in_accel_y_raw", "0\n");
+    write(&root, "bus/iio/devices/iio:device0/in_accel_z_raw", "866\n");
+    write(
+        &root,
+        "bus/iio/devices/iio:device0/in_accel_scale",
+        "0.01\n",
+    );
+    write(&root, "bus/iio/devices/iio:device0/name", "accel_3d\n");
+    write(
+        &root,
+        "bus/iio/devices/iio:device1/in_illuminance_raw",
+        "120\n",
+    );
+    write(&root, "class/dmi/id/sys_vendor", "ACME\n");
+    write(&root, "class/dmi/id/product_name", "Flip 14\n");
+    // SAFETY: this test binary has a single test; nothing reads env concurrently.
+    unsafe { std::env::set_var(linux::SYSFS_ROOT_ENV, &root) };
+
+    assert_eq!(linux::sysfs_root(), root);
+    assert_eq!(quirks::machine_model().as_deref(), Some("ACME Flip 14"));
+    assert_eq!(
+        check_permissions(Source::LinuxTilt),
+        PermissionStatus::Granted
+    );
+
+    let base = OpenConfig::new(SampleRate::hz(60.0))
+        .persistence(false)
+        .attach_daemon(false);
+
+    let tilt = open_with_config(base.clone().prefer(vec![Source::LinuxTilt]))
+        .await
+        .expect("open tilt");
+    assert_eq!(tilt.info().note, "linux_sys_tilt");
+    assert_eq!(
+        tilt.hardware_id().as_deref(),
+        Some("iio:accel_3d@ACME Flip 14")
+    );
+    let s = timeout(Duration::from_secs(2), tilt.subscribe().next())
+        .await
+        .expect("tilt sample")
+        .unwrap();
+    // Pitched 30° from face-up: 150° in the lid-angle frame.
+    assert!((s.angle_deg - 150.0).abs() < 0.1, "angle {}", s.angle_deg);
+
+    let als = open_with_config(base.clone().prefer(vec![Source::LinuxALS]))
+        .await
+        .expect("open als");
+    assert_eq!(als.info().note, "linux_sys_als");
+    assert_eq!(als.hardware_id(), None);
+    let s = timeout(Duration::from_secs(2), als.subscribe_als().next())
+        .await
+        .expect("als reading")
+        .unwrap();
+    assert!((0.0..=1.0).contains(&s.angle_deg));
+    let l = timeout(Duration::from_secs(2), als.subscribe_lux().next())
+        .await
+        .expect("lux sample")
+        .unwrap();
+    assert_eq!(l.source, Source::LinuxALS);
+    assert_eq!(l.lux, 120.0);
+    assert!(tilt.subscribe_lux().next().await.is_none());
+
+    // Warm standby: the mock takes over once the accelerometer disappears;
+    // the ALS is skipped, as its samples aren't degrees.
+    let dev = open_with_config(
+        base.prefer(vec![Source::LinuxTilt, Source::LinuxALS, Source::Mock])
+            .allow_mock(true)
+            .warm_standby(true),
+    )
+    .await
+    .expect("open with standby");
+    assert_eq!(dev.info().source, Source::LinuxTilt);
+    let mut events = dev.events();
+    let mut samples = dev.subscribe();
+    timeout(Duration::from_secs(2), samples.next())
+        .await
+        .expect("primary sample");
+    fs::remove_dir_all(root.join("bus/iio/devices/iio:device0")).unwrap();
+    let switched = timeout(Duration::from_secs(5), async {
+        while let Some(e) = events.next().await {
+            if let Event::SourceSwitched { from, to, reason } = e {
+                return (from, to, reason);
+            }
+        }
+        panic!("events ended");
+    })
+    .await
+    .expect("switch event");
+    assert_eq!(
+        switched,
+        (Source::LinuxTilt, Source::Mock, SwitchReason::Stalled)
+    );
+    let s = timeout(Duration::from_secs(2), async {
+        loop {
+            let s = samples.next().await.unwrap();
+            if s.source == Source::Mock {
+                return s;
+            }
+        }
+    })
+    .await
+    .expect("standby sample");
+    assert_eq!(s.source, Source::Mock);
+
+    fs::remove_dir_all(&root).ok();
+}
@@ -1,13 +1,13 @@
 #![cfg(feature = "mock")]
 
-use booklid_rust::{OpenConfig, open_with_config};
+use booklid_rust::{OpenConfig, SampleRate, open_with_config};
 
 use futures_util::StreamExt;
 use tokio::time::{Duration, sleep, timeout};
 
 #[tokio::test(flavor = "current_thread")]
 async fn open_with_mock_returns_and_latest_updates() {
-    let dev = open_with_config(OpenConfig::new(60.0).allow_mock(true))
+    let dev = open_with_config(OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true))
         .await
         .expect("open mock");
     // latest should become Some within ~1s
@@ -24,7 +24,7 @@ async fn open_with_mock_returns_and_latest_updates() {
 
 #[tokio::test(flavor = "current_thread")]
 async fn subscribe_yields_items_quickly() {
-    let dev = open_with_config(OpenConfig::new(60.0).allow_mock(true))
+    let dev = open_with_config(OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true))
         .await
         .expect("open mock");
     let mut s = dev.subscribe();
@@ -38,7 +38,7 @@ async fn subscribe_yields_items_quickly() {
 async fn smoothing_reduces_jitter() {
     // use futures_util::StreamExt;
 
-    let dev = open_with_config(OpenConfig::new(60.0).allow_mock(true))
+    let dev = open_with_config(OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true))
         .await
         .expect("open mock");
 
@@ -91,3 +91,642 @@ where
         .sum::<f32>()
         / (vals.len().max(1) as f32)
 }
+
+#[test]
+fn subscribe_blocking_receives_samples() {
+    let dev = booklid_rust::open_blocking_with_config(
+        OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true),
+    )
+    .expect("open mock");
+    let rx = dev.subscribe_blocking();
+    let s = rx
+        .recv_timeout(std::time::Duration::from_millis(750))
+        .expect("sample within timeout");
+    assert_eq!(s.source, booklid_rust::Source::Mock);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn wait_for_and_stats() {
+    let dev = open_with_config(OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true))
+        .await
+        .expect("open mock");
+    let s = dev
+        .wait_for(|s| s.angle_deg > 0.0, Duration::from_millis(750))
+        .await;
+    assert!(s.is_some(), "wait_for timed out");
+    sleep(Duration::from_millis(100)).await;
+    let st = dev.stats();
+    assert!(st.samples > 0);
+    assert!(st.live, "mock confidence is 1.0, gate should be live");
+    assert_eq!(st.gate_transitions, 1);
+    assert_eq!(dev.health(), booklid_rust::Health::Healthy);
+    let chosen = dev.probes().last().expect("at least one probe");
+    assert!(chosen.ok && chosen.source == dev.info().source);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn clones_share_one_sampler() {
+    let dev = open_with_config(OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true))
+        .await
+        .expect("open mock");
+    let other = dev.clone();
+    let h = tokio::spawn(async move {
+        other
+            .wait_for(|_| true, Duration::from_millis(750))
+            .await
+            .is_some()
+    });
+    assert!(h.await.unwrap(), "clone did not observe samples");
+    sleep(Duration::from_millis(50)).await;
+    // Counters are shared, not per-clone.
+    assert!(dev.stats().samples > 0);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn watch_and_broadcast_bridges() {
+    let dev = open_with_config(OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true))
+        .await
+        .expect("open mock");
+
+    let (tx, mut brx) = tokio::sync::broadcast::channel(16);
+    let fwd = dev.broadcast_to(tx);
+    let got = timeout(Duration::from_millis(750), brx.recv())
+        .await
+        .expect("no timeout");
+    assert!(got.is_ok());
+    fwd.abort();
+
+    let mut wrx = dev.into_watch();
+    timeout(Duration::from_millis(750), wrx.changed())
+        .await
+        .expect("no timeout")
+        .expect("sender alive");
+    assert!(wrx.borrow().is_some());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn slow_subscriber_lag_is_reported() {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    let dev = open_with_config(OpenConfig::new(SampleRate::hz(1000.0)).allow_mock(true))
+        .await
+        .expect("open mock");
+    let seen = Arc::new(AtomicU64::new(0));
+    let seen_c = Arc::clone(&seen);
+    dev.on_lag(move |_, n| {
+        seen_c.fetch_add(n, Ordering::Relaxed);
+    });
+
+    let (id, mut s) = dev.subscribe_with_id();
+    // Fall well behind the 256-sample queue before polling.
+    sleep(Duration::from_millis(600)).await;
+    let _ = s.next().await;
+
+    let lag = dev.subscriber_lag();
+    let mine = lag.iter().find(|l| l.id == id).expect("subscriber listed");
+    assert!(mine.missed > 0);
+    assert_eq!(seen.load(Ordering::Relaxed), mine.missed);
+    assert!(dev.stats().lagged >= mine.missed);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn cancelled_open_returns_cancelled() {
+    let token = booklid_rust::CancellationToken::new();
+    token.cancel();
+    let r = booklid_rust::open_with_config_cancellable(
+        OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true),
+        token,
+    )
+    .await;
+    assert!(matches!(r, Err(booklid_rust::Error::Cancelled)));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn histogram_counts_samples() {
+    use booklid_rust::HistogramConfig;
+    let cfg = OpenConfig::new(SampleRate::hz(120.0))
+        .allow_mock(true)
+        .prefer(vec![booklid_rust::Source::Mock])
+        .persistence(false)
+        .histogram(HistogramConfig::default());
+    let dev = open_with_config(cfg).await.expect("open mock");
+    let mut s = dev.subscribe();
+    for _ in 0..5 {
+        s.next().await;
+    }
+    let h = dev.histogram().expect("histogram enabled");
+    assert!(h.total() >= 5);
+    // The mock oscillates around 95–115°.
+    let mode = h.mode().unwrap();
+    assert!((70.0..=140.0).contains(&mode), "mode {mode}");
+    dev.reset_histogram();
+    assert!(dev.histogram().unwrap().total() <= 1);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn output_map_scales_emitted_values() {
+    use booklid_rust::OutputMap;
+    let cfg = OpenConfig::new(SampleRate::hz(120.0))
+        .allow_mock(true)
+        .prefer(vec![booklid_rust::Source::Mock])
+        .persistence(false)
+        .output_map(OutputMap::new(0.0..=180.0, 0.0..=1.0));
+    let dev = open_with_config(cfg).await.expect("open mock");
+    let mut s = dev.subscribe();
+    for _ in 0..5 {
+        let v = s.next().await.expect("sample").angle_deg;
+        assert!((0.0..=1.0).contains(&v), "mapped {v}");
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn axis_inverts_emitted_values() {
+    use booklid_rust::AxisTransform;
+    let cfg = OpenConfig::new(SampleRate::hz(120.0))
+        .allow_mock(true)
+        .prefer(vec![booklid_rust::Source::Mock])
+        .persistence(false)
+        .axis(AxisTransform {
+            invert: true,
+            offset_deg: 0.0,
+        });
+    let dev = open_with_config(cfg).await.expect("open mock");
+    let mut s = dev.subscribe();
+    for _ in 0..5 {
+        let v = s.next().await.expect("sample").angle_deg;
+        assert!(v < 0.0, "inverted {v}");
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn source_weights_order_and_exclude() {
+    use booklid_rust::Source;
+    let base = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .persistence(false)
+        .attach_daemon(false);
+
+    let dev = open_with_config(base.clone().weight(Source::Mock, 10.0))
+        .await
+        .expect("open mock");
+    assert_eq!(dev.info().source, Source::Mock);
+
+    assert!(
+        base.clone()
+            .weight(Source::ALS, f32::NAN)
+            .validate()
+            .is_err()
+    );
+    let zero_but_preferred = base.weight(Source::Mock, 0.0).prefer(vec![Source::Mock]);
+    assert!(zero_but_preferred.validate().is_err());
+}
+
+#[cfg(not(target_os = "windows"))]
+#[tokio::test(flavor = "current_thread")]
+async fn open_with_progress_reports_probes() {
+    use booklid_rust::{InitEvent, Source, open_with_progress};
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .persistence(false)
+        .attach_daemon(false)
+        .prefer(vec![Source::WinHinge, Source::Mock]);
+
+    let (events, open) = open_with_progress(cfg);
+    let dev = open.await.expect("open mock");
+    assert_eq!(dev.info().source, Source::Mock);
+
+    let events: Vec<InitEvent> = events.collect().await;
+    assert_eq!(events[0], InitEvent::ProbingSource(Source::WinHinge));
+    assert_eq!(events[1], InitEvent::ProbingSource(Source::Mock));
+    assert!(events.iter().any(|e| matches!(
+        e,
+        InitEvent::SourceFailed { source: Source::WinHinge, reason } if !reason.is_empty()
+    )));
+    assert_eq!(
+        events.last(),
+        Some(&InitEvent::SourceSelected(Source::Mock))
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn open_lazy_attaches_in_background() {
+    use booklid_rust::{Event, Health, Source, open_lazy};
+    let dev = open_lazy(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .expect("lazy client");
+    assert_eq!(dev.health(), Health::Acquiring);
+    assert!(dev.latest().is_none());
+
+    let mut events = dev.events();
+    let attached = timeout(Duration::from_secs(2), async {
+        while let Some(e) = events.next().await {
+            if let Event::Attached { source } = e {
+                return source;
+            }
+        }
+        panic!("events ended");
+    })
+    .await
+    .expect("attached");
+    assert_eq!(attached, Source::Mock);
+    assert_eq!(dev.info().source, Source::Mock);
+    let s = timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample")
+        .unwrap();
+    assert_eq!(s.source, Source::Mock);
+    assert_ne!(dev.health(), Health::Acquiring);
+}
+
+#[test]
+fn open_lazy_outside_a_runtime_uses_the_internal_one() {
+    use booklid_rust::{Source, open_lazy};
+    let dev = open_lazy(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .expect("lazy client");
+    let started = std::time::Instant::now();
+    while dev.latest().is_none() {
+        assert!(started.elapsed() < Duration::from_secs(2), "no sample");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(dev.info().source, Source::Mock);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn raw_is_unsmoothed_and_uncorrected() {
+    use booklid_rust::{AxisTransform, Source};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock])
+            .smoothing(0.05)
+            .axis(AxisTransform {
+                invert: true,
+                offset_deg: 0.0,
+            }),
+    )
+    .await
+    .expect("open mock");
+    let got: Vec<_> = timeout(Duration::from_secs(2), dev.subscribe().take(20).collect())
+        .await
+        .expect("samples");
+    // The mock's raw readings sit around +95°; emitted values are inverted.
+    assert!(got.iter().all(|s| s.raw > 0.0 && s.angle_deg < 0.0));
+    assert!(got.iter().any(|s| (s.raw + s.angle_deg).abs() > 1e-3));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn calibrate_reference_shifts_the_axis_offset() {
+    use booklid_rust::{AxisTransform, Source};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock])
+            .smoothing(1.0)
+            .axis(AxisTransform {
+                invert: true,
+                offset_deg: 0.0,
+            }),
+    )
+    .await
+    .expect("open mock");
+    assert!(dev.calibrate_reference(f32::NAN).is_err());
+    let mut s = dev.subscribe();
+    s.next().await.unwrap();
+
+    // The existing inversion is kept; only the offset moves.
+    let axis = dev.calibrate_reference(100.0).expect("calibrate");
+    assert!(axis.invert);
+    for _ in 0..5 {
+        let got = s.next().await.unwrap();
+        assert!((got.angle_deg + got.raw - axis.offset_deg).abs() < 1e-3);
+        // The mock moves under a degree per sample.
+        assert!((got.angle_deg - 100.0).abs() < 10.0, "{}", got.angle_deg);
+    }
+
+    dev.calibrate_zero().expect("calibrate");
+    let got = s.next().await.unwrap();
+    assert!(got.angle_deg.abs() < 2.0, "{}", got.angle_deg);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn velocity_tracks_the_readings() {
+    use booklid_rust::Source;
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock])
+            .smoothing(0.05),
+    )
+    .await
+    .expect("open mock");
+    let got: Vec<_> = timeout(Duration::from_secs(3), dev.subscribe().take(60).collect())
+        .await
+        .expect("samples");
+    // Integrating the velocity gives back the movement, give or take the
+    // low-pass lag; smoothing does not hold it back.
+    let travelled: f32 = got
+        .windows(2)
+        .map(|w| w[1].velocity_deg_s * (w[1].timestamp - w[0].timestamp).as_secs_f32())
+        .sum();
+    let moved = got[got.len() - 1].raw - got[0].raw;
+    assert!(moved.abs() > 5.0, "mock barely moved: {moved}");
+    assert!((travelled - moved).abs() < 5.0, "{travelled} vs {moved}");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn kalman_filter_tracks_with_less_lag_than_ema() {
+    use booklid_rust::{Filter, Source};
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .persistence(false)
+        .attach_daemon(false)
+        .prefer(vec![Source::Mock]);
+    assert!(
+        cfg.clone()
+            .filter(Filter::Kalman { q: 1.0, r: 0.0 })
+            .validate()
+            .is_err()
+    );
+
+    // Mean distance between the smoothed and raw readings once settled.
+    async fn lag(cfg: OpenConfig) -> f32 {
+        let dev = open_with_config(cfg).await.expect("open mock");
+        let got: Vec<_> = timeout(Duration::from_secs(3), dev.subscribe().take(80).collect())
+            .await
+            .expect("samples");
+        let settled = &got[20..];
+        settled
+            .iter()
+            .map(|s| (s.angle_deg - s.raw).abs())
+            .sum::<f32>()
+            / settled.len() as f32
+    }
+    let ema = lag(cfg.clone().smoothing(0.05)).await;
+    let kalman = lag(cfg.filter(Filter::Kalman { q: 500.0, r: 1.0 })).await;
+    assert!(kalman < ema / 2.0, "kalman {kalman} vs ema {ema}");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn time_constant_smoothing_is_rate_independent() {
+    use booklid_rust::Source;
+    // The mock advances its waveform per sample, so its lid moves three
+    // times as fast in °/s at 90 Hz as at 30 Hz. A fixed time constant lags
+    // by about speed × tau, i.e. three times as far; a fixed alpha lags by
+    // the same number of samples, i.e. about as far.
+    async fn lag(hz: f32, tau: Option<Duration>) -> f32 {
+        let cfg = OpenConfig::new(SampleRate::hz(hz))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]);
+        let cfg = match tau {
+            Some(tau) => cfg.smoothing_tau(tau),
+            None => cfg.smoothing(0.2),
+        };
+        let dev = open_with_config(cfg).await.expect("open mock");
+        let got: Vec<_> = timeout(Duration::from_secs(5), dev.subscribe().take(40).collect())
+            .await
+            .expect("samples");
+        let settled = &got[15..];
+        settled
+            .iter()
+            .map(|s| (s.angle_deg - s.raw).abs())
+            .sum::<f32>()
+            / settled.len() as f32
+    }
+    let tau = Some(Duration::from_millis(100));
+    let (slow, fast) = (lag(30.0, tau).await, lag(90.0, tau).await);
+    assert!(fast > 2.0 * slow, "tau: {slow} at 30 Hz vs {fast} at 90 Hz");
+    let (slow, fast) = (lag(30.0, None).await, lag(90.0, None).await);
+    assert!(
+        fast < 1.5 * slow,
+        "alpha: {slow} at 30 Hz vs {fast} at 90 Hz"
+    );
+
+    let none = lag(30.0, Some(Duration::ZERO)).await;
+    assert!(none < 1e-3, "tau 0 lagged by {none}");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn filter_params_switch_at_runtime() {
+    use booklid_rust::{FilterParams, Source};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .await
+    .expect("open mock");
+
+    // Mean distance between the smoothed and raw readings once settled.
+    async fn lag(dev: &booklid_rust::AngleClient) -> f32 {
+        let got: Vec<_> = timeout(Duration::from_secs(3), dev.subscribe().take(40).collect())
+            .await
+            .expect("samples");
+        let settled = &got[20..];
+        settled
+            .iter()
+            .map(|s| (s.angle_deg - s.raw).abs())
+            .sum::<f32>()
+            / settled.len() as f32
+    }
+    dev.set_filter_params(FilterParams::stable());
+    let stable = lag(&dev).await;
+    dev.set_filter_params(FilterParams::responsive());
+    let responsive = lag(&dev).await;
+    assert!(
+        responsive < stable / 3.0,
+        "responsive {responsive} vs stable {stable}"
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn switching_filters_does_not_jump() {
+    use booklid_rust::{Filter, Source};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock])
+            .smoothing(0.3),
+    )
+    .await
+    .expect("open mock");
+    let mut s = dev.subscribe();
+    let mut prev = s.next().await.unwrap().angle_deg;
+    for i in 0..30 {
+        match i {
+            10 => dev.set_filter(Filter::Kalman { q: 500.0, r: 1.0 }),
+            20 => dev.set_smoothing(0.3),
+            _ => {}
+        }
+        let a = s.next().await.unwrap().angle_deg;
+        // The mock moves under 2° per tick.
+        assert!((a - prev).abs() < 3.0, "jumped from {prev} to {a}");
+        prev = a;
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn subscribe_raw_skips_smoothing() {
+    use booklid_rust::Source;
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock])
+            .smoothing(0.05),
+    )
+    .await
+    .expect("open mock");
+    let mut smoothed = dev.subscribe();
+    let mut raw = dev.subscribe_raw();
+    for _ in 0..20 {
+        let (s, r) = timeout(Duration::from_secs(1), async {
+            (smoothed.next().await.unwrap(), raw.next().await.unwrap())
+        })
+        .await
+        .expect("samples");
+        assert_eq!(s.timestamp, r.timestamp);
+        assert_eq!(r.angle_deg, s.raw);
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn subscribe_raw_corrects_each_sample_once() {
+    use booklid_rust::{AxisTransform, Source};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock])
+            .confidence_scale(Source::Mock, 4.0)
+            .axis(AxisTransform {
+                invert: true,
+                offset_deg: 360.0,
+            }),
+    )
+    .await
+    .expect("open mock");
+    let mut smoothed = dev.subscribe();
+    let mut raw = dev.subscribe_raw();
+    let mut moving = false;
+    for _ in 0..20 {
+        let (s, r) = timeout(Duration::from_secs(1), async {
+            (smoothed.next().await.unwrap(), raw.next().await.unwrap())
+        })
+        .await
+        .expect("samples");
+        assert_eq!(r.angle_deg, 360.0 - s.raw);
+        assert_eq!(r.confidence, s.confidence);
+        assert_eq!(r.velocity_deg_s, s.velocity_deg_s);
+        moving |= s.velocity_deg_s != 0.0;
+    }
+    assert!(moving);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn self_test_passes_on_mock_and_fails_when_closed() {
+    use booklid_rust::{SelfTestFailure, Source};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .await
+    .expect("open mock");
+    let report = dev.self_test(20).await;
+    assert!(report.passed(), "{report:?}");
+    assert_eq!((report.source, report.samples), (Source::Mock, 20));
+    assert!(report.rate_hz.unwrap() > 5.0);
+    assert!((75.0..=115.0).contains(&report.mean), "{report:?}");
+
+    dev.close();
+    let report = dev.self_test(5).await;
+    assert_eq!(
+        report.failures,
+        vec![SelfTestFailure::TooFewSamples { got: 0, wanted: 5 }]
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn events_since_replays_missed_events() {
+    use booklid_rust::{Event, Source};
+    use std::time::Instant;
+    let start = Instant::now();
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .await
+    .expect("open mock");
+    // Nobody subscribed to events(); the gate still goes live.
+    timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample");
+    let log = dev.events_since(start);
+    let (at, ev) = *log.first().expect("logged event");
+    assert!(matches!(ev, Event::Live { .. }), "{ev:?}");
+    assert!(at >= start);
+    assert!(dev.events_since(at).iter().all(|(t, _)| *t > at));
+    assert!(dev.events_since(Instant::now()).is_empty());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn soak_reports_snapshots_and_drift() {
+    use booklid_rust::{Health, Source, soak};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .await
+    .expect("open mock");
+    let mut seen = 0;
+    let report = soak(
+        &dev,
+        Duration::from_millis(700),
+        Duration::from_millis(200),
+        |_| seen += 1,
+    )
+    .await;
+    assert_eq!(seen, report.snapshots.len());
+    assert!(report.snapshots.len() >= 4, "{report:?}");
+    let last = report.snapshots.last().unwrap();
+    assert!(last.elapsed >= Duration::from_millis(700));
+    assert!(last.samples > report.snapshots[0].samples);
+    assert_eq!(last.health, Health::Healthy);
+    assert!(report.max_drift.unwrap() < 0.5, "{report:?}");
+    assert_eq!((report.reconnects, report.source_switches), (0, 0));
+    assert!(cfg!(not(target_os = "linux")) || report.rss_growth_bytes.is_some());
+}
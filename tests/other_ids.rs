@@ -0,0 +1,30 @@
+// Separate test binary: the cap on interned `Source::Other` ids is
+// process-global, and other tests deserialize ids of their own.
+
+use booklid_rust::{
+    AngleDevice, BackendContext, BackendFactory, Error, Result, Source, register_backend,
+};
+use futures_util::{FutureExt, future::BoxFuture};
+
+struct Broken;
+
+impl BackendFactory for Broken {
+    fn source(&self) -> Source {
+        Source::Other("broken")
+    }
+    fn open(&self, _ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        async { Err(Error::Backend("no such hardware".into())) }.boxed()
+    }
+}
+
+#[test]
+fn other_ids_from_input_are_capped() {
+    let parse = |id: String| serde_json::from_value::<Source>(serde_json::json!({ "Other": id }));
+    let flood: Vec<_> = (0..1000).map(|i| parse(format!("flood-{i}"))).collect();
+    assert!(flood[0].is_ok());
+    assert!(flood.last().unwrap().is_err());
+    // Ids seen before the cap keep resolving, and registered ones always do.
+    assert!(parse("flood-0".into()).is_ok());
+    register_backend(Broken);
+    assert_eq!(parse("broken".into()).unwrap(), Source::Other("broken"));
+}
@@ -0,0 +1,29 @@
+//! Permission pre-flight checks.
+
+use booklid_rust::{PermissionStatus, Source, check_permissions};
+
+#[test]
+fn permission_preflight_reports_platform_limits() {
+    assert_eq!(check_permissions(Source::Mock), PermissionStatus::Granted);
+    assert_eq!(
+        check_permissions(Source::Other("x")),
+        PermissionStatus::Unknown
+    );
+    if !cfg!(target_os = "windows") {
+        let s = check_permissions(Source::WinHinge);
+        assert!(matches!(s, PermissionStatus::Unavailable { .. }));
+        assert!(s.hint().is_some());
+    }
+    if cfg!(target_os = "linux") && !cfg!(feature = "linux_libinput") {
+        assert_eq!(
+            check_permissions(Source::LinuxLidSwitch).hint(),
+            Some("enable the `linux_libinput` feature")
+        );
+    }
+    if cfg!(target_os = "linux") && !cfg!(feature = "linux_upower") {
+        assert_eq!(
+            check_permissions(Source::LinuxUPowerLid).hint(),
+            Some("enable the `linux_upower` feature")
+        );
+    }
+}
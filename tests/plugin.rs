@@ -0,0 +1,108 @@
+#![cfg(feature = "plugins")]
+//! The plugin ABI, driven by an in-process function table.
+
+use booklid_rust::plugin::{ABI_VERSION, PluginReading, PluginV1, load_plugins, register_plugin};
+use booklid_rust::{OpenConfig, SampleRate, Source, open_with_config};
+use futures_util::StreamExt;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::time::{Duration, timeout};
+
+static OPENED: AtomicU32 = AtomicU32::new(0);
+static CLOSED: AtomicU32 = AtomicU32::new(0);
+
+extern "C" fn open(_hz: f32) -> *mut c_void {
+    OPENED.fetch_add(1, Ordering::SeqCst);
+    Box::into_raw(Box::new(0u32)).cast()
+}
+
+extern "C" fn read(handle: *mut c_void, out: *mut PluginReading) -> i32 {
+    // SAFETY: `handle` came from `open`, `out` is the host's reading slot.
+    let n = unsafe { &mut *handle.cast::<u32>() };
+    *n += 1;
+    if *n == 1 {
+        return 0; // warming up
+    }
+    unsafe {
+        *out = PluginReading {
+            angle_deg: 123.0,
+            confidence: 0.9,
+        }
+    };
+    1
+}
+
+extern "C" fn close(handle: *mut c_void) {
+    drop(unsafe { Box::from_raw(handle.cast::<u32>()) });
+    CLOSED.fetch_add(1, Ordering::SeqCst);
+}
+
+static PLUGIN: PluginV1 = PluginV1 {
+    abi_version: ABI_VERSION,
+    id: c"test_plugin".as_ptr(),
+    open,
+    read,
+    close,
+};
+
+static FUTURE_PLUGIN: PluginV1 = PluginV1 {
+    abi_version: ABI_VERSION + 1,
+    id: c"future_plugin".as_ptr(),
+    open,
+    read,
+    close,
+};
+
+#[tokio::test(flavor = "current_thread")]
+async fn registered_plugin_samples_and_closes() {
+    let src = unsafe { register_plugin(&PLUGIN) }.expect("register");
+    assert_eq!(src, Source::Other("test_plugin"));
+
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(100.0))
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![src])
+            .smoothing(1.0),
+    )
+    .await
+    .expect("open plugin");
+    assert_eq!(dev.info().source, src);
+    assert_eq!(dev.info().note, "plugin");
+    let s = timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample")
+        .unwrap();
+    assert_eq!((s.angle_deg, s.raw, s.confidence), (123.0, 123.0, 0.9));
+    assert_eq!(s.source, src);
+
+    // Closing aborts the sampler, which releases the handle once it unwinds.
+    dev.close();
+    timeout(Duration::from_secs(1), async {
+        while CLOSED.load(Ordering::SeqCst) < OPENED.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("plugin handle closed");
+
+    let err = unsafe { register_plugin(&FUTURE_PLUGIN) }.unwrap_err();
+    assert!(err.to_string().contains("ABI version"), "{err}");
+}
+
+#[test]
+fn load_plugins_reports_bad_libraries() {
+    let dir = std::env::temp_dir().join(format!("booklid-plugins-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let bogus = dir.join(format!("bogus.{}", std::env::consts::DLL_EXTENSION));
+    std::fs::write(&bogus, b"not a library").unwrap();
+    std::fs::write(dir.join("README.txt"), b"ignored").unwrap();
+
+    let loaded = unsafe { load_plugins(&dir) };
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].0, bogus);
+    assert!(loaded[0].1.is_err());
+
+    assert!(unsafe { load_plugins(dir.join("missing")) }.is_empty());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
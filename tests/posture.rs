@@ -0,0 +1,86 @@
+//! Posture classification.
+
+use booklid_rust::{AngleSample, AngleStreamExt, Posture, PostureConfig, Source};
+use futures_util::StreamExt;
+use std::time::Instant;
+
+fn sample(source: Source, angle_deg: f32, confidence: f32) -> AngleSample {
+    AngleSample::new(angle_deg, source, Instant::now()).confidence(confidence)
+}
+
+#[test]
+fn angles_map_to_postures() {
+    let cfg = PostureConfig::default();
+    let at = |a| cfg.classify(&sample(Source::WinHinge, a, 1.0));
+    assert_eq!(at(2.0), Posture::Closed);
+    assert_eq!(at(110.0), Posture::Laptop);
+    assert_eq!(at(180.0), Posture::Flat);
+    assert_eq!(at(280.0), Posture::Tent);
+    assert_eq!(at(355.0), Posture::Tablet);
+    assert_eq!(
+        cfg.classify(&sample(Source::WinHinge, 110.0, 0.2)),
+        Posture::Unknown
+    );
+    assert_eq!(
+        cfg.classify(&sample(Source::WinALS, 0.5, 1.0)),
+        Posture::Unknown
+    );
+}
+
+#[tokio::test]
+async fn posture_stream_reports_changes_with_hysteresis() {
+    // 158..163 straddles the laptop/flat bound at 160 without clearing it by 5°.
+    let angles = [100.0, 158.0, 163.0, 158.0, 170.0, 162.0, 150.0, 5.0];
+    let got: Vec<_> = futures_util::stream::iter(angles.map(|a| sample(Source::WinHinge, a, 1.0)))
+        .postures(PostureConfig::default())
+        .collect()
+        .await;
+    assert_eq!(
+        got,
+        [
+            Posture::Laptop,
+            Posture::Flat,
+            Posture::Laptop,
+            Posture::Closed
+        ]
+    );
+}
+
+#[tokio::test]
+async fn posture_jumps_across_several_bounds() {
+    // 198 is within 5° of the flat/tent bound, but well clear of laptop's;
+    // 202 is near flat's bounds, far from closed's.
+    let angles = [100.0, 198.0, 5.0, 202.0];
+    let got: Vec<_> = futures_util::stream::iter(angles.map(|a| sample(Source::WinHinge, a, 1.0)))
+        .postures(PostureConfig::default())
+        .collect()
+        .await;
+    assert_eq!(
+        got,
+        [
+            Posture::Laptop,
+            Posture::Flat,
+            Posture::Closed,
+            Posture::Tent
+        ]
+    );
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test]
+async fn client_reports_posture() {
+    use booklid_rust::{OpenConfig, SampleRate, open_with_config};
+    let dev = open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .prefer(vec![Source::Mock]),
+    )
+    .await
+    .expect("open mock");
+    // The mock sweeps roughly 75..115°: always laptop.
+    let first = dev.subscribe_posture().next().await.unwrap();
+    assert_eq!(first, Posture::Laptop);
+    assert_eq!(dev.current_posture(), Posture::Laptop);
+}
@@ -0,0 +1,92 @@
+//! `OpenConfig::prefer_low_power` and per-source power costs.
+
+use booklid_rust::{
+    AngleDevice, AngleSample, AngleStream, BackendContext, BackendFactory, DeviceInfo, OpenConfig,
+    PowerCost, Result, SampleRate, Source, open_with_config, register_backend,
+};
+use futures_util::{FutureExt, StreamExt, future::BoxFuture};
+use std::time::Instant;
+
+struct Still(Source);
+
+impl AngleDevice for Still {
+    fn latest(&self) -> Option<AngleSample> {
+        Some(AngleSample::new(100.0, self.0, Instant::now()))
+    }
+    fn subscribe(&self) -> AngleStream {
+        futures_util::stream::pending().boxed()
+    }
+    fn set_smoothing(&self, _alpha: f32) {}
+    fn confidence(&self) -> f32 {
+        1.0
+    }
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(self.0, "still")
+    }
+}
+
+struct Factory(Source, PowerCost);
+
+impl BackendFactory for Factory {
+    fn source(&self) -> Source {
+        self.0
+    }
+    fn open(&self, _ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        let src = self.0;
+        async move { Ok(Box::new(Still(src)) as Box<dyn AngleDevice>) }.boxed()
+    }
+    fn power_cost(&self) -> PowerCost {
+        self.1
+    }
+}
+
+#[test]
+fn sources_are_annotated_with_power_cost() {
+    assert_eq!(Source::WinHinge.power_cost(), PowerCost::Low);
+    assert_eq!(Source::WinLidSwitch.power_cost(), PowerCost::Low);
+    assert_eq!(Source::LinuxLidSwitch.power_cost(), PowerCost::Low);
+    assert_eq!(Source::LinuxUPowerLid.power_cost(), PowerCost::Low);
+    assert_eq!(Source::LinuxAcpiLid.power_cost(), PowerCost::Moderate);
+    assert_eq!(Source::HingeFeature.power_cost(), PowerCost::High);
+    assert_eq!(Source::LinuxTilt.power_cost(), PowerCost::Moderate);
+    assert!(PowerCost::Low < PowerCost::High);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn low_power_mode_prefers_the_cheaper_source() {
+    register_backend(Factory(Source::Other("hungry"), PowerCost::High));
+    register_backend(Factory(Source::Other("frugal"), PowerCost::Low));
+    let base = OpenConfig::new(SampleRate::hz(30.0))
+        .persistence(false)
+        .attach_daemon(false)
+        // Leave only the registered sources, whatever this machine has.
+        .disable(vec![
+            Source::HingeFeature,
+            Source::HingeHid,
+            Source::HingeIOKit,
+            Source::HingeSMC,
+            Source::ALS,
+            Source::WinHinge,
+            Source::WinPosture,
+            Source::WinTilt,
+            Source::WinALS,
+            Source::LinuxHinge,
+            Source::LinuxTilt,
+            Source::LinuxALS,
+            Source::MacClamshell,
+            Source::WinLidSwitch,
+            Source::LinuxLidSwitch,
+            Source::LinuxUPowerLid,
+            Source::LinuxAcpiLid,
+        ]);
+
+    // Registration order decides between equally accurate sources...
+    let dev = open_with_config(base.clone()).await.expect("open");
+    assert_eq!(dev.info().source, Source::Other("hungry"));
+
+    // ...unless power counts.
+    let dev = open_with_config(base.prefer_low_power(true))
+        .await
+        .expect("open low power");
+    assert_eq!(dev.info().source, Source::Other("frugal"));
+}
@@ -0,0 +1,149 @@
+#![cfg(all(feature = "record", feature = "mock"))]
+
+use booklid_rust::record::{Recorder, Recording};
+use booklid_rust::{
+    OpenConfig, SampleRate, Source, open_replay, open_replay_with_control, open_with_config,
+};
+use futures_util::StreamExt;
+use std::time::{Duration, SystemTime};
+
+#[tokio::test]
+async fn recording_round_trips_with_aligned_timestamps() {
+    let path = std::env::temp_dir().join(format!("booklid-rec-{}.jsonl", std::process::id()));
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .attach_daemon(false);
+    let dev = open_with_config(cfg.clone()).await.expect("open mock");
+
+    let rec = Recorder::start(&dev, &path).expect("start recorder");
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    rec.stop().await.expect("stop recorder");
+
+    let r = Recording::load(&path).expect("load");
+    assert_eq!(r.header.source, Source::Mock);
+    let age = SystemTime::now()
+        .duration_since(r.header.started_at())
+        .unwrap();
+    assert!(age < Duration::from_secs(5));
+    assert!(r.samples.len() >= 5, "only {} samples", r.samples.len());
+    assert!(r.samples.windows(2).all(|w| w[0].offset <= w[1].offset));
+    assert!(
+        r.samples
+            .iter()
+            .all(|s| s.raw.is_some() && s.velocity_deg_s.is_some())
+    );
+    let first = r.samples[0];
+    assert_eq!(r.offset_of(r.wall_time(&first)), Some(first.offset));
+
+    // Replay rebuilds Instants whose spacing matches the recorded offsets.
+    let replay = open_replay(&path, cfg).await.expect("open replay");
+    assert_eq!(replay.info().note, "replay");
+    let got: Vec<_> = replay.subscribe().take(3).collect().await;
+    // Subscribing may miss the first few samples; line up on the first seen.
+    let at = r
+        .samples
+        .iter()
+        .position(|s| s.angle_deg == got[0].angle_deg)
+        .expect("replayed sample is in the recording");
+    for (pair, rec) in got.windows(2).zip(r.samples[at..].windows(2)) {
+        let dt = pair[1].timestamp - pair[0].timestamp;
+        assert_eq!(dt, rec[1].offset - rec[0].offset);
+        assert_eq!(pair[1].angle_deg, rec[1].angle_deg);
+        assert_eq!(Some(pair[1].raw), rec[1].raw);
+        assert_eq!(Some(pair[1].velocity_deg_s), rec[1].velocity_deg_s);
+    }
+    std::fs::remove_file(&path).ok();
+}
+
+#[test]
+fn recordings_without_raw_still_load() {
+    let path = std::env::temp_dir().join(format!("booklid-v1-{}.jsonl", std::process::id()));
+    std::fs::write(
+        &path,
+        "{\"version\":1,\"source\":\"Mock\",\"started_utc_ms\":0}\n\
+         {\"t_us\":0,\"angle_deg\":90.0,\"source\":\"Mock\",\"confidence\":1.0}\n",
+    )
+    .unwrap();
+    let r = Recording::load(&path).expect("load");
+    assert_eq!(r.samples[0].raw, None);
+    let s = r.samples_at(std::time::Instant::now()).next().unwrap();
+    assert_eq!(s.raw, 90.0);
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn replay_speed_pause_and_seek() {
+    let path = std::env::temp_dir().join(format!("booklid-scrub-{}.jsonl", std::process::id()));
+    let mut text = String::from("{\"version\":1,\"source\":\"Mock\",\"started_utc_ms\":0}\n");
+    for i in 0..40 {
+        text += &format!(
+            "{{\"t_us\":{},\"angle_deg\":{i}.0,\"source\":\"Mock\",\"confidence\":1.0}}\n",
+            i * 100_000
+        );
+    }
+    std::fs::write(&path, text).unwrap();
+
+    let cfg = OpenConfig::new(SampleRate::hz(10.0));
+    let (replay, ctl) = open_replay_with_control(&path, cfg)
+        .await
+        .expect("open replay");
+    assert_eq!(ctl.duration(), Duration::from_millis(3900));
+    assert!(ctl.set_speed(200.0).is_err());
+    ctl.set_speed(10.0).unwrap();
+
+    let mut s = replay.subscribe();
+    let a = s.next().await.unwrap();
+    let b = s.next().await.unwrap();
+    assert_eq!(b.angle_deg, a.angle_deg + 1.0);
+    assert_eq!(b.timestamp - a.timestamp, Duration::from_millis(10));
+
+    ctl.pause();
+    assert!(ctl.is_paused());
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    let mut s = replay.subscribe();
+    let idle = tokio::time::timeout(Duration::from_millis(100), s.next()).await;
+    assert!(idle.is_err(), "paused replay delivered {idle:?}");
+
+    // Seeking by wall-clock time lands on the recording's timeline.
+    ctl.seek_to(SystemTime::UNIX_EPOCH + Duration::from_millis(2500));
+    ctl.resume();
+    let c = s.next().await.unwrap();
+    assert_eq!(c.angle_deg, 25.0);
+    assert_eq!(ctl.position(), Duration::from_millis(2500));
+
+    ctl.pause();
+    ctl.seek(Duration::from_millis(1000));
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    let mut s = replay.subscribe();
+    ctl.resume();
+    let d = s.next().await.unwrap();
+    assert_eq!(d.angle_deg, 10.0);
+    std::fs::remove_file(&path).ok();
+}
+
+#[tokio::test]
+async fn annotations_are_recorded_on_the_sample_timeline() {
+    let path = std::env::temp_dir().join(format!("booklid-notes-{}.jsonl", std::process::id()));
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .prefer(vec![Source::Mock])
+        .attach_daemon(false);
+    let dev = open_with_config(cfg).await.expect("open mock");
+
+    let rec = Recorder::start(&dev, &path).expect("start recorder");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    dev.clone().annotate("user clicked suspend");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    dev.annotate("screen locked");
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    rec.stop().await.expect("stop recorder");
+
+    let r = Recording::load(&path).expect("load");
+    let labels: Vec<_> = r.annotations.iter().map(|a| a.label.as_str()).collect();
+    assert_eq!(labels, ["user clicked suspend", "screen locked"]);
+    let (a, b) = (r.annotations[0].offset, r.annotations[1].offset);
+    assert!(a >= Duration::from_millis(90) && b >= a + Duration::from_millis(90));
+    assert!(r.samples.iter().any(|s| s.offset < a));
+    assert!(r.samples.iter().any(|s| s.offset > a && s.offset < b));
+    std::fs::remove_file(&path).ok();
+}
@@ -0,0 +1,248 @@
+//! Third-party backends registered with `register_backend`.
+
+use booklid_rust::calibrate::{AngleCalibration, CalibrationPoint};
+use booklid_rust::{
+    AngleDevice, AngleSample, AngleStream, BackendContext, BackendFactory, Capabilities,
+    DeviceInfo, Error, OpenConfig, Result, SampleRate, Source, open_with_config, quirks,
+    register_backend,
+};
+use futures_util::{FutureExt, StreamExt, future::BoxFuture};
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+
+/// A sensor stuck at 42°.
+struct Fixed {
+    hz: f32,
+}
+
+fn sample() -> AngleSample {
+    AngleSample::new(42.0, Source::Other("fixed"), Instant::now())
+}
+
+impl AngleDevice for Fixed {
+    fn latest(&self) -> Option<AngleSample> {
+        Some(sample())
+    }
+    fn subscribe(&self) -> AngleStream {
+        let period = Duration::from_secs_f32(1.0 / self.hz);
+        futures_util::stream::unfold((), move |()| async move {
+            tokio::time::sleep(period).await;
+            Some((sample(), ()))
+        })
+        .boxed()
+    }
+    fn set_smoothing(&self, _alpha: f32) {}
+    fn confidence(&self) -> f32 {
+        1.0
+    }
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(Source::Other("fixed"), "fixed")
+    }
+}
+
+struct FixedFactory;
+
+impl BackendFactory for FixedFactory {
+    fn source(&self) -> Source {
+        Source::Other("fixed")
+    }
+    fn open(&self, ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        async move { Ok(Box::new(Fixed { hz: ctx.hz }) as Box<dyn AngleDevice>) }.boxed()
+    }
+}
+
+/// A `Fixed` sensor that only learns its hardware id after a while, like a
+/// HID backend reading its first reports.
+struct LateId {
+    fixed: Fixed,
+    opened: Instant,
+}
+
+fn late(mut s: AngleSample) -> AngleSample {
+    s.source = Source::Other("late");
+    s
+}
+
+impl AngleDevice for LateId {
+    fn latest(&self) -> Option<AngleSample> {
+        self.fixed.latest().map(late)
+    }
+    fn subscribe(&self) -> AngleStream {
+        self.fixed.subscribe().map(late).boxed()
+    }
+    fn set_smoothing(&self, _alpha: f32) {}
+    fn confidence(&self) -> f32 {
+        1.0
+    }
+    fn info(&self) -> DeviceInfo {
+        DeviceInfo::new(Source::Other("late"), "late")
+    }
+    fn hardware_id(&self) -> Option<String> {
+        (self.opened.elapsed() >= Duration::from_millis(200)).then(|| "late:1".into())
+    }
+}
+
+struct LateIdFactory;
+
+impl BackendFactory for LateIdFactory {
+    fn source(&self) -> Source {
+        Source::Other("late")
+    }
+    fn open(&self, ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        async move {
+            let dev = LateId {
+                fixed: Fixed { hz: ctx.hz },
+                opened: Instant::now(),
+            };
+            Ok(Box::new(dev) as Box<dyn AngleDevice>)
+        }
+        .boxed()
+    }
+}
+
+struct Broken;
+
+impl BackendFactory for Broken {
+    fn source(&self) -> Source {
+        Source::Other("broken")
+    }
+    fn open(&self, _ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        async { Err(Error::Backend("no such hardware".into())) }.boxed()
+    }
+}
+
+/// A sensor the OS refuses to open.
+struct Refused;
+
+impl BackendFactory for Refused {
+    fn source(&self) -> Source {
+        Source::Other("refused")
+    }
+    fn open(&self, _ctx: BackendContext) -> BoxFuture<'static, Result<Box<dyn AngleDevice>>> {
+        async {
+            Err(Error::PermissionDenied {
+                sensor: Source::Other("refused"),
+                hint: "grant access".into(),
+            })
+        }
+        .boxed()
+    }
+}
+
+fn base() -> OpenConfig {
+    OpenConfig::new(SampleRate::hz(60.0))
+        .persistence(false)
+        .attach_daemon(false)
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn registered_backend_is_selected_and_gated() {
+    register_backend(FixedFactory);
+    let dev = open_with_config(base().prefer(vec![Source::Other("fixed")]))
+        .await
+        .expect("open registered backend");
+    assert_eq!(dev.info().source, Source::Other("fixed"));
+    assert!(
+        dev.probes()
+            .iter()
+            .any(|p| p.source == Source::Other("fixed") && p.ok)
+    );
+    let s = timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample")
+        .unwrap();
+    assert_eq!(s.angle_deg, 42.0);
+    assert_eq!(dev.latest().map(|s| s.angle_deg), Some(42.0));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn failing_registered_backend_falls_through() {
+    register_backend(Broken);
+    register_backend(FixedFactory);
+    let dev =
+        open_with_config(base().prefer(vec![Source::Other("broken"), Source::Other("fixed")]))
+            .await
+            .expect("open");
+    assert_eq!(dev.info().source, Source::Other("fixed"));
+    assert!(
+        dev.probes()
+            .iter()
+            .any(|p| p.source == Source::Other("broken") && !p.ok)
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn calibration_follows_a_late_hardware_id() {
+    register_backend(LateIdFactory);
+    let id = match quirks::machine_model() {
+        Some(model) => format!("late:1@{model}"),
+        None => "late:1".into(),
+    };
+    let point = |raw, angle_deg| CalibrationPoint { raw, angle_deg };
+    let cal = AngleCalibration::fit(
+        Source::Other("late"),
+        &[point(0.0, 0.0), point(100.0, 50.0)],
+    )
+    .unwrap()
+    .for_hardware(Some(id.clone()));
+    let dev = open_with_config(base().prefer(vec![Source::Other("late")]).calibration(cal))
+        .await
+        .expect("open");
+    // Tied to a sensor: not applied while the backend has no id.
+    assert_eq!(dev.hardware_id(), None);
+    assert!(dev.calibrations().is_empty());
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(dev.hardware_id(), Some(id));
+    assert_eq!(dev.calibrations().len(), 1);
+    let s = timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample")
+        .unwrap();
+    assert_eq!(s.angle_deg, 21.0);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn refused_backend_is_reported_over_no_backend() {
+    register_backend(Broken);
+    register_backend(Refused);
+    let cfg = base()
+        .prefer(vec![Source::Other("refused")])
+        .only_preferred(true);
+    let err = open_with_config(cfg).await.err().expect("nothing opens");
+    match err {
+        Error::PermissionDenied { sensor, hint } => {
+            assert_eq!(sensor, Source::Other("refused"));
+            assert_eq!(hint, "grant access");
+        }
+        e => panic!("expected PermissionDenied, got {e:?}"),
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn watch_degrees_yields_plain_angles() {
+    register_backend(FixedFactory);
+    let got: Vec<f32> = timeout(
+        Duration::from_secs(2),
+        booklid_rust::watch_degrees(SampleRate::hz(30.0))
+            .take(3)
+            .collect(),
+    )
+    .await
+    .expect("three angles");
+    assert_eq!(got.len(), 3);
+    // A real sensor outranks the registered one where present.
+    assert!(
+        got.iter()
+            .all(|d| d.is_finite() && (-5.0..=365.0).contains(d))
+    );
+}
+
+#[test]
+fn other_source_round_trips() {
+    let s = Source::Other("acme_hinge");
+    let json = serde_json::to_string(&s).unwrap();
+    let back: Source = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, s);
+    assert!(!Capabilities::for_source(back).true_degrees);
+}
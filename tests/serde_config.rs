@@ -0,0 +1,28 @@
+#![cfg(feature = "serde")]
+
+use booklid_rust::{OpenConfig, SampleRate, Source, win::WinReporting};
+use std::time::Duration;
+
+#[test]
+fn open_config_round_trips() {
+    let cfg = OpenConfig::new(SampleRate::hz(30.0))
+        .prefer(vec![Source::WinHinge])
+        .fail_after(Duration::from_millis(1500))
+        .hid_override(0x1209, 0xb00c, 0x20, 0x008A, 2)
+        .win_reporting(WinReporting::low_power());
+    let json = serde_json::to_string(&cfg).unwrap();
+    let back: OpenConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(back.rate, SampleRate::hz(30.0));
+    assert_eq!(back.prefer_sources, vec![Source::WinHinge]);
+    assert_eq!(back.fail_after, Duration::from_millis(1500));
+    assert_eq!(back.hid_override, cfg.hid_override);
+    assert_eq!(back.win_reporting, WinReporting::low_power());
+}
+
+#[test]
+fn partial_config_uses_defaults_and_validates_rate() {
+    let cfg: OpenConfig = serde_json::from_str(r#"{ "rate": 10.0 }"#).unwrap();
+    assert_eq!(cfg.rate.as_hz(), 10.0);
+    assert!(cfg.persistence);
+    assert!(serde_json::from_str::<OpenConfig>(r#"{ "rate": 0.0 }"#).is_err());
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "mock")]
+
+// Separate test binary: `shutdown()` is process-global and would close
+// clients belonging to tests running in parallel.
+
+use booklid_rust::{OpenConfig, SampleRate};
+
+#[test]
+fn shutdown_closes_clients_and_allows_reopen() {
+    let cfg = OpenConfig::new(SampleRate::hz(60.0)).allow_mock(true);
+    let dev = booklid_rust::open_blocking_with_config(cfg.clone()).expect("open mock");
+    let rx = dev.subscribe_blocking();
+    rx.recv_timeout(std::time::Duration::from_millis(750))
+        .expect("sample before shutdown");
+
+    booklid_rust::shutdown();
+    assert!(dev.latest().is_none());
+    assert_eq!(dev.confidence(), 0.0);
+    // Forwarding stops with the runtime; drain whatever was already queued.
+    while rx
+        .recv_timeout(std::time::Duration::from_millis(200))
+        .is_ok()
+    {}
+
+    let again = booklid_rust::open_blocking_with_config(cfg).expect("reopen after shutdown");
+    let rx = again.subscribe_blocking();
+    assert!(
+        rx.recv_timeout(std::time::Duration::from_millis(750))
+            .is_ok()
+    );
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "mock")]
+
+//! Following power notifications: pausing across sleep, and clamshell
+//! closes taken as authoritative.
+
+use booklid_rust::{
+    AngleClient, LidEvent, LidEventConfig, OpenConfig, PowerEvent, PowerNotifications, SampleRate,
+    Source, open_with_config,
+};
+use futures_util::{StreamExt, stream::BoxStream};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::timeout;
+use tokio_stream::wrappers::BroadcastStream;
+
+#[derive(Clone)]
+struct FakePower(broadcast::Sender<PowerEvent>);
+
+impl FakePower {
+    fn new() -> Self {
+        Self(broadcast::channel(16).0)
+    }
+
+    /// Send `ev` and give the client a moment to act on it.
+    async fn send(&self, ev: PowerEvent) {
+        self.0.send(ev).unwrap();
+        tokio::time::sleep(Duration::from_millis(30)).await;
+    }
+}
+
+impl PowerNotifications for FakePower {
+    fn subscribe(&self) -> BoxStream<'static, PowerEvent> {
+        BroadcastStream::new(self.0.subscribe())
+            .filter_map(|it| async move { it.ok() })
+            .boxed()
+    }
+}
+
+async fn open_mock() -> AngleClient {
+    open_with_config(
+        OpenConfig::new(SampleRate::hz(60.0))
+            .allow_mock(true)
+            .persistence(false)
+            .attach_daemon(false)
+            .power_notifications(false)
+            .prefer(vec![Source::Mock])
+            .smoothing(1.0),
+    )
+    .await
+    .expect("open mock")
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn sleep_pauses_sampling_until_wake() {
+    let dev = open_mock().await;
+    let power = FakePower::new();
+    dev.follow_power(power.clone());
+    let mut s = dev.subscribe();
+    s.next().await.unwrap();
+    assert!(!dev.is_suspended());
+
+    power.send(PowerEvent::WillSleep).await;
+    assert!(dev.is_suspended());
+    assert!(dev.latest().is_none(), "no stale reading while asleep");
+    let mut s = dev.subscribe();
+    assert!(
+        timeout(Duration::from_millis(150), s.next()).await.is_err(),
+        "no samples while asleep"
+    );
+
+    power.send(PowerEvent::DidWake).await;
+    assert!(!dev.is_suspended());
+    let got = timeout(Duration::from_secs(1), s.next())
+        .await
+        .expect("resumed")
+        .unwrap();
+    assert_eq!(got.source, Source::Mock);
+    assert!(dev.latest().is_some());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn clamshell_close_is_authoritative() {
+    let dev = open_mock().await;
+    let power = FakePower::new();
+    dev.follow_power(power.clone());
+    // The mock sweeps roughly 75..115°: open.
+    dev.set_lid_thresholds(10.0, 20.0).unwrap();
+    let mut s = dev.subscribe();
+    let mut events = dev.subscribe_events(LidEventConfig::default().debounce(Duration::ZERO));
+    s.next().await.unwrap();
+    assert!(dev.is_open());
+
+    power.send(PowerEvent::ClamshellClosed).await;
+    let ev = timeout(Duration::from_secs(1), events.next())
+        .await
+        .expect("lid event");
+    assert_eq!(ev, Some(LidEvent::LidClosed));
+
+    // Angles well above the open threshold keep arriving, and only angles:
+    // the lid stays closed.
+    for _ in 0..5 {
+        assert_eq!(s.next().await.unwrap().source, Source::Mock);
+    }
+    assert!(dev.is_closed());
+    assert!(
+        timeout(Duration::from_millis(100), events.next())
+            .await
+            .is_err(),
+        "angles do not reopen the lid"
+    );
+
+    power.send(PowerEvent::ClamshellOpened).await;
+    s.next().await.unwrap();
+    assert!(dev.is_open());
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn waking_lets_angles_decide_again() {
+    let dev = open_mock().await;
+    let power = FakePower::new();
+    dev.follow_power(power.clone());
+    dev.set_lid_thresholds(10.0, 20.0).unwrap();
+    let mut s = dev.subscribe();
+    let mut events = dev.subscribe_events(LidEventConfig::default().debounce(Duration::ZERO));
+    s.next().await.unwrap();
+
+    power.send(PowerEvent::ClamshellClosed).await;
+    power.send(PowerEvent::WillSleep).await;
+    // The lid opened while asleep, unseen.
+    power.send(PowerEvent::DidWake).await;
+    s.next().await.unwrap();
+    assert!(dev.is_open());
+    let mut next = async || timeout(Duration::from_secs(1), events.next()).await;
+    assert_eq!(next().await, Ok(Some(LidEvent::LidClosed)));
+    assert_eq!(next().await, Ok(Some(LidEvent::LidOpened)));
+}
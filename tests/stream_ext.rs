@@ -0,0 +1,69 @@
+use booklid_rust::{AngleSample, AngleStreamExt, Source};
+use futures_util::{StreamExt, stream};
+use std::time::Instant;
+
+fn samples(vals: &[(f32, f32)]) -> impl futures_util::Stream<Item = AngleSample> + Send + 'static {
+    let v: Vec<AngleSample> = vals
+        .iter()
+        .map(|&(angle_deg, confidence)| {
+            AngleSample::new(angle_deg, Source::Mock, Instant::now()).confidence(confidence)
+        })
+        .collect();
+    stream::iter(v)
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn deadband_and_confidence_filter() {
+    let out: Vec<f32> = samples(&[(10.0, 1.0), (10.5, 1.0), (12.0, 0.2), (13.0, 1.0)])
+        .min_confidence(0.5)
+        .deadband(2.0)
+        .map(|s| s.angle_deg)
+        .collect()
+        .await;
+    assert_eq!(out, vec![10.0, 13.0]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn zones_emit_on_change_only() {
+    let out: Vec<&str> = samples(&[
+        (5.0, 1.0),
+        (6.0, 1.0),
+        (95.0, 1.0),
+        (200.0, 1.0),
+        (3.0, 1.0),
+    ])
+    .zones(vec![(0.0..=10.0, "closed"), (10.0..=180.0, "open")])
+    .collect()
+    .await;
+    assert_eq!(out, vec!["closed", "open", "closed"]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn smoothed_follows_ema() {
+    let out: Vec<f32> = samples(&[(0.0, 1.0), (10.0, 1.0)])
+        .smoothed(0.5)
+        .map(|s| s.angle_deg)
+        .collect()
+        .await;
+    assert_eq!(out, vec![0.0, 5.0]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn aggregated_summarizes_each_window() {
+    use std::time::Duration;
+    let t0 = Instant::now();
+    let at = |ms: u64, angle_deg: f32| {
+        AngleSample::new(angle_deg, Source::Mock, t0 + Duration::from_millis(ms))
+    };
+    // Two samples in [0, 100), none in [100, 200), one in [200, 300).
+    let out: Vec<_> = stream::iter(vec![at(0, 10.0), at(50, 20.0), at(250, 40.0)])
+        .aggregated(Duration::from_millis(100))
+        .collect()
+        .await;
+    assert_eq!(out.len(), 2);
+    let a = out[0];
+    assert_eq!((a.count, a.min, a.max, a.mean), (2, 10.0, 20.0, 15.0));
+    assert!((a.stddev - 5.0).abs() < 1e-4);
+    assert_eq!(out[1].start, t0 + Duration::from_millis(200));
+    assert_eq!((out[1].count, out[1].stddev), (1, 0.0));
+}
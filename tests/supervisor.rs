@@ -0,0 +1,39 @@
+#![cfg(feature = "mock")]
+
+// Separate test binary: the mock's panic hook is read from the environment.
+
+use booklid_rust::{Event, OpenConfig, SampleRate, open_with_config};
+use futures_util::StreamExt;
+use tokio::time::{Duration, timeout};
+
+#[tokio::test]
+async fn panicked_sampler_is_restarted() {
+    // SAFETY: the only test in this binary.
+    unsafe { std::env::set_var("BOOKLID_MOCK_PANIC_AFTER", "5") };
+
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .attach_daemon(false);
+    let dev = open_with_config(cfg).await.expect("open mock");
+    let mut events = dev.events();
+
+    let restarted = timeout(Duration::from_secs(2), async {
+        while let Some(ev) = events.next().await {
+            if let Event::SamplerRestarted { restarts } = ev {
+                return restarts;
+            }
+        }
+        0
+    })
+    .await
+    .expect("restart event");
+    assert_eq!(restarted, 1);
+    assert_eq!(dev.stats().sampler_restarts, 1);
+
+    // Samples flow again after the restart, past the lock the panic poisoned.
+    let s = timeout(Duration::from_secs(1), dev.subscribe().next())
+        .await
+        .expect("sample after restart");
+    assert!(s.is_some());
+    assert!(dev.latest().is_some());
+}
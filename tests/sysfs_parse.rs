@@ -0,0 +1,143 @@
+#![cfg(target_os = "linux")]
+
+//! Parsing of raw sysfs attribute contents.
+
+use booklid_rust::LidSwitchState;
+use booklid_rust::linux::{
+    ScanType, accel_threshold_raw, parse_acpi_lid_state, parse_attr, parse_hwmon_lux,
+    parse_mount_matrix, parse_scan_type, parse_tilt_class, pick_sampling_frequency,
+    pitch_from_accel, scan_layout,
+};
+
+#[test]
+fn attrs_must_be_finite_numbers() {
+    assert_eq!(parse_attr("120\n"), Some(120.0));
+    assert_eq!(parse_attr(" 0.009576 "), Some(0.009576));
+    assert_eq!(parse_attr("nan"), None);
+    assert_eq!(parse_attr("inf\n"), None);
+    assert_eq!(parse_attr(""), None);
+    assert_eq!(parse_attr("12 lux"), None);
+}
+
+#[test]
+fn hwmon_lux_detects_millilux() {
+    assert_eq!(parse_hwmon_lux("350\n"), Some(350.0));
+    assert_eq!(parse_hwmon_lux("350000\n"), Some(350.0));
+}
+
+#[test]
+fn pitch_handles_degenerate_vectors() {
+    let p = pitch_from_accel(-500.0, 0.0, 866.0).unwrap();
+    assert!((p - 30.0).abs() < 0.01, "{p}");
+    assert_eq!(pitch_from_accel(0.0, 0.0, 0.0), None);
+    assert_eq!(
+        pitch_from_accel(f32::MAX, f32::MAX, 0.0).map(|p| p.round()),
+        Some(-90.0)
+    );
+    assert_eq!(pitch_from_accel(f32::NAN, 0.0, 1.0), None);
+}
+
+#[test]
+fn pitch_covers_the_full_turn() {
+    // Leaning over past upright towards face-down.
+    let p = pitch_from_accel(-500.0, 0.0, -866.0).unwrap();
+    assert!((p - 150.0).abs() < 0.01, "{p}");
+    // Tipped back past flat, and face-down.
+    let p = pitch_from_accel(500.0, 0.0, -866.0).unwrap();
+    assert!((p + 150.0).abs() < 0.01, "{p}");
+    assert_eq!(pitch_from_accel(0.0, 0.0, -1.0).map(f32::abs), Some(180.0));
+}
+
+#[test]
+fn acpi_lid_state_reads_open_and_closed() {
+    assert_eq!(
+        parse_acpi_lid_state("state:      open\n"),
+        Some(LidSwitchState::Open)
+    );
+    assert_eq!(
+        parse_acpi_lid_state("state:      closed\n"),
+        Some(LidSwitchState::Closed)
+    );
+    assert_eq!(parse_acpi_lid_state("state:      unknown\n"), None);
+    assert_eq!(parse_acpi_lid_state(""), None);
+}
+
+#[test]
+fn mount_matrix_reads_three_rows() {
+    assert_eq!(
+        parse_mount_matrix("0, 1, 0; -1, 0, 0; 0, 0, 1\n"),
+        Some([[0.0, 1.0, 0.0], [-1.0, 0.0, 0.0], [0.0, 0.0, 1.0]])
+    );
+    assert_eq!(parse_mount_matrix("1, 0, 0; 0, 1, 0"), None);
+    assert_eq!(parse_mount_matrix("1, 0; 0, 1; 0, 0"), None);
+    assert_eq!(parse_mount_matrix("1, 0, 0, 0; 0, 1, 0; 0, 0, 1"), None);
+    assert_eq!(
+        parse_mount_matrix("1, 0, 0; 0, 1, 0; 0, 0, 1; 0, 0, 0"),
+        None
+    );
+    assert_eq!(parse_mount_matrix("1, 0, 0; 0, x, 0; 0, 0, 1"), None);
+}
+
+#[test]
+fn tilt_classes_map_to_pitch() {
+    assert_eq!(parse_tilt_class("face-up"), Some(0.0));
+    assert_eq!(parse_tilt_class("vertical"), Some(90.0));
+    assert_eq!(parse_tilt_class("face-down"), Some(180.0));
+    assert_eq!(parse_tilt_class("undefined"), None);
+    assert_eq!(parse_tilt_class(""), None);
+}
+
+#[test]
+fn scan_types_decode_stored_words() {
+    let t = parse_scan_type("le:s12/16>>4\n").unwrap();
+    assert_eq!((t.bits, t.storage_bits, t.shift, t.bytes()), (12, 16, 4, 2));
+    assert_eq!(t.decode(&0xfff0u16.to_le_bytes()), Some(-1));
+    assert_eq!(t.decode(&0x7ff0u16.to_le_bytes()), Some(2047));
+    assert_eq!(t.decode(&[0]), None);
+    let t = parse_scan_type("be:u24/32>>0").unwrap();
+    assert_eq!(t.decode(&[0xff, 0x01, 0x02, 0x03]), Some(0x010203));
+    assert_eq!(parse_scan_type("le:s16/16X2>>0").unwrap().bytes(), 4);
+    assert_eq!(parse_scan_type("le:s17/16>>0"), None);
+    assert_eq!(parse_scan_type("xe:s16/16>>0"), None);
+    assert_eq!(parse_scan_type("le:s16/24>>0"), None);
+}
+
+#[test]
+fn scan_layout_aligns_each_channel() {
+    let t = |s| parse_scan_type(s).unwrap();
+    let s16 = t("le:s16/16>>0");
+    let ts: ScanType = t("le:s64/64>>0");
+    assert_eq!(scan_layout(&[s16, s16, s16, ts]), (vec![0, 2, 4, 8], 16));
+    assert_eq!(scan_layout(&[s16, s16, s16]), (vec![0, 2, 4], 6));
+    assert_eq!(
+        scan_layout(&[t("le:u8/8>>0"), t("le:s32/32>>0")]),
+        (vec![0, 4], 8)
+    );
+}
+
+#[test]
+fn sampling_frequency_picks_a_supported_rate() {
+    let listed = "12.5 25 50 100 200\n";
+    assert_eq!(pick_sampling_frequency(listed, 60.0), Some(100.0));
+    assert_eq!(pick_sampling_frequency(listed, 50.0), Some(50.0));
+    assert_eq!(pick_sampling_frequency(listed, 400.0), Some(200.0));
+    assert_eq!(pick_sampling_frequency("[1 1 100]", 60.0), Some(60.0));
+    assert_eq!(pick_sampling_frequency("[1 1 40]", 60.0), Some(40.0));
+    assert_eq!(pick_sampling_frequency("", 60.0), None);
+    assert_eq!(pick_sampling_frequency("fast slow", 60.0), None);
+}
+
+#[test]
+fn wake_thresholds_round_away_from_the_settled_reading() {
+    // 9.75 m/s² at 0.25 m/s² per count, 0.5 m/s² either side.
+    assert_eq!(accel_threshold_raw(9.75, 0.5, 0.25, true), Some(41));
+    assert_eq!(accel_threshold_raw(9.75, 0.5, 0.25, false), Some(37));
+    // Coarse counts never land inside the band.
+    assert_eq!(accel_threshold_raw(0.0, 0.5, 0.3, true), Some(2));
+    assert_eq!(accel_threshold_raw(0.0, 0.5, 0.3, false), Some(-2));
+    assert_eq!(accel_threshold_raw(-4.0, 0.5, 1.0, true), Some(-3));
+    // A negative scale only sets the unit.
+    assert_eq!(accel_threshold_raw(9.75, 0.5, -0.25, true), Some(41));
+    assert_eq!(accel_threshold_raw(1.0, 0.5, 0.0, true), None);
+    assert_eq!(accel_threshold_raw(1.0, 0.5, f32::NAN, false), None);
+}
@@ -0,0 +1,27 @@
+#![cfg(target_os = "linux")]
+
+//! The udev rules installer.
+
+use booklid_rust::linux::{UDEV_RULES, write_udev_rules};
+
+#[test]
+fn udev_rules_write_is_idempotent() {
+    let dir = std::env::temp_dir().join(format!("booklid-udev-{}", std::process::id()));
+    let path = dir.join("70-booklid.rules");
+    assert!(write_udev_rules(&path).unwrap());
+    assert!(!write_udev_rules(&path).unwrap());
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), UDEV_RULES);
+    std::fs::remove_dir_all(&dir).ok();
+
+    // Access through logind, and only to the hidraw devices probed.
+    let rules = UDEV_RULES.lines().filter(|l| !l.starts_with('#'));
+    for rule in rules {
+        assert!(rule.ends_with(r#"TAG+="uaccess""#), "{rule}");
+        if rule.contains("hidraw") {
+            assert!(
+                rule.contains("DRIVERS==") || rule.contains("KERNELS=="),
+                "{rule}"
+            );
+        }
+    }
+}
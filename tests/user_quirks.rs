@@ -0,0 +1,214 @@
+//! The user quirks file. Its own test binary: the file is read once per
+//! process from `BOOKLID_QUIRKS`, or per open from `OpenConfig::quirks_file`.
+
+use booklid_rust::quirks::{self, Layout, UserQuirks};
+use booklid_rust::{AxisTransform, Source};
+
+#[test]
+fn parses_hid_axis_and_source_overrides() {
+    let q = UserQuirks::parse(
+        r#"
+        [sources]
+        prefer = ["LinuxTilt", "LinuxALS"]
+        disable = ["WinALS"]
+
+        [[hid]]
+        vendor_id = 0x05ac
+        product_id = 0x8104
+        model = "MacBookPro18"
+        report_id = 2
+        scale = 0.015625
+
+        [[hid]]
+        vendor_id = 0x1209
+        product_id = 0x0001
+        offset = 3
+        layout = "i16be"
+        max_deg = 180.0
+
+        [[axis]]
+        model = "LENOVO 82"
+        source = "LinuxTilt"
+        invert = true
+        offset_deg = 180.0
+        "#,
+    )
+    .expect("parse");
+    assert_eq!(q.prefer, vec![Source::LinuxTilt, Source::LinuxALS]);
+    assert_eq!(q.disable, vec![Source::WinALS]);
+
+    assert_eq!(q.hid[0].model, "MacBookPro18");
+    assert_eq!(q.hid[0].format.report_id, 2);
+    assert_eq!(q.hid[0].format.scale, 1.0 / 64.0);
+    // Unset fields keep the default format.
+    assert_eq!(q.hid[0].format.offset, 1);
+    assert_eq!(q.hid[1].model, "");
+    assert_eq!(q.hid[1].format.report_id, 1);
+    assert_eq!(q.hid[1].format.layout, Layout::I16Be);
+    assert_eq!(q.hid[1].format.max_deg, 180.0);
+
+    assert_eq!(
+        quirks::lookup_in(&q.axis, "LENOVO 82XY", Source::LinuxTilt),
+        Some(AxisTransform {
+            invert: true,
+            offset_deg: 180.0
+        })
+    );
+
+    // Typos and unusable formats are errors rather than silently ignored.
+    assert!(UserQuirks::parse("[sources]\nprefered = []").is_err());
+    assert!(UserQuirks::parse("[[hid]]\nvendor_id = 1\nproduct_id = 2\nscale = 0.0").is_err());
+    assert_eq!(UserQuirks::parse("").expect("empty"), UserQuirks::default());
+}
+
+#[test]
+fn parses_and_matches_windows_custom_hinges() {
+    let q = UserQuirks::parse(
+        r#"
+        [[win_custom_hinge]]
+        interface_id = "4a8b0f52-3b1c-4d2e-9f60-7a1b2c3d4e5f"
+        angle_key = "{4a8b0f52-3b1c-4d2e-9f60-7a1b2c3d4e5f} 2"
+
+        [[win_custom_hinge]]
+        model = "Microsoft Corporation Surface Book"
+        interface_id = "{0F1E2D3C-4B5A-6978-8796-A5B4C3D2E1F0}"
+        angle_key = "{0F1E2D3C-4B5A-6978-8796-A5B4C3D2E1F0} 5"
+        "#,
+    )
+    .expect("parse");
+    assert_eq!(q.win_custom_hinge[0].model, "");
+    assert_eq!(
+        q.win_custom_hinge[0].interface_id,
+        0x4a8b0f52_3b1c_4d2e_9f60_7a1b2c3d4e5f
+    );
+    assert_eq!(
+        q.win_custom_hinge[1].interface_id,
+        0x0f1e2d3c_4b5a_6978_8796_a5b4c3d2e1f0
+    );
+
+    // A model's own entry is tried before the catch-all.
+    let keys = |model| {
+        quirks::win_custom_hinges_in(&q.win_custom_hinge, model)
+            .iter()
+            .map(|h| h.angle_key.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        keys(Some("Microsoft Corporation Surface Book 3")),
+        vec![
+            q.win_custom_hinge[1].angle_key.clone(),
+            q.win_custom_hinge[0].angle_key.clone()
+        ]
+    );
+    assert_eq!(
+        keys(Some("LENOVO 82")),
+        vec![q.win_custom_hinge[0].angle_key.clone()]
+    );
+    assert_eq!(keys(None), vec![q.win_custom_hinge[0].angle_key.clone()]);
+
+    assert_eq!(quirks::parse_guid("4a8b0f52-3b1c-4d2e-9f60"), None);
+    assert_eq!(
+        quirks::parse_guid("{4a8b0f52-3b1c-4d2e-9f60-7a1b2c3d4e5g}"),
+        None
+    );
+    assert!(
+        UserQuirks::parse("[[win_custom_hinge]]\ninterface_id = \"nope\"\nangle_key = \"k\"")
+            .is_err()
+    );
+    assert!(
+        quirks::register_win_custom_hinge(quirks::WinCustomHinge {
+            model: "".into(),
+            interface_id: 1,
+            angle_key: " ".into(),
+        })
+        .is_err()
+    );
+}
+
+#[test]
+fn surfaces_ship_with_a_custom_hinge() {
+    let table = quirks::WIN_CUSTOM_HINGES;
+    for model in [
+        "Microsoft Corporation Surface Book 3",
+        "Microsoft Corporation Surface Laptop Studio 2",
+    ] {
+        let hinges = quirks::win_custom_hinges_in(table, Some(model));
+        assert_eq!(hinges.len(), 1, "{model}");
+        assert_eq!(
+            quirks::parse_guid(hinges[0].angle_key.split(' ').next().unwrap()),
+            Some(hinges[0].interface_id)
+        );
+    }
+    assert!(quirks::win_custom_hinges_in(table, Some("LENOVO 82")).is_empty());
+}
+
+#[cfg(feature = "mock")]
+#[tokio::test(flavor = "current_thread")]
+async fn file_overrides_apply_to_opens() {
+    use booklid_rust::{OpenConfig, SampleRate, open_with_config};
+    use futures_util::StreamExt;
+
+    let path = std::env::temp_dir().join(format!("booklid-quirks-{}.toml", std::process::id()));
+    std::fs::write(
+        &path,
+        "[sources]\ndisable = [\"Mock\"]\n\n[[axis]]\nsource = \"Mock\"\noffset_deg = 10.0\n",
+    )
+    .unwrap();
+    // SAFETY: the only test in this binary that reads the environment.
+    unsafe { std::env::set_var("BOOKLID_QUIRKS", &path) };
+    assert_eq!(quirks::user_file_path().as_deref(), Some(path.as_path()));
+    assert_eq!(quirks::user().disable, vec![Source::Mock]);
+
+    let cfg = OpenConfig::new(SampleRate::hz(60.0))
+        .allow_mock(true)
+        .persistence(false)
+        .attach_daemon(false)
+        .smoothing(1.0);
+
+    // Naming the source in code overrides the file's `disable`; the axis
+    // entry applies since the config sets none.
+    let dev = open_with_config(cfg.clone().prefer(vec![Source::Mock]))
+        .await
+        .expect("open mock");
+    let s = dev.subscribe().next().await.unwrap();
+    assert!((s.angle_deg - (s.raw + 10.0)).abs() < 1e-3, "{s:?}");
+
+    // Otherwise the file disables it.
+    let others = vec![
+        Source::HingeFeature,
+        Source::HingeHid,
+        Source::HingeIOKit,
+        Source::HingeSMC,
+        Source::ALS,
+        Source::WinHinge,
+        Source::WinPosture,
+        Source::WinTilt,
+        Source::WinALS,
+        Source::LinuxHinge,
+        Source::LinuxTilt,
+        Source::LinuxALS,
+        Source::MacClamshell,
+        Source::WinLidSwitch,
+        Source::LinuxLidSwitch,
+        Source::LinuxUPowerLid,
+        Source::LinuxAcpiLid,
+    ];
+    assert!(open_with_config(cfg.clone().disable(others)).await.is_err());
+
+    // A file named in the config is used instead, and fails the open when it
+    // does not load.
+    let own = std::env::temp_dir().join(format!("booklid-quirks-cfg-{}.toml", std::process::id()));
+    std::fs::write(&own, "[[axis]]\nsource = \"Mock\"\noffset_deg = -5.0\n").unwrap();
+    let cfg = cfg.prefer(vec![Source::Mock]);
+    let dev = open_with_config(cfg.clone().quirks_file(&own))
+        .await
+        .expect("open mock");
+    let s = dev.subscribe().next().await.unwrap();
+    assert!((s.angle_deg - (s.raw - 5.0)).abs() < 1e-3, "{s:?}");
+    drop(dev);
+    std::fs::write(&own, "[[axis]]\nsource = 3\n").unwrap();
+    assert!(open_with_config(cfg.quirks_file(&own)).await.is_err());
+
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&own);
+}
@@ -0,0 +1,439 @@
+//! Windows sampling logic driven by scripted fake sensors (runs anywhere).
+
+use booklid_rust::convention::{hinge_from_gravity, hinge_from_gravity_near};
+use booklid_rust::win::{HingeState, WinAngle, WinReporting, WinSensor, WinSensorKind};
+use booklid_rust::{AngleDevice, Backoff, Error, OpenConfig, Result, SelfTestFailure, Source};
+use futures_util::StreamExt;
+use std::{
+    collections::VecDeque,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU32, Ordering},
+    },
+    time::Duration,
+};
+use tokio::sync::Notify;
+use tokio::time::timeout;
+
+/// Plays back `script`, then repeats its last entry.
+#[derive(Clone)]
+struct Scripted {
+    script: Arc<Mutex<VecDeque<Result<Option<f32>>>>>,
+    last: Arc<Mutex<Option<f32>>>,
+}
+
+impl Scripted {
+    fn new(script: Vec<Result<Option<f32>>>) -> Self {
+        Self {
+            script: Arc::new(Mutex::new(script.into())),
+            last: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl WinSensor for Scripted {
+    fn read(&self) -> Result<Option<f32>> {
+        match self.script.lock().unwrap().pop_front() {
+            Some(Ok(v)) => {
+                *self.last.lock().unwrap() = v;
+                Ok(v)
+            }
+            Some(Err(e)) => Err(e),
+            None => Ok(*self.last.lock().unwrap()),
+        }
+    }
+}
+
+fn failed() -> Result<Option<f32>> {
+    Err(Error::Other("read failed".into()))
+}
+
+async fn take(dev: &WinAngle, n: usize) -> Vec<f32> {
+    let stream = dev.subscribe().take(n).map(|s| s.angle_deg).collect();
+    timeout(Duration::from_secs(2), stream)
+        .await
+        .expect("samples")
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn steady_tilt_smooths_and_gains_confidence() {
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Tilt,
+        Scripted::new(vec![Ok(None), Ok(Some(30.0))]),
+        100.0,
+        Backoff::default(),
+    );
+    assert_eq!(dev.info().source, Source::WinTilt);
+    let got = take(&dev, 10).await;
+    // Pitch 30° maps to a 150° lid angle.
+    assert!(got.iter().all(|v| (v - 150.0).abs() < 1e-4), "{got:?}");
+    assert!(dev.confidence() > 0.99);
+    let s = dev.latest().unwrap();
+    assert_eq!(s.source, Source::WinTilt);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn non_finite_readings_are_dropped_before_the_filter() {
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Tilt,
+        Scripted::new(vec![
+            Ok(Some(30.0)),
+            Ok(Some(f32::NAN)),
+            Ok(Some(f32::INFINITY)),
+            Ok(Some(30.0)),
+        ]),
+        100.0,
+        Backoff::default(),
+    );
+    dev.set_outlier_rejection(None);
+    let got = take(&dev, 10).await;
+    assert!(got.iter().all(|v| (v - 150.0).abs() < 1e-4), "{got:?}");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn tilt_smooths_across_the_closed_seam() {
+    // Pitch 178° is a 2° lid angle, -178° is 358°: 4° apart, not 356°.
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Tilt,
+        Scripted::new(vec![Ok(Some(178.0)), Ok(Some(-178.0))]),
+        100.0,
+        Backoff::default(),
+    );
+    let got = take(&dev, 10).await;
+    assert!(
+        got.iter().all(|v| v.min(360.0 - v) <= 2.0 + 1e-3),
+        "{got:?}"
+    );
+    let s = dev.latest().unwrap();
+    assert!(s.velocity_deg_s.abs() < 1000.0, "{}", s.velocity_deg_s);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn hardware_id_comes_from_the_sensor() {
+    struct Identified(Scripted);
+    impl WinSensor for Identified {
+        fn read(&self) -> Result<Option<f32>> {
+            self.0.read()
+        }
+        fn device_id(&self) -> Option<String> {
+            Some(r"\\?\ACPI#HINGE0001#0".into())
+        }
+    }
+
+    let plain = WinAngle::with_sensor(
+        WinSensorKind::Hinge,
+        Scripted::new(vec![Ok(Some(90.0))]),
+        100.0,
+        Backoff::default(),
+    );
+    assert_eq!(plain.hardware_id(), None);
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Hinge,
+        Identified(Scripted::new(vec![Ok(Some(90.0))])),
+        100.0,
+        Backoff::default(),
+    );
+    assert_eq!(
+        dev.hardware_id().as_deref(),
+        Some(r"win:\\?\ACPI#HINGE0001#0")
+    );
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn hinge_skips_out_of_range_readings() {
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Hinge,
+        Scripted::new(vec![Ok(Some(500.0)), Ok(Some(-40.0)), Ok(Some(90.0))]),
+        100.0,
+        Backoff::default(),
+    );
+    dev.set_smoothing(1.0);
+    assert_eq!(take(&dev, 1).await, vec![90.0]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn light_publishes_lux_separately() {
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Light,
+        Scripted::new(vec![Ok(Some(250.0))]),
+        100.0,
+        Backoff::default(),
+    );
+    let l = timeout(Duration::from_secs(2), dev.subscribe_lux().next())
+        .await
+        .expect("lux sample")
+        .unwrap();
+    assert_eq!((l.lux, l.source), (250.0, Source::WinALS));
+
+    let tilt = WinAngle::with_sensor(
+        WinSensorKind::Tilt,
+        Scripted::new(vec![Ok(Some(30.0))]),
+        100.0,
+        Backoff::default(),
+    );
+    take(&tilt, 2).await;
+    let r = timeout(Duration::from_millis(50), tilt.subscribe_lux().next()).await;
+    assert!(matches!(r, Ok(None)), "tilt publishes no light");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn self_test_flags_a_jittery_sensor() {
+    let flapping = (0..40).map(|i| Ok(Some(if i % 2 == 0 { 0.0 } else { 90.0 })));
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Tilt,
+        Scripted::new(flapping.collect()),
+        100.0,
+        Backoff::default(),
+    );
+    let report = dev.self_test(10).await;
+    assert_eq!(report.samples, 10);
+    assert!(matches!(
+        report.failures.as_slice(),
+        [SelfTestFailure::Noisy { jitter }] if *jitter > 80.0
+    ));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn failed_reads_back_off_then_recover() {
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Tilt,
+        Scripted::new(vec![failed(), failed(), Ok(Some(10.0))]),
+        100.0,
+        Backoff::constant(Duration::from_millis(5)),
+    );
+    assert_eq!(take(&dev, 1).await, vec![170.0]);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn sampler_stops_when_attempts_are_exhausted() {
+    let backoff = Backoff {
+        max_attempts: Some(2),
+        ..Backoff::constant(Duration::from_millis(5))
+    };
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Light,
+        Scripted::new(vec![failed(), failed(), failed(), Ok(Some(100.0))]),
+        100.0,
+        backoff,
+    );
+    let mut s = dev.subscribe();
+    let r = timeout(Duration::from_millis(300), s.next()).await;
+    assert!(
+        r.is_err() || r.unwrap().is_none(),
+        "no sample after giving up"
+    );
+    assert!(dev.latest().is_none());
+}
+
+/// A sensor that pushes readings: `watch` hands out the notifier and
+/// records the interval asked for; `read` counts calls.
+#[derive(Clone, Default)]
+struct Pushed {
+    notify: Arc<Mutex<Option<Arc<Notify>>>>,
+    interval: Arc<Mutex<Option<Duration>>>,
+    reads: Arc<AtomicU32>,
+}
+
+impl WinSensor for Pushed {
+    fn read(&self) -> Result<Option<f32>> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        Ok(Some(20.0))
+    }
+
+    fn watch(&self, interval: Duration, notify: Arc<Notify>) -> Result<bool> {
+        *self.interval.lock().unwrap() = Some(interval);
+        *self.notify.lock().unwrap() = Some(notify);
+        Ok(true)
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn watched_sensor_is_read_when_it_reports() {
+    let sensor = Pushed::default();
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Tilt,
+        sensor.clone(),
+        50.0,
+        Backoff::default(),
+    );
+    assert_eq!(
+        *sensor.interval.lock().unwrap(),
+        Some(Duration::from_millis(20))
+    );
+
+    // No reports, no reads (well within the quiet-sensor fallback).
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(sensor.reads.load(Ordering::Relaxed), 0);
+
+    let notify = sensor.notify.lock().unwrap().clone().unwrap();
+    let mut s = dev.subscribe();
+    for _ in 0..3 {
+        notify.notify_one();
+        let got = timeout(Duration::from_millis(200), s.next())
+            .await
+            .expect("sample per report")
+            .unwrap();
+        assert_eq!(got.raw, 160.0);
+    }
+    assert_eq!(sensor.reads.load(Ordering::Relaxed), 3);
+
+    // A still lid is read anyway now and then.
+    timeout(Duration::from_secs(2), s.next())
+        .await
+        .expect("quiet read")
+        .unwrap();
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn without_quiet_reads_a_still_sensor_is_read_once() {
+    struct Silent(Pushed);
+    impl WinSensor for Silent {
+        fn read(&self) -> Result<Option<f32>> {
+            self.0.read()
+        }
+        fn watch(&self, interval: Duration, notify: Arc<Notify>) -> Result<bool> {
+            self.0.watch(interval, notify)
+        }
+        fn quiet_read(&self) -> Duration {
+            WinReporting::low_power().quiet_read
+        }
+    }
+
+    let sensor = Pushed::default();
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Tilt,
+        Silent(sensor.clone()),
+        50.0,
+        Backoff::default(),
+    );
+    // Once at start, then nothing well past the default quiet read.
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+    assert_eq!(sensor.reads.load(Ordering::Relaxed), 1);
+    assert!(dev.latest().is_some());
+
+    let mut s = dev.subscribe();
+    let notify = sensor.notify.lock().unwrap().clone().unwrap();
+    notify.notify_one();
+    timeout(Duration::from_millis(200), s.next())
+        .await
+        .expect("sample per report")
+        .unwrap();
+    assert_eq!(sensor.reads.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn posture_reads_as_its_hinge_state_angle() {
+    assert_eq!(HingeState::from_raw(0), None);
+    assert_eq!(HingeState::from_raw(6), None);
+    let states = [1, 2, 3, 4, 5].map(|raw| HingeState::from_raw(raw).unwrap());
+    assert_eq!(
+        states.map(HingeState::angle_deg),
+        [0.0, 90.0, 180.0, 270.0, 360.0]
+    );
+
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::Posture,
+        Scripted::new(vec![Ok(Some(HingeState::Convex.angle_deg()))]),
+        100.0,
+        Backoff::default(),
+    );
+    dev.set_smoothing(1.0);
+    assert_eq!(take(&dev, 1).await, vec![270.0]);
+    let got = dev.latest().unwrap();
+    assert_eq!(got.source, Source::WinPosture);
+    assert_eq!(dev.info().note, "win_posture");
+}
+
+/// `v` turned by `deg` about the hinge (x) axis.
+fn about_hinge(v: [f32; 3], deg: f32) -> [f32; 3] {
+    let (s, c) = deg.to_radians().sin_cos();
+    [v[0], v[1] * c - v[2] * s, v[1] * s + v[2] * c]
+}
+
+#[test]
+fn hinge_angle_from_lid_and_base_gravity() {
+    let angle = |lid, base| hinge_from_gravity(lid, base).unwrap();
+    // Base flat on a desk; the lid's gravity turns with the hinge.
+    let base = [0.0, 0.0, -1.0];
+    for hinge in [0.0f32, 45.0, 90.0, 135.0, 180.0, 270.0] {
+        let lid = about_hinge(base, hinge - 180.0);
+        assert!((angle(lid, base) - hinge).abs() < 0.01, "{hinge}°");
+
+        // Tipping the whole device about the hinge changes nothing.
+        let got = angle(about_hinge(lid, 30.0), about_hinge(base, 30.0));
+        assert!((got - hinge).abs() < 0.01, "{hinge}° tipped");
+    }
+    // Jitter around closed stays at closed instead of wrapping to 360°.
+    assert_eq!(angle(about_hinge(base, -181.0), base), 0.0);
+
+    // Standing on its side, gravity runs along the hinge: no angle.
+    assert_eq!(hinge_from_gravity([1.0, 0.0, 0.1], [1.0, 0.1, 0.0]), None);
+}
+
+#[test]
+fn hinge_angle_follows_the_lid_past_350() {
+    let base = [0.0, 0.0, -1.0];
+    let at = |hinge: f32| about_hinge(base, hinge - 180.0);
+
+    // Folding back into tablet mode keeps going instead of snapping to 0°.
+    let mut prev = None;
+    for hinge in (300..=360).step_by(5).chain([361, 358]) {
+        let got = hinge_from_gravity_near(at(hinge as f32), base, prev).unwrap();
+        let want = (hinge as f32).min(360.0);
+        assert!((got - want).abs() < 0.01, "{hinge}°: {got}");
+        prev = Some(got);
+    }
+    // Closing from the other side stays at closed through the same readings.
+    let mut prev = None;
+    for hinge in [20.0, 10.0, 5.0, 0.0, -2.0, 3.0] {
+        let got = hinge_from_gravity_near(at(hinge), base, prev).unwrap();
+        assert!((got - hinge.max(0.0)).abs() < 0.01, "{hinge}°: {got}");
+        prev = Some(got);
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn accelerometer_hinge_reports_as_the_hinge() {
+    let dev = WinAngle::with_sensor(
+        WinSensorKind::AccelHinge,
+        Scripted::new(vec![Ok(Some(110.0))]),
+        100.0,
+        Backoff::default(),
+    );
+    dev.set_smoothing(1.0);
+    assert_eq!(take(&dev, 1).await, vec![110.0]);
+    assert_eq!(dev.info().source, Source::WinHinge);
+    assert_eq!(dev.info().note, "win_accel_hinge");
+}
+
+#[test]
+fn win_reporting_floors_the_interval_and_validates() {
+    let period = Duration::from_millis(16);
+    assert_eq!(WinReporting::default().interval(period), period);
+    assert_eq!(
+        WinReporting::default().quiet_read,
+        Duration::from_millis(500)
+    );
+    assert_eq!(WinReporting::low_power().quiet_read, Duration::ZERO);
+    assert_eq!(
+        WinReporting::low_power().interval(period),
+        Duration::from_millis(100)
+    );
+    assert_eq!(
+        WinReporting::low_power().interval(Duration::from_secs(1)),
+        Duration::from_secs(1)
+    );
+
+    let cfg = OpenConfig::default();
+    assert!(
+        cfg.clone()
+            .win_reporting(WinReporting::low_power())
+            .validate()
+            .is_ok()
+    );
+    let negative = WinReporting {
+        threshold_deg: -1.0,
+        ..WinReporting::default()
+    };
+    assert!(cfg.win_reporting(negative).validate().is_err());
+}